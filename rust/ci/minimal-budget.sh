@@ -0,0 +1,30 @@
+#!/bin/sh
+# Check the `minimal` feature profile against its dependency and binary-size budget.
+#
+# Run from anywhere; CI runs it on every change to the Rust SDK. Raise a budget only
+# deliberately, in the same change that explains the growth.
+set -eu
+
+MAX_DEPS=${MAX_DEPS:-115}
+MAX_BYTES=${MAX_BYTES:-5500000}
+
+cd "$(dirname "$0")/.."
+FEATURES="--no-default-features --features minimal"
+
+deps=$(cargo tree $FEATURES -e normal --prefix none | sed 's/ (\*)$//' | sort -u | wc -l)
+echo "minimal: $deps crates (budget $MAX_DEPS)"
+
+cargo build --release $FEATURES --example minimal
+bytes=$(wc -c < target/release/examples/minimal)
+echo "minimal: $bytes bytes (budget $MAX_BYTES)"
+
+status=0
+if [ "$deps" -gt "$MAX_DEPS" ]; then
+    echo "error: dependency budget exceeded" >&2
+    status=1
+fi
+if [ "$bytes" -gt "$MAX_BYTES" ]; then
+    echo "error: binary-size budget exceeded" >&2
+    status=1
+fi
+exit $status
@@ -0,0 +1,16 @@
+// examples/minimal.rs
+//
+// The smallest realistic consumer of the SDK: classify one file and print the response.
+// ci/minimal-budget.sh builds it with the `minimal` profile to track binary size.
+
+use traceix_sdk::TraceixSdk;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let path = std::env::args().nth(1).ok_or("usage: minimal <file>")?;
+
+    let sdk = TraceixSdk::new(None)?;
+    let result = sdk.ai_prediction(path)?;
+    println!("{result:#}");
+
+    Ok(())
+}
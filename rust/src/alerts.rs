@@ -0,0 +1,112 @@
+// src/alerts.rs
+
+use crate::{decode_response, Endpoint, TraceixError, TraceixSdk};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// The event an alert rule fires on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum AlertTrigger {
+    /// The verdict of a matching sample changed (e.g. after a rescan).
+    VerdictChanged,
+    /// An analysis of a matching sample completed.
+    AnalysisCompleted,
+}
+
+/// A rule to create: which samples to watch and what to do when `trigger` fires.
+///
+/// A sample matches when it carries `tag` (if set) and is one of `hashes` (if non-empty).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NewAlertRule {
+    pub name: String,
+    pub trigger: AlertTrigger,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hashes: Vec<String>,
+    /// Webhook to deliver [`AlertPayload`]s to. Falls back to the account webhook when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook_url: Option<Url>,
+}
+
+impl NewAlertRule {
+    /// Notify when any sample tagged `tag` changes verdict.
+    pub fn verdict_change_for_tag(name: impl Into<String>, tag: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            trigger: AlertTrigger::VerdictChanged,
+            tag: Some(tag.into()),
+            hashes: Vec::new(),
+            webhook_url: None,
+        }
+    }
+}
+
+/// An alert rule stored on the server.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AlertRule {
+    pub id: String,
+    #[serde(flatten)]
+    pub rule: NewAlertRule,
+    #[serde(default)]
+    pub created_at: Option<String>,
+}
+
+/// The body POSTed to a webhook when an alert rule fires.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AlertPayload {
+    pub rule_id: String,
+    pub trigger: AlertTrigger,
+    pub uuid: String,
+    #[serde(default)]
+    pub sha256: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub previous_verdict: Option<String>,
+    #[serde(default)]
+    pub verdict: Option<String>,
+    #[serde(default)]
+    pub triggered_at: Option<String>,
+}
+
+impl AlertPayload {
+    /// Parse a webhook request body.
+    pub fn from_slice(body: &[u8]) -> Result<Self, TraceixError> {
+        serde_json::from_slice(body)
+            .map_err(|e| TraceixError::InvalidArgument(format!("invalid alert payload: {e}")))
+    }
+}
+
+impl TraceixSdk {
+    /// Create a server-side alert rule.
+    pub fn create_alert_rule(&self, rule: &NewAlertRule) -> Result<AlertRule, TraceixError> {
+        if rule.tag.is_none() && rule.hashes.is_empty() {
+            return Err(TraceixError::InvalidArgument(
+                "an alert rule needs a tag or at least one hash".to_string(),
+            ));
+        }
+
+        let body = serde_json::to_value(rule).expect("alert rule serializes to JSON");
+        decode_response(self.post_json(&Endpoint::ALERT_CREATE, &body)?)
+    }
+
+    /// List the account's alert rules, fetching every page.
+    pub fn list_alert_rules(&self) -> Result<Vec<AlertRule>, TraceixError> {
+        self.list_all(&Endpoint::ALERT_LIST, "rules")
+    }
+
+    /// Delete an alert rule by id.
+    pub fn delete_alert_rule(&self, id: &str) -> Result<(), TraceixError> {
+        if id.is_empty() {
+            return Err(TraceixError::InvalidArgument(
+                "alert rule id is empty".to_string(),
+            ));
+        }
+
+        self.post_json(&Endpoint::ALERT_DELETE, &serde_json::json!({ "id": id }))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,131 @@
+// src/api.rs
+
+use crate::{SearchType, TraceixError, TraceixSdk, UploadOptions};
+use serde_json::Value;
+use std::path::Path;
+
+/// The core Traceix calls, implemented by [`TraceixSdk`] and, with the `mock` feature,
+/// by `MockTraceix`.
+///
+/// Take `&dyn TraceixApi` (or a generic `impl TraceixApi`) in application code instead
+/// of a concrete [`TraceixSdk`] so it can be unit-tested without a network.
+///
+/// ```no_run
+/// # use std::path::Path;
+/// # use traceix_sdk::{TraceixApi, TraceixError, TraceixSdk};
+/// fn triage(api: &dyn TraceixApi, sample: &Path) -> Result<bool, TraceixError> {
+///     let result = api.ai_prediction(sample)?;
+///     Ok(result["prediction"] == "malicious")
+/// }
+///
+/// # fn main() -> Result<(), TraceixError> {
+/// let sdk = TraceixSdk::new(None)?;
+/// triage(&sdk, Path::new("invoice.exe"))?;
+/// # Ok(())
+/// # }
+/// ```
+pub trait TraceixApi: Send + Sync {
+    /// Sends a request to the prediction endpoint with explicit upload options.
+    fn ai_prediction_with_options(
+        &self,
+        filename: &Path,
+        options: &UploadOptions,
+    ) -> Result<Value, TraceixError>;
+
+    /// Extract the CAPA capabilities with explicit upload options.
+    fn capa_extraction_with_options(
+        &self,
+        filename: &Path,
+        options: &UploadOptions,
+    ) -> Result<Value, TraceixError>;
+
+    /// Extract EXIF metadata with explicit upload options.
+    fn exif_extraction_with_options(
+        &self,
+        filename: &Path,
+        options: &UploadOptions,
+    ) -> Result<Value, TraceixError>;
+
+    /// Check the status of a provided UUID.
+    fn check_status(&self, uuid: &str) -> Result<Value, TraceixError>;
+
+    /// Cancel a queued or running analysis by UUID.
+    fn cancel(&self, uuid: &str) -> Result<Value, TraceixError>;
+
+    /// Search by file hash (capa or exif).
+    fn hash_search(&self, file_hash: &str, search_type: SearchType) -> Result<Value, TraceixError>;
+
+    /// List all public IPFS datasets currently available.
+    fn list_all_ipfs_datasets(&self) -> Result<Value, TraceixError>;
+
+    /// Get a public IPFS dataset by CID.
+    fn get_public_ipfs_dataset(&self, cid: &str) -> Result<Value, TraceixError>;
+
+    /// Search by file hash to see if the dataset has been uploaded to the public domain.
+    fn search_ipfs_dataset_by_hash(&self, file_hash: &str) -> Result<Value, TraceixError>;
+
+    /// Sends a request to the prediction endpoint.
+    fn ai_prediction(&self, filename: &Path) -> Result<Value, TraceixError> {
+        self.ai_prediction_with_options(filename, &UploadOptions::default())
+    }
+
+    /// Extract the CAPA capabilities from the filename.
+    fn capa_extraction(&self, filename: &Path) -> Result<Value, TraceixError> {
+        self.capa_extraction_with_options(filename, &UploadOptions::default())
+    }
+
+    /// Extract EXIF metadata from the filename.
+    fn exif_extraction(&self, filename: &Path) -> Result<Value, TraceixError> {
+        self.exif_extraction_with_options(filename, &UploadOptions::default())
+    }
+}
+
+impl TraceixApi for TraceixSdk {
+    fn ai_prediction_with_options(
+        &self,
+        filename: &Path,
+        options: &UploadOptions,
+    ) -> Result<Value, TraceixError> {
+        TraceixSdk::ai_prediction_with_options(self, filename, options)
+    }
+
+    fn capa_extraction_with_options(
+        &self,
+        filename: &Path,
+        options: &UploadOptions,
+    ) -> Result<Value, TraceixError> {
+        TraceixSdk::capa_extraction_with_options(self, filename, options)
+    }
+
+    fn exif_extraction_with_options(
+        &self,
+        filename: &Path,
+        options: &UploadOptions,
+    ) -> Result<Value, TraceixError> {
+        TraceixSdk::exif_extraction_with_options(self, filename, options)
+    }
+
+    fn check_status(&self, uuid: &str) -> Result<Value, TraceixError> {
+        TraceixSdk::check_status(self, uuid)
+    }
+
+    fn cancel(&self, uuid: &str) -> Result<Value, TraceixError> {
+        TraceixSdk::cancel(self, uuid)
+    }
+
+    fn hash_search(&self, file_hash: &str, search_type: SearchType) -> Result<Value, TraceixError> {
+        TraceixSdk::hash_search(self, file_hash, search_type)
+    }
+
+    fn list_all_ipfs_datasets(&self) -> Result<Value, TraceixError> {
+        TraceixSdk::list_all_ipfs_datasets(self)
+    }
+
+    fn get_public_ipfs_dataset(&self, cid: &str) -> Result<Value, TraceixError> {
+        TraceixSdk::get_public_ipfs_dataset(self, cid)
+    }
+
+    fn search_ipfs_dataset_by_hash(&self, file_hash: &str) -> Result<Value, TraceixError> {
+        TraceixSdk::search_ipfs_dataset_by_hash(self, file_hash)
+    }
+}
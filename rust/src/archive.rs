@@ -0,0 +1,419 @@
+// src/archive.rs
+
+use crate::exif::strip_exif_bytes;
+use crate::{FileType, Submission, TraceixError, TraceixSdk, UploadOptions};
+use flate2::read::GzDecoder;
+use reqwest::blocking::multipart::Part;
+use sevenz_rust::{Password, SevenZReader};
+use std::fs::File;
+use std::io::{Cursor, Read, Seek, SeekFrom};
+use std::path::Path;
+use zeroize::Zeroizing;
+use zip::result::ZipError;
+use zip::ZipArchive;
+
+/// How [`TraceixSdk::submit_archive`] expands an archive. The size and count limits
+/// guard against archive bombs: everything is extracted in memory, never to disk.
+#[derive(Clone, Debug)]
+pub struct ArchiveOptions {
+    /// Password for encrypted zip and 7z archives, e.g. the customary `infected`. Also
+    /// tried on nested archives.
+    pub password: Option<String>,
+    /// Levels of archives to expand: 1 submits the members of the archive as they are,
+    /// 2 also expands archives found inside it, and so on.
+    pub max_depth: usize,
+    /// Most members submitted, counted over all levels. Extraction stops at the first
+    /// member over the limit.
+    pub max_members: usize,
+    /// Largest uncompressed size of a single member. Larger members are skipped.
+    pub max_member_size: u64,
+    /// Most uncompressed bytes extracted in total. Members that would exceed it are
+    /// skipped.
+    pub max_total_size: u64,
+    pub upload: UploadOptions,
+}
+
+impl Default for ArchiveOptions {
+    fn default() -> Self {
+        Self {
+            password: None,
+            max_depth: 3,
+            max_members: 1000,
+            max_member_size: 256 * 1024 * 1024,
+            max_total_size: 1024 * 1024 * 1024,
+            upload: UploadOptions::default(),
+        }
+    }
+}
+
+/// Why [`TraceixSdk::submit_archive`] left a member out.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MemberSkipReason {
+    /// Larger than [`ArchiveOptions::max_member_size`].
+    TooLarge,
+    /// Extracting it would exceed [`ArchiveOptions::max_total_size`].
+    TotalSizeExceeded,
+    /// [`ArchiveOptions::max_members`] was reached; no further members were read.
+    TooManyMembers,
+    /// Encrypted, and no password or the wrong one was given.
+    Encrypted,
+    /// The member couldn't be extracted, e.g. because the archive is truncated.
+    Unreadable(String),
+}
+
+/// A member of an archive, with the outcome of submitting it.
+pub struct ArchiveMember {
+    /// Path inside the archive. Members of nested archives are joined with `/`, e.g.
+    /// `attachments/invoice.zip/invoice.exe`.
+    pub path: String,
+    pub size: u64,
+    pub result: Result<Submission, TraceixError>,
+}
+
+/// A member [`TraceixSdk::submit_archive`] did not submit.
+#[derive(Clone, Debug)]
+pub struct SkippedMember {
+    pub path: String,
+    pub reason: MemberSkipReason,
+}
+
+/// The outcome of [`TraceixSdk::submit_archive`], in archive order.
+#[non_exhaustive]
+pub struct ArchiveSummary {
+    pub members: Vec<ArchiveMember>,
+    pub skipped: Vec<SkippedMember>,
+}
+
+impl TraceixSdk {
+    /// Extract a zip, 7z, tar or gzipped tar archive in memory and submit each member
+    /// for AI prediction, e.g. malware delivered in a password-protected zip.
+    ///
+    /// Archives inside the archive are expanded too, up to
+    /// [`ArchiveOptions::max_depth`]; deeper ones, and those that can't be opened, are
+    /// submitted as files. Upload failures are reported per member in
+    /// [`ArchiveMember::result`]; only a file that isn't a readable archive fails the
+    /// whole call.
+    ///
+    /// ```no_run
+    /// # use traceix_sdk::{ArchiveOptions, TraceixSdk};
+    /// # fn main() -> Result<(), traceix_sdk::TraceixError> {
+    /// let sdk = TraceixSdk::new(None)?;
+    /// let options = ArchiveOptions {
+    ///     password: Some("infected".into()),
+    ///     ..ArchiveOptions::default()
+    /// };
+    /// let summary = sdk.submit_archive("samples.zip", &options)?;
+    /// for member in &summary.members {
+    ///     match &member.result {
+    ///         Ok(submission) => println!("{}: {}", member.path, submission.uuid()),
+    ///         Err(e) => eprintln!("{}: {e}", member.path),
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn submit_archive(
+        &self,
+        path: impl AsRef<Path>,
+        options: &ArchiveOptions,
+    ) -> Result<ArchiveSummary, TraceixError> {
+        let path = path.as_ref();
+        let mut file = File::open(path)?;
+        let file_type = FileType::detect_file(path)?;
+        let mut extractor = Extractor {
+            options,
+            total: 0,
+            done: false,
+            files: Vec::new(),
+            skipped: Vec::new(),
+        };
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        extractor
+            .expand(&mut file, file_type, &name, "", 1)
+            .map_err(|reason| {
+                TraceixError::InvalidArgument(format!("{}: {reason}", path.display()))
+            })?;
+
+        let members = extractor
+            .files
+            .into_iter()
+            .map(|(path, data)| {
+                let size = data.len() as u64;
+                let result = self.submit_member(&path, data, &options.upload);
+                ArchiveMember { path, size, result }
+            })
+            .collect();
+        Ok(ArchiveSummary {
+            members,
+            skipped: extractor.skipped,
+        })
+    }
+
+    fn submit_member(
+        &self,
+        path: &str,
+        data: Vec<u8>,
+        options: &UploadOptions,
+    ) -> Result<Submission, TraceixError> {
+        let mut data = Zeroizing::new(data);
+        if options.strip_metadata {
+            data = Zeroizing::new(strip_exif_bytes(&data)?);
+        }
+        let len = data.len() as u64;
+        let name = path.rsplit('/').next().unwrap_or(path).to_string();
+        let part = Part::reader_with_length(self.body_reader(Cursor::new(data)), len)
+            .file_name(name)
+            .mime_str("application/octet-stream")
+            .map_err(TraceixError::Http)?;
+        self.upload_part(part, options)
+    }
+}
+
+/// Collects the members of an archive and its nested archives, within the limits.
+struct Extractor<'a> {
+    options: &'a ArchiveOptions,
+    /// Uncompressed bytes extracted so far.
+    total: u64,
+    /// Set once the member limit is reached.
+    done: bool,
+    files: Vec<(String, Vec<u8>)>,
+    skipped: Vec<SkippedMember>,
+}
+
+impl Extractor<'_> {
+    /// Read the members of the archive in `source`, naming them under `prefix`.
+    fn expand<R: Read + Seek>(
+        &mut self,
+        source: &mut R,
+        file_type: FileType,
+        name: &str,
+        prefix: &str,
+        depth: usize,
+    ) -> Result<(), String> {
+        match file_type {
+            FileType::Zip => self.expand_zip(source, prefix, depth),
+            FileType::SevenZip => self.expand_7z(source, prefix, depth),
+            FileType::Tar => self.expand_tar(source, prefix, depth),
+            FileType::Gzip => {
+                let inner = name
+                    .strip_suffix(".gz")
+                    .or_else(|| name.strip_suffix(".tgz"))
+                    .unwrap_or(name);
+                let path = format!("{prefix}{inner}");
+                let Some(data) = self.read_member(&path, GzDecoder::new(source)) else {
+                    return Ok(());
+                };
+                if FileType::detect(&data) == FileType::Tar {
+                    self.expand_tar(&mut Cursor::new(data), prefix, depth)
+                } else {
+                    self.add(path, data, depth);
+                    Ok(())
+                }
+            }
+            _ => Err("not a zip, 7z or tar archive".into()),
+        }
+    }
+
+    fn expand_zip<R: Read + Seek>(
+        &mut self,
+        source: &mut R,
+        prefix: &str,
+        depth: usize,
+    ) -> Result<(), String> {
+        let mut zip = ZipArchive::new(source).map_err(|e| e.to_string())?;
+        for index in 0..zip.len() {
+            if self.done {
+                break;
+            }
+            let path = match zip.name_for_index(index) {
+                Some(name) => format!("{prefix}{name}"),
+                None => format!("{prefix}#{index}"),
+            };
+            let entry = match &self.options.password {
+                Some(password) => zip.by_index_decrypt(index, password.as_bytes()),
+                None => zip.by_index(index),
+            };
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    let reason = match e {
+                        ZipError::InvalidPassword
+                        | ZipError::UnsupportedArchive(ZipError::PASSWORD_REQUIRED) => {
+                            MemberSkipReason::Encrypted
+                        }
+                        e => MemberSkipReason::Unreadable(e.to_string()),
+                    };
+                    self.skip(path, reason);
+                    continue;
+                }
+            };
+            if entry.is_dir() {
+                continue;
+            }
+            if let Some(data) = self.read_member(&path, entry) {
+                self.add(path, data, depth);
+            }
+        }
+        Ok(())
+    }
+
+    fn expand_7z<R: Read + Seek>(
+        &mut self,
+        source: &mut R,
+        prefix: &str,
+        depth: usize,
+    ) -> Result<(), String> {
+        let len = source.seek(SeekFrom::End(0)).map_err(|e| e.to_string())?;
+        source.rewind().map_err(|e| e.to_string())?;
+        let password = self
+            .options
+            .password
+            .as_deref()
+            .map_or_else(Password::empty, Password::from);
+        let mut archive = SevenZReader::new(source, len, password).map_err(|e| match e {
+            sevenz_rust::Error::PasswordRequired | sevenz_rust::Error::MaybeBadPassword(_) => {
+                "encrypted, and no password or the wrong one was given".to_string()
+            }
+            e => e.to_string(),
+        })?;
+
+        // Members of solid archives can only be read in order, so collect them first
+        // and expand nested archives afterwards.
+        let mut members = Vec::new();
+        let result = archive.for_each_entries(|entry, reader| {
+            if entry.is_directory() {
+                return Ok(true);
+            }
+            // Called again for the first entry of every later block, even after one
+            // returned false.
+            if self.done {
+                return Ok(false);
+            }
+            let path = format!("{prefix}{}", entry.name());
+            // A member of a solid block can only be skipped by decompressing it, so stop
+            // reading the block at the first member over a limit instead; later blocks
+            // are still read.
+            if self.files.len() < self.options.max_members {
+                if let Some(reason) = self.over_limit(entry.size()) {
+                    self.skip(path, reason);
+                    return Ok(false);
+                }
+            }
+            let Some(data) = self.read_member(&path, &mut *reader) else {
+                return Ok(false);
+            };
+            members.push((path, data));
+            Ok(!self.done)
+        });
+        if let Err(e) = result {
+            self.skip(
+                format!("{prefix}*"),
+                MemberSkipReason::Unreadable(e.to_string()),
+            );
+        }
+        for (path, data) in members {
+            self.add(path, data, depth);
+        }
+        Ok(())
+    }
+
+    fn expand_tar<R: Read>(&mut self, source: R, prefix: &str, depth: usize) -> Result<(), String> {
+        let mut tar = tar::Archive::new(source);
+        for entry in tar.entries().map_err(|e| e.to_string())? {
+            if self.done {
+                break;
+            }
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    self.skip(
+                        format!("{prefix}*"),
+                        MemberSkipReason::Unreadable(e.to_string()),
+                    );
+                    break;
+                }
+            };
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let path = format!("{prefix}{}", String::from_utf8_lossy(&entry.path_bytes()));
+            if let Some(data) = self.read_member(&path, entry) {
+                self.add(path, data, depth);
+            }
+        }
+        Ok(())
+    }
+
+    /// Read one member within the size and count limits, recording why if it can't be.
+    fn read_member(&mut self, path: &str, reader: impl Read) -> Option<Vec<u8>> {
+        if self.files.len() >= self.options.max_members {
+            self.done = true;
+            self.skip(path.to_string(), MemberSkipReason::TooManyMembers);
+            return None;
+        }
+        let remaining = self.options.max_total_size.saturating_sub(self.total);
+        let limit = self.options.max_member_size.min(remaining);
+
+        let mut data = Vec::new();
+        // Read one byte past the limit to tell a member that fits exactly from one that
+        // doesn't, without trusting the size the archive declares.
+        if let Err(e) = reader.take(limit + 1).read_to_end(&mut data) {
+            self.skip(
+                path.to_string(),
+                MemberSkipReason::Unreadable(e.to_string()),
+            );
+            return None;
+        }
+        if let Some(reason) = self.over_limit(data.len() as u64) {
+            self.skip(path.to_string(), reason);
+            return None;
+        }
+        self.total += data.len() as u64;
+        Some(data)
+    }
+
+    /// Which limit a member of `size` uncompressed bytes would exceed, if any.
+    fn over_limit(&self, size: u64) -> Option<MemberSkipReason> {
+        if size > self.options.max_member_size {
+            Some(MemberSkipReason::TooLarge)
+        } else if size > self.options.max_total_size.saturating_sub(self.total) {
+            Some(MemberSkipReason::TotalSizeExceeded)
+        } else {
+            None
+        }
+    }
+
+    /// Keep a member, expanding it if it is itself an archive and `depth` allows.
+    fn add(&mut self, path: String, data: Vec<u8>, depth: usize) {
+        let file_type = FileType::detect(&data);
+        if depth < self.options.max_depth && file_type.is_archive() {
+            let name = path.rsplit('/').next().unwrap_or(&path).to_string();
+            let prefix = format!("{path}/");
+            let (files, skipped, total) = (self.files.len(), self.skipped.len(), self.total);
+            let mut source = Cursor::new(data);
+            if self
+                .expand(&mut source, file_type, &name, &prefix, depth + 1)
+                .is_ok()
+            {
+                return;
+            }
+            // Not actually an archive we can open: drop any partial results and submit
+            // it as a file.
+            self.files.truncate(files);
+            self.skipped.truncate(skipped);
+            self.total = total;
+            self.done = false;
+            self.files.push((path, source.into_inner()));
+            return;
+        }
+        self.files.push((path, data));
+    }
+
+    fn skip(&mut self, path: String, reason: MemberSkipReason) {
+        self.skipped.push(SkippedMember { path, reason });
+    }
+}
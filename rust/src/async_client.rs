@@ -0,0 +1,528 @@
+// src/async_client.rs
+
+use crate::instrument;
+use crate::limits::Limits;
+#[cfg(feature = "blocking")]
+use crate::middleware::MiddlewareChain;
+use crate::observer::Observers;
+use crate::{
+    build_url, exif, parse_base_url, upload_fields, upload_file_name, Analyses, AnalysisSelection,
+    Cid, Endpoint, FileHash, FullUpload, GiveUpEvent, JobStatus, Preflight, Priority, RequestEvent,
+    ResponseEvent, RetryEvent, RetryPolicy, SearchType, TraceixError, TraceixSdkBuilder,
+    UploadOptions,
+};
+use bytes::Bytes;
+use reqwest::header::HeaderMap;
+use reqwest::multipart;
+use reqwest::{Body, Client, RequestBuilder, StatusCode};
+use serde_json::Value;
+use std::fmt;
+use std::future::Future;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::AsyncRead;
+use tokio_util::io::ReaderStream;
+use url::Url;
+use zeroize::Zeroizing;
+
+/// Non-blocking counterpart of [`TraceixSdk`](crate::TraceixSdk), built on the async
+/// `reqwest::Client` so it can be called directly from tokio services.
+///
+/// Configure it with the same [`TraceixSdkBuilder`] as the blocking client, finishing
+/// with [`build_async`](TraceixSdkBuilder::build_async).
+///
+/// ```no_run
+/// # async fn run() -> Result<(), traceix_sdk::TraceixError> {
+/// let sdk = traceix_sdk::AsyncTraceixSdk::new(None)?;
+/// let result = sdk.ai_prediction("/path/to/file.exe").await?;
+/// println!("{result:#}");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct AsyncTraceixSdk {
+    pub(crate) headers: HeaderMap,
+    pub(crate) base_url: Url,
+    pub(crate) client: Client,
+    pub(crate) limits: Arc<Limits>,
+    pub(crate) observers: Observers,
+    pub(crate) retry: Arc<RetryPolicy>,
+    pub(crate) timeout: Duration,
+    pub(crate) connect_timeout: Option<Duration>,
+    #[cfg(feature = "blocking")]
+    pub(crate) middleware: MiddlewareChain,
+    pub(crate) preflight: Option<Arc<Preflight>>,
+}
+
+/// Shows where the client points; the headers, which hold the API key, are left out.
+impl fmt::Debug for AsyncTraceixSdk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncTraceixSdk")
+            .field("base_url", &self.base_url.as_str())
+            .field("api_key", &"<redacted>")
+            .field("timeout", &self.timeout)
+            .field("connect_timeout", &self.connect_timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AsyncTraceixSdk {
+    pub const SDK_VERSION: &'static str = crate::SDK_VERSION;
+
+    /// Initialize the SDK. If `api_key` is `None`, it will read TRACEIX_API_KEY from the environment.
+    /// The API root is taken from TRACEIX_BASE_URL when set.
+    pub fn new(api_key: Option<String>) -> Result<Self, TraceixError> {
+        let mut builder = TraceixSdkBuilder::new();
+        if let Some(key) = api_key {
+            builder = builder.api_key(key);
+        }
+        builder.build_async()
+    }
+
+    /// Start configuring a client; finish with [`TraceixSdkBuilder::build_async`].
+    pub fn builder() -> TraceixSdkBuilder {
+        TraceixSdkBuilder::new()
+    }
+
+    /// Point the client at another server, e.g. a staging server or an on-prem mirror.
+    pub fn with_base_url(mut self, base_url: &str) -> Result<Self, TraceixError> {
+        self.base_url = parse_base_url(base_url)?;
+        Ok(self)
+    }
+
+    /// Send a POST to `endpoint` and decode the JSON response, retrying under `retry`.
+    ///
+    /// `build` attaches the body and is called again for every attempt, so streamed
+    /// bodies are reopened rather than replayed.
+    async fn send<F, Fut>(
+        &self,
+        endpoint: &Endpoint,
+        source: Option<&Path>,
+        priority: Priority,
+        retry: &RetryPolicy,
+        mut build: F,
+    ) -> Result<Value, TraceixError>
+    where
+        F: FnMut(RequestBuilder) -> Fut,
+        Fut: Future<Output = Result<RequestBuilder, TraceixError>>,
+    {
+        let url = build_url(&self.base_url, endpoint.path);
+        let span = instrument::async_call(endpoint, &url);
+        instrument::in_call(&span, async {
+            let mut attempt = 1;
+            loop {
+                let error = match self
+                    .attempt(endpoint, &url, source, priority, attempt, &mut build)
+                    .await
+                {
+                    Ok((status, value)) => {
+                        instrument::finish_call(&span, attempt, Some(status.as_u16()));
+                        return Ok(value);
+                    }
+                    Err(e) => e,
+                };
+                if !retry.should_retry(endpoint, &error, attempt) {
+                    instrument::finish_call(&span, attempt, error.status().map(|s| s.as_u16()));
+                    if attempt > 1 {
+                        self.observers.giveup(&GiveUpEvent {
+                            endpoint: Some(endpoint),
+                            source,
+                            attempts: attempt,
+                            error: &error,
+                        });
+                    }
+                    return Err(error.with_context(endpoint, &url, source));
+                }
+
+                let delay = retry.delay(attempt, &error);
+                instrument::trace_retry(attempt, delay, &error);
+                self.observers.retry(&RetryEvent {
+                    endpoint: Some(endpoint),
+                    source,
+                    attempt,
+                    delay,
+                    error: &error,
+                });
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+        })
+        .await
+    }
+
+    /// One attempt at a request: middleware (with the `blocking` feature), client-side
+    /// limits, observers, metrics and tracing apply as for the blocking client.
+    async fn attempt<F, Fut>(
+        &self,
+        endpoint: &Endpoint,
+        url: &Url,
+        source: Option<&Path>,
+        priority: Priority,
+        attempt: u32,
+        build: &mut F,
+    ) -> Result<(StatusCode, Value), TraceixError>
+    where
+        F: FnMut(RequestBuilder) -> Fut,
+        Fut: Future<Output = Result<RequestBuilder, TraceixError>>,
+    {
+        let request = self.client.post(url.clone()).headers(self.headers.clone());
+        #[cfg_attr(not(feature = "blocking"), allow(unused_mut))]
+        let mut request = build(request).await?.build()?;
+        let timeout = request.timeout().copied().unwrap_or(self.timeout);
+        #[cfg(feature = "blocking")]
+        self.middleware.request_async(endpoint, &mut request)?;
+        let _permit = self.limits.acquire_async(endpoint.class, priority).await;
+        self.observers.request(&RequestEvent {
+            endpoint,
+            url,
+            source,
+        });
+
+        let started = Instant::now();
+        let result = async {
+            let resp = self.client.execute(request).await?;
+            let status = resp.status();
+            let headers = resp.headers().clone();
+            if status.is_client_error() || status.is_server_error() {
+                let body = resp.bytes().await.unwrap_or_default();
+                return Err(TraceixError::api(status, &headers, &body));
+            }
+            let body = resp.bytes().await?;
+            #[cfg(feature = "blocking")]
+            self.middleware
+                .response_async(endpoint, status, &headers, &body)?;
+            let json = reqwest::Response::from(http::Response::new(body))
+                .json()
+                .await?;
+            Ok((status, json))
+        }
+        .await
+        .map_err(|e: TraceixError| e.timed_out(Some(timeout), self.connect_timeout));
+
+        let elapsed = started.elapsed();
+        let status = match &result {
+            Ok((status, _)) => Some(*status),
+            Err(e) => e.status(),
+        };
+        instrument::record_request(endpoint, status.map(|s| s.as_u16()), elapsed);
+        instrument::trace_attempt(
+            attempt,
+            status.map(|s| s.as_u16()),
+            elapsed,
+            result.as_ref().err(),
+        );
+
+        self.observers.response(&ResponseEvent {
+            endpoint,
+            url,
+            source,
+            status,
+            elapsed,
+            error: result.as_ref().err(),
+        });
+        result
+    }
+
+    async fn post_json(&self, endpoint: &Endpoint, body: &Value) -> Result<Value, TraceixError> {
+        self.send(
+            endpoint,
+            None,
+            Priority::default(),
+            &self.retry,
+            |request| async { Ok(request.json(body)) },
+        )
+        .await
+    }
+
+    async fn upload_file(
+        &self,
+        endpoint: &Endpoint,
+        filename: &Path,
+        options: &UploadOptions,
+    ) -> Result<Value, TraceixError> {
+        self.send(
+            endpoint,
+            Some(filename),
+            options.priority,
+            &self.retry,
+            move |request| async move {
+                let mut request = request.multipart(self.file_form(filename, options).await?);
+                if let Some(timeout) = options.timeout {
+                    request = request.timeout(timeout);
+                }
+                Ok(request)
+            },
+        )
+        .await
+    }
+
+    /// Multipart body streaming `filename` from disk, plus the fields carried by `options`.
+    async fn file_form(
+        &self,
+        filename: &Path,
+        options: &UploadOptions,
+    ) -> Result<multipart::Form, TraceixError> {
+        if let Some(preflight) = &self.preflight {
+            preflight.check(filename)?;
+        }
+        let part = if options.strip_metadata {
+            let original = Zeroizing::new(tokio::fs::read(filename).await?);
+            let data = Zeroizing::new(exif::strip_exif_bytes(&original)?);
+            let len = data.len() as u64;
+            // Owned by the body so the copy is wiped once the request is done with it.
+            multipart::Part::stream_with_length(Body::from(Bytes::from_owner(data)), len)
+        } else {
+            let file = tokio::fs::File::open(filename).await?;
+            let len = file.metadata().await?.len();
+            multipart::Part::stream_with_length(Body::from(file), len)
+        };
+        let part = part
+            .file_name(upload_file_name(filename))
+            .mime_str("application/octet-stream")?;
+
+        Ok(upload_form(part, options))
+    }
+
+    /// Send a file to Traceix for AI classification.
+    pub async fn ai_prediction(&self, filename: impl AsRef<Path>) -> Result<Value, TraceixError> {
+        self.ai_prediction_with_options(filename, &UploadOptions::default())
+            .await
+    }
+
+    /// AI classification with explicit upload options (e.g. priority).
+    pub async fn ai_prediction_with_options(
+        &self,
+        filename: impl AsRef<Path>,
+        options: &UploadOptions,
+    ) -> Result<Value, TraceixError> {
+        self.upload_file(&Endpoint::AI_PREDICTION, filename.as_ref(), options)
+            .await
+    }
+
+    /// Send everything read from `reader` for AI classification under `filename`.
+    ///
+    /// The body is streamed as the reader yields data, so sockets, tokio files and
+    /// decompression streams are piped through without being held in memory; a slow
+    /// connection simply stops the reader from being polled. Metadata stripping needs
+    /// the whole file and is rejected here.
+    ///
+    /// ```no_run
+    /// # use traceix_sdk::{AsyncTraceixSdk, UploadOptions};
+    /// # async fn run() -> Result<(), traceix_sdk::TraceixError> {
+    /// let sdk = AsyncTraceixSdk::new(None)?;
+    /// let file = tokio::fs::File::open("/path/to/sample.bin").await?;
+    /// let result = sdk
+    ///     .upload_reader(file, "sample.bin", &UploadOptions::default())
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn upload_reader(
+        &self,
+        reader: impl AsyncRead + Send + 'static,
+        filename: &str,
+        options: &UploadOptions,
+    ) -> Result<Value, TraceixError> {
+        if options.strip_metadata {
+            return Err(TraceixError::InvalidArgument(
+                "strip_metadata is not supported for streamed uploads".to_string(),
+            ));
+        }
+
+        let part = multipart::Part::stream(Body::wrap_stream(ReaderStream::new(reader)))
+            .file_name(filename.to_string())
+            .mime_str("application/octet-stream")?;
+        let mut form = Some(upload_form(part, options));
+
+        // The reader can only be consumed once, so the upload is never retried.
+        self.send(
+            &Endpoint::AI_PREDICTION,
+            None,
+            options.priority,
+            &RetryPolicy::none(),
+            move |mut request| {
+                let form = form.take();
+                async move {
+                    if let Some(timeout) = options.timeout {
+                        request = request.timeout(timeout);
+                    }
+                    Ok(request.multipart(form.expect("streamed uploads are sent once")))
+                }
+            },
+        )
+        .await
+    }
+
+    /// Extract the CAPA capabilities from the filename.
+    pub async fn capa_extraction(&self, filename: impl AsRef<Path>) -> Result<Value, TraceixError> {
+        self.capa_extraction_with_options(filename, &UploadOptions::default())
+            .await
+    }
+
+    /// Extract the CAPA capabilities with explicit upload options.
+    pub async fn capa_extraction_with_options(
+        &self,
+        filename: impl AsRef<Path>,
+        options: &UploadOptions,
+    ) -> Result<Value, TraceixError> {
+        self.upload_file(&Endpoint::CAPA_EXTRACTION, filename.as_ref(), options)
+            .await
+    }
+
+    /// Extract EXIF metadata from the filename.
+    pub async fn exif_extraction(&self, filename: impl AsRef<Path>) -> Result<Value, TraceixError> {
+        self.exif_extraction_with_options(filename, &UploadOptions::default())
+            .await
+    }
+
+    /// Extract EXIF metadata with explicit upload options.
+    pub async fn exif_extraction_with_options(
+        &self,
+        filename: impl AsRef<Path>,
+        options: &UploadOptions,
+    ) -> Result<Value, TraceixError> {
+        self.upload_file(&Endpoint::EXIF_EXTRACTION, filename.as_ref(), options)
+            .await
+    }
+
+    /// Full upload: runs the selected analyses (e.g. `Analyses::PREDICTION | Analyses::CAPA`).
+    pub async fn full_upload(
+        &self,
+        filename: impl AsRef<Path>,
+        analyses: Analyses,
+    ) -> Result<FullUpload, TraceixError> {
+        self.full_upload_with_options(filename, analyses, &UploadOptions::default())
+            .await
+    }
+
+    /// Like [`AsyncTraceixSdk::full_upload`], selecting analyses with named flags.
+    pub async fn full_upload_with(
+        &self,
+        filename: impl AsRef<Path>,
+        selection: AnalysisSelection,
+    ) -> Result<FullUpload, TraceixError> {
+        self.full_upload(filename, selection.into()).await
+    }
+
+    /// Full upload with explicit upload options (e.g. priority).
+    pub async fn full_upload_with_options(
+        &self,
+        filename: impl AsRef<Path>,
+        analyses: Analyses,
+        options: &UploadOptions,
+    ) -> Result<FullUpload, TraceixError> {
+        if analyses.is_empty() {
+            return Err(TraceixError::InvalidArgument(
+                "no analyses selected".to_string(),
+            ));
+        }
+
+        let filename = filename.as_ref();
+        let mut upload = FullUpload::default();
+        if analyses.contains(Analyses::PREDICTION) {
+            upload.prediction = Some(self.ai_prediction_with_options(filename, options).await?);
+        }
+        if analyses.contains(Analyses::CAPA) {
+            upload.capa = Some(self.capa_extraction_with_options(filename, options).await?);
+        }
+        if analyses.contains(Analyses::EXIF) {
+            upload.exif = Some(self.exif_extraction_with_options(filename, options).await?);
+        }
+        Ok(upload)
+    }
+
+    /// Check the status of an uploaded file using its UUID.
+    pub async fn check_status(&self, uuid: &str) -> Result<Value, TraceixError> {
+        if uuid.is_empty() {
+            return Err(TraceixError::NoUuidProvided);
+        }
+
+        self.post_json(
+            &Endpoint::CHECK_STATUS,
+            &serde_json::json!({ "uuid": uuid }),
+        )
+        .await
+    }
+
+    /// Fetch the status of an analysis as a [`JobStatus`].
+    pub async fn job_status(&self, uuid: &str) -> Result<JobStatus, TraceixError> {
+        let body = self.check_status(uuid).await?;
+        JobStatus::from_response(&body).ok_or_else(|| {
+            TraceixError::UnexpectedResponse(format!("no recognizable status in {body}"))
+        })
+    }
+
+    /// Cancel a queued or running analysis by UUID.
+    pub async fn cancel(&self, uuid: &str) -> Result<Value, TraceixError> {
+        if uuid.is_empty() {
+            return Err(TraceixError::NoUuidProvided);
+        }
+
+        self.post_json(&Endpoint::CANCEL, &serde_json::json!({ "uuid": uuid }))
+            .await
+    }
+
+    /// Search by file hash (capa or exif), validated as in
+    /// [`TraceixSdk::hash_search`](crate::TraceixSdk::hash_search).
+    pub async fn hash_search(
+        &self,
+        file_hash: impl AsRef<str>,
+        search_type: SearchType,
+    ) -> Result<Value, TraceixError> {
+        let endpoint = match search_type {
+            SearchType::Capa => &Endpoint::CAPA_SEARCH,
+            SearchType::Exif => &Endpoint::EXIF_SEARCH,
+        };
+        let hash = FileHash::parse(file_hash.as_ref())?;
+
+        self.post_json(
+            endpoint,
+            &serde_json::json!({ hash.field(): hash.as_str() }),
+        )
+        .await
+    }
+
+    /// List all public IPFS datasets currently available.
+    pub async fn list_all_ipfs_datasets(&self) -> Result<Value, TraceixError> {
+        self.send(
+            &Endpoint::IPFS_LIST_ALL,
+            None,
+            Priority::default(),
+            &self.retry,
+            |request| async { Ok(request) },
+        )
+        .await
+    }
+
+    /// Get a public IPFS dataset by CID. Malformed CIDs are rejected before any
+    /// request is made.
+    pub async fn get_public_ipfs_dataset(&self, cid: &str) -> Result<Value, TraceixError> {
+        let cid = Cid::parse(cid)?;
+        self.post_json(
+            &Endpoint::IPFS_SEARCH,
+            &serde_json::json!({ "cid": cid.to_string() }),
+        )
+        .await
+    }
+
+    /// Search by file hash to see if the dataset has been uploaded to the public domain.
+    pub async fn search_ipfs_dataset_by_hash(
+        &self,
+        file_hash: &str,
+    ) -> Result<Value, TraceixError> {
+        self.post_json(
+            &Endpoint::IPFS_FIND,
+            &serde_json::json!({ "sha_hash": file_hash }),
+        )
+        .await
+    }
+}
+
+/// A multipart form with the file `part` and the fields carried by `options`.
+fn upload_form(part: multipart::Part, options: &UploadOptions) -> multipart::Form {
+    upload_fields(options).into_iter().fold(
+        multipart::Form::new().part("file", part),
+        |form, (name, value)| form.text(name, value),
+    )
+}
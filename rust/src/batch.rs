@@ -0,0 +1,595 @@
+// src/batch.rs
+
+use crate::dead_letter::{DeadLetter, DeadLetterQueue};
+use crate::directory;
+use crate::error::display_chain;
+use crate::filter::{HashFilter, ListKind, ListMatch};
+use crate::instrument;
+use crate::observer::{GiveUpEvent, RetryEvent};
+use crate::{TraceixError, TraceixSdk, UploadOptions};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// A single input pushed into a batch.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BatchItem {
+    pub path: PathBuf,
+    pub options: UploadOptions,
+}
+
+/// A finished batch item together with the outcome of its operation.
+#[derive(Debug)]
+pub struct BatchResult<T> {
+    pub item: BatchItem,
+    /// Set when the file matched the batch's [`HashFilter`] and was still processed.
+    pub flag: Option<ListMatch>,
+    pub result: Result<T, TraceixError>,
+}
+
+/// An item skipped because it matched the allowlist.
+#[derive(Clone, Debug)]
+pub struct SuppressedItem {
+    pub item: BatchItem,
+    pub hash: String,
+}
+
+/// Options controlling how a batch is executed.
+#[derive(Clone, Debug)]
+pub struct BatchOptions {
+    /// Number of worker threads processing items concurrently.
+    pub workers: usize,
+    /// Allowlist/denylist checked against each file's hashes before it is processed.
+    pub filter: Option<Arc<HashFilter>>,
+    /// How many times an item is tried before it counts as permanently failed. Only
+    /// transient failures are tried again: rate limiting, timeouts, connection errors and
+    /// 5xx responses. Any other error fails the item at once.
+    pub max_attempts: u32,
+    /// Pause between attempts, multiplied by the attempt number.
+    pub retry_delay: Duration,
+    /// Where permanently failed items are recorded. Their results are still delivered.
+    pub dead_letters: Option<Arc<DeadLetterQueue>>,
+}
+
+impl Default for BatchOptions {
+    fn default() -> Self {
+        Self {
+            workers: 4,
+            filter: None,
+            max_attempts: 1,
+            retry_delay: Duration::from_secs(1),
+            dead_letters: None,
+        }
+    }
+}
+
+/// The single analysis a bulk directory pipeline runs on every file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BulkAnalysis {
+    /// AI prediction only.
+    Prediction,
+    /// EXIF extraction only, e.g. for photo-heavy investigations.
+    Exif,
+}
+
+#[derive(Default)]
+struct Queue {
+    // One lane per priority, indexed by `Priority as usize`.
+    lanes: [VecDeque<BatchItem>; 3],
+    closed: bool,
+}
+
+impl Queue {
+    fn pop(&mut self) -> Option<BatchItem> {
+        self.lanes
+            .iter_mut()
+            .rev()
+            .find_map(|lane| lane.pop_front())
+    }
+}
+
+#[derive(Default)]
+struct Shared {
+    queue: Mutex<Queue>,
+    ready: Condvar,
+    suppressed: Mutex<Vec<SuppressedItem>>,
+    // Items a worker has taken off the queue but not finished, keyed by a per-batch id.
+    active: Mutex<HashMap<u64, BatchItem>>,
+    idle: Condvar,
+    next_id: AtomicU64,
+}
+
+/// Marks an item as in flight until dropped.
+struct InFlight<'a> {
+    shared: &'a Shared,
+    id: u64,
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.shared.active.lock().unwrap().remove(&self.id);
+        self.shared.idle.notify_all();
+    }
+}
+
+impl Shared {
+    fn push(&self, item: BatchItem) -> Result<(), TraceixError> {
+        let mut queue = self.queue.lock().unwrap();
+        if queue.closed {
+            return Err(TraceixError::BatchClosed);
+        }
+        queue.lanes[item.options.priority as usize].push_back(item);
+        self.ready.notify_one();
+        Ok(())
+    }
+
+    fn close(&self) {
+        self.queue.lock().unwrap().closed = true;
+        self.ready.notify_all();
+    }
+
+    /// Blocks until an item is available, or returns `None` once the queue is closed and drained.
+    ///
+    /// The item stays registered as in flight until the returned guard is dropped.
+    fn next(&self) -> Option<(InFlight<'_>, BatchItem)> {
+        let mut queue = self.queue.lock().unwrap();
+        loop {
+            if let Some(item) = queue.pop() {
+                // Registered while the queue is still locked, so `shutdown` never
+                // sees an item that is neither queued nor active.
+                let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+                self.active.lock().unwrap().insert(id, item.clone());
+                return Some((InFlight { shared: self, id }, item));
+            }
+            if queue.closed {
+                return None;
+            }
+            queue = self.ready.wait(queue).unwrap();
+        }
+    }
+}
+
+/// Producer side of a batch. Cheap to clone and hand to other threads.
+#[derive(Clone)]
+pub struct BatchSender {
+    shared: Arc<Shared>,
+}
+
+impl BatchSender {
+    /// Queue a file with default upload options.
+    pub fn push(&self, path: impl Into<PathBuf>) -> Result<(), TraceixError> {
+        self.push_with_options(path, UploadOptions::default())
+    }
+
+    /// Queue a file with explicit upload options. Higher priority items are picked up first.
+    pub fn push_with_options(
+        &self,
+        path: impl Into<PathBuf>,
+        options: UploadOptions,
+    ) -> Result<(), TraceixError> {
+        self.shared.push(BatchItem {
+            path: path.into(),
+            options,
+        })
+    }
+
+    /// Stop accepting new items. Items already queued are still processed.
+    pub fn close(&self) {
+        self.shared.close();
+    }
+
+    /// Queue every item from a checkpoint, interrupted ones first. Returns how many were queued.
+    pub fn restore(&self, checkpoint: &BatchCheckpoint) -> Result<usize, TraceixError> {
+        let mut restored = 0;
+        for item in checkpoint.items() {
+            self.shared.push(item.clone())?;
+            restored += 1;
+        }
+        Ok(restored)
+    }
+}
+
+/// Queue state captured by [`BatchHandle::shutdown`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BatchCheckpoint {
+    /// Items that had not been started.
+    pub pending: Vec<BatchItem>,
+    /// Items still in flight when the shutdown deadline passed.
+    pub interrupted: Vec<BatchItem>,
+    pub saved_at: chrono::DateTime<Utc>,
+}
+
+impl BatchCheckpoint {
+    /// Whether the batch stopped with nothing left to do.
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty() && self.interrupted.is_empty()
+    }
+
+    /// Interrupted items followed by pending ones.
+    pub fn items(&self) -> impl Iterator<Item = &BatchItem> {
+        self.interrupted.iter().chain(&self.pending)
+    }
+
+    /// Write the checkpoint as JSON to `path`, replacing it atomically.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), TraceixError> {
+        let path = path.as_ref();
+        let tmp = path.with_extension("part");
+        let write = || -> io::Result<()> {
+            let mut writer = BufWriter::new(File::create(&tmp)?);
+            serde_json::to_writer_pretty(&mut writer, self)?;
+            writer.flush()
+        };
+        write().map_err(|e| TraceixError::file(&tmp, e))?;
+        fs::rename(&tmp, path).map_err(|e| TraceixError::file(path, e))
+    }
+
+    /// Read a checkpoint written with [`BatchCheckpoint::save`].
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, TraceixError> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| TraceixError::file(path, e))?;
+        serde_json::from_reader(BufReader::new(file)).map_err(|e| {
+            if e.is_io() {
+                return TraceixError::file(path, e.into());
+            }
+            TraceixError::InvalidArgument(format!(
+                "invalid batch checkpoint {}: {e}",
+                path.display()
+            ))
+        })
+    }
+}
+
+/// A running batch: push inputs in, receive results as they complete.
+///
+/// Results arrive in completion order, not submission order. The result
+/// channel disconnects once the batch is closed and every queued item has
+/// been processed. Dropping the handle closes the batch.
+pub struct BatchHandle<T> {
+    sender: BatchSender,
+    results: Receiver<BatchResult<T>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl<T> BatchHandle<T> {
+    /// A producer that can push items from other threads.
+    pub fn sender(&self) -> BatchSender {
+        self.sender.clone()
+    }
+
+    /// Queue a file with default upload options.
+    pub fn push(&self, path: impl Into<PathBuf>) -> Result<(), TraceixError> {
+        self.sender.push(path)
+    }
+
+    /// Queue a file with explicit upload options.
+    pub fn push_with_options(
+        &self,
+        path: impl Into<PathBuf>,
+        options: UploadOptions,
+    ) -> Result<(), TraceixError> {
+        self.sender.push_with_options(path, options)
+    }
+
+    /// Stop accepting new items.
+    pub fn close(&self) {
+        self.sender.close();
+    }
+
+    /// Block until the next result is available. Returns `None` once the batch is finished.
+    pub fn recv(&self) -> Option<BatchResult<T>> {
+        self.results.recv().ok()
+    }
+
+    /// The underlying result channel, for use with `select`-style consumers.
+    pub fn results(&self) -> &Receiver<BatchResult<T>> {
+        &self.results
+    }
+
+    /// Items skipped so far because they matched the allowlist.
+    pub fn suppressed(&self) -> Vec<SuppressedItem> {
+        self.sender.shared.suppressed.lock().unwrap().clone()
+    }
+
+    /// Iterate over results as they complete. Ends once the batch is finished.
+    pub fn iter(&self) -> mpsc::Iter<'_, BatchResult<T>> {
+        self.results.iter()
+    }
+
+    /// Stop the batch gracefully: reject new items, take everything still queued off the
+    /// queue, and wait up to `deadline` for in-flight items to finish.
+    ///
+    /// The returned checkpoint lists the unstarted items plus any still running at the
+    /// deadline; persist it with [`BatchCheckpoint::save`] and feed it to
+    /// [`BatchSender::restore`] in a later run. Results that completed before the deadline
+    /// can still be read from this handle. Interrupted items keep running in the
+    /// background, so restoring them may analyze a file twice.
+    pub fn shutdown(&self, deadline: Duration) -> BatchCheckpoint {
+        let shared = &self.sender.shared;
+        let pending = {
+            let mut queue = shared.queue.lock().unwrap();
+            queue.closed = true;
+            queue
+                .lanes
+                .iter_mut()
+                .rev()
+                .flat_map(|lane| lane.drain(..))
+                .collect()
+        };
+        shared.ready.notify_all();
+
+        let active = shared.active.lock().unwrap();
+        let (active, _) = shared
+            .idle
+            .wait_timeout_while(active, deadline, |active| !active.is_empty())
+            .unwrap();
+        let mut interrupted: Vec<(u64, BatchItem)> = active
+            .iter()
+            .map(|(id, item)| (*id, item.clone()))
+            .collect();
+        interrupted.sort_by_key(|(id, _)| *id);
+
+        BatchCheckpoint {
+            pending,
+            interrupted: interrupted.into_iter().map(|(_, item)| item).collect(),
+            saved_at: Utc::now(),
+        }
+    }
+
+    /// Close the batch and wait for all workers to finish, discarding unread results.
+    pub fn join(mut self) {
+        self.close();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl<T> Drop for BatchHandle<T> {
+    fn drop(&mut self) {
+        self.sender.close();
+    }
+}
+
+impl TraceixSdk {
+    /// Start a streaming batch that runs `op` for every pushed item on a pool of worker threads.
+    ///
+    /// ```no_run
+    /// # use traceix_sdk::{TraceixSdk, batch::BatchOptions};
+    /// # fn main() -> Result<(), traceix_sdk::TraceixError> {
+    /// let sdk = TraceixSdk::new(None)?;
+    /// let batch = sdk.batch(BatchOptions::default(), |sdk, item| {
+    ///     sdk.ai_prediction_with_options(&item.path, &item.options)
+    /// });
+    ///
+    /// batch.push("/evidence/a.exe")?;
+    /// batch.push("/evidence/b.dll")?;
+    /// batch.close();
+    ///
+    /// for done in batch.iter() {
+    ///     println!("{}: {:?}", done.item.path.display(), done.result.is_ok());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn batch<T, F>(&self, options: BatchOptions, op: F) -> BatchHandle<T>
+    where
+        T: Send + 'static,
+        F: Fn(&TraceixSdk, &BatchItem) -> Result<T, TraceixError> + Send + Sync + 'static,
+    {
+        let shared = Arc::new(Shared::default());
+        let op = Arc::new(op);
+        let (tx, rx) = mpsc::channel();
+
+        let workers = (0..options.workers.max(1))
+            .map(|_| {
+                let shared = Arc::clone(&shared);
+                let op = Arc::clone(&op);
+                let tx = tx.clone();
+                let sdk = self.clone();
+                let options = options.clone();
+                thread::spawn(move || {
+                    let filter = options.filter.as_ref();
+                    while let Some((_in_flight, item)) = shared.next() {
+                        let flag = match filter.map(|f| (f, f.check_file(&item.path))) {
+                            Some((_, Err(e))) => {
+                                let done = BatchResult {
+                                    item,
+                                    flag: None,
+                                    result: Err(e),
+                                };
+                                if tx.send(done).is_err() {
+                                    shared.close();
+                                    break;
+                                }
+                                continue;
+                            }
+                            Some((f, Ok(Some(m))))
+                                if m.kind == ListKind::Allow && f.suppress_allowlisted =>
+                            {
+                                let hash = m.hash;
+                                instrument::record_batch_item("suppressed");
+                                shared
+                                    .suppressed
+                                    .lock()
+                                    .unwrap()
+                                    .push(SuppressedItem { item, hash });
+                                continue;
+                            }
+                            Some((_, Ok(flag))) => flag,
+                            None => None,
+                        };
+
+                        let result = run_with_retries(&sdk, &item, &options, &*op);
+                        if tx.send(BatchResult { item, flag, result }).is_err() {
+                            // Receiver dropped; nobody is listening anymore.
+                            shared.close();
+                            break;
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        BatchHandle {
+            sender: BatchSender { shared },
+            results: rx,
+            workers,
+        }
+    }
+
+    /// Run a single analysis over every file under `dir` (recursively) using the batch machinery.
+    ///
+    /// Unlike [`TraceixSdk::full_upload`], only the selected analysis is requested, so no quota
+    /// is spent on the others. The returned batch is already closed.
+    pub fn bulk_directory(
+        &self,
+        dir: impl AsRef<Path>,
+        analysis: BulkAnalysis,
+        options: BatchOptions,
+    ) -> Result<BatchHandle<Value>, TraceixError> {
+        let files = directory::list_files(dir.as_ref())?;
+
+        let batch = self.batch(options, move |sdk, item| match analysis {
+            BulkAnalysis::Prediction => sdk.ai_prediction_with_options(&item.path, &item.options),
+            BulkAnalysis::Exif => sdk.exif_extraction_with_options(&item.path, &item.options),
+        });
+        for file in files {
+            batch.push(file)?;
+        }
+        batch.close();
+        Ok(batch)
+    }
+
+    /// Run only AI prediction over every file under `dir`.
+    pub fn bulk_predict(
+        &self,
+        dir: impl AsRef<Path>,
+        options: BatchOptions,
+    ) -> Result<BatchHandle<Value>, TraceixError> {
+        self.bulk_directory(dir, BulkAnalysis::Prediction, options)
+    }
+
+    /// Run only EXIF extraction over every file under `dir`.
+    pub fn bulk_exif(
+        &self,
+        dir: impl AsRef<Path>,
+        options: BatchOptions,
+    ) -> Result<BatchHandle<Value>, TraceixError> {
+        self.bulk_directory(dir, BulkAnalysis::Exif, options)
+    }
+}
+
+/// Run `op` up to `options.max_attempts` times, dead-lettering the item once it fails with an
+/// error that isn't [transient](is_transient) or runs out of attempts.
+fn run_with_retries<T>(
+    sdk: &TraceixSdk,
+    item: &BatchItem,
+    options: &BatchOptions,
+    op: &(dyn Fn(&TraceixSdk, &BatchItem) -> Result<T, TraceixError> + Send + Sync),
+) -> Result<T, TraceixError> {
+    let max_attempts = options.max_attempts.max(1);
+    let mut first_failed_at = None;
+    let mut attempt = 1;
+    loop {
+        let error = match op(sdk, item) {
+            Ok(value) => {
+                instrument::record_batch_item("ok");
+                return Ok(value);
+            }
+            Err(e) => e,
+        };
+        let failed_at = Utc::now();
+        let first_failed_at = *first_failed_at.get_or_insert(failed_at);
+
+        if attempt >= max_attempts || !is_transient(&error) {
+            instrument::record_batch_item("error");
+            sdk.observers.giveup(&GiveUpEvent {
+                endpoint: None,
+                source: Some(&item.path),
+                attempts: attempt,
+                error: &error,
+            });
+            if let Some(dead_letters) = &options.dead_letters {
+                instrument::record_batch_item("dead_lettered");
+                dead_letters.push(DeadLetter {
+                    item: item.clone(),
+                    error: display_chain(&error),
+                    attempts: attempt,
+                    first_failed_at,
+                    last_failed_at: failed_at,
+                });
+            }
+            return Err(error);
+        }
+
+        let delay = options.retry_delay * attempt;
+        sdk.observers.retry(&RetryEvent {
+            endpoint: None,
+            source: Some(&item.path),
+            attempt,
+            delay,
+            error: &error,
+        });
+        sdk.pause(delay)?;
+        attempt += 1;
+    }
+}
+
+/// Whether a failed item may succeed if tried again: the API was rate limiting, couldn't be
+/// reached, or failed on its side. Rejected requests, unreadable files, and cancellation are
+/// final.
+fn is_transient(error: &TraceixError) -> bool {
+    error.is_rate_limited() || error.is_unavailable()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderMap;
+    use reqwest::StatusCode;
+
+    fn api(status: u16) -> TraceixError {
+        TraceixError::api(
+            StatusCode::from_u16(status).unwrap(),
+            &HeaderMap::new(),
+            b"{}",
+        )
+    }
+
+    #[test]
+    fn rate_limits_and_server_errors_are_transient() {
+        assert!(is_transient(&api(429)));
+        assert!(is_transient(&api(500)));
+        assert!(is_transient(&api(503)));
+    }
+
+    #[test]
+    fn rejected_requests_and_local_errors_are_final() {
+        assert!(!is_transient(&api(400)));
+        assert!(!is_transient(&api(401)));
+        assert!(!is_transient(&api(413)));
+        assert!(!is_transient(&TraceixError::Cancelled));
+        assert!(!is_transient(&TraceixError::Io(std::io::Error::from(
+            std::io::ErrorKind::NotFound
+        ))));
+    }
+
+    #[test]
+    fn checkpoint_file_errors_name_the_file() {
+        let path = std::env::temp_dir().join("traceix-missing-dir/checkpoint.json");
+        match BatchCheckpoint::load(&path) {
+            Err(TraceixError::File { path: failed, .. }) => assert_eq!(failed, path),
+            other => panic!("expected a file error, got {:?}", other.err()),
+        }
+    }
+}
@@ -0,0 +1,299 @@
+// src/bin/traceix.rs
+//
+// Command-line front-end for the SDK. Build with `--features cli`.
+
+use clap::{Parser, Subcommand, ValueEnum};
+use serde_json::Value;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+use traceix_sdk::{Priority, SearchType, TraceixError, TraceixSdk, UploadOptions};
+
+#[derive(Parser)]
+#[command(
+    name = "traceix",
+    version,
+    about = "Traceix malware analysis from the command line"
+)]
+struct Cli {
+    /// API key. Falls back to TRACEIX_API_KEY, then to `api_key` in the config file.
+    #[arg(long, global = true)]
+    api_key: Option<String>,
+
+    /// API root, e.g. an on-prem mirror. Falls back to TRACEIX_BASE_URL / the config file.
+    #[arg(long, global = true)]
+    base_url: Option<String>,
+
+    /// Config file with `key = value` lines (`api_key`, `base_url`).
+    /// Defaults to $XDG_CONFIG_HOME/traceix/config or ~/.config/traceix/config.
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    /// How to print responses.
+    #[arg(long, short, global = true, value_enum, default_value_t = Output::Table)]
+    output: Output,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Output {
+    Json,
+    Table,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Upload a file for AI classification.
+    Upload {
+        file: PathBuf,
+        #[arg(long, value_enum, default_value_t = CliPriority::Normal)]
+        priority: CliPriority,
+        /// Tag the submission; may be repeated.
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+        /// Free-text description of the sample.
+        #[arg(long)]
+        description: Option<String>,
+        /// Investigation the sample belongs to.
+        #[arg(long)]
+        case_id: Option<String>,
+        /// Where the sample was collected.
+        #[arg(long)]
+        source: Option<String>,
+        /// Keep the sample out of public datasets.
+        #[arg(long)]
+        private: bool,
+        /// Remove EXIF, XMP and IPTC metadata from images before uploading.
+        #[arg(long)]
+        strip_metadata: bool,
+        /// Wait for the analysis to finish and print the final result.
+        #[arg(long)]
+        wait: bool,
+        /// Give up waiting after this many seconds.
+        #[arg(long, default_value_t = 600)]
+        timeout: u64,
+    },
+    /// Show the status of an analysis.
+    Status { uuid: String },
+    /// Search CAPA or EXIF results by MD5, SHA-1, or SHA-256 hash.
+    Search {
+        sha256: String,
+        #[arg(long = "type", value_enum, default_value_t = CliSearchType::Capa)]
+        search_type: CliSearchType,
+    },
+    /// Extract CAPA capabilities from a file.
+    Capa { file: PathBuf },
+    /// Extract EXIF metadata from a file.
+    Exif { file: PathBuf },
+    /// Public IPFS datasets.
+    Ipfs {
+        #[command(subcommand)]
+        command: IpfsCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum IpfsCommand {
+    /// List all public datasets.
+    List,
+    /// Fetch a dataset by CID.
+    Get { cid: String },
+    /// Find the dataset containing a file hash.
+    Find { hash: String },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum CliPriority {
+    Bulk,
+    Normal,
+    Urgent,
+}
+
+impl From<CliPriority> for Priority {
+    fn from(priority: CliPriority) -> Self {
+        match priority {
+            CliPriority::Bulk => Priority::Bulk,
+            CliPriority::Normal => Priority::Normal,
+            CliPriority::Urgent => Priority::Urgent,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum CliSearchType {
+    Capa,
+    Exif,
+}
+
+impl From<CliSearchType> for SearchType {
+    fn from(search_type: CliSearchType) -> Self {
+        match search_type {
+            CliSearchType::Capa => SearchType::Capa,
+            CliSearchType::Exif => SearchType::Exif,
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(&cli) {
+        Ok(response) => {
+            print(&response, cli.output);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            let mut message = e.to_string();
+            let mut source = std::error::Error::source(&e);
+            while let Some(cause) = source {
+                message = format!("{message}: {cause}");
+                source = cause.source();
+            }
+            eprintln!("traceix: {message}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: &Cli) -> Result<Value, TraceixError> {
+    let sdk = connect(cli)?;
+    match &cli.command {
+        Command::Upload {
+            file,
+            priority,
+            tags,
+            description,
+            case_id,
+            source,
+            private,
+            strip_metadata,
+            wait,
+            timeout,
+        } => {
+            let options = UploadOptions {
+                priority: (*priority).into(),
+                tags: tags.clone(),
+                description: description.clone(),
+                case_id: case_id.clone(),
+                source: source.clone(),
+                private: *private,
+                strip_metadata: *strip_metadata,
+                ..UploadOptions::default()
+            };
+            let submission = sdk.upload_with_options(file, &options)?;
+            if *wait {
+                submission.result_timeout(Duration::from_secs(*timeout))
+            } else {
+                Ok(submission.response().clone())
+            }
+        }
+        Command::Status { uuid } => sdk.check_status(uuid),
+        Command::Search {
+            sha256,
+            search_type,
+        } => sdk.hash_search(sha256, (*search_type).into()),
+        Command::Capa { file } => sdk.capa_extraction(file),
+        Command::Exif { file } => sdk.exif_extraction(file),
+        Command::Ipfs { command } => match command {
+            IpfsCommand::List => sdk.list_all_ipfs_datasets(),
+            IpfsCommand::Get { cid } => sdk.get_public_ipfs_dataset(cid),
+            IpfsCommand::Find { hash } => sdk.search_ipfs_dataset_by_hash(hash),
+        },
+    }
+}
+
+/// Build the SDK from flags, then the environment, then the config file.
+fn connect(cli: &Cli) -> Result<TraceixSdk, TraceixError> {
+    let config = read_config(cli.config.clone())?;
+    let setting = |flag: &Option<String>, var: &str, key: &str| {
+        flag.clone()
+            .or_else(|| env::var(var).ok().filter(|v| !v.is_empty()))
+            .or_else(|| {
+                config
+                    .iter()
+                    .find(|(k, _)| k == key)
+                    .map(|(_, v)| v.clone())
+            })
+    };
+
+    let mut builder =
+        TraceixSdk::builder().user_agent_suffix(concat!("traceix-cli/", env!("CARGO_PKG_VERSION")));
+    if let Some(key) = setting(&cli.api_key, "TRACEIX_API_KEY", "api_key") {
+        builder = builder.api_key(key);
+    }
+    if let Some(url) = setting(&cli.base_url, "TRACEIX_BASE_URL", "base_url") {
+        builder = builder.base_url(url);
+    }
+    builder.build()
+}
+
+/// `key = value` pairs from the config file. A missing default file is not an error.
+fn read_config(path: Option<PathBuf>) -> Result<Vec<(String, String)>, TraceixError> {
+    let explicit = path.is_some();
+    let Some(path) = path.or_else(default_config_path) else {
+        return Ok(Vec::new());
+    };
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if !explicit && e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    Ok(contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| {
+            let value = value.trim().trim_matches('"');
+            (key.trim().to_string(), value.to_string())
+        })
+        .collect())
+}
+
+fn default_config_path() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("traceix").join("config"))
+}
+
+fn print(response: &Value, output: Output) {
+    match output {
+        Output::Json => println!("{response:#}"),
+        Output::Table => {
+            let mut rows = Vec::new();
+            flatten("", response, &mut rows);
+            let width = rows.iter().map(|(key, _)| key.len()).max().unwrap_or(0);
+            for (key, value) in rows {
+                println!("{key:<width$}  {value}");
+            }
+        }
+    }
+}
+
+/// Flatten nested objects and arrays into `a.b[0].c` keys with scalar values.
+fn flatten(prefix: &str, value: &Value, rows: &mut Vec<(String, String)>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                let key = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                flatten(&key, value, rows);
+            }
+        }
+        Value::Array(items) if !items.is_empty() => {
+            for (i, item) in items.iter().enumerate() {
+                flatten(&format!("{prefix}[{i}]"), item, rows);
+            }
+        }
+        Value::String(s) => rows.push((prefix.to_string(), s.clone())),
+        other => rows.push((prefix.to_string(), other.to_string())),
+    }
+}
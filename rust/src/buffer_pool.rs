@@ -0,0 +1,83 @@
+// src/buffer_pool.rs
+
+use std::io::{self, Read};
+use std::mem;
+use std::sync::{Arc, Mutex};
+
+/// Idle buffers kept for reuse; anything beyond this is freed when returned.
+const MAX_IDLE_BUFFERS: usize = 32;
+
+/// Read buffers for streaming uploads, shared by every clone of an SDK instance so
+/// concurrent uploads (e.g. batch workers) reuse them instead of allocating one per file.
+#[derive(Debug)]
+pub(crate) struct BufferPool {
+    size: usize,
+    idle: Mutex<Vec<Box<[u8]>>>,
+}
+
+impl BufferPool {
+    pub(crate) fn new(size: usize) -> Self {
+        Self {
+            size,
+            idle: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn take(&self) -> Box<[u8]> {
+        self.idle
+            .lock()
+            .unwrap()
+            .pop()
+            .unwrap_or_else(|| vec![0; self.size].into_boxed_slice())
+    }
+
+    fn give(&self, buf: Box<[u8]>) {
+        let mut idle = self.idle.lock().unwrap();
+        if buf.len() == self.size && idle.len() < MAX_IDLE_BUFFERS {
+            idle.push(buf);
+        }
+    }
+
+    /// Buffer `inner` through a pooled buffer, returned to the pool when the reader is dropped.
+    pub(crate) fn reader<R: Read>(self: &Arc<Self>, inner: R) -> PooledReader<R> {
+        PooledReader {
+            inner,
+            buf: self.take(),
+            pos: 0,
+            filled: 0,
+            pool: Arc::clone(self),
+        }
+    }
+}
+
+/// A buffered reader whose buffer is borrowed from a [`BufferPool`].
+pub(crate) struct PooledReader<R> {
+    inner: R,
+    buf: Box<[u8]>,
+    pos: usize,
+    filled: usize,
+    pool: Arc<BufferPool>,
+}
+
+impl<R: Read> Read for PooledReader<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if self.pos == self.filled {
+            // Large reads bypass the buffer entirely, like `BufReader`.
+            if out.len() >= self.buf.len() {
+                return self.inner.read(out);
+            }
+            self.filled = self.inner.read(&mut self.buf)?;
+            self.pos = 0;
+        }
+        let n = out.len().min(self.filled - self.pos);
+        out[..n].copy_from_slice(&self.buf[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+impl<R> Drop for PooledReader<R> {
+    fn drop(&mut self) {
+        self.pool.give(mem::take(&mut self.buf));
+    }
+}
@@ -0,0 +1,623 @@
+// src/builder.rs
+
+#[cfg(feature = "blocking")]
+use crate::buffer_pool::BufferPool;
+#[cfg(feature = "blocking")]
+use crate::capture::RequestLog;
+use crate::endpoint::EndpointClass;
+#[cfg(feature = "blocking")]
+use crate::ipfs::GatewayPool;
+use crate::limits::{Limits, RateLimit};
+#[cfg(feature = "blocking")]
+use crate::middleware::{Middleware, MiddlewareChain};
+use crate::observer::{Observer, Observers};
+#[cfg(feature = "blocking")]
+use crate::options::Priority;
+use crate::proxy::ProxySettings;
+use crate::retry::RetryPolicy;
+use crate::secret::ApiKey;
+#[cfg(feature = "blocking")]
+use crate::throttle::Throttle;
+#[cfg(any(feature = "default-tls", feature = "rustls"))]
+use crate::tls::{ClientIdentity, TlsSettings};
+#[cfg(feature = "blocking")]
+use crate::transport::{SharedTransport, Transport};
+#[cfg(feature = "signed-results")]
+use crate::verify::ResultVerifier;
+#[cfg(feature = "async")]
+use crate::AsyncTraceixSdk;
+use crate::{
+    build_headers, build_user_agent, resolve_api_key, resolve_base_url, telemetry_from_env,
+    Preflight, TraceixError,
+};
+#[cfg(feature = "blocking")]
+use crate::{parse_base_url, TraceixSdk};
+#[cfg(feature = "blocking")]
+use reqwest::blocking::Client;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::Proxy;
+#[cfg(any(feature = "default-tls", feature = "rustls"))]
+use reqwest::{Certificate, Identity};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Default time limit for each request, see [`TraceixSdkBuilder::timeout`].
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default size of the read buffer used when streaming files into upload bodies.
+pub const DEFAULT_UPLOAD_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Configures and builds a [`TraceixSdk`] or, with the `async` feature, an
+/// [`AsyncTraceixSdk`](crate::AsyncTraceixSdk).
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # use traceix_sdk::{EndpointClass, RateLimit};
+/// # #[cfg(feature = "blocking")]
+/// # fn main() -> Result<(), traceix_sdk::TraceixError> {
+/// # use traceix_sdk::TraceixSdk;
+/// let sdk = TraceixSdk::builder()
+///     .api_key("your-api-key-here")
+///     .timeout(Duration::from_secs(120))
+///     .user_agent_suffix("my-pipeline/2.1")
+///     .upload_chunk_size(1024 * 1024)
+///     .max_upload_rate(2 * 1024 * 1024)
+///     .concurrency_limit(EndpointClass::Upload, 4)
+///     .concurrency_limit(EndpointClass::Search, 32)
+///     .rate_limit(RateLimit::per_second(10))
+///     .endpoint_rate_limit(EndpointClass::Upload, RateLimit::per_second(1))
+///     .build()?;
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "blocking"))]
+/// # fn main() {}
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TraceixSdkBuilder {
+    api_key: Option<ApiKey>,
+    base_url: Option<String>,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    proxy: ProxySettings,
+    #[cfg(any(feature = "default-tls", feature = "rustls"))]
+    tls: TlsSettings,
+    headers: Vec<(String, String)>,
+    user_agent_suffix: Option<String>,
+    telemetry: Option<bool>,
+    #[cfg(feature = "blocking")]
+    upload_chunk_size: Option<usize>,
+    #[cfg(feature = "blocking")]
+    max_upload_rate: Option<u64>,
+    preflight: Option<Preflight>,
+    concurrency_limits: HashMap<EndpointClass, usize>,
+    rate_limits: HashMap<EndpointClass, RateLimit>,
+    global_rate_limit: Option<RateLimit>,
+    observers: Observers,
+    retry: Option<RetryPolicy>,
+    #[cfg(feature = "blocking")]
+    transport: Option<SharedTransport>,
+    #[cfg(feature = "blocking")]
+    middleware: MiddlewareChain,
+    #[cfg(feature = "blocking")]
+    dry_run: bool,
+    #[cfg(feature = "blocking")]
+    capture_requests: Option<usize>,
+    #[cfg(feature = "blocking")]
+    ipfs_gateways: Vec<String>,
+    #[cfg(feature = "ipfs-node")]
+    ipfs_node: Option<String>,
+    #[cfg(feature = "signed-results")]
+    result_verification_key: Option<Vec<u8>>,
+}
+
+impl TraceixSdkBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// API key to authenticate with. Falls back to TRACEIX_API_KEY when unset.
+    pub fn api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(ApiKey::new(api_key.into()));
+        self
+    }
+
+    /// Root URL of the API, e.g. a staging server or an on-prem mirror.
+    /// May include a path prefix. Falls back to TRACEIX_BASE_URL, then
+    /// [`DEFAULT_BASE_URL`](crate::DEFAULT_BASE_URL).
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = Some(base_url.into());
+        self
+    }
+
+    /// Overall time limit for each request, from connecting until the response body has
+    /// been read. Defaults to [`DEFAULT_TIMEOUT`]; [`TraceixSdk::with_timeout`] overrides
+    /// it for a handle and [`UploadOptions::timeout`](crate::UploadOptions::timeout) for
+    /// single uploads. Exceeding it fails with [`TraceixError::Timeout`].
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Time limit for establishing a connection. Unlimited by default.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Send all requests through this proxy, e.g. `http://proxy.corp:3128` or, with the
+    /// `socks` feature, `socks5://proxy.corp:1080`. Without it, `HTTPS_PROXY` (or
+    /// `ALL_PROXY`) is used when set.
+    pub fn proxy(mut self, url: impl Into<String>) -> Self {
+        self.proxy.url = Some(url.into());
+        self
+    }
+
+    /// Authenticate to the proxy with HTTP Basic credentials. Credentials embedded in
+    /// the proxy URL work too; these take precedence.
+    pub fn proxy_credentials(
+        mut self,
+        username: impl Into<String>,
+        password: impl Into<String>,
+    ) -> Self {
+        self.proxy.credentials = Some((username.into(), password.into()));
+        self
+    }
+
+    /// Hosts to reach without the proxy, in `NO_PROXY` syntax: domains (matching their
+    /// subdomains too), IP addresses, or CIDR ranges. Replaces the `NO_PROXY` variable.
+    pub fn no_proxy_hosts<I, S>(mut self, hosts: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.proxy.no_proxy = Some(hosts.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Connect directly, ignoring [`proxy`](Self::proxy) and any proxy variables.
+    pub fn disable_proxy(mut self) -> Self {
+        self.proxy.disabled = true;
+        self
+    }
+
+    /// Trust the CA certificates in this PEM bundle in addition to the built-in roots,
+    /// e.g. an internal CA that intercepts TLS.
+    #[cfg(any(feature = "default-tls", feature = "rustls"))]
+    pub fn add_root_certificate(mut self, pem: impl Into<Vec<u8>>) -> Self {
+        self.tls.root_certificates.push(pem.into());
+        self
+    }
+
+    /// Only accept servers whose certificate chain includes a certificate with this
+    /// SHA-256 fingerprint (hex, colons optional). Add several pins to allow for
+    /// rotation; the normal chain and hostname checks still apply.
+    ///
+    /// Pinning switches TLS to rustls with the Mozilla root store, whichever TLS
+    /// feature is enabled.
+    #[cfg(feature = "cert-pinning")]
+    pub fn pin_server_certificate(mut self, sha256: impl Into<String>) -> Self {
+        self.tls.pins.push(sha256.into());
+        self
+    }
+
+    /// Present this client certificate to servers that require mutual TLS. Takes the
+    /// PEM certificate (chain) and the PEM private key; with the `default-tls` backend
+    /// the key must be PKCS#8 (`BEGIN PRIVATE KEY`).
+    #[cfg(any(feature = "default-tls", feature = "rustls"))]
+    pub fn client_identity_pem(
+        mut self,
+        certificate: impl Into<Vec<u8>>,
+        private_key: impl Into<Vec<u8>>,
+    ) -> Self {
+        self.tls.identity = Some(ClientIdentity::Pem {
+            certificate: certificate.into(),
+            key: private_key.into(),
+        });
+        self
+    }
+
+    /// Present the client certificate in this DER-encoded PKCS#12 (`.p12`/`.pfx`)
+    /// archive to servers that require mutual TLS.
+    #[cfg(feature = "default-tls")]
+    pub fn client_identity_pkcs12(
+        mut self,
+        der: impl Into<Vec<u8>>,
+        password: impl Into<String>,
+    ) -> Self {
+        self.tls.identity = Some(ClientIdentity::Pkcs12 {
+            der: der.into(),
+            password: password.into(),
+        });
+        self
+    }
+
+    /// Add a header sent with every request, e.g. a tenant or routing header required by
+    /// an API gateway in front of the server. Cannot replace the API key or user agent.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Text appended to the user agent, e.g. `my-pipeline/2.1`.
+    pub fn user_agent_suffix(mut self, suffix: impl Into<String>) -> Self {
+        self.user_agent_suffix = Some(suffix.into());
+        self
+    }
+
+    /// Whether the user agent reports the OS, architecture, and crate version.
+    /// Defaults to on unless TRACEIX_DISABLE_TELEMETRY=1.
+    pub fn telemetry(mut self, enabled: bool) -> Self {
+        self.telemetry = Some(enabled);
+        self
+    }
+
+    /// Size in bytes of the buffer files are read through while being streamed into
+    /// upload bodies: how much of the file each disk read fetches. Larger buffers mean
+    /// fewer reads, which helps with slow or network-mounted storage, at the cost of one
+    /// buffer per concurrent upload. How the body is written to the connection is up to
+    /// the HTTP client and unaffected. Defaults to [`DEFAULT_UPLOAD_CHUNK_SIZE`].
+    #[cfg(feature = "blocking")]
+    pub fn upload_chunk_size(mut self, bytes: usize) -> Self {
+        self.upload_chunk_size = Some(bytes.max(1));
+        self
+    }
+
+    /// Cap the combined upload bandwidth at `bytes_per_sec`, shared by all clones of the
+    /// SDK and all threads, so bulk submissions don't saturate a constrained uplink.
+    /// Uploads are unthrottled by default.
+    #[cfg(feature = "blocking")]
+    pub fn max_upload_rate(mut self, bytes_per_sec: u64) -> Self {
+        self.max_upload_rate = Some(bytes_per_sec.max(1));
+        self
+    }
+
+    /// Check every file against `preflight` before uploading it, failing with
+    /// [`TraceixError::FileTooLarge`] or [`TraceixError::UnsupportedFileType`] instead of
+    /// sending it. Files are uploaded unchecked by default.
+    pub fn preflight(mut self, preflight: Preflight) -> Self {
+        self.preflight = Some(preflight);
+        self
+    }
+
+    /// Allow at most `max` requests of `class` in flight at once, across all clones of the
+    /// SDK and all threads (e.g. 32 concurrent searches but only 4 concurrent uploads).
+    /// Classes without a limit are unbounded.
+    pub fn concurrency_limit(mut self, class: EndpointClass, max: usize) -> Self {
+        self.concurrency_limits.insert(class, max.max(1));
+        self
+    }
+
+    /// Cap the total request rate across all endpoints.
+    pub fn rate_limit(mut self, limit: RateLimit) -> Self {
+        self.global_rate_limit = Some(limit);
+        self
+    }
+
+    /// Cap the request rate for one endpoint class. Applies in addition to
+    /// [`TraceixSdkBuilder::rate_limit`], e.g. 10 requests/sec overall but 1 upload/sec.
+    pub fn endpoint_rate_limit(mut self, class: EndpointClass, limit: RateLimit) -> Self {
+        self.rate_limits.insert(class, limit);
+        self
+    }
+
+    /// How failed requests are retried. Defaults to [`RetryPolicy::default`];
+    /// use [`RetryPolicy::none`] to surface every failure immediately.
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// Sleep and retry when the server answers `429 Too Many Requests`, honouring its
+    /// `Retry-After` header up to [`RetryPolicy::max_retry_after`]. Shorthand for
+    /// [`RetryPolicy::respect_rate_limits`] on the configured (or default) retry policy.
+    pub fn respect_rate_limits(mut self, enabled: bool) -> Self {
+        self.retry = Some(self.retry.unwrap_or_default().respect_rate_limits(enabled));
+        self
+    }
+
+    /// Send requests through `transport` instead of the built-in `reqwest` client,
+    /// e.g. a mock in unit tests. Client settings such as the proxy and timeouts only
+    /// apply to the built-in client.
+    #[cfg(feature = "blocking")]
+    pub fn transport(mut self, transport: impl Transport + 'static) -> Self {
+        self.transport = Some(SharedTransport(Arc::new(transport)));
+        self
+    }
+
+    /// Add a middleware that can change every outgoing request, e.g. to sign it.
+    /// Request hooks run in registration order.
+    #[cfg(feature = "blocking")]
+    pub fn middleware(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Build every request without sending it. Inputs are validated and files opened as
+    /// usual, but each call then fails with [`TraceixError::DryRun`] carrying the
+    /// [`PlannedRequest`](crate::PlannedRequest); calls that make several requests stop
+    /// at the first. Useful to debug an integration or in CI jobs that must not reach
+    /// the live API.
+    ///
+    /// ```no_run
+    /// # use traceix_sdk::TraceixSdk;
+    /// # fn main() -> Result<(), traceix_sdk::TraceixError> {
+    /// let sdk = TraceixSdk::builder().dry_run(true).build()?;
+    /// let error = sdk.ai_prediction("sample.exe").unwrap_err();
+    /// let planned = error.planned_request().expect("nothing was sent");
+    /// println!("{planned} with headers {:?}", planned.headers);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "blocking")]
+    pub fn dry_run(mut self, enabled: bool) -> Self {
+        self.dry_run = enabled;
+        self
+    }
+
+    /// Keep the last `limit` requests with their responses, for
+    /// [`TraceixSdk::captured_requests`]. Each can be turned into a `curl` command with
+    /// [`CapturedRequest::to_curl`](crate::CapturedRequest::to_curl) to reproduce a
+    /// problem for support. Small response bodies are read into memory to capture them.
+    #[cfg(feature = "blocking")]
+    pub fn capture_requests(mut self, limit: usize) -> Self {
+        self.capture_requests = Some(limit);
+        self
+    }
+
+    /// Add an IPFS HTTP gateway that public datasets are downloaded from. Call it once
+    /// per gateway, most preferred first; when none are added,
+    /// [`DEFAULT_IPFS_GATEWAYS`](crate::DEFAULT_IPFS_GATEWAYS) are used. Downloads fail
+    /// over to the next gateway and favour those that have been fastest and most
+    /// reliable. Every block is verified against its CID, so any gateway, including a
+    /// local node's, is safe to use.
+    #[cfg(feature = "blocking")]
+    pub fn ipfs_gateway(mut self, url: impl Into<String>) -> Self {
+        self.ipfs_gateways.push(url.into());
+        self
+    }
+
+    /// Kubo RPC API of an IPFS node, e.g. [`DEFAULT_IPFS_NODE`](crate::DEFAULT_IPFS_NODE).
+    /// Datasets are then downloaded through this node instead of public gateways, and
+    /// can be pinned on it with [`TraceixSdk::pin_dataset`].
+    #[cfg(feature = "ipfs-node")]
+    pub fn ipfs_node(mut self, url: impl Into<String>) -> Self {
+        self.ipfs_node = Some(url.into());
+        self
+    }
+
+    /// Register an observer notified about every request, retry, and give-up.
+    /// Observers are called in registration order.
+    pub fn observer(mut self, observer: impl Observer + 'static) -> Self {
+        self.observers.push(Arc::new(observer));
+        self
+    }
+
+    /// Raw 32-byte Ed25519 public key used to check the detached JWS the server attaches
+    /// to result payloads. Results that verify report
+    /// [`AnalysisResult::verified`](crate::AnalysisResult::verified) as `true`.
+    #[cfg(feature = "signed-results")]
+    pub fn result_verification_key(mut self, public_key: impl AsRef<[u8]>) -> Self {
+        self.result_verification_key = Some(public_key.as_ref().to_vec());
+        self
+    }
+
+    #[cfg(feature = "blocking")]
+    pub fn build(self) -> Result<TraceixSdk, TraceixError> {
+        let headers = self.resolve_headers()?;
+        let base_url = resolve_base_url(self.base_url.as_deref())?;
+        let gateway_urls: Vec<&str> = if self.ipfs_gateways.is_empty() {
+            crate::DEFAULT_IPFS_GATEWAYS.to_vec()
+        } else {
+            self.ipfs_gateways.iter().map(String::as_str).collect()
+        };
+        let ipfs_gateways = gateway_urls
+            .into_iter()
+            .map(parse_base_url)
+            .collect::<Result<_, _>>()?;
+        #[cfg(feature = "ipfs-node")]
+        let ipfs_node = self.ipfs_node.as_deref().map(parse_base_url).transpose()?;
+
+        let timeout = self.timeout.unwrap_or(DEFAULT_TIMEOUT);
+        let client = self.configure(Client::builder())?.build()?;
+        let limits = Arc::new(self.limits());
+        let transport: Arc<dyn Transport> = match self.transport {
+            Some(SharedTransport(transport)) => transport,
+            None => Arc::new(client.clone()),
+        };
+
+        let upload_chunk_size = self.upload_chunk_size.unwrap_or(DEFAULT_UPLOAD_CHUNK_SIZE);
+
+        #[cfg(feature = "signed-results")]
+        let verifier = self
+            .result_verification_key
+            .as_deref()
+            .map(ResultVerifier::new)
+            .transpose()?
+            .map(Arc::new);
+
+        Ok(TraceixSdk {
+            headers,
+            base_url,
+            client,
+            limits,
+            observers: self.observers,
+            retry: Arc::new(self.retry.unwrap_or_default()),
+            timeout,
+            connect_timeout: self.connect_timeout,
+            transport,
+            middleware: self.middleware,
+            buffers: Arc::new(BufferPool::new(upload_chunk_size)),
+            throttle: self
+                .max_upload_rate
+                .map(|rate| Arc::new(Throttle::new(rate))),
+            preflight: self.preflight.map(Arc::new),
+            cancel: None,
+            priority: Priority::default(),
+            dry_run: self.dry_run,
+            capture: self
+                .capture_requests
+                .map(|limit| Arc::new(RequestLog::new(limit))),
+            ipfs_gateways: Arc::new(GatewayPool::new(ipfs_gateways)),
+            #[cfg(feature = "ipfs-node")]
+            ipfs_node,
+            #[cfg(feature = "signed-results")]
+            verifier,
+        })
+    }
+
+    /// Build a non-blocking [`AsyncTraceixSdk`] with these settings.
+    ///
+    /// It shares the API key, base URL, headers, user agent, timeouts, proxy, TLS
+    /// settings (including pinning and client certificates), concurrency and rate
+    /// limits, retry policy, preflight, middleware, and observers with the blocking
+    /// client. The custom transport, dry run, request capture, upload rate and chunk
+    /// size, IPFS, and result verification settings only apply to [`TraceixSdk`].
+    ///
+    /// Only the `async` feature is needed; the settings tied to [`TraceixSdk`] exist only
+    /// with the `blocking` feature, and so does middleware, whose hooks take
+    /// `reqwest::blocking` types. Middleware runs against a blocking mirror of each
+    /// request: changes to its method, URL, headers, timeout, and in-memory bodies are
+    /// carried over, and the response it sees is a buffered copy.
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use traceix_sdk::{AsyncTraceixSdk, EndpointClass, RetryPolicy};
+    /// # async fn run() -> Result<(), traceix_sdk::TraceixError> {
+    /// let sdk = AsyncTraceixSdk::builder()
+    ///     .timeout(Duration::from_secs(120))
+    ///     .proxy("http://proxy.corp:3128")
+    ///     .concurrency_limit(EndpointClass::Upload, 4)
+    ///     .retry_policy(RetryPolicy::default().max_attempts(5))
+    ///     .build_async()?;
+    /// let result = sdk.ai_prediction("/path/to/file.exe").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "async")]
+    pub fn build_async(self) -> Result<AsyncTraceixSdk, TraceixError> {
+        let headers = self.resolve_headers()?;
+        let base_url = resolve_base_url(self.base_url.as_deref())?;
+        let client = self.configure(reqwest::Client::builder())?.build()?;
+
+        Ok(AsyncTraceixSdk {
+            headers,
+            base_url,
+            client,
+            limits: Arc::new(self.limits()),
+            observers: self.observers,
+            retry: Arc::new(self.retry.unwrap_or_default()),
+            timeout: self.timeout.unwrap_or(DEFAULT_TIMEOUT),
+            connect_timeout: self.connect_timeout,
+            #[cfg(feature = "blocking")]
+            middleware: self.middleware,
+            preflight: self.preflight.map(Arc::new),
+        })
+    }
+
+    /// Headers sent with every request: the API key, the user agent, and the extra
+    /// headers, which cannot replace the first two.
+    fn resolve_headers(&self) -> Result<HeaderMap, TraceixError> {
+        let key = resolve_api_key(self.api_key.clone())?;
+        let user_agent = build_user_agent(
+            self.telemetry.unwrap_or_else(telemetry_from_env),
+            self.user_agent_suffix.as_deref(),
+        );
+        let mut headers = build_headers(&key, &user_agent)?;
+        for (name, value) in &self.headers {
+            let name = HeaderName::from_bytes(name.as_bytes()).map_err(|_| {
+                TraceixError::InvalidArgument(format!("invalid header name {name:?}"))
+            })?;
+            let value = HeaderValue::from_str(value).map_err(|_| {
+                TraceixError::InvalidArgument(format!("invalid value for header {name}"))
+            })?;
+            if !headers.contains_key(&name) {
+                headers.insert(name, value);
+            }
+        }
+        Ok(headers)
+    }
+
+    /// Apply the timeouts, TLS, and proxy settings to a blocking or async client.
+    fn configure<B: HttpClientBuilder>(&self, client: B) -> Result<B, TraceixError> {
+        let mut client = client.timeout(self.timeout.unwrap_or(DEFAULT_TIMEOUT));
+        if let Some(timeout) = self.connect_timeout {
+            client = client.connect_timeout(timeout);
+        }
+        #[cfg(any(feature = "default-tls", feature = "rustls"))]
+        let client = self.tls.apply(client)?;
+        self.proxy.apply(client)
+    }
+
+    fn limits(&self) -> Limits {
+        Limits::new(
+            &self.concurrency_limits,
+            &self.rate_limits,
+            self.global_rate_limit,
+        )
+    }
+}
+
+/// The `reqwest` client builder methods the shared settings use, so they configure the
+/// blocking and async clients alike.
+pub(crate) trait HttpClientBuilder: Sized {
+    fn timeout(self, timeout: Duration) -> Self;
+    fn connect_timeout(self, timeout: Duration) -> Self;
+    fn proxy(self, proxy: Proxy) -> Self;
+    fn no_proxy(self) -> Self;
+    #[cfg(any(feature = "default-tls", feature = "rustls"))]
+    fn add_root_certificate(self, certificate: Certificate) -> Self;
+    #[cfg(any(feature = "default-tls", feature = "rustls"))]
+    fn identity(self, identity: Identity) -> Self;
+    #[cfg(all(feature = "rustls", not(feature = "default-tls")))]
+    fn use_rustls_tls(self) -> Self;
+    #[cfg(feature = "cert-pinning")]
+    fn use_preconfigured_tls(self, config: rustls::ClientConfig) -> Self;
+}
+
+macro_rules! impl_http_client_builder {
+    ($builder:ty) => {
+        impl HttpClientBuilder for $builder {
+            fn timeout(self, timeout: Duration) -> Self {
+                <$builder>::timeout(self, timeout)
+            }
+
+            fn connect_timeout(self, timeout: Duration) -> Self {
+                <$builder>::connect_timeout(self, timeout)
+            }
+
+            fn proxy(self, proxy: Proxy) -> Self {
+                <$builder>::proxy(self, proxy)
+            }
+
+            fn no_proxy(self) -> Self {
+                <$builder>::no_proxy(self)
+            }
+
+            #[cfg(any(feature = "default-tls", feature = "rustls"))]
+            fn add_root_certificate(self, certificate: Certificate) -> Self {
+                <$builder>::add_root_certificate(self, certificate)
+            }
+
+            #[cfg(any(feature = "default-tls", feature = "rustls"))]
+            fn identity(self, identity: Identity) -> Self {
+                <$builder>::identity(self, identity)
+            }
+
+            #[cfg(all(feature = "rustls", not(feature = "default-tls")))]
+            fn use_rustls_tls(self) -> Self {
+                <$builder>::use_rustls_tls(self)
+            }
+
+            #[cfg(feature = "cert-pinning")]
+            fn use_preconfigured_tls(self, config: rustls::ClientConfig) -> Self {
+                <$builder>::use_preconfigured_tls(self, config)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "blocking")]
+impl_http_client_builder!(reqwest::blocking::ClientBuilder);
+#[cfg(feature = "async")]
+impl_http_client_builder!(reqwest::ClientBuilder);
@@ -0,0 +1,141 @@
+// src/cancel.rs
+
+use crate::TraceixError;
+use std::fmt;
+use std::io::{self, Read};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Aborts in-flight work on a [`TraceixSdk`](crate::TraceixSdk) handle created with
+/// [`with_cancellation`](crate::TraceixSdk::with_cancellation).
+///
+/// Clones share the same state, so one clone can be handed to a UI's cancel button or a
+/// shutdown handler while another is attached to the SDK. Once cancelled, a token stays
+/// cancelled; create a new one for the next operation.
+///
+/// The async client needs no token: dropping one of its futures, e.g. from
+/// `tokio::select!`, cancels the call.
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    cancelled: Mutex<bool>,
+    changed: Condvar,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel every operation using this token. Blocked calls return
+    /// [`TraceixError::Cancelled`] promptly.
+    pub fn cancel(&self) {
+        *self.inner.cancelled.lock().unwrap() = true;
+        self.inner.changed.notify_all();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        *self.inner.cancelled.lock().unwrap()
+    }
+
+    /// Fail with [`TraceixError::Cancelled`] if the token has been cancelled.
+    pub(crate) fn check(&self) -> Result<(), TraceixError> {
+        if self.is_cancelled() {
+            return Err(TraceixError::Cancelled);
+        }
+        Ok(())
+    }
+
+    /// Sleep for `duration`, waking early with [`TraceixError::Cancelled`] on cancellation.
+    pub(crate) fn sleep(&self, duration: Duration) -> Result<(), TraceixError> {
+        let deadline = Instant::now() + duration;
+        let mut cancelled = self.inner.cancelled.lock().unwrap();
+        loop {
+            if *cancelled {
+                return Err(TraceixError::Cancelled);
+            }
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                return Ok(());
+            }
+            cancelled = self.inner.changed.wait_timeout(cancelled, left).unwrap().0;
+        }
+    }
+
+    /// Run `work` on a helper thread and wait for it, giving up with
+    /// [`TraceixError::Cancelled`] as soon as the token is cancelled.
+    ///
+    /// A blocking request can't be interrupted, so the abandoned thread keeps running
+    /// until `work` returns and its result is dropped. Bodies read through a
+    /// [`CancellableReader`] fail at their next read, which ends an upload still being
+    /// sent, but a request waiting on the server holds its connection until the
+    /// response arrives or the request times out.
+    pub(crate) fn run<T, F>(&self, work: F) -> Result<T, TraceixError>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> Result<T, TraceixError> + Send + 'static,
+    {
+        self.check()?;
+        let slot = Arc::new(Mutex::new(None));
+        let filled = Arc::clone(&slot);
+        let token = self.clone();
+        thread::spawn(move || {
+            let result = work();
+            *filled.lock().unwrap() = Some(result);
+            // Notify under the lock so the waiter can't miss the wakeup between
+            // checking the slot and going to sleep.
+            let _cancelled = token.inner.cancelled.lock().unwrap();
+            token.inner.changed.notify_all();
+        });
+
+        let mut cancelled = self.inner.cancelled.lock().unwrap();
+        loop {
+            if let Some(result) = slot.lock().unwrap().take() {
+                return result;
+            }
+            if *cancelled {
+                return Err(TraceixError::Cancelled);
+            }
+            cancelled = self.inner.changed.wait(cancelled).unwrap();
+        }
+    }
+}
+
+impl fmt::Debug for CancellationToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CancellationToken")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+/// Fails reads from `inner` once an optional [`CancellationToken`] is cancelled, so an
+/// abandoned upload stops streaming its body and a download stops being written.
+pub(crate) struct CancellableReader<R> {
+    inner: R,
+    token: Option<CancellationToken>,
+}
+
+impl<R> CancellableReader<R> {
+    pub(crate) fn new(inner: R, token: Option<CancellationToken>) -> Self {
+        Self { inner, token }
+    }
+}
+
+impl<R: Read> Read for CancellableReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self
+            .token
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+        {
+            return Err(io::Error::other("operation cancelled"));
+        }
+        self.inner.read(buf)
+    }
+}
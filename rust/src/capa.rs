@@ -0,0 +1,341 @@
+// src/capa.rs
+
+//! Typed view of CAPA results, as returned by
+//! [`TraceixSdk::capa_extraction`](crate::TraceixSdk::capa_extraction) and
+//! [`TraceixSdk::get_capa_result`](crate::TraceixSdk::get_capa_result): the rules that
+//! matched, where they matched, and the MITRE ATT&CK techniques and MBC behaviors they
+//! map to.
+//!
+//! ```
+//! # use traceix_sdk::CapaReport;
+//! let result = serde_json::json!({
+//!     "rules": {
+//!         "create process on Windows": {
+//!             "meta": {
+//!                 "namespace": "host-interaction/process/create",
+//!                 "attack": [{ "tactic": "Execution", "technique": "System Services",
+//!                              "subtechnique": "Service Execution", "id": "T1569.002" }],
+//!                 "mbc": [{ "objective": "Process", "behavior": "Create Process",
+//!                           "id": "C0017" }],
+//!             },
+//!             "matches": [[{ "type": "absolute", "value": 4198400 }, {}]],
+//!         },
+//!     },
+//! });
+//! let report = CapaReport::from_value(&result)?;
+//! assert_eq!(report.attack_techniques()[0].id, "T1569.002");
+//! assert_eq!(report.rules_in_namespace("host-interaction/process").count(), 1);
+//! # Ok::<(), traceix_sdk::TraceixError>(())
+//! ```
+
+use crate::TraceixError;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Layer format version written by [`to_navigator_layer`], understood by ATT&CK
+/// Navigator 4.9 and later.
+const NAVIGATOR_LAYER_VERSION: &str = "4.5";
+
+/// Every rule that matched in one CAPA analysis.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct CapaReport {
+    /// Matched rules, sorted by name.
+    pub rules: Vec<CapaRule>,
+}
+
+/// One matched CAPA rule.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct CapaRule {
+    pub name: String,
+    /// Slash-separated category, e.g. `host-interaction/process/create`.
+    pub namespace: Option<String>,
+    /// Where the rule applies, e.g. `function` or `file`.
+    pub scopes: Vec<String>,
+    pub attack: Vec<AttackTechnique>,
+    pub mbc: Vec<MbcBehavior>,
+    /// Virtual addresses or file offsets of the matches, in ascending order. Empty for
+    /// file-scope rules.
+    pub addresses: Vec<u64>,
+}
+
+/// A MITRE ATT&CK technique a rule maps to.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct AttackTechnique {
+    /// Technique ID, e.g. `T1055` or `T1569.002`.
+    pub id: String,
+    #[serde(default)]
+    pub tactic: String,
+    #[serde(default)]
+    pub technique: String,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub subtechnique: Option<String>,
+}
+
+/// A Malware Behavior Catalog behavior a rule maps to.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct MbcBehavior {
+    /// Behavior ID, e.g. `C0017` or `E1055.m01`.
+    pub id: String,
+    #[serde(default)]
+    pub objective: String,
+    #[serde(default)]
+    pub behavior: String,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub method: Option<String>,
+}
+
+impl CapaReport {
+    /// Parse a CAPA result document. Accepts CAPA's own JSON output and the same
+    /// document wrapped in a `capa`, `result` or `data` field.
+    pub fn from_value(value: &Value) -> Result<Self, TraceixError> {
+        let rules = find_rules(value)
+            .ok_or_else(|| TraceixError::UnexpectedResponse(format!("no CAPA rules in {value}")))?;
+        let mut rules: Vec<CapaRule> = rules
+            .iter()
+            .map(|(name, rule)| CapaRule::from_value(name, rule))
+            .collect();
+        rules.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(Self { rules })
+    }
+
+    /// The distinct ATT&CK techniques of all matched rules, sorted by ID.
+    pub fn attack_techniques(&self) -> Vec<&AttackTechnique> {
+        let all: BTreeSet<&AttackTechnique> =
+            self.rules.iter().flat_map(|rule| &rule.attack).collect();
+        dedupe_by_id(all.into_iter(), |t| &t.id)
+    }
+
+    /// This report alone as an ATT&CK Navigator layer. See [`to_navigator_layer`].
+    pub fn to_navigator_layer(&self, name: &str) -> Value {
+        to_navigator_layer(name, [self])
+    }
+
+    /// The distinct MBC behaviors of all matched rules, sorted by ID.
+    pub fn mbc_behaviors(&self) -> Vec<&MbcBehavior> {
+        let all: BTreeSet<&MbcBehavior> = self.rules.iter().flat_map(|rule| &rule.mbc).collect();
+        dedupe_by_id(all.into_iter(), |b| &b.id)
+    }
+
+    /// The distinct namespaces of all matched rules, sorted.
+    pub fn namespaces(&self) -> BTreeSet<&str> {
+        self.rules
+            .iter()
+            .filter_map(|rule| rule.namespace.as_deref())
+            .collect()
+    }
+
+    /// Rules in `namespace` or any namespace below it, so `anti-analysis` also matches
+    /// `anti-analysis/anti-debugging/debugger-detection`.
+    pub fn rules_in_namespace<'a>(
+        &'a self,
+        namespace: &'a str,
+    ) -> impl Iterator<Item = &'a CapaRule> + 'a {
+        let namespace = namespace.trim_end_matches('/');
+        self.rules.iter().filter(move |rule| {
+            rule.namespace.as_deref().is_some_and(|ns| {
+                ns == namespace
+                    || ns
+                        .strip_prefix(namespace)
+                        .is_some_and(|rest| rest.starts_with('/'))
+            })
+        })
+    }
+
+    /// Rules mapped to the ATT&CK technique `id`. A technique ID such as `T1055` also
+    /// matches its sub-techniques, e.g. `T1055.012`.
+    pub fn rules_for_technique<'a>(
+        &'a self,
+        id: &'a str,
+    ) -> impl Iterator<Item = &'a CapaRule> + 'a {
+        self.rules.iter().filter(move |rule| {
+            rule.attack.iter().any(|t| {
+                t.id.eq_ignore_ascii_case(id)
+                    || t.id
+                        .split_once('.')
+                        .is_some_and(|(parent, _)| parent.eq_ignore_ascii_case(id))
+            })
+        })
+    }
+}
+
+impl CapaRule {
+    fn from_value(name: &str, rule: &Value) -> Self {
+        let meta = rule.get("meta").unwrap_or(&Value::Null);
+        let strings = |key: &str| -> Vec<String> {
+            match meta.get(key) {
+                Some(Value::String(s)) => vec![s.clone()],
+                Some(Value::Array(items)) => items
+                    .iter()
+                    .filter_map(|v| v.as_str().map(str::to_string))
+                    .collect(),
+                _ => Vec::new(),
+            }
+        };
+        // CAPA 7 splits `scope` into `scopes.static`/`scopes.dynamic`.
+        let scopes = match meta.get("scopes") {
+            Some(Value::Object(scopes)) => scopes
+                .values()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect(),
+            _ => strings("scope"),
+        };
+
+        let mut addresses: Vec<u64> = match rule.get("matches") {
+            // `[[address, match], ...]` with addresses like `{"type": "absolute", "value": n}`.
+            Some(Value::Array(matches)) => matches
+                .iter()
+                .filter_map(|m| m.get(0)?.get("value")?.as_u64())
+                .collect(),
+            // Older output keys matches by the address itself.
+            Some(Value::Object(matches)) => matches.keys().filter_map(|k| k.parse().ok()).collect(),
+            _ => Vec::new(),
+        };
+        addresses.sort_unstable();
+        addresses.dedup();
+
+        Self {
+            name: meta
+                .get("name")
+                .and_then(Value::as_str)
+                .unwrap_or(name)
+                .to_string(),
+            namespace: meta
+                .get("namespace")
+                .and_then(Value::as_str)
+                .filter(|ns| !ns.is_empty())
+                .map(str::to_string),
+            scopes,
+            attack: entries(meta.get("attack")),
+            mbc: entries(meta.get("mbc")),
+            addresses,
+        }
+    }
+}
+
+/// Build an ATT&CK Navigator layer named `name` from the techniques in `reports`, to
+/// load with "Open Existing Layer" in Navigator.
+///
+/// Each technique is scored with the number of matched rules mapping to it, summed
+/// over all reports, so techniques shared by several samples stand out when layering a
+/// campaign. The comment on each technique lists those rules.
+///
+/// ```
+/// # use traceix_sdk::capa::to_navigator_layer;
+/// # use traceix_sdk::CapaReport;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # let result = serde_json::json!({ "rules": {} });
+/// let report = CapaReport::from_value(&result)?;
+/// let layer = to_navigator_layer("dropper.exe", [&report]);
+/// println!("{}", serde_json::to_string_pretty(&layer)?);
+/// # Ok(())
+/// # }
+/// ```
+pub fn to_navigator_layer<'a>(
+    name: &str,
+    reports: impl IntoIterator<Item = &'a CapaReport>,
+) -> Value {
+    // Navigator places a technique per tactic, so the same ID under two tactics is two
+    // entries.
+    let mut techniques: BTreeMap<(&str, String), BTreeSet<&str>> = BTreeMap::new();
+    let mut scores: BTreeMap<(&str, String), u64> = BTreeMap::new();
+    for report in reports {
+        for rule in &report.rules {
+            let mut seen = BTreeSet::new();
+            for technique in &rule.attack {
+                let key = (technique.id.as_str(), tactic_slug(&technique.tactic));
+                if !seen.insert(key.clone()) {
+                    continue;
+                }
+                techniques
+                    .entry(key.clone())
+                    .or_default()
+                    .insert(&rule.name);
+                *scores.entry(key).or_default() += 1;
+            }
+        }
+    }
+
+    let max_score = scores.values().copied().max().unwrap_or(0).max(1);
+    let techniques: Vec<Value> = techniques
+        .into_iter()
+        .map(|((id, tactic), rules)| {
+            let mut entry = json!({
+                "techniqueID": id,
+                "score": scores[&(id, tactic.clone())],
+                "comment": rules.into_iter().collect::<Vec<_>>().join("; "),
+                "enabled": true,
+                "showSubtechniques": true,
+            });
+            if !tactic.is_empty() {
+                entry["tactic"] = tactic.into();
+            }
+            entry
+        })
+        .collect();
+
+    json!({
+        "name": name,
+        "versions": { "layer": NAVIGATOR_LAYER_VERSION },
+        "domain": "enterprise-attack",
+        "description": "Techniques identified by CAPA in Traceix analyses",
+        "techniques": techniques,
+        "gradient": {
+            "colors": ["#ffe766", "#ff6666"],
+            "minValue": 1,
+            "maxValue": max_score,
+        },
+        "hideDisabled": false,
+    })
+}
+
+/// Navigator's form of a tactic name, e.g. `defense-evasion` for "Defense Evasion".
+fn tactic_slug(tactic: &str) -> String {
+    tactic
+        .split_whitespace()
+        .map(str::to_ascii_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// The `rules` object of a CAPA document, looking through common wrappers.
+fn find_rules(value: &Value) -> Option<&Map<String, Value>> {
+    if let Some(rules) = value.get("rules").and_then(Value::as_object) {
+        return Some(rules);
+    }
+    ["capa", "result", "data"]
+        .iter()
+        .find_map(|key| find_rules(value.get(key)?))
+}
+
+/// Deserialize the ATT&CK or MBC entries of a rule, skipping malformed ones.
+fn entries<T: for<'de> Deserialize<'de>>(value: Option<&Value>) -> Vec<T> {
+    value
+        .and_then(Value::as_array)
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| T::deserialize(item).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Keep the first of each ID from an ordered sequence, since rules may describe the
+/// same technique with slightly different names.
+fn dedupe_by_id<'a, T>(items: impl Iterator<Item = &'a T>, id: impl Fn(&T) -> &str) -> Vec<&'a T> {
+    let mut seen = BTreeSet::new();
+    items
+        .filter(|item| seen.insert(id(item).to_string()))
+        .collect()
+}
+
+fn empty_as_none<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    Ok(Option::<String>::deserialize(deserializer)?.filter(|s| !s.is_empty()))
+}
@@ -0,0 +1,198 @@
+// src/capture.rs
+
+use crate::transport::buffered_response;
+use crate::{PlannedBody, PlannedRequest, TraceixError};
+use chrono::{DateTime, Utc};
+use reqwest::blocking::Response;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::StatusCode;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Responses up to this size are read into the capture; larger ones, such as
+/// downloads, are passed through unread.
+const MAX_CAPTURED_BODY: u64 = 64 * 1024;
+
+/// A request the SDK sent and what came back, recorded by a client built with
+/// [`TraceixSdkBuilder::capture_requests`](crate::TraceixSdkBuilder::capture_requests).
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct CapturedRequest {
+    /// The request as sent, with secret headers redacted.
+    pub request: PlannedRequest,
+    pub sent_at: DateTime<Utc>,
+    pub elapsed: Duration,
+    /// HTTP status, or `None` if no response was received.
+    pub status: Option<StatusCode>,
+    /// The response body, when it was small enough to capture.
+    pub response_body: Option<String>,
+    /// Why the request failed, if it did.
+    pub error: Option<String>,
+}
+
+impl CapturedRequest {
+    /// A `curl` command repeating the request, for a support ticket. The API key is
+    /// read from `$TRACEIX_API_KEY` and other secrets are left as `<redacted>`.
+    /// Multipart uploads are rendered with their file only, not their form fields.
+    ///
+    /// ```no_run
+    /// # use traceix_sdk::TraceixSdk;
+    /// # fn main() -> Result<(), traceix_sdk::TraceixError> {
+    /// let sdk = TraceixSdk::builder().capture_requests(20).build()?;
+    /// if let Err(e) = sdk.ai_prediction("sample.exe") {
+    ///     eprintln!("{e}");
+    ///     if let Some(last) = sdk.captured_requests().last() {
+    ///         eprintln!("reproduce with:\n{}", last.to_curl());
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_curl(&self) -> String {
+        let request = &self.request;
+        let multipart_file = match (&request.body, &request.file) {
+            (PlannedBody::Stream { content_type }, Some(file))
+                if content_type
+                    .as_deref()
+                    .is_some_and(|ct| ct.starts_with("multipart/form-data")) =>
+            {
+                Some(file)
+            }
+            _ => None,
+        };
+
+        let mut lines = vec![format!(
+            "curl -X {} {}",
+            request.method,
+            shell_quote(request.url.as_str())
+        )];
+        for (name, value) in &request.headers {
+            // curl picks its own multipart boundary.
+            if multipart_file.is_some() && name == "content-type" {
+                continue;
+            }
+            if name == "x-api-key" {
+                lines.push(format!("-H \"{name}: $TRACEIX_API_KEY\""));
+            } else {
+                lines.push(format!("-H {}", shell_quote(&format!("{name}: {value}"))));
+            }
+        }
+        match (&request.body, multipart_file) {
+            (_, Some(file)) => lines.push(format!(
+                "-F {}",
+                shell_quote(&format!("file=@{}", file.display()))
+            )),
+            (PlannedBody::Json(json), None) => {
+                lines.push(format!("--data-raw {}", shell_quote(&json.to_string())))
+            }
+            (PlannedBody::Bytes { len, .. }, None) => lines.push(format!(
+                "--data-binary @body.bin  # {len} bytes, not captured"
+            )),
+            (PlannedBody::Stream { .. }, None) => {
+                lines.push("--data-binary @body.bin  # streamed body, not captured".into())
+            }
+            (PlannedBody::Empty, None) => {}
+        }
+        lines.join(" \\\n  ")
+    }
+}
+
+/// Wrap `value` in single quotes for a POSIX shell.
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// The most recent requests of an SDK instance, oldest first.
+pub(crate) struct RequestLog {
+    limit: usize,
+    entries: Mutex<VecDeque<CapturedRequest>>,
+}
+
+impl RequestLog {
+    pub(crate) fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            entries: Mutex::new(VecDeque::with_capacity(limit)),
+        }
+    }
+
+    pub(crate) fn entries(&self) -> Vec<CapturedRequest> {
+        self.entries.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Record the outcome of `request`, reading a small response body into the
+    /// capture and handing back an equivalent response.
+    pub(crate) fn record(
+        &self,
+        request: PlannedRequest,
+        sent_at: DateTime<Utc>,
+        elapsed: Duration,
+        result: Result<Response, TraceixError>,
+    ) -> Result<Response, TraceixError> {
+        let (result, response_body) = match result {
+            Ok(resp) if capturable(&resp) => match buffer(resp) {
+                Ok((resp, body)) => (Ok(resp), Some(body)),
+                Err(e) => (Err(e), None),
+            },
+            Ok(resp) => (Ok(resp), None),
+            Err(e) => {
+                let body = match e.root() {
+                    TraceixError::Api { body, .. } => Some(truncate(body)),
+                    _ => None,
+                };
+                (Err(e), body)
+            }
+        };
+        let captured = CapturedRequest {
+            request,
+            sent_at,
+            elapsed,
+            status: match &result {
+                Ok(resp) => Some(resp.status()),
+                Err(e) => e.status(),
+            },
+            response_body,
+            error: result.as_ref().err().map(ToString::to_string),
+        };
+
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() == self.limit {
+            entries.pop_front();
+        }
+        if self.limit > 0 {
+            entries.push_back(captured);
+        }
+        result
+    }
+}
+
+/// Whether the body of `resp` is small enough, or JSON of unknown length.
+fn capturable(resp: &Response) -> bool {
+    match resp.content_length() {
+        Some(len) => len <= MAX_CAPTURED_BODY,
+        None => resp
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|ct| ct.to_str().ok())
+            .is_some_and(|ct| ct.starts_with("application/json")),
+    }
+}
+
+/// Read the body of `resp`, returning a response that replays it and its text.
+fn buffer(resp: Response) -> Result<(Response, String), TraceixError> {
+    let status = resp.status();
+    let headers = resp.headers().clone();
+    let url = resp.url().clone();
+    let bytes = resp.bytes()?.to_vec();
+    let text = truncate(&String::from_utf8_lossy(&bytes));
+    Ok((buffered_response(status, headers, url, bytes)?, text))
+}
+
+fn truncate(body: &str) -> String {
+    let mut end = body.len().min(MAX_CAPTURED_BODY as usize);
+    while !body.is_char_boundary(end) {
+        end -= 1;
+    }
+    body[..end].to_string()
+}
@@ -0,0 +1,336 @@
+// src/cassette.rs
+
+use crate::dry_run::SECRET_HEADERS;
+use crate::hashing::to_hex;
+use crate::transport::buffered_response;
+use crate::{TraceixError, Transport};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use reqwest::blocking::{Client, Request, Response};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use url::Url;
+
+/// Replaces scrubbed header values in recordings.
+const SCRUBBED: &str = "<scrubbed>";
+
+/// A [`Transport`] that records API traffic to a cassette file once and replays it in
+/// tests, VCR-style.
+///
+/// When recording, each request goes to the real API and the exchange is appended to a
+/// JSON cassette; when replaying, requests are answered from the cassette without
+/// touching the network.
+///
+/// Recordings are safe to commit: secret headers such as the API key are scrubbed, and
+/// request bodies other than JSON are kept only as a digest, so uploaded samples never
+/// end up in the cassette. Multipart boundaries are normalized first, so an upload of
+/// the same file with the same options matches its recording.
+///
+/// ```no_run
+/// # use traceix_sdk::{Cassette, TraceixSdk};
+/// # fn main() -> Result<(), traceix_sdk::TraceixError> {
+/// // Records on the first run (with a real API key), replays on every run after.
+/// let cassette = Cassette::new("tests/cassettes/status.json")?;
+/// let sdk = TraceixSdk::builder()
+///     .api_key(std::env::var("TRACEIX_API_KEY").unwrap_or_else(|_| "replay".into()))
+///     .transport(cassette)
+///     .build()?;
+/// let status = sdk.check_status("4f0e7a52-6a9c-4c69-9a47-2b1f0d0c9a11")?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Cassette {
+    path: PathBuf,
+    mode: Mode,
+    interactions: Mutex<Vec<Interaction>>,
+}
+
+enum Mode {
+    Record(Box<dyn Transport>),
+    /// Which recorded interactions have been played back.
+    Replay(Mutex<Vec<bool>>),
+}
+
+#[derive(Serialize, Deserialize)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+struct RecordedRequest {
+    method: String,
+    url: Url,
+    headers: Vec<(String, String)>,
+    body: RequestBody,
+}
+
+/// What identifies a request body: JSON in full, anything else by digest.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum RequestBody {
+    Empty,
+    Json(Value),
+    Digest { sha256: String, len: usize },
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct RecordedResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: ResponseBody,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ResponseBody {
+    Text(String),
+    Base64(String),
+}
+
+impl Cassette {
+    /// Replay `path` if it exists, otherwise record a new cassette there.
+    pub fn new(path: impl Into<PathBuf>) -> Result<Self, TraceixError> {
+        let path = path.into();
+        if path.exists() {
+            Self::replay(path)
+        } else {
+            Self::record(path)
+        }
+    }
+
+    /// Send requests to the API and record them to `path`, replacing any cassette there.
+    pub fn record(path: impl Into<PathBuf>) -> Result<Self, TraceixError> {
+        Self::record_with(path, Client::builder().build()?)
+    }
+
+    /// Like [`Cassette::record`], sending requests through `inner`, e.g. a client with
+    /// the proxy and TLS settings the API needs.
+    pub fn record_with(
+        path: impl Into<PathBuf>,
+        inner: impl Transport + 'static,
+    ) -> Result<Self, TraceixError> {
+        Ok(Self {
+            path: path.into(),
+            mode: Mode::Record(Box::new(inner)),
+            interactions: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// Answer requests from the cassette at `path`. A request with no matching
+    /// recording fails with [`TraceixError::InvalidArgument`]; each recording is played
+    /// back once, in order, so repeated calls such as status polls replay faithfully.
+    pub fn replay(path: impl Into<PathBuf>) -> Result<Self, TraceixError> {
+        let path = path.into();
+        let file: CassetteFile = serde_json::from_slice(&fs::read(&path)?).map_err(|e| {
+            TraceixError::InvalidArgument(format!("invalid cassette {}: {e}", path.display()))
+        })?;
+        let played = vec![false; file.interactions.len()];
+        Ok(Self {
+            path,
+            mode: Mode::Replay(Mutex::new(played)),
+            interactions: Mutex::new(file.interactions),
+        })
+    }
+
+    /// Path of the cassette file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether this cassette is recording rather than replaying.
+    pub fn is_recording(&self) -> bool {
+        matches!(self.mode, Mode::Record(_))
+    }
+
+    fn record_exchange(
+        &self,
+        inner: &dyn Transport,
+        request: Request,
+        recorded: RecordedRequest,
+    ) -> Result<Response, TraceixError> {
+        let response = inner.execute(request)?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        let url = response.url().clone();
+        let body = response.bytes()?.to_vec();
+
+        let mut interactions = self.interactions.lock().unwrap();
+        interactions.push(Interaction {
+            request: recorded,
+            response: RecordedResponse {
+                status: status.as_u16(),
+                headers: scrub(&headers),
+                body: match std::str::from_utf8(&body) {
+                    Ok(text) => ResponseBody::Text(text.to_string()),
+                    Err(_) => ResponseBody::Base64(STANDARD.encode(&body)),
+                },
+            },
+        });
+        // Saved after every exchange, so a test that fails halfway keeps what it recorded.
+        self.save(&interactions)?;
+        buffered_response(status, headers, url, body)
+    }
+
+    fn replay_exchange(
+        &self,
+        played: &Mutex<Vec<bool>>,
+        recorded: RecordedRequest,
+    ) -> Result<Response, TraceixError> {
+        let interactions = self.interactions.lock().unwrap();
+        let mut played = played.lock().unwrap();
+        let index = interactions
+            .iter()
+            .enumerate()
+            .position(|(i, interaction)| !played[i] && interaction.request.matches(&recorded))
+            .ok_or_else(|| {
+                TraceixError::InvalidArgument(format!(
+                    "no recording of {} {} left in cassette {}",
+                    recorded.method,
+                    recorded.url,
+                    self.path.display()
+                ))
+            })?;
+        played[index] = true;
+
+        let response = &interactions[index].response;
+        let status = StatusCode::from_u16(response.status).map_err(|_| {
+            TraceixError::InvalidArgument(format!("invalid status {} in cassette", response.status))
+        })?;
+        let mut headers = HeaderMap::new();
+        for (name, value) in &response.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(value),
+            ) {
+                headers.append(name, value);
+            }
+        }
+        let body = match &response.body {
+            ResponseBody::Text(text) => text.clone().into_bytes(),
+            ResponseBody::Base64(encoded) => STANDARD.decode(encoded).map_err(|e| {
+                TraceixError::InvalidArgument(format!("invalid body in cassette: {e}"))
+            })?,
+        };
+        buffered_response(status, headers, recorded.url, body)
+    }
+
+    /// Write the cassette next to its final location, then move it into place.
+    fn save(&self, interactions: &[Interaction]) -> Result<(), TraceixError> {
+        if let Some(dir) = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let file = CassetteFile {
+            interactions: interactions.to_vec(),
+        };
+        let partial = self.path.with_extension("partial");
+        fs::write(
+            &partial,
+            serde_json::to_vec_pretty(&file).expect("cassette serializes"),
+        )?;
+        fs::rename(&partial, &self.path)?;
+        Ok(())
+    }
+}
+
+impl Transport for Cassette {
+    fn execute(&self, mut request: Request) -> Result<Response, TraceixError> {
+        let recorded = RecordedRequest::new(&mut request)?;
+        match &self.mode {
+            Mode::Record(inner) => self.record_exchange(inner.as_ref(), request, recorded),
+            Mode::Replay(played) => self.replay_exchange(played, recorded),
+        }
+    }
+}
+
+impl RecordedRequest {
+    /// Describe `request`, reading its body into memory so it can still be sent.
+    fn new(request: &mut Request) -> Result<Self, TraceixError> {
+        let content_type = request
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|ct| ct.to_str().ok())
+            .map(str::to_string);
+        let body = match request.body_mut() {
+            None => RequestBody::Empty,
+            Some(body) => {
+                let bytes = body.buffer()?;
+                let json = (content_type.as_deref() == Some("application/json"))
+                    .then(|| serde_json::from_slice(bytes).ok())
+                    .flatten();
+                match json {
+                    Some(json) => RequestBody::Json(json),
+                    None => {
+                        let boundary = content_type
+                            .as_deref()
+                            .and_then(|ct| ct.split_once("boundary="))
+                            .map(|(_, boundary)| boundary.as_bytes())
+                            .filter(|boundary| !boundary.is_empty());
+                        let normalized = match boundary {
+                            Some(boundary) => replace(bytes, boundary, b"BOUNDARY"),
+                            None => bytes.to_vec(),
+                        };
+                        RequestBody::Digest {
+                            sha256: to_hex(&Sha256::digest(&normalized)),
+                            len: normalized.len(),
+                        }
+                    }
+                }
+            }
+        };
+
+        Ok(Self {
+            method: request.method().to_string(),
+            url: request.url().clone(),
+            headers: scrub(request.headers()),
+            body,
+        })
+    }
+
+    /// Requests match on method, URL and body; headers are kept for reference only.
+    fn matches(&self, other: &RecordedRequest) -> bool {
+        self.method == other.method && self.url == other.url && self.body == other.body
+    }
+}
+
+/// Headers as name/value pairs, with secret values scrubbed.
+fn scrub(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let secret = value.is_sensitive()
+                || SECRET_HEADERS.contains(&name.as_str())
+                || name.as_str() == "set-cookie";
+            let value = if secret {
+                SCRUBBED.to_string()
+            } else {
+                String::from_utf8_lossy(value.as_bytes()).into_owned()
+            };
+            (name.to_string(), value)
+        })
+        .collect()
+}
+
+/// `data` with every occurrence of `from` replaced by `to`.
+fn replace(data: &[u8], from: &[u8], to: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len());
+    let mut rest = data;
+    while let Some(at) = rest.windows(from.len()).position(|window| window == from) {
+        out.extend_from_slice(&rest[..at]);
+        out.extend_from_slice(to);
+        rest = &rest[at + from.len()..];
+    }
+    out.extend_from_slice(rest);
+    out
+}
@@ -0,0 +1,520 @@
+// src/cid.rs
+
+//! Content identifiers for IPFS datasets, parsed and validated locally so a malformed
+//! CID is rejected before any request is made.
+
+use crate::hashing::to_hex;
+use crate::TraceixError;
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha512};
+use std::fmt;
+use std::str::FromStr;
+
+/// Multicodec of dag-pb blocks, the UnixFS file and directory nodes.
+pub const DAG_PB: u64 = 0x70;
+/// Multicodec of raw blocks, holding file bytes as they are.
+pub const RAW: u64 = 0x55;
+
+/// Multihash codes of the hash functions a [`Cid`] can be verified with.
+pub const IDENTITY: u64 = 0x00;
+pub const SHA1: u64 = 0x11;
+pub const SHA2_256: u64 = 0x12;
+pub const SHA2_512: u64 = 0x13;
+
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const BASE32_ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum CidVersion {
+    /// A bare base58btc sha2-256 multihash of a dag-pb block, e.g. `Qm...`.
+    V0,
+    /// Self-describing: version, content codec and multihash, in any multibase.
+    V1,
+}
+
+/// A validated IPFS content identifier.
+///
+/// Parses CIDv0 and CIDv1 in base32, base58btc and base16. Displays in the canonical
+/// form: base58btc for CIDv0 and lowercase base32 for CIDv1.
+///
+/// ```
+/// use traceix_sdk::{Cid, CidVersion};
+///
+/// let v0: Cid = "QmdfTbBqBPQ7VNxZEYEj14VmRuZBkqFbiwReogJgS1zR1n".parse()?;
+/// assert_eq!(v0.version(), CidVersion::V0);
+/// let v1 = v0.to_v1();
+/// assert_eq!(
+///     v1.to_string(),
+///     "bafybeihdwdcefgh4dqkjv67uzcmw7ojee6xedzdetojuzjevtenxquvyku"
+/// );
+/// assert_eq!(v1.to_v0()?, v0);
+/// assert!("Qm-not-a-cid".parse::<Cid>().is_err());
+/// # Ok::<(), traceix_sdk::TraceixError>(())
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Cid {
+    version: CidVersion,
+    codec: u64,
+    hash_code: u64,
+    digest: Vec<u8>,
+}
+
+impl Cid {
+    /// Validate the text form of a CID, ignoring surrounding whitespace.
+    pub fn parse(text: &str) -> Result<Self, TraceixError> {
+        let text = text.trim();
+        let invalid = |reason: String| {
+            TraceixError::InvalidArgument(format!("invalid CID {text:?}: {reason}"))
+        };
+        if text.starts_with("Qm") {
+            if text.len() != 46 {
+                return Err(invalid(format!(
+                    "a CIDv0 has 46 characters, not {}",
+                    text.len()
+                )));
+            }
+            let bytes = base58_decode(text, 0).map_err(invalid)?;
+            return Self::from_bytes(&bytes).map_err(|e| reword(e, &invalid));
+        }
+        let mut chars = text.chars();
+        let bytes = match chars.next() {
+            Some('b') => base32_decode(chars.as_str()),
+            Some('B') => base32_decode(&chars.as_str().to_ascii_lowercase()),
+            Some('z') => base58_decode(chars.as_str(), 1),
+            Some('f' | 'F') => hex_decode(chars.as_str()),
+            Some(prefix) => {
+                return Err(invalid(format!(
+                    "unsupported multibase prefix {prefix:?}; expected 'b' (base32), \
+                     'z' (base58btc) or 'f' (base16)"
+                )))
+            }
+            None => return Err(invalid("empty".into())),
+        }
+        .map_err(invalid)?;
+        if bytes.first() == Some(&(SHA2_256 as u8)) {
+            return Err(invalid("a CIDv0 can't carry a multibase prefix".into()));
+        }
+        Self::from_bytes(&bytes).map_err(|e| reword(e, &invalid))
+    }
+
+    /// Validate the binary form of a CID, as found in dag-pb links.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, TraceixError> {
+        let (cid, len) = Self::read_prefix(bytes)?;
+        if len != bytes.len() {
+            return Err(TraceixError::InvalidArgument(format!(
+                "{} unexpected bytes after the multihash",
+                bytes.len() - len
+            )));
+        }
+        Ok(cid)
+    }
+
+    /// Read the binary CID at the start of `bytes`, returning it and its length.
+    pub(crate) fn read_prefix(bytes: &[u8]) -> Result<(Self, usize), TraceixError> {
+        let invalid = |reason: String| TraceixError::InvalidArgument(reason);
+        let mut at = 0;
+        // A CIDv0 is a bare sha2-256 multihash.
+        if bytes.first() == Some(&(SHA2_256 as u8)) {
+            let (hash_code, digest) = read_multihash(bytes, &mut at).map_err(invalid)?;
+            let cid = Self {
+                version: CidVersion::V0,
+                codec: DAG_PB,
+                hash_code,
+                digest,
+            };
+            return Ok((cid, at));
+        }
+        let version =
+            read_varint(bytes, &mut at).ok_or_else(|| invalid("truncated version".into()))?;
+        if version != 1 {
+            return Err(invalid(format!("unsupported CID version {version}")));
+        }
+        let codec = read_varint(bytes, &mut at).ok_or_else(|| invalid("truncated codec".into()))?;
+        let (hash_code, digest) = read_multihash(bytes, &mut at).map_err(invalid)?;
+        let cid = Self {
+            version: CidVersion::V1,
+            codec,
+            hash_code,
+            digest,
+        };
+        Ok((cid, at))
+    }
+
+    /// The binary form, as used inside blocks.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        if self.version == CidVersion::V1 {
+            write_varint(&mut bytes, 1);
+            write_varint(&mut bytes, self.codec);
+        }
+        write_varint(&mut bytes, self.hash_code);
+        write_varint(&mut bytes, self.digest.len() as u64);
+        bytes.extend_from_slice(&self.digest);
+        bytes
+    }
+
+    pub fn version(&self) -> CidVersion {
+        self.version
+    }
+
+    /// Multicodec of the content, e.g. [`DAG_PB`] or [`RAW`].
+    pub fn codec(&self) -> u64 {
+        self.codec
+    }
+
+    /// Multihash code of the hash function, e.g. [`SHA2_256`].
+    pub fn hash_code(&self) -> u64 {
+        self.hash_code
+    }
+
+    /// Name of the hash function, e.g. `sha2-256`.
+    pub fn hash_name(&self) -> &'static str {
+        match self.hash_code {
+            IDENTITY => "identity",
+            SHA1 => "sha1",
+            SHA2_256 => "sha2-256",
+            SHA2_512 => "sha2-512",
+            _ => "unknown",
+        }
+    }
+
+    /// The raw digest bytes.
+    pub fn digest(&self) -> &[u8] {
+        &self.digest
+    }
+
+    /// The same content as a CIDv1. CIDv1s are returned unchanged.
+    pub fn to_v1(&self) -> Cid {
+        Cid {
+            version: CidVersion::V1,
+            ..self.clone()
+        }
+    }
+
+    /// The same content as a CIDv0. Only dag-pb content hashed with sha2-256 has one.
+    pub fn to_v0(&self) -> Result<Cid, TraceixError> {
+        if self.codec != DAG_PB || self.hash_code != SHA2_256 || self.digest.len() != 32 {
+            return Err(TraceixError::InvalidArgument(format!(
+                "{self} has no CIDv0 form: only dag-pb content hashed with sha2-256 does"
+            )));
+        }
+        Ok(Cid {
+            version: CidVersion::V0,
+            ..self.clone()
+        })
+    }
+
+    /// Check that `data` is the block this CID names, failing with
+    /// [`TraceixError::IntegrityMismatch`] if it isn't.
+    pub fn verify(&self, data: &[u8]) -> Result<(), TraceixError> {
+        let actual = match self.hash_code {
+            IDENTITY => data.to_vec(),
+            SHA1 => Sha1::digest(data).to_vec(),
+            SHA2_256 => Sha256::digest(data).to_vec(),
+            SHA2_512 => Sha512::digest(data).to_vec(),
+            code => {
+                return Err(TraceixError::InvalidArgument(format!(
+                    "can't verify {self}: unsupported multihash 0x{code:x}"
+                )))
+            }
+        };
+        if actual != self.digest {
+            return Err(TraceixError::IntegrityMismatch {
+                algorithm: self.hash_name(),
+                expected: to_hex(&self.digest),
+                actual: to_hex(&actual),
+            });
+        }
+        Ok(())
+    }
+
+    /// The data of an identity CID, which is stored inline instead of hashed.
+    #[cfg(feature = "blocking")]
+    pub(crate) fn inline_data(&self) -> Option<&[u8]> {
+        (self.hash_code == IDENTITY).then_some(self.digest.as_slice())
+    }
+}
+
+/// Put the CID's text back into an error from [`Cid::from_bytes`].
+fn reword(error: TraceixError, invalid: &impl Fn(String) -> TraceixError) -> TraceixError {
+    match error {
+        TraceixError::InvalidArgument(reason) => invalid(reason),
+        e => e,
+    }
+}
+
+impl fmt::Display for Cid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.version {
+            CidVersion::V0 => f.write_str(&base58_encode(&self.to_bytes())),
+            CidVersion::V1 => write!(f, "b{}", base32_encode(&self.to_bytes())),
+        }
+    }
+}
+
+impl FromStr for Cid {
+    type Err = TraceixError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl TryFrom<String> for Cid {
+    type Error = TraceixError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Self::parse(&s)
+    }
+}
+
+impl From<Cid> for String {
+    fn from(cid: Cid) -> Self {
+        cid.to_string()
+    }
+}
+
+/// Read the multihash at `at` into its code and digest, checking the digest length.
+fn read_multihash(bytes: &[u8], at: &mut usize) -> Result<(u64, Vec<u8>), String> {
+    let code = read_varint(bytes, at).ok_or("truncated multihash")?;
+    let len = read_varint(bytes, at).ok_or("truncated multihash")?;
+    let available = bytes.len() - *at;
+    if (available as u64) < len {
+        return Err(format!(
+            "multihash declares {len} digest bytes but has {available}"
+        ));
+    }
+    let digest = &bytes[*at..*at + len as usize];
+    *at += len as usize;
+    let expected = match code {
+        SHA1 => Some(20),
+        SHA2_256 => Some(32),
+        SHA2_512 => Some(64),
+        _ => None,
+    };
+    if let Some(expected) = expected.filter(|&n| n != digest.len()) {
+        return Err(format!(
+            "a 0x{code:x} digest has {expected} bytes, not {}",
+            digest.len()
+        ));
+    }
+    Ok((code, digest.to_vec()))
+}
+
+/// Read an unsigned LEB128 varint at `at`, advancing past it.
+pub(crate) fn read_varint(bytes: &[u8], at: &mut usize) -> Option<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *bytes.get(*at)?;
+        *at += 1;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn bad_char(encoding: &str, c: char, at: usize) -> String {
+    format!("invalid {encoding} character {c:?} at position {at}")
+}
+
+fn base58_decode(text: &str, offset: usize) -> Result<Vec<u8>, String> {
+    let mut bytes: Vec<u8> = Vec::new();
+    for (i, c) in text.char_indices() {
+        let mut carry = BASE58_ALPHABET
+            .iter()
+            .position(|&a| char::from(a) == c)
+            .ok_or_else(|| bad_char("base58btc", c, i + offset))? as u32;
+        for byte in bytes.iter_mut().rev() {
+            carry += u32::from(*byte) * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.insert(0, carry as u8);
+            carry >>= 8;
+        }
+    }
+    let zeros = text.bytes().take_while(|&c| c == b'1').count();
+    let mut out = vec![0; zeros];
+    out.extend(bytes);
+    Ok(out)
+}
+
+fn base58_encode(bytes: &[u8]) -> String {
+    let mut digits: Vec<u8> = Vec::new();
+    for &byte in bytes {
+        let mut carry = u32::from(byte);
+        for digit in digits.iter_mut().rev() {
+            carry += u32::from(*digit) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.insert(0, (carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    let zeros = bytes.iter().take_while(|&&b| b == 0).count();
+    std::iter::repeat_n(b'1', zeros)
+        .chain(digits.iter().map(|&d| BASE58_ALPHABET[d as usize]))
+        .map(char::from)
+        .collect()
+}
+
+fn base32_decode(text: &str) -> Result<Vec<u8>, String> {
+    let mut out = Vec::with_capacity(text.len() * 5 / 8);
+    let (mut buffer, mut bits) = (0u32, 0);
+    for (i, c) in text.char_indices() {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|&a| char::from(a) == c)
+            .ok_or_else(|| bad_char("base32", c, i + 1))? as u32;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    Ok(out)
+}
+
+fn base32_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len() * 8 / 5 + 1);
+    let (mut buffer, mut bits) = (0u32, 0);
+    for &byte in bytes {
+        buffer = (buffer << 8) | u32::from(byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(char::from(
+                BASE32_ALPHABET[((buffer >> bits) & 31) as usize],
+            ));
+        }
+    }
+    if bits > 0 {
+        out.push(char::from(
+            BASE32_ALPHABET[((buffer << (5 - bits)) & 31) as usize],
+        ));
+    }
+    out
+}
+
+fn hex_decode(text: &str) -> Result<Vec<u8>, String> {
+    if let Some((i, c)) = text.char_indices().find(|(_, c)| !c.is_ascii_hexdigit()) {
+        return Err(bad_char("base16", c, i + 1));
+    }
+    if !text.len().is_multiple_of(2) {
+        return Err("base16 text has an odd number of digits".into());
+    }
+    Ok((0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&text[i..i + 2], 16).expect("checked hex digits"))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const V0: &str = "QmdfTbBqBPQ7VNxZEYEj14VmRuZBkqFbiwReogJgS1zR1n";
+    const V1: &str = "bafybeihdwdcefgh4dqkjv67uzcmw7ojee6xedzdetojuzjevtenxquvyku";
+
+    #[test]
+    fn text_forms_round_trip() {
+        for text in [V0, V1] {
+            assert_eq!(Cid::parse(text).unwrap().to_string(), text);
+        }
+        let v1 = Cid::parse(V1).unwrap();
+        let bytes = v1.to_bytes();
+        let hex = to_hex(&bytes);
+        let base58 = base58_encode(&bytes);
+        for text in [
+            format!("f{hex}"),
+            format!("F{}", hex.to_ascii_uppercase()),
+            format!("z{base58}"),
+            V1.to_ascii_uppercase(),
+            format!("  {V1}\n"),
+        ] {
+            assert_eq!(Cid::parse(&text).unwrap(), v1, "{text}");
+        }
+    }
+
+    #[test]
+    fn binary_forms_round_trip() {
+        for text in [V0, V1] {
+            let cid = Cid::parse(text).unwrap();
+            assert_eq!(Cid::from_bytes(&cid.to_bytes()).unwrap(), cid);
+        }
+    }
+
+    #[test]
+    fn verifies_block_contents() {
+        let cid = Cid::parse(&format!("f01551220{}", to_hex(&Sha256::digest(b"hello")))).unwrap();
+        assert_eq!(cid.codec(), RAW);
+        assert_eq!(cid.hash_name(), "sha2-256");
+        cid.verify(b"hello").unwrap();
+        assert!(matches!(
+            cid.verify(b"jello"),
+            Err(TraceixError::IntegrityMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_malformed_text() {
+        for text in [
+            "",
+            "Qm",
+            &V0[..45],
+            "QmdfTbBqBPQ7VNxZEYEj14VmRuZBkqFbiwReogJgS1zR10",
+            "xafybeihdwdcefgh4dqkjv67uzcmw7ojee6xedzdetojuzjevtenxquvyku",
+            "bafybeihdwdcefgh4dqkjv67uzcmw7ojee6xedzdetojuzjevtenxquvyk1",
+            &V1[..20],
+            "f0155122",
+            "f01551220zz",
+            "f1220aaaa",
+        ] {
+            assert!(
+                matches!(Cid::parse(text), Err(TraceixError::InvalidArgument(_))),
+                "{text:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_bytes() {
+        let bytes = Cid::parse(V1).unwrap().to_bytes();
+        for len in 0..bytes.len() {
+            assert!(Cid::from_bytes(&bytes[..len]).is_err(), "{len} bytes");
+        }
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(Cid::from_bytes(&trailing).is_err());
+
+        // Version 2, and a sha2-256 digest of the wrong length.
+        assert!(Cid::from_bytes(&[0x02, 0x55, 0x00, 0x00]).is_err());
+        assert!(Cid::from_bytes(&[0x01, 0x55, 0x12, 0x01, 0xaa]).is_err());
+        // A digest length beyond the end of the input.
+        assert!(Cid::from_bytes(&[0x01, 0x55, 0x00, 0xff, 0xff, 0x03]).is_err());
+    }
+
+    #[test]
+    fn only_sha256_dag_pb_has_a_v0_form() {
+        let v0 = Cid::parse(V0).unwrap();
+        assert_eq!(v0.to_v1().to_v0().unwrap(), v0);
+        let raw = Cid::parse(&format!("f01551220{}", "00".repeat(32))).unwrap();
+        assert!(raw.to_v0().is_err());
+    }
+}
@@ -0,0 +1,400 @@
+// src/datasets.rs
+
+use crate::pagination::{Cursor, Page, Paginator};
+use crate::{decode_response, Endpoint, TraceixError, TraceixSdk};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, VecDeque};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Default number of datasets per page of [`TraceixSdk::list_datasets`].
+pub const DEFAULT_DATASET_PAGE_SIZE: usize = 100;
+
+/// Shortest interval [`TraceixSdk::watch_datasets`] polls at.
+const MIN_WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Fetches one page of datasets for a [`DatasetPages`] paginator.
+type FetchDatasets<'a> =
+    dyn FnMut(Option<&Cursor>, usize) -> Result<Page<DatasetSummary>, TraceixError> + 'a;
+
+/// Iterator over every public dataset matching a [`DatasetListOptions`], returned by
+/// [`TraceixSdk::iter_datasets`].
+pub type DatasetPages<'a> = Paginator<DatasetSummary, Box<FetchDatasets<'a>>>;
+
+/// Filters and paging for [`TraceixSdk::list_datasets`]. Every filter is optional.
+#[derive(Clone, Debug)]
+pub struct DatasetListOptions {
+    /// Only datasets whose name contains this text, ignoring case.
+    pub name: Option<String>,
+    /// Only datasets carrying this tag.
+    pub tag: Option<String>,
+    /// Only datasets published after this time.
+    pub created_after: Option<DateTime<Utc>>,
+    /// Zero-based page to fetch, counted in `page_size` datasets.
+    pub page: u64,
+    pub page_size: usize,
+}
+
+impl Default for DatasetListOptions {
+    fn default() -> Self {
+        Self {
+            name: None,
+            tag: None,
+            created_after: None,
+            page: 0,
+            page_size: DEFAULT_DATASET_PAGE_SIZE,
+        }
+    }
+}
+
+impl DatasetListOptions {
+    fn matches(&self, dataset: &DatasetSummary) -> bool {
+        let name = self.name.as_ref().is_none_or(|wanted| {
+            dataset
+                .name
+                .as_ref()
+                .is_some_and(|name| name.to_lowercase().contains(&wanted.to_lowercase()))
+        });
+        let tag = self
+            .tag
+            .as_ref()
+            .is_none_or(|wanted| dataset.tags.iter().any(|tag| tag == wanted));
+        let created = self
+            .created_after
+            .is_none_or(|after| dataset.created_at.is_some_and(|created| created > after));
+        name && tag && created
+    }
+}
+
+/// A public IPFS dataset, as listed by [`TraceixSdk::list_datasets`].
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[non_exhaustive]
+pub struct DatasetSummary {
+    pub cid: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default, alias = "created", deserialize_with = "timestamp")]
+    pub created_at: Option<DateTime<Utc>>,
+    /// Total size of the dataset in bytes.
+    #[serde(default)]
+    pub size: Option<u64>,
+    /// Number of samples in the dataset.
+    #[serde(default, alias = "count", alias = "samples")]
+    pub sample_count: Option<u64>,
+}
+
+/// An RFC 3339 string or a Unix timestamp in seconds; anything else is dropped.
+fn timestamp<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<DateTime<Utc>>, D::Error> {
+    Ok(match Option::<Value>::deserialize(deserializer)? {
+        Some(Value::String(s)) => DateTime::parse_from_rfc3339(&s)
+            .ok()
+            .map(|t| t.with_timezone(&Utc)),
+        Some(Value::Number(n)) => n.as_i64().and_then(|s| DateTime::from_timestamp(s, 0)),
+        _ => None,
+    })
+}
+
+/// A change to the public dataset catalog, reported by [`TraceixSdk::watch_datasets`].
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum DatasetEvent {
+    /// A dataset that wasn't listed at the previous poll.
+    Added(DatasetSummary),
+    /// A dataset still listed under the same CID whose details, e.g. its name or tags,
+    /// have changed.
+    Updated {
+        previous: DatasetSummary,
+        current: DatasetSummary,
+    },
+    /// A dataset that is no longer listed.
+    Removed(DatasetSummary),
+}
+
+impl DatasetEvent {
+    /// The dataset as it is now, or as it was last listed for [`DatasetEvent::Removed`].
+    pub fn dataset(&self) -> &DatasetSummary {
+        match self {
+            DatasetEvent::Added(dataset) | DatasetEvent::Removed(dataset) => dataset,
+            DatasetEvent::Updated { current, .. } => current,
+        }
+    }
+
+    pub fn cid(&self) -> &str {
+        &self.dataset().cid
+    }
+}
+
+/// Blocking iterator over changes to the dataset catalog, returned by
+/// [`TraceixSdk::watch_datasets`].
+///
+/// Each call to `next` returns a change already seen, or else waits for the next poll.
+/// A poll that fails yields the error and the watch carries on at the following poll.
+/// It only ends once the SDK's [cancellation token](TraceixSdk::with_cancellation) is
+/// cancelled, after yielding [`TraceixError::Cancelled`].
+pub struct DatasetWatch<'a> {
+    sdk: &'a TraceixSdk,
+    interval: Duration,
+    next_poll: Instant,
+    known: Option<BTreeMap<String, DatasetSummary>>,
+    pending: VecDeque<DatasetEvent>,
+    finished: bool,
+}
+
+impl DatasetWatch<'_> {
+    /// Every dataset listed at the last successful poll, ordered by CID.
+    pub fn datasets(&self) -> impl Iterator<Item = &DatasetSummary> {
+        self.known.iter().flat_map(BTreeMap::values)
+    }
+
+    /// Compare `current` with the previous poll and queue the differences.
+    fn diff(&mut self, current: BTreeMap<String, DatasetSummary>) {
+        if let Some(known) = &self.known {
+            for (cid, dataset) in &current {
+                match known.get(cid) {
+                    None => self.pending.push_back(DatasetEvent::Added(dataset.clone())),
+                    Some(previous) if previous != dataset => {
+                        self.pending.push_back(DatasetEvent::Updated {
+                            previous: previous.clone(),
+                            current: dataset.clone(),
+                        })
+                    }
+                    Some(_) => {}
+                }
+            }
+            for (cid, dataset) in known {
+                if !current.contains_key(cid) {
+                    self.pending
+                        .push_back(DatasetEvent::Removed(dataset.clone()));
+                }
+            }
+        }
+        self.known = Some(current);
+    }
+
+    fn wait(&self) -> Result<(), TraceixError> {
+        let left = self.next_poll.saturating_duration_since(Instant::now());
+        match &self.sdk.cancel {
+            Some(token) => token.sleep(left),
+            None => {
+                thread::sleep(left);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Iterator for DatasetWatch<'_> {
+    type Item = Result<DatasetEvent, TraceixError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(Ok(event));
+            }
+            if self.finished {
+                return None;
+            }
+            if let Err(e) = self.wait() {
+                self.finished = true;
+                return Some(Err(e));
+            }
+            self.next_poll = Instant::now() + self.interval;
+            match self.sdk.all_datasets() {
+                Ok(datasets) => self.diff(
+                    datasets
+                        .into_iter()
+                        .map(|dataset| (dataset.cid.clone(), dataset))
+                        .collect(),
+                ),
+                Err(e) => {
+                    self.finished = self.sdk.cancel.as_ref().is_some_and(|t| t.is_cancelled());
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+/// The listing as returned by the server: a page object, or the whole catalog as a
+/// bare array.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DatasetList {
+    Page {
+        #[serde(alias = "items", alias = "data")]
+        datasets: Vec<DatasetSummary>,
+        #[serde(default)]
+        next_token: Option<String>,
+    },
+    All(Vec<DatasetSummary>),
+}
+
+impl TraceixSdk {
+    /// Fetch one page of the public IPFS datasets.
+    ///
+    /// [`Page::next`] is the cursor of the following page, or `None` on the last one.
+    /// Use [`TraceixSdk::iter_datasets`] to walk every page.
+    pub fn list_datasets(
+        &self,
+        options: &DatasetListOptions,
+    ) -> Result<Page<DatasetSummary>, TraceixError> {
+        let page_size = options.page_size.max(1);
+        let offset = options.page * page_size as u64;
+        self.fetch_datasets(options, Some(&Cursor::Offset(offset)), page_size)
+    }
+
+    /// Iterate over every public dataset matching `options`, starting at `options.page`
+    /// and requesting further pages as needed.
+    ///
+    /// ```no_run
+    /// # use chrono::{Duration, Utc};
+    /// # use traceix_sdk::{DatasetListOptions, TraceixSdk};
+    /// # fn main() -> Result<(), traceix_sdk::TraceixError> {
+    /// let sdk = TraceixSdk::new(None)?;
+    /// let options = DatasetListOptions {
+    ///     tag: Some("ransomware".to_string()),
+    ///     created_after: Some(Utc::now() - Duration::days(7)),
+    ///     ..DatasetListOptions::default()
+    /// };
+    /// for dataset in sdk.iter_datasets(options) {
+    ///     let dataset = dataset?;
+    ///     println!("{} {:?}", dataset.cid, dataset.name);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn iter_datasets(&self, options: DatasetListOptions) -> DatasetPages<'_> {
+        let start = Cursor::Offset(options.page * options.page_size.max(1) as u64);
+        let page_size = options.page_size;
+        Paginator::resume(
+            start,
+            page_size,
+            Box::new(move |cursor, page_size| self.fetch_datasets(&options, cursor, page_size)),
+        )
+    }
+
+    /// Watch the public dataset catalog, polling it every `interval` (at least a
+    /// second) and reporting each dataset added, updated or removed since the previous
+    /// poll.
+    ///
+    /// The first poll is made straight away and only records the current catalog;
+    /// events start with the second. The iterator blocks between polls, so run it on a
+    /// thread of its own.
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use traceix_sdk::{DatasetEvent, TraceixSdk};
+    /// # fn main() -> Result<(), traceix_sdk::TraceixError> {
+    /// let sdk = TraceixSdk::new(None)?;
+    /// for event in sdk.watch_datasets(Duration::from_secs(15 * 60)) {
+    ///     match event {
+    ///         Ok(DatasetEvent::Added(dataset)) => println!("new dataset {}", dataset.cid),
+    ///         Ok(other) => println!("{} changed", other.cid()),
+    ///         Err(e) => eprintln!("poll failed: {e}"),
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn watch_datasets(&self, interval: Duration) -> DatasetWatch<'_> {
+        DatasetWatch {
+            sdk: self,
+            interval: interval.max(MIN_WATCH_INTERVAL),
+            next_poll: Instant::now(),
+            known: None,
+            pending: VecDeque::new(),
+            finished: false,
+        }
+    }
+
+    /// The whole catalog. Servers that page it are followed to the last page.
+    fn all_datasets(&self) -> Result<Vec<DatasetSummary>, TraceixError> {
+        let list: DatasetList = decode_response(self.list_all_ipfs_datasets()?)?;
+        let (mut datasets, next_token) = match list {
+            DatasetList::All(datasets) => (datasets, None),
+            DatasetList::Page {
+                datasets,
+                next_token,
+            } => (datasets, next_token.filter(|token| !token.is_empty())),
+        };
+        if let Some(token) = next_token {
+            let options = DatasetListOptions::default();
+            let rest = Paginator::resume(
+                Cursor::Token(token),
+                options.page_size,
+                |cursor: Option<&Cursor>, page_size| {
+                    self.fetch_datasets(&options, cursor, page_size)
+                },
+            );
+            for dataset in rest {
+                datasets.push(dataset?);
+            }
+        }
+        Ok(datasets)
+    }
+
+    fn fetch_datasets(
+        &self,
+        options: &DatasetListOptions,
+        cursor: Option<&Cursor>,
+        page_size: usize,
+    ) -> Result<Page<DatasetSummary>, TraceixError> {
+        let mut body = Map::new();
+        body.insert("limit".into(), page_size.into());
+        let offset = match cursor {
+            Some(Cursor::Token(token)) => {
+                body.insert("next_token".into(), token.clone().into());
+                None
+            }
+            Some(Cursor::Offset(offset)) => Some(*offset),
+            None => Some(0),
+        };
+        if let Some(offset) = offset {
+            body.insert("offset".into(), offset.into());
+        }
+        if let Some(name) = &options.name {
+            body.insert("name".into(), name.clone().into());
+        }
+        if let Some(tag) = &options.tag {
+            body.insert("tag".into(), tag.clone().into());
+        }
+        if let Some(after) = options.created_after {
+            body.insert("created_after".into(), after.to_rfc3339().into());
+        }
+
+        let list: DatasetList =
+            decode_response(self.post_json(&Endpoint::IPFS_LIST_ALL, &Value::Object(body))?)?;
+        Ok(match list {
+            DatasetList::Page {
+                datasets,
+                next_token: Some(token),
+            } => Page::from_token(datasets, Some(token)),
+            DatasetList::Page { datasets, .. } if datasets.len() <= page_size => match offset {
+                Some(offset) => Page::from_offset(datasets, offset, page_size),
+                None => Page::from_token(datasets, None),
+            },
+            // A server that ignores paging returns the whole catalog every time, so
+            // filter and page it here.
+            DatasetList::Page { datasets, .. } | DatasetList::All(datasets) => {
+                let offset = offset.unwrap_or(0);
+                let matching: Vec<_> = datasets
+                    .into_iter()
+                    .filter(|dataset| options.matches(dataset))
+                    .collect();
+                let start = (offset as usize).min(matching.len());
+                let end = (start + page_size).min(matching.len());
+                let page = matching[start..end].to_vec();
+                let next = (end < matching.len()).then_some(Cursor::Offset(end as u64));
+                Page { items: page, next }
+            }
+        })
+    }
+}
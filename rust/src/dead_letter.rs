@@ -0,0 +1,104 @@
+// src/dead_letter.rs
+
+use crate::batch::{BatchItem, BatchSender};
+use crate::TraceixError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// A batch item that still failed after its last attempt.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub item: BatchItem,
+    /// Display form of the final error.
+    pub error: String,
+    pub attempts: u32,
+    pub first_failed_at: DateTime<Utc>,
+    pub last_failed_at: DateTime<Utc>,
+}
+
+/// Store for permanently failed batch items, so they can be inspected, exported, and requeued.
+///
+/// Share one between batches through
+/// [`BatchOptions::dead_letters`](crate::batch::BatchOptions::dead_letters).
+#[derive(Debug, Default)]
+pub struct DeadLetterQueue {
+    entries: Mutex<Vec<DeadLetter>>,
+}
+
+impl DeadLetterQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load entries previously written with [`DeadLetterQueue::save`].
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, TraceixError> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| TraceixError::file(path, e))?;
+        let entries = serde_json::from_reader(BufReader::new(file)).map_err(|e| {
+            if e.is_io() {
+                return TraceixError::file(path, e.into());
+            }
+            TraceixError::InvalidArgument(format!(
+                "invalid dead-letter file {}: {e}",
+                path.display()
+            ))
+        })?;
+        Ok(Self {
+            entries: Mutex::new(entries),
+        })
+    }
+
+    pub fn push(&self, letter: DeadLetter) {
+        self.entries.lock().unwrap().push(letter);
+    }
+
+    /// A snapshot of the current entries.
+    pub fn list(&self) -> Vec<DeadLetter> {
+        self.entries.lock().unwrap().clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.lock().unwrap().is_empty()
+    }
+
+    /// Write the entries as JSON to `writer`.
+    pub fn export(&self, writer: impl Write) -> Result<(), TraceixError> {
+        serde_json::to_writer_pretty(writer, &*self.entries.lock().unwrap())
+            .map_err(|e| TraceixError::Io(e.into()))
+    }
+
+    /// Write the entries as JSON to `path`, replacing it atomically.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), TraceixError> {
+        let path = path.as_ref();
+        let tmp = path.with_extension("part");
+        let write = || -> io::Result<()> {
+            let mut writer = BufWriter::new(File::create(&tmp)?);
+            serde_json::to_writer_pretty(&mut writer, &*self.entries.lock().unwrap())?;
+            writer.flush()
+        };
+        write().map_err(|e| TraceixError::file(&tmp, e))?;
+        fs::rename(&tmp, path).map_err(|e| TraceixError::file(path, e))
+    }
+
+    /// Remove every entry and push its item back onto `batch`. Returns how many were requeued.
+    ///
+    /// If the batch is closed, the remaining entries are kept.
+    pub fn requeue(&self, batch: &BatchSender) -> Result<usize, TraceixError> {
+        let mut entries = self.entries.lock().unwrap();
+        let mut requeued = 0;
+        while let Some(letter) = entries.first() {
+            batch.push_with_options(letter.item.path.clone(), letter.item.options.clone())?;
+            entries.remove(0);
+            requeued += 1;
+        }
+        Ok(requeued)
+    }
+}
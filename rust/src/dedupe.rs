@@ -0,0 +1,116 @@
+// src/dedupe.rs
+
+use crate::{hashing, SearchType, Submission, TraceixError, TraceixSdk, UploadOptions};
+use reqwest::StatusCode;
+use serde_json::Value;
+use std::path::Path;
+
+/// What [`TraceixSdk::upload_if_unknown`] did with a file.
+#[derive(Clone)]
+#[non_exhaustive]
+pub enum UploadOutcome {
+    /// The hash was already known, so nothing was uploaded.
+    Known {
+        sha256: String,
+        /// The CAPA search result for the hash.
+        result: Value,
+    },
+    /// The hash was unknown and the file was uploaded for analysis.
+    Submitted {
+        sha256: String,
+        submission: Box<Submission>,
+    },
+}
+
+impl UploadOutcome {
+    /// SHA-256 of the file, lowercase hex.
+    pub fn sha256(&self) -> &str {
+        match self {
+            UploadOutcome::Known { sha256, .. } | UploadOutcome::Submitted { sha256, .. } => sha256,
+        }
+    }
+
+    /// Whether the upload was skipped because the hash was known.
+    pub fn is_known(&self) -> bool {
+        matches!(self, UploadOutcome::Known { .. })
+    }
+
+    /// The new submission, if the file was uploaded.
+    pub fn submission(&self) -> Option<&Submission> {
+        match self {
+            UploadOutcome::Submitted { submission, .. } => Some(submission.as_ref()),
+            UploadOutcome::Known { .. } => None,
+        }
+    }
+}
+
+impl TraceixSdk {
+    /// Upload `filename` for AI prediction only if Traceix doesn't know it yet.
+    ///
+    /// The SHA-256 is computed locally and looked up with a CAPA [`hash_search`](Self::hash_search).
+    /// The upload is skipped only when the search reports a result (`found: true`, or a
+    /// non-empty `results`, `result`, or `data` field); an empty or unrecognized response,
+    /// or `404 Not Found`, means the file is uploaded.
+    ///
+    /// ```no_run
+    /// # use traceix_sdk::{TraceixSdk, UploadOutcome};
+    /// # fn main() -> Result<(), traceix_sdk::TraceixError> {
+    /// let sdk = TraceixSdk::new(None)?;
+    /// match sdk.upload_if_unknown("/evidence/invoice.exe")? {
+    ///     UploadOutcome::Known { result, .. } => println!("already analyzed: {result}"),
+    ///     UploadOutcome::Submitted { submission, .. } => println!("queued {}", submission.uuid()),
+    ///     _ => {}
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn upload_if_unknown(
+        &self,
+        filename: impl AsRef<Path>,
+    ) -> Result<UploadOutcome, TraceixError> {
+        self.upload_if_unknown_with_options(filename, &UploadOptions::default())
+    }
+
+    /// Like [`TraceixSdk::upload_if_unknown`], with explicit upload options.
+    pub fn upload_if_unknown_with_options(
+        &self,
+        filename: impl AsRef<Path>,
+        options: &UploadOptions,
+    ) -> Result<UploadOutcome, TraceixError> {
+        let filename = filename.as_ref();
+        let sha256 = hashing::sha256_file(filename)?;
+
+        match self.hash_search(&sha256, SearchType::Capa) {
+            Ok(result) if is_known(&result) => return Ok(UploadOutcome::Known { sha256, result }),
+            Ok(_) => {}
+            Err(e) if e.status() == Some(StatusCode::NOT_FOUND) => {}
+            Err(e) => return Err(e),
+        }
+
+        let submission = Box::new(self.upload_with_options(filename, options)?);
+        Ok(UploadOutcome::Submitted { sha256, submission })
+    }
+}
+
+/// Whether a search response reports a result: `found: true`, or a non-empty `results`,
+/// `result`, or `data` field. Any other shape counts as unknown, so the file is uploaded
+/// rather than silently skipped.
+fn is_known(response: &Value) -> bool {
+    let Value::Object(map) = response else {
+        return false;
+    };
+    if map.contains_key("error") {
+        return false;
+    }
+    if let Some(found) = map.get("found").and_then(Value::as_bool) {
+        return found;
+    }
+    ["results", "result", "data"]
+        .iter()
+        .filter_map(|key| map.get(*key))
+        .any(|value| match value {
+            Value::Array(items) => !items.is_empty(),
+            Value::Object(fields) => !fields.is_empty(),
+            _ => false,
+        })
+}
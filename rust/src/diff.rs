@@ -0,0 +1,125 @@
+// src/diff.rs
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// Keys whose changes indicate the verdict itself moved, not just supporting detail.
+const VERDICT_KEYS: &[&str] = &[
+    "verdict",
+    "prediction",
+    "label",
+    "classification",
+    "malicious",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// A single difference between two result documents.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct FieldChange {
+    /// JSON pointer (RFC 6901) to the changed value, e.g. `/result/verdict`.
+    pub path: String,
+    pub kind: ChangeKind,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+/// Structured difference between two analysis results.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct ResultDiff {
+    pub changes: Vec<FieldChange>,
+}
+
+impl ResultDiff {
+    /// Compare two JSON documents. Objects are compared key by key and arrays index by index;
+    /// anything else is reported as a whole-value modification.
+    pub fn between(before: &Value, after: &Value) -> Self {
+        let mut diff = ResultDiff::default();
+        diff.walk(String::new(), before, after);
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// The change recorded at exactly `pointer`, if any.
+    pub fn change_at(&self, pointer: &str) -> Option<&FieldChange> {
+        self.changes.iter().find(|c| c.path == pointer)
+    }
+
+    /// Changes to verdict-like fields (`verdict`, `prediction`, `label`, ...).
+    pub fn verdict_changes(&self) -> impl Iterator<Item = &FieldChange> {
+        self.changes.iter().filter(|c| {
+            c.path
+                .rsplit('/')
+                .next()
+                .is_some_and(|key| VERDICT_KEYS.contains(&key))
+        })
+    }
+
+    /// Whether the verdict differs between the two results.
+    pub fn verdict_changed(&self) -> bool {
+        self.verdict_changes().next().is_some()
+    }
+
+    fn walk(&mut self, path: String, before: &Value, after: &Value) {
+        match (before, after) {
+            (Value::Object(a), Value::Object(b)) => {
+                for (key, old) in a {
+                    let child = format!("{path}/{}", escape(key));
+                    match b.get(key) {
+                        Some(new) => self.walk(child, old, new),
+                        None => self.push(child, ChangeKind::Removed, Some(old), None),
+                    }
+                }
+                for (key, new) in b {
+                    if !a.contains_key(key) {
+                        let child = format!("{path}/{}", escape(key));
+                        self.push(child, ChangeKind::Added, None, Some(new));
+                    }
+                }
+            }
+            (Value::Array(a), Value::Array(b)) => {
+                for i in 0..a.len().max(b.len()) {
+                    let child = format!("{path}/{i}");
+                    match (a.get(i), b.get(i)) {
+                        (Some(old), Some(new)) => self.walk(child, old, new),
+                        (Some(old), None) => self.push(child, ChangeKind::Removed, Some(old), None),
+                        (None, Some(new)) => self.push(child, ChangeKind::Added, None, Some(new)),
+                        (None, None) => {}
+                    }
+                }
+            }
+            (old, new) if old != new => {
+                self.push(path, ChangeKind::Modified, Some(old), Some(new));
+            }
+            _ => {}
+        }
+    }
+
+    fn push(
+        &mut self,
+        path: String,
+        kind: ChangeKind,
+        before: Option<&Value>,
+        after: Option<&Value>,
+    ) {
+        self.changes.push(FieldChange {
+            path,
+            kind,
+            before: before.cloned(),
+            after: after.cloned(),
+        });
+    }
+}
+
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
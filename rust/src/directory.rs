@@ -0,0 +1,395 @@
+// src/directory.rs
+
+use crate::batch::BatchOptions;
+use crate::{Analyses, FullUpload, TraceixError, TraceixSdk, UploadOptions};
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Which files under a directory [`TraceixSdk::submit_directory`] submits, and how.
+///
+/// Glob patterns use `*` (any run of characters except `/`), `**` (any number of
+/// directories), and `?`. A pattern without a `/` is matched against the file name,
+/// otherwise against the path relative to the directory, with `/` separators. A file
+/// is submitted when it matches at least one include pattern (if any are given), no
+/// exclude pattern, one of the extensions (if any are given), and the size bounds.
+///
+/// ```no_run
+/// # use traceix_sdk::{SubmitOptions, TraceixSdk};
+/// # fn main() -> Result<(), traceix_sdk::TraceixError> {
+/// let sdk = TraceixSdk::new(None)?;
+/// let options = SubmitOptions::default()
+///     .extension("exe")
+///     .extension("dll")
+///     .exclude("Windows/WinSxS/**")
+///     .max_size(100 * 1024 * 1024)
+///     .concurrency(8);
+/// let summary = sdk.submit_directory("/mnt/image", &options)?;
+/// println!(
+///     "{} submitted, {} skipped, {} failed",
+///     summary.submitted.len(),
+///     summary.skipped.len(),
+///     summary.failed.len()
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct SubmitOptions {
+    include: Vec<String>,
+    exclude: Vec<String>,
+    extensions: Vec<String>,
+    min_size: Option<u64>,
+    max_size: Option<u64>,
+    concurrency: usize,
+    analyses: Analyses,
+    upload: UploadOptions,
+}
+
+impl Default for SubmitOptions {
+    /// Every regular file, four at a time, with all analyses.
+    fn default() -> Self {
+        Self {
+            include: Vec::new(),
+            exclude: Vec::new(),
+            extensions: Vec::new(),
+            min_size: None,
+            max_size: None,
+            concurrency: 4,
+            analyses: Analyses::default(),
+            upload: UploadOptions::default(),
+        }
+    }
+}
+
+impl SubmitOptions {
+    /// Only submit files matching this glob. Can be called repeatedly.
+    pub fn include(mut self, pattern: impl Into<String>) -> Self {
+        self.include.push(pattern.into());
+        self
+    }
+
+    /// Skip files matching this glob. Can be called repeatedly.
+    pub fn exclude(mut self, pattern: impl Into<String>) -> Self {
+        self.exclude.push(pattern.into());
+        self
+    }
+
+    /// Only submit files with this extension, compared case-insensitively and with or
+    /// without the leading dot. Can be called repeatedly.
+    pub fn extension(mut self, extension: impl AsRef<str>) -> Self {
+        let extension = extension.as_ref().trim_start_matches('.');
+        self.extensions.push(extension.to_ascii_lowercase());
+        self
+    }
+
+    /// Skip files smaller than `bytes`.
+    pub fn min_size(mut self, bytes: u64) -> Self {
+        self.min_size = Some(bytes);
+        self
+    }
+
+    /// Skip files larger than `bytes`.
+    pub fn max_size(mut self, bytes: u64) -> Self {
+        self.max_size = Some(bytes);
+        self
+    }
+
+    /// Number of files uploaded at the same time. At least 1.
+    pub fn concurrency(mut self, files: usize) -> Self {
+        self.concurrency = files.max(1);
+        self
+    }
+
+    /// Which analyses to run on each file. Defaults to all of them.
+    pub fn analyses(mut self, analyses: Analyses) -> Self {
+        self.analyses = analyses;
+        self
+    }
+
+    /// Upload options applied to every file.
+    pub fn upload_options(mut self, options: UploadOptions) -> Self {
+        self.upload = options;
+        self
+    }
+}
+
+/// Why [`TraceixSdk::submit_directory`] left a file out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SkipReason {
+    /// No include pattern matched.
+    NotIncluded,
+    /// An exclude pattern matched.
+    Excluded,
+    /// The extension is not one of the selected ones.
+    Extension,
+    TooSmall,
+    TooLarge,
+    /// Symbolic links, sockets, devices, and the like are never followed or uploaded.
+    NotRegularFile,
+}
+
+/// A file uploaded by [`TraceixSdk::submit_directory`].
+#[derive(Debug)]
+pub struct SubmittedFile {
+    pub path: PathBuf,
+    pub upload: FullUpload,
+}
+
+/// A file [`TraceixSdk::submit_directory`] did not upload.
+#[derive(Clone, Debug)]
+pub struct SkippedFile {
+    pub path: PathBuf,
+    pub reason: SkipReason,
+}
+
+/// A file or directory that could not be read or uploaded.
+#[derive(Debug)]
+pub struct FailedFile {
+    pub path: PathBuf,
+    pub error: TraceixError,
+}
+
+/// The outcome of [`TraceixSdk::submit_directory`], with each list sorted by path.
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct DirectorySummary {
+    pub submitted: Vec<SubmittedFile>,
+    pub skipped: Vec<SkippedFile>,
+    pub failed: Vec<FailedFile>,
+}
+
+impl TraceixSdk {
+    /// Walk `dir` recursively and submit every file selected by `options`, several at a
+    /// time, e.g. to triage an acquired disk image.
+    ///
+    /// Errors reading individual entries or uploading individual files are collected in
+    /// [`DirectorySummary::failed`] instead of stopping the walk; only an invalid pattern
+    /// or an unreadable `dir` fails the whole call.
+    pub fn submit_directory(
+        &self,
+        dir: impl AsRef<Path>,
+        options: &SubmitOptions,
+    ) -> Result<DirectorySummary, TraceixError> {
+        let dir = dir.as_ref();
+        let selector = Selector::new(options)?;
+        let mut summary = DirectorySummary::default();
+        let mut files = Vec::new();
+        let entries = fs::read_dir(dir)?;
+        walk(dir, dir, entries, &selector, &mut files, &mut summary);
+
+        let analyses = options.analyses;
+        let batch = self.batch(
+            BatchOptions {
+                workers: options.concurrency,
+                ..BatchOptions::default()
+            },
+            move |sdk, item| sdk.full_upload_with_options(&item.path, analyses, &item.options),
+        );
+        for file in files {
+            batch.push_with_options(file, options.upload.clone())?;
+        }
+        batch.close();
+
+        for done in batch.iter() {
+            match done.result {
+                Ok(upload) => summary.submitted.push(SubmittedFile {
+                    path: done.item.path,
+                    upload,
+                }),
+                Err(error) => summary.failed.push(FailedFile {
+                    path: done.item.path,
+                    error,
+                }),
+            }
+        }
+        batch.join();
+
+        summary.submitted.sort_by(|a, b| a.path.cmp(&b.path));
+        summary.skipped.sort_by(|a, b| a.path.cmp(&b.path));
+        summary.failed.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(summary)
+    }
+}
+
+/// Every regular file under `dir`, sorted. Symlinks are not followed; the first entry
+/// that cannot be read fails the call.
+pub(crate) fn list_files(dir: &Path) -> Result<Vec<PathBuf>, TraceixError> {
+    let options = SubmitOptions::default();
+    let selector = Selector::new(&options)?;
+    let mut summary = DirectorySummary::default();
+    let mut files = Vec::new();
+    let entries = fs::read_dir(dir)?;
+    walk(dir, dir, entries, &selector, &mut files, &mut summary);
+    if let Some(failed) = summary.failed.into_iter().next() {
+        return Err(failed.error);
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Collect the files under a directory that `selector` accepts, recording the rest.
+fn walk(
+    root: &Path,
+    dir: &Path,
+    entries: fs::ReadDir,
+    selector: &Selector,
+    files: &mut Vec<PathBuf>,
+    summary: &mut DirectorySummary,
+) {
+    for entry in entries {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                summary.failed.push(FailedFile {
+                    path: dir.to_path_buf(),
+                    error: e.into(),
+                });
+                continue;
+            }
+        };
+        let path = entry.path();
+        let file_type = match entry.file_type() {
+            Ok(file_type) => file_type,
+            Err(e) => {
+                summary.failed.push(FailedFile {
+                    path,
+                    error: e.into(),
+                });
+                continue;
+            }
+        };
+
+        if file_type.is_dir() {
+            match fs::read_dir(&path) {
+                Ok(children) => walk(root, &path, children, selector, files, summary),
+                Err(e) => summary.failed.push(FailedFile {
+                    path,
+                    error: e.into(),
+                }),
+            }
+            continue;
+        }
+
+        let decision = if file_type.is_file() {
+            entry
+                .metadata()
+                .map(|metadata| selector.check(root, &path, metadata.len()))
+        } else {
+            Ok(Err(SkipReason::NotRegularFile))
+        };
+        match decision {
+            Ok(Ok(())) => files.push(path),
+            Ok(Err(reason)) => summary.skipped.push(SkippedFile { path, reason }),
+            Err(e) => summary.failed.push(FailedFile {
+                path,
+                error: e.into(),
+            }),
+        }
+    }
+}
+
+/// The compiled filters of a [`SubmitOptions`].
+struct Selector<'a> {
+    include: Vec<Glob>,
+    exclude: Vec<Glob>,
+    options: &'a SubmitOptions,
+}
+
+impl<'a> Selector<'a> {
+    fn new(options: &'a SubmitOptions) -> Result<Self, TraceixError> {
+        let compile = |patterns: &[String]| {
+            patterns
+                .iter()
+                .map(|p| Glob::new(p))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        Ok(Self {
+            include: compile(&options.include)?,
+            exclude: compile(&options.exclude)?,
+            options,
+        })
+    }
+
+    fn check(&self, root: &Path, path: &Path, len: u64) -> Result<(), SkipReason> {
+        let relative = relative_path(root, path);
+        let name = relative.rsplit('/').next().unwrap_or_default();
+
+        if !self.include.is_empty() && !self.include.iter().any(|g| g.matches(&relative, name)) {
+            return Err(SkipReason::NotIncluded);
+        }
+        if self.exclude.iter().any(|g| g.matches(&relative, name)) {
+            return Err(SkipReason::Excluded);
+        }
+        if !self.options.extensions.is_empty() {
+            let extension = path
+                .extension()
+                .map(|e| e.to_string_lossy().to_ascii_lowercase());
+            if !extension.is_some_and(|e| self.options.extensions.contains(&e)) {
+                return Err(SkipReason::Extension);
+            }
+        }
+        if self.options.min_size.is_some_and(|min| len < min) {
+            return Err(SkipReason::TooSmall);
+        }
+        if self.options.max_size.is_some_and(|max| len > max) {
+            return Err(SkipReason::TooLarge);
+        }
+        Ok(())
+    }
+}
+
+/// `path` relative to `root`, with `/` separators, as globs are matched against it.
+pub(crate) fn relative_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// A glob pattern compiled to a regular expression.
+pub(crate) struct Glob {
+    regex: Regex,
+    /// Whether the pattern is matched against the full relative path, not the file name.
+    path: bool,
+}
+
+impl Glob {
+    pub(crate) fn new(pattern: &str) -> Result<Self, TraceixError> {
+        let pattern = pattern.trim_start_matches("./");
+        let mut regex = String::from("^");
+        let mut chars = pattern.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '*' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    if chars.peek() == Some(&'/') {
+                        chars.next();
+                        regex.push_str("(?:.*/)?");
+                    } else {
+                        regex.push_str(".*");
+                    }
+                }
+                '*' => regex.push_str("[^/]*"),
+                '?' => regex.push_str("[^/]"),
+                c => regex.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+        regex.push('$');
+
+        let regex = Regex::new(&regex)
+            .map_err(|e| TraceixError::InvalidArgument(format!("invalid glob {pattern:?}: {e}")))?;
+        Ok(Self {
+            regex,
+            path: pattern.contains('/'),
+        })
+    }
+
+    /// Match against a relative path as built by [`relative_path`] and its file name.
+    pub(crate) fn matches(&self, relative: &str, name: &str) -> bool {
+        self.regex.is_match(if self.path { relative } else { name })
+    }
+}
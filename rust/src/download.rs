@@ -0,0 +1,100 @@
+// src/download.rs
+
+use crate::cancel::{CancellableReader, CancellationToken};
+use crate::hashing::{normalize_hex_digest, HashAlgorithm, HashingWriter};
+use crate::{Endpoint, TraceixError, TraceixSdk};
+use reqwest::blocking::Response;
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+
+/// A file streamed to disk by the SDK.
+#[derive(Clone, Debug)]
+pub struct Download {
+    pub path: PathBuf,
+    pub bytes: u64,
+    /// Hex digest of the written bytes.
+    pub digest: String,
+    pub algorithm: HashAlgorithm,
+}
+
+/// Stream `resp` into `dest`, going through a `.part` file that is only renamed into
+/// place once the body has been fully written (and, if `expected` is set, verified).
+pub(crate) fn stream_to_file(
+    resp: Response,
+    dest: &Path,
+    algorithm: HashAlgorithm,
+    expected: Option<&str>,
+    cancel: Option<CancellationToken>,
+) -> Result<Download, TraceixError> {
+    let partial = partial_path(dest);
+    let file = File::create(&partial).map_err(|e| TraceixError::file(&partial, e))?;
+    let mut writer = HashingWriter::new(BufWriter::new(file), algorithm);
+
+    let mut body = CancellableReader::new(resp, cancel.clone());
+    let written = io::copy(&mut body, &mut writer).and_then(|_| {
+        let bytes = writer.written();
+        let (inner, digest) = writer.finish()?;
+        inner.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+        Ok((bytes, digest))
+    });
+    let (bytes, digest) = match written {
+        Ok(done) => done,
+        Err(e) => {
+            let _ = fs::remove_file(&partial);
+            if cancel.is_some_and(|token| token.is_cancelled()) {
+                return Err(TraceixError::Cancelled);
+            }
+            return Err(TraceixError::file(&partial, e));
+        }
+    };
+
+    if let Some(expected) = expected {
+        if !digest.eq_ignore_ascii_case(expected) {
+            let _ = fs::remove_file(&partial);
+            return Err(TraceixError::IntegrityMismatch {
+                algorithm: algorithm.as_str(),
+                expected: expected.to_ascii_lowercase(),
+                actual: digest,
+            });
+        }
+    }
+
+    fs::rename(&partial, dest).map_err(|e| TraceixError::file(dest, e))?;
+    Ok(Download {
+        path: dest.to_path_buf(),
+        bytes,
+        digest,
+        algorithm,
+    })
+}
+
+pub(crate) fn partial_path(dest: &Path) -> PathBuf {
+    let mut name = dest.file_name().map(OsString::from).unwrap_or_default();
+    name.push(".part");
+    dest.with_file_name(name)
+}
+
+impl TraceixSdk {
+    /// Download the original sample identified by `hash` (MD5, SHA-1 or SHA-256) to `dest`.
+    ///
+    /// Requires an account with sample-download privileges. The body is streamed to disk
+    /// and hashed on the way; if the digest doesn't match `hash` the partial file is
+    /// removed and [`TraceixError::IntegrityMismatch`] is returned.
+    pub fn download_sample(
+        &self,
+        hash: &str,
+        dest: impl AsRef<Path>,
+    ) -> Result<Download, TraceixError> {
+        let (algorithm, hash) = normalize_hex_digest(hash)?;
+
+        let dest = dest.as_ref();
+        let body = serde_json::json!({ algorithm.as_str(): hash });
+        let resp = self.send(&Endpoint::SAMPLE_DOWNLOAD, Some(dest), |request| {
+            Ok(request.json(&body))
+        })?;
+
+        stream_to_file(resp, dest, algorithm, Some(&hash), self.cancel.clone())
+    }
+}
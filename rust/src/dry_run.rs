@@ -0,0 +1,119 @@
+// src/dry_run.rs
+
+use reqwest::Method;
+use serde_json::Value;
+use std::fmt;
+use std::path::PathBuf;
+use url::Url;
+
+/// Headers whose values are replaced by `<redacted>` even when not marked sensitive.
+#[cfg(feature = "blocking")]
+pub(crate) const SECRET_HEADERS: [&str; 4] = [
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "x-api-key",
+];
+
+/// A request a client in dry-run mode built but did not send, returned in
+/// [`TraceixError::DryRun`](crate::TraceixError::DryRun).
+///
+/// It is the request exactly as it would have gone out, after middleware ran; secret
+/// header values are redacted.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct PlannedRequest {
+    /// Name of the [`Endpoint`](crate::Endpoint), e.g. `ipfs_block` for IPFS gateways,
+    /// or `None` for the object download behind `upload_s3`.
+    pub endpoint: Option<&'static str>,
+    pub method: Method,
+    pub url: Url,
+    pub headers: Vec<(String, String)>,
+    pub body: PlannedBody,
+    /// Local file the body would be read from.
+    pub file: Option<PathBuf>,
+}
+
+/// What a [`PlannedRequest`] would send as its body.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum PlannedBody {
+    Empty,
+    /// A JSON document.
+    Json(Value),
+    /// Bytes already in memory that aren't JSON.
+    Bytes {
+        content_type: Option<String>,
+        len: usize,
+    },
+    /// A body streamed while sending, e.g. a multipart upload read from a file.
+    Stream {
+        content_type: Option<String>,
+    },
+}
+
+impl PlannedRequest {
+    /// Value of the header `name`, if the request carries it.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl PlannedRequest {
+    pub(crate) fn new(
+        endpoint: Option<&'static str>,
+        request: &reqwest::blocking::Request,
+        file: Option<&std::path::Path>,
+    ) -> Self {
+        let headers = request
+            .headers()
+            .iter()
+            .map(|(name, value)| {
+                let value = if value.is_sensitive() || SECRET_HEADERS.contains(&name.as_str()) {
+                    "<redacted>".to_string()
+                } else {
+                    String::from_utf8_lossy(value.as_bytes()).into_owned()
+                };
+                (name.to_string(), value)
+            })
+            .collect::<Vec<_>>();
+        let content_type = headers
+            .iter()
+            .find(|(name, _)| name == "content-type")
+            .map(|(_, value)| value.clone());
+        let body = match request.body() {
+            None => PlannedBody::Empty,
+            Some(body) => match body.as_bytes() {
+                None => PlannedBody::Stream { content_type },
+                Some(bytes) => match serde_json::from_slice(bytes) {
+                    Ok(json) if content_type.as_deref() == Some("application/json") => {
+                        PlannedBody::Json(json)
+                    }
+                    _ => PlannedBody::Bytes {
+                        content_type,
+                        len: bytes.len(),
+                    },
+                },
+            },
+        };
+
+        Self {
+            endpoint,
+            method: request.method().clone(),
+            url: request.url().clone(),
+            headers,
+            body,
+            file: file.map(std::path::Path::to_path_buf),
+        }
+    }
+}
+
+impl fmt::Display for PlannedRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.method, self.url)
+    }
+}
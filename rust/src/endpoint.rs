@@ -0,0 +1,256 @@
+// src/endpoint.rs
+
+/// The public Traceix API.
+pub const DEFAULT_BASE_URL: &str = "https://ai.perkinsfund.org";
+
+/// Groups of endpoints that share client-side limits (see
+/// [`TraceixSdkBuilder::concurrency_limit`](crate::TraceixSdkBuilder::concurrency_limit)).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum EndpointClass {
+    /// Endpoints that take a file body.
+    Upload,
+    /// Hash, CAPA/EXIF, and dataset searches.
+    Search,
+    /// Status, result, and upload offset polling.
+    Status,
+    /// Sample and report downloads.
+    Download,
+    /// Account, alerting, and other management calls.
+    Other,
+}
+
+impl EndpointClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EndpointClass::Upload => "upload",
+            EndpointClass::Search => "search",
+            EndpointClass::Status => "status",
+            EndpointClass::Download => "download",
+            EndpointClass::Other => "other",
+        }
+    }
+}
+
+/// A Traceix API endpoint: a stable name used in errors and diagnostics, and its path.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Endpoint {
+    pub name: &'static str,
+    pub path: &'static str,
+    pub class: EndpointClass,
+    /// Whether sending a request twice has the same effect as sending it once. Requests
+    /// to endpoints that submit or create something are not retried once they may have
+    /// reached the server, unless the retry policy opts in with
+    /// [`retry_non_idempotent`](crate::RetryPolicy::retry_non_idempotent).
+    pub idempotent: bool,
+}
+
+impl Endpoint {
+    pub const AI_PREDICTION: Endpoint = Endpoint::new(
+        "ai_prediction",
+        "/api/traceix/v1/upload",
+        EndpointClass::Upload,
+    )
+    .non_idempotent();
+    pub const CAPA_EXTRACTION: Endpoint = Endpoint::new(
+        "capa_extraction",
+        "/api/traceix/v1/capa",
+        EndpointClass::Upload,
+    )
+    .non_idempotent();
+    pub const EXIF_EXTRACTION: Endpoint = Endpoint::new(
+        "exif_extraction",
+        "/api/traceix/v1/exif",
+        EndpointClass::Upload,
+    )
+    .non_idempotent();
+    pub const CHECK_STATUS: Endpoint = Endpoint::new(
+        "check_status",
+        "/api/v1/traceix/status",
+        EndpointClass::Status,
+    );
+    pub const CANCEL: Endpoint =
+        Endpoint::new("cancel", "/api/traceix/v1/cancel", EndpointClass::Other);
+    pub const RESULT: Endpoint = Endpoint::new(
+        "get_result",
+        "/api/traceix/v1/result",
+        EndpointClass::Status,
+    );
+    pub const RESCAN: Endpoint =
+        Endpoint::new("rescan", "/api/traceix/v1/rescan", EndpointClass::Other).non_idempotent();
+    pub const SAMPLE_DOWNLOAD: Endpoint = Endpoint::new(
+        "download_sample",
+        "/api/traceix/v1/sample/download",
+        EndpointClass::Download,
+    );
+    pub const SHARE_LINK: Endpoint = Endpoint::new(
+        "create_share_link",
+        "/api/traceix/v1/share",
+        EndpointClass::Other,
+    )
+    .non_idempotent();
+    pub const REPORT_DOWNLOAD: Endpoint = Endpoint::new(
+        "download_report",
+        "/api/traceix/v1/report/download",
+        EndpointClass::Download,
+    );
+    pub const NOTIFICATIONS: Endpoint = Endpoint::new(
+        "notification_settings",
+        "/api/traceix/v1/account/notifications",
+        EndpointClass::Other,
+    );
+    pub const NOTIFICATIONS_UPDATE: Endpoint = Endpoint::new(
+        "update_notification_settings",
+        "/api/traceix/v1/account/notifications/update",
+        EndpointClass::Other,
+    );
+    pub const ALERT_CREATE: Endpoint = Endpoint::new(
+        "create_alert_rule",
+        "/api/traceix/v1/alerts/create",
+        EndpointClass::Other,
+    )
+    .non_idempotent();
+    pub const ALERT_LIST: Endpoint = Endpoint::new(
+        "list_alert_rules",
+        "/api/traceix/v1/alerts/list",
+        EndpointClass::Other,
+    );
+    pub const ALERT_DELETE: Endpoint = Endpoint::new(
+        "delete_alert_rule",
+        "/api/traceix/v1/alerts/delete",
+        EndpointClass::Other,
+    );
+    pub const SAVED_SEARCH_SAVE: Endpoint = Endpoint::new(
+        "save_search",
+        "/api/traceix/v1/searches/save",
+        EndpointClass::Other,
+    )
+    .non_idempotent();
+    pub const SAVED_SEARCH_LIST: Endpoint = Endpoint::new(
+        "list_saved_searches",
+        "/api/traceix/v1/searches/list",
+        EndpointClass::Other,
+    );
+    pub const SAVED_SEARCH_RUN: Endpoint = Endpoint::new(
+        "run_saved_search",
+        "/api/traceix/v1/searches/run",
+        EndpointClass::Search,
+    );
+    pub const WATCHLIST_ADD: Endpoint = Endpoint::new(
+        "watchlist_add",
+        "/api/traceix/v1/watchlist/add",
+        EndpointClass::Other,
+    )
+    .non_idempotent();
+    pub const WATCHLIST_REMOVE: Endpoint = Endpoint::new(
+        "watchlist_remove",
+        "/api/traceix/v1/watchlist/remove",
+        EndpointClass::Other,
+    );
+    pub const WATCHLIST_LIST: Endpoint = Endpoint::new(
+        "watchlist_list",
+        "/api/traceix/v1/watchlist/list",
+        EndpointClass::Other,
+    );
+    pub const IMPHASH_SEARCH: Endpoint = Endpoint::new(
+        "imphash_search",
+        "/api/traceix/v1/imphash/search",
+        EndpointClass::Search,
+    );
+    pub const CAPA_SEARCH: Endpoint = Endpoint::new(
+        "capa_search",
+        "/api/traceix/v1/capa/search",
+        EndpointClass::Search,
+    );
+    pub const EXIF_SEARCH: Endpoint = Endpoint::new(
+        "exif_search",
+        "/api/traceix/v1/exif/search",
+        EndpointClass::Search,
+    );
+    pub const SIMILARITY_SEARCH: Endpoint = Endpoint::new(
+        "similarity_search",
+        "/api/traceix/v1/similarity/search",
+        EndpointClass::Search,
+    );
+    pub const UPLOAD_OFFSET: Endpoint = Endpoint::new(
+        "upload_offset",
+        "/api/traceix/v1/upload/offset",
+        EndpointClass::Status,
+    );
+    pub const UPLOAD_RESUME: Endpoint = Endpoint::new(
+        "resume_upload",
+        "/api/traceix/v1/upload/resume",
+        EndpointClass::Upload,
+    );
+    pub const UPLOAD_SESSION_CREATE: Endpoint = Endpoint::new(
+        "create_upload_session",
+        "/api/traceix/v1/upload/session",
+        EndpointClass::Other,
+    )
+    .non_idempotent();
+    pub const UPLOAD_SESSION_STATUS: Endpoint = Endpoint::new(
+        "upload_session_status",
+        "/api/traceix/v1/upload/session/status",
+        EndpointClass::Status,
+    );
+    pub const UPLOAD_SESSION_PART: Endpoint = Endpoint::new(
+        "upload_session_part",
+        "/api/traceix/v1/upload/session/part",
+        EndpointClass::Upload,
+    );
+    pub const UPLOAD_SESSION_COMPLETE: Endpoint = Endpoint::new(
+        "complete_upload_session",
+        "/api/traceix/v1/upload/session/complete",
+        EndpointClass::Other,
+    )
+    .non_idempotent();
+    pub const UPLOAD_LIST: Endpoint = Endpoint::new(
+        "list_uploads",
+        "/api/traceix/v1/uploads",
+        EndpointClass::Other,
+    );
+    pub const UPLOAD_DELETE: Endpoint = Endpoint::new(
+        "delete_upload",
+        "/api/traceix/v1/uploads/delete",
+        EndpointClass::Other,
+    );
+    pub const IPFS_LIST_ALL: Endpoint = Endpoint::new(
+        "list_all_ipfs_datasets",
+        "/api/traceix/v1/ipfs/listall",
+        EndpointClass::Search,
+    );
+    pub const IPFS_SEARCH: Endpoint = Endpoint::new(
+        "get_public_ipfs_dataset",
+        "/api/traceix/v1/ipfs/search",
+        EndpointClass::Search,
+    );
+    pub const IPFS_FIND: Endpoint = Endpoint::new(
+        "search_ipfs_dataset_by_hash",
+        "/api/traceix/v1/ipfs/find",
+        EndpointClass::Search,
+    );
+    /// A block fetched from an IPFS gateway or node. Not part of the API: the request
+    /// goes to the gateway or node, without the API key.
+    pub const IPFS_BLOCK: Endpoint = Endpoint::new("ipfs_block", "/ipfs", EndpointClass::Download);
+    /// Pinning a dataset on the configured IPFS node through its RPC API.
+    pub const IPFS_PIN: Endpoint =
+        Endpoint::new("ipfs_pin", "/api/v0/pin/add", EndpointClass::Other);
+    /// Removing a pin from the configured IPFS node.
+    pub const IPFS_UNPIN: Endpoint =
+        Endpoint::new("ipfs_unpin", "/api/v0/pin/rm", EndpointClass::Other);
+
+    pub const fn new(name: &'static str, path: &'static str, class: EndpointClass) -> Self {
+        Self {
+            name,
+            path,
+            class,
+            idempotent: true,
+        }
+    }
+
+    /// Mark the endpoint as one whose requests must not be repeated blindly.
+    pub const fn non_idempotent(mut self) -> Self {
+        self.idempotent = false;
+        self
+    }
+}
@@ -0,0 +1,371 @@
+// src/error.rs
+
+use crate::{Endpoint, FileType, JobStatus, PlannedRequest};
+use chrono::{DateTime, Utc};
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use serde_json::Value;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use thiserror::Error;
+use url::Url;
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum TraceixError {
+    #[error("You did not provide an API key")]
+    NoApiKey,
+    #[error("Search must be of type capa or exif")]
+    InvalidSearchType,
+    #[error("You did not provide a UUID required by the endpoint")]
+    NoUuidProvided,
+    #[error("The batch is closed to new items")]
+    BatchClosed,
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+    #[error("Invalid base URL {url:?}: {reason}")]
+    InvalidBaseUrl { url: String, reason: String },
+    #[error("Invalid job status transition from {from} to {to}")]
+    InvalidTransition { from: JobStatus, to: JobStatus },
+    #[error("Unexpected API response: {0}")]
+    UnexpectedResponse(String),
+    #[error("Analysis {uuid} did not complete: {status}")]
+    JobNotCompleted { uuid: String, status: JobStatus },
+    /// The server has no submission with this UUID, e.g. because it was already deleted.
+    #[error("Submission {uuid} not found")]
+    SubmissionNotFound { uuid: String },
+    /// The account may not perform the operation on this submission, e.g. because
+    /// another account made it. `message` is the server's explanation.
+    #[error("Not permitted on submission {uuid}: {message}")]
+    NotPermitted { uuid: String, message: String },
+    /// The file is larger than the configured [`Preflight`](crate::Preflight) allows.
+    #[error("File is {size} bytes, over the {limit}-byte upload limit")]
+    FileTooLarge { size: u64, limit: u64 },
+    /// The file's type isn't among those the configured [`Preflight`](crate::Preflight)
+    /// accepts.
+    #[error("File type {file_type} is not accepted for upload")]
+    UnsupportedFileType { file_type: FileType },
+    #[error("Timed out after {waited:?} waiting for analysis {uuid}")]
+    WaitTimeout { uuid: String, waited: Duration },
+    #[error("Integrity check failed: expected {algorithm} {expected}, got {actual}")]
+    IntegrityMismatch {
+        algorithm: &'static str,
+        expected: String,
+        actual: String,
+    },
+    /// An error raised while calling `endpoint`, with the URL and local file involved.
+    #[error("Request to endpoint {endpoint} failed ({url}{})", file_suffix(.path))]
+    Context {
+        endpoint: &'static str,
+        url: String,
+        path: Option<PathBuf>,
+        source: Box<TraceixError>,
+    },
+    /// The server answered with an error status. `code` and `message` are taken from
+    /// the JSON error body when there is one, so callers can tell e.g. an exceeded
+    /// quota from an unsupported file type.
+    #[error("API error {status}: {message}{}", code_suffix(.code))]
+    Api {
+        status: StatusCode,
+        code: Option<String>,
+        message: String,
+        request_id: Option<String>,
+        /// Rate-limit headers sent with the response, e.g. on `429 Too Many Requests`.
+        rate_limit: Option<Box<RateLimitStatus>>,
+        /// The raw response body.
+        body: String,
+    },
+    /// The request did not finish within its time limit. `after` is the limit that was
+    /// exceeded, when known: the connect timeout if no connection could be made,
+    /// otherwise the overall request timeout.
+    #[error("Request timed out{}", after_suffix(.after))]
+    Timeout {
+        after: Option<Duration>,
+        source: reqwest::Error,
+    },
+    /// The client is in dry-run mode, so the request was built but not sent.
+    #[error("Dry run: {0} not sent")]
+    DryRun(Box<PlannedRequest>),
+    /// The operation was stopped through a [`CancellationToken`](crate::CancellationToken).
+    #[error("Operation cancelled")]
+    Cancelled,
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// Reading or writing the local file at `path` failed.
+    #[error("IO error on {}", .path.display())]
+    File { path: PathBuf, source: io::Error },
+}
+
+/// `error` followed by each of its causes, joined with `: `, for places that can only
+/// keep a string, such as dead-letter records.
+#[cfg(feature = "blocking")]
+pub(crate) fn display_chain(error: &dyn std::error::Error) -> String {
+    let mut text = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        text.push_str(": ");
+        text.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    text
+}
+
+fn after_suffix(after: &Option<Duration>) -> String {
+    after.map(|d| format!(" after {d:?}")).unwrap_or_default()
+}
+
+fn code_suffix(code: &Option<String>) -> String {
+    code.as_ref().map(|c| format!(" ({c})")).unwrap_or_default()
+}
+
+/// Rate-limit state reported by the server through `Retry-After` and `X-RateLimit-*` headers.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RateLimitStatus {
+    /// How long the server asked the client to wait before retrying.
+    pub retry_after: Option<Duration>,
+    /// Requests allowed in the current window (`X-RateLimit-Limit`).
+    pub limit: Option<u64>,
+    /// Requests left in the current window (`X-RateLimit-Remaining`).
+    pub remaining: Option<u64>,
+    /// Time until the window resets (`X-RateLimit-Reset`).
+    pub reset: Option<Duration>,
+}
+
+impl RateLimitStatus {
+    /// Read the rate-limit headers, or `None` if the response carried none.
+    pub(crate) fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let header = |name: &str| headers.get(name)?.to_str().ok().map(str::trim);
+        let number = |name: &str| header(name)?.parse::<u64>().ok();
+
+        let status = Self {
+            retry_after: header("retry-after").and_then(parse_retry_after),
+            limit: number("x-ratelimit-limit"),
+            remaining: number("x-ratelimit-remaining"),
+            reset: number("x-ratelimit-reset").map(reset_delay),
+        };
+        (status != Self::default()).then_some(status)
+    }
+
+    /// How long to wait before the next request: `Retry-After` if sent, otherwise the
+    /// time until the window resets once it is exhausted.
+    pub fn wait(&self) -> Option<Duration> {
+        self.retry_after.or(match self.remaining {
+            Some(0) | None => self.reset,
+            Some(_) => None,
+        })
+    }
+}
+
+/// `Retry-After` is either a number of seconds or an HTTP date.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (at.with_timezone(&Utc) - Utc::now())
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+/// `X-RateLimit-Reset` is either seconds until the reset or a Unix timestamp.
+fn reset_delay(value: u64) -> Duration {
+    // Anything past 2001-09-09 is taken as a timestamp rather than a delay.
+    const EPOCH_THRESHOLD: u64 = 1_000_000_000;
+    if value < EPOCH_THRESHOLD {
+        return Duration::from_secs(value);
+    }
+    let now = Utc::now().timestamp().max(0) as u64;
+    Duration::from_secs(value.saturating_sub(now))
+}
+
+/// Headers servers and gateways commonly use to echo a request ID.
+const REQUEST_ID_HEADERS: [&str; 3] = ["x-request-id", "x-correlation-id", "x-amzn-requestid"];
+
+fn file_suffix(path: &Option<PathBuf>) -> String {
+    path.as_ref()
+        .map(|p| format!(", file {}", p.display()))
+        .unwrap_or_default()
+}
+
+impl TraceixError {
+    #[cfg_attr(not(any(feature = "blocking", feature = "async")), allow(dead_code))]
+    pub(crate) fn with_context(self, endpoint: &Endpoint, url: &Url, path: Option<&Path>) -> Self {
+        TraceixError::Context {
+            endpoint: endpoint.name,
+            url: url.to_string(),
+            path: path.map(Path::to_path_buf),
+            source: Box::new(self),
+        }
+    }
+
+    /// Wrap an I/O error with the local file it happened on.
+    pub(crate) fn file(path: &Path, source: io::Error) -> Self {
+        TraceixError::File {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+
+    /// Turn a timed-out [`TraceixError::Http`] into [`TraceixError::Timeout`], given the
+    /// request and connect timeouts that were in effect.
+    #[cfg_attr(not(any(feature = "blocking", feature = "async")), allow(dead_code))]
+    pub(crate) fn timed_out(
+        self,
+        timeout: Option<Duration>,
+        connect_timeout: Option<Duration>,
+    ) -> Self {
+        match self {
+            TraceixError::Http(source) if source.is_timeout() => {
+                let after = if source.is_connect() {
+                    connect_timeout
+                } else {
+                    timeout
+                };
+                TraceixError::Timeout { after, source }
+            }
+            other => other,
+        }
+    }
+
+    /// Build an [`TraceixError::Api`] from an error response.
+    #[cfg_attr(not(any(feature = "blocking", feature = "async")), allow(dead_code))]
+    pub(crate) fn api(status: StatusCode, headers: &HeaderMap, body: &[u8]) -> Self {
+        let body = String::from_utf8_lossy(body).into_owned();
+        let json: Option<Value> = serde_json::from_str(&body).ok();
+        // Some servers nest the details, e.g. {"error": {"code": ..., "message": ...}}.
+        let details = json
+            .as_ref()
+            .map(|json| match json.get("error") {
+                Some(error) if error.is_object() => error,
+                _ => json,
+            })
+            .filter(|details| details.is_object());
+        let field = |names: &[&str]| {
+            details.and_then(|details| {
+                names.iter().find_map(|name| match details.get(*name)? {
+                    Value::String(s) if !s.is_empty() => Some(s.clone()),
+                    Value::Number(n) => Some(n.to_string()),
+                    _ => None,
+                })
+            })
+        };
+
+        let code = field(&["code", "error_code", "type"]);
+        // `Message` is how Kubo, the IPFS node, spells it.
+        let message = field(&["message", "Message", "error", "detail", "msg"])
+            .or_else(|| {
+                let text = body.trim();
+                (!text.is_empty() && json.is_none() && text.len() <= 200).then(|| text.to_string())
+            })
+            .unwrap_or_else(|| status.canonical_reason().unwrap_or("error").to_string());
+        let request_id = REQUEST_ID_HEADERS
+            .iter()
+            .find_map(|name| headers.get(*name)?.to_str().ok())
+            .map(str::to_string)
+            .or_else(|| field(&["request_id", "requestId"]));
+
+        TraceixError::Api {
+            status,
+            code,
+            message,
+            request_id,
+            rate_limit: RateLimitStatus::from_headers(headers).map(Box::new),
+            body,
+        }
+    }
+
+    /// Whether the server rejected the request for exceeding its rate limit (HTTP 429).
+    pub fn is_rate_limited(&self) -> bool {
+        self.status() == Some(StatusCode::TOO_MANY_REQUESTS)
+    }
+
+    /// Rate-limit headers from the failed response, if any were sent.
+    pub fn rate_limit(&self) -> Option<&RateLimitStatus> {
+        match self.root() {
+            TraceixError::Api { rate_limit, .. } => rate_limit.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// How long the server asked to wait before retrying, if it said.
+    pub fn retry_after(&self) -> Option<Duration> {
+        self.rate_limit().and_then(RateLimitStatus::wait)
+    }
+
+    /// HTTP status of the failed response, if the server answered.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self.root() {
+            TraceixError::Api { status, .. } => Some(*status),
+            TraceixError::Http(e) => e.status(),
+            _ => None,
+        }
+    }
+
+    /// Whether the request ran into a timeout.
+    pub fn is_timeout(&self) -> bool {
+        matches!(self.root(), TraceixError::Timeout { .. })
+    }
+
+    /// Whether the API couldn't be reached or failed on its side: connection errors,
+    /// timeouts and 5xx responses, as opposed to a request it rejected.
+    pub fn is_unavailable(&self) -> bool {
+        match self.root() {
+            TraceixError::Timeout { .. } => true,
+            TraceixError::Http(e) => {
+                !e.is_decode() && e.status().is_none_or(|status| status.is_server_error())
+            }
+            TraceixError::Api { status, .. } => status.is_server_error(),
+            _ => false,
+        }
+    }
+
+    /// Whether the operation was stopped through a
+    /// [`CancellationToken`](crate::CancellationToken).
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self.root(), TraceixError::Cancelled)
+    }
+
+    /// The request a dry-run client would have sent, if this is a
+    /// [`TraceixError::DryRun`].
+    pub fn planned_request(&self) -> Option<&PlannedRequest> {
+        match self.root() {
+            TraceixError::DryRun(planned) => Some(planned),
+            _ => None,
+        }
+    }
+
+    /// The underlying error, looking through any [`TraceixError::Context`] wrapping.
+    pub fn root(&self) -> &TraceixError {
+        match self {
+            TraceixError::Context { source, .. } => source.root(),
+            other => other,
+        }
+    }
+
+    /// Name of the endpoint that was being called, if known.
+    pub fn endpoint(&self) -> Option<&'static str> {
+        match self {
+            TraceixError::Context { endpoint, .. } => Some(endpoint),
+            _ => None,
+        }
+    }
+
+    /// Local file the failing request or operation was reading from or writing to, if
+    /// any.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            TraceixError::Context {
+                path: Some(path), ..
+            } => Some(path),
+            TraceixError::Context { source, .. } => source.path(),
+            TraceixError::File { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+}
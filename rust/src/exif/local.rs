@@ -0,0 +1,72 @@
+// src/exif/local.rs
+
+//! Reading EXIF metadata without the API, e.g. on air-gapped triage machines.
+
+use super::ExifReport;
+use crate::TraceixError;
+use exif::{Field, In, Reader};
+use serde_json::{Map, Value};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+/// Read the EXIF metadata of the image at `path` locally, into the same
+/// [`ExifReport`] the API's results parse into. Nothing is sent over the network.
+///
+/// Handles JPEG, TIFF and camera RAW, PNG, WebP and HEIF. An image without EXIF gives
+/// an empty report; a file in another format fails with
+/// [`TraceixError::InvalidArgument`]. Tag names follow the EXIF specification, so a few
+/// keys in [`ExifReport::other`] differ from ExifTool's.
+pub fn read_exif(path: impl AsRef<Path>) -> Result<ExifReport, TraceixError> {
+    let path = path.as_ref();
+    let mut file = BufReader::new(File::open(path)?);
+    let exif = match Reader::new().read_from_container(&mut file) {
+        Ok(exif) => exif,
+        Err(exif::Error::NotFound(_)) => return Ok(ExifReport::default()),
+        Err(exif::Error::Io(e)) => return Err(e.into()),
+        Err(e) => {
+            return Err(TraceixError::InvalidArgument(format!(
+                "{}: {e}",
+                path.display()
+            )))
+        }
+    };
+
+    // Tags of the embedded thumbnail describe the thumbnail, not the photo.
+    let tags: Map<String, Value> = exif
+        .fields()
+        .filter(|field| field.ifd_num == In::PRIMARY)
+        .map(|field| (field.tag.to_string(), json_value(field)))
+        .collect();
+    Ok(ExifReport::from_tags(&tags))
+}
+
+/// Convert a field to JSON the way ExifTool's numeric output would show it: text as a
+/// string, single numbers as numbers, several as an array.
+fn json_value(field: &Field) -> Value {
+    let mut numbers: Vec<Value> = match &field.value {
+        exif::Value::Ascii(strings) => {
+            return strings
+                .first()
+                .map(|s| Value::String(String::from_utf8_lossy(s).trim().to_string()))
+                .unwrap_or(Value::Null)
+        }
+        exif::Value::Byte(v) => v.iter().map(|&n| n.into()).collect(),
+        exif::Value::Short(v) => v.iter().map(|&n| n.into()).collect(),
+        exif::Value::Long(v) => v.iter().map(|&n| n.into()).collect(),
+        exif::Value::SByte(v) => v.iter().map(|&n| n.into()).collect(),
+        exif::Value::SShort(v) => v.iter().map(|&n| n.into()).collect(),
+        exif::Value::SLong(v) => v.iter().map(|&n| n.into()).collect(),
+        exif::Value::Rational(v) => v.iter().map(|r| r.to_f64().into()).collect(),
+        exif::Value::SRational(v) => v.iter().map(|r| r.to_f64().into()).collect(),
+        exif::Value::Float(v) => v.iter().map(|&n| f64::from(n).into()).collect(),
+        exif::Value::Double(v) => v.iter().map(|&n| n.into()).collect(),
+        // Opaque bytes such as `UserComment`; use the crate's readable form.
+        _ => return Value::String(field.display_value().to_string()),
+    };
+    if numbers.len() == 1 {
+        numbers.pop().unwrap_or_default()
+    } else {
+        Value::Array(numbers)
+    }
+}
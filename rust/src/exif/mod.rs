@@ -0,0 +1,403 @@
+// src/exif/mod.rs
+
+//! Typed view of EXIF results, as returned by
+//! [`TraceixSdk::exif_extraction`](crate::TraceixSdk::exif_extraction) and
+//! [`TraceixSdk::get_exif_result`](crate::TraceixSdk::get_exif_result).
+//!
+//! Tag names are matched without their ExifTool group prefix (`EXIF:Make` and `Make`
+//! are the same tag), and values in both ExifTool's readable and numeric (`-n`) forms
+//! are understood.
+//!
+//! ```
+//! # use traceix_sdk::ExifReport;
+//! let result = serde_json::json!({
+//!     "EXIF:Make": "Canon",
+//!     "EXIF:Model": "Canon EOS 5D Mark IV",
+//!     "EXIF:DateTimeOriginal": "2023:06:01 14:30:05",
+//!     "EXIF:OffsetTimeOriginal": "+02:00",
+//!     "EXIF:GPSLatitude": "48 deg 51' 29.52\" N",
+//!     "EXIF:GPSLongitude": "2 deg 17' 40.20\" E",
+//!     "EXIF:GPSAltitude": "35 m Above Sea Level",
+//!     "EXIF:LensModel": "EF24-70mm f/2.8L II USM",
+//! });
+//! let report = ExifReport::from_value(&result)?;
+//! let gps = report.gps.unwrap();
+//! assert!((gps.lat - 48.8582).abs() < 1e-4 && (gps.lon - 2.2945).abs() < 1e-4);
+//! assert_eq!(report.date_taken.unwrap().to_utc().unwrap().to_rfc3339(), "2023-06-01T12:30:05+00:00");
+//! assert!(report.other.contains_key("EXIF:LensModel"));
+//! # Ok::<(), traceix_sdk::TraceixError>(())
+//! ```
+
+#[cfg(feature = "local-exif")]
+mod local;
+mod strip;
+
+#[cfg(feature = "local-exif")]
+pub use local::read_exif;
+pub use strip::{strip_exif, strip_exif_bytes};
+
+use crate::TraceixError;
+use chrono::{DateTime, FixedOffset, NaiveDateTime, TimeZone, Utc};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fmt;
+#[cfg(feature = "blocking")]
+use {crate::TraceixSdk, std::path::Path};
+
+/// Tags read into [`ExifReport`]'s typed fields, and so left out of
+/// [`ExifReport::other`].
+const KNOWN_TAGS: &[&str] = &[
+    "make",
+    "model",
+    "software",
+    "datetimeoriginal",
+    "createdate",
+    "datetimedigitized",
+    "modifydate",
+    "datetime",
+    "offsettimeoriginal",
+    "offsettimedigitized",
+    "offsettime",
+    "gpslatitude",
+    "gpslatituderef",
+    "gpslongitude",
+    "gpslongituderef",
+    "gpsaltitude",
+    "gpsaltituderef",
+];
+
+/// Where [`TraceixSdk::exif_report`](crate::TraceixSdk::exif_report) reads EXIF
+/// metadata from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ExtractionMode {
+    /// Upload the file for EXIF extraction by the API.
+    #[default]
+    Remote,
+    /// Read the metadata locally with [`read_exif`]; nothing is sent. Needs the
+    /// `local-exif` feature.
+    #[cfg(feature = "local-exif")]
+    Local,
+    /// Use the API, but read the metadata locally when it can't be reached or fails on
+    /// its side (see [`TraceixError::is_unavailable`]). Needs the `local-exif` feature.
+    #[cfg(feature = "local-exif")]
+    RemoteWithLocalFallback,
+}
+
+/// The EXIF metadata of one file.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct ExifReport {
+    /// Camera or device manufacturer.
+    pub make: Option<String>,
+    /// Camera or device model.
+    pub model: Option<String>,
+    /// Software that wrote or last edited the file.
+    pub software: Option<String>,
+    /// When the photo was taken (`DateTimeOriginal`).
+    pub date_taken: Option<ExifTimestamp>,
+    /// When the image was digitized (`CreateDate`), e.g. scanned.
+    pub date_digitized: Option<ExifTimestamp>,
+    /// When the file was last changed (`ModifyDate`).
+    pub date_modified: Option<ExifTimestamp>,
+    pub gps: Option<GpsCoordinates>,
+    /// Every other tag, under its original name.
+    pub other: BTreeMap<String, Value>,
+}
+
+/// An EXIF date and time. EXIF stores local time; the UTC offset is only known when
+/// the file carries an `OffsetTime*` tag or the value includes one. Serializes as
+/// RFC 3339, without an offset when it is unknown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExifTimestamp {
+    pub local: NaiveDateTime,
+    pub offset: Option<FixedOffset>,
+}
+
+impl ExifTimestamp {
+    /// The timestamp with its offset, if the offset is known.
+    pub fn with_offset(&self) -> Option<DateTime<FixedOffset>> {
+        self.offset?.from_local_datetime(&self.local).single()
+    }
+
+    /// The timestamp in UTC, if the offset is known.
+    pub fn to_utc(&self) -> Option<DateTime<Utc>> {
+        self.with_offset().map(|t| t.with_timezone(&Utc))
+    }
+
+    /// Parse `2023:06:01 14:30:05`, optionally with fractional seconds and an offset
+    /// such as `+02:00` or `Z`.
+    fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let (datetime, offset) = split_offset(value);
+        let local = [
+            "%Y:%m:%d %H:%M:%S%.f",
+            "%Y-%m-%d %H:%M:%S%.f",
+            "%Y-%m-%dT%H:%M:%S%.f",
+        ]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(datetime, format).ok())?;
+        Some(Self { local, offset })
+    }
+}
+
+impl fmt::Display for ExifTimestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.with_offset() {
+            Some(t) => write!(f, "{}", t.to_rfc3339()),
+            None => write!(f, "{}", self.local.format("%Y-%m-%dT%H:%M:%S")),
+        }
+    }
+}
+
+impl Serialize for ExifTimestamp {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Where a photo was taken, in signed decimal degrees (south and west negative).
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct GpsCoordinates {
+    pub lat: f64,
+    pub lon: f64,
+    /// Meters above sea level; negative below it.
+    pub altitude: Option<f64>,
+}
+
+impl GpsCoordinates {
+    /// Convert degrees, minutes and seconds to decimal degrees. `reference` is the
+    /// hemisphere (`N`, `S`, `E` or `W`); `S` and `W` give a negative result.
+    pub fn dms_to_decimal(degrees: f64, minutes: f64, seconds: f64, reference: char) -> f64 {
+        let value = degrees.abs() + minutes / 60.0 + seconds / 3600.0;
+        if matches!(reference.to_ascii_uppercase(), 'S' | 'W') || degrees < 0.0 {
+            -value
+        } else {
+            value
+        }
+    }
+}
+
+impl fmt::Display for GpsCoordinates {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.6}, {:.6}", self.lat, self.lon)
+    }
+}
+
+impl ExifReport {
+    /// Parse an EXIF result document: a flat object of tags, possibly wrapped in an
+    /// `exif`, `result`, `data` or `metadata` field or in a one-element array as
+    /// ExifTool prints it.
+    pub fn from_value(value: &Value) -> Result<Self, TraceixError> {
+        let tags = find_tags(value)
+            .ok_or_else(|| TraceixError::UnexpectedResponse(format!("no EXIF tags in {value}")))?;
+        Ok(Self::from_tags(tags))
+    }
+
+    fn from_tags(tags: &Map<String, Value>) -> Self {
+        let by_name: BTreeMap<String, &Value> = tags
+            .iter()
+            .map(|(key, value)| (tag_name(key), value))
+            .collect();
+        let text = |name: &str| {
+            by_name
+                .get(name)
+                .and_then(|v| value_text(v))
+                .filter(|s| !s.is_empty())
+        };
+        let timestamp = |names: &[&str], offset_tag: &str| {
+            let mut stamp = names
+                .iter()
+                .find_map(|name| ExifTimestamp::parse(&text(name)?))?;
+            if stamp.offset.is_none() {
+                stamp.offset = text(offset_tag).and_then(|o| parse_offset(o.trim()));
+            }
+            Some(stamp)
+        };
+
+        Self {
+            make: text("make"),
+            model: text("model"),
+            software: text("software"),
+            date_taken: timestamp(&["datetimeoriginal"], "offsettimeoriginal"),
+            date_digitized: timestamp(&["createdate", "datetimedigitized"], "offsettimedigitized"),
+            date_modified: timestamp(&["modifydate", "datetime"], "offsettime"),
+            gps: gps(&by_name),
+            other: tags
+                .iter()
+                .filter(|(key, _)| !KNOWN_TAGS.contains(&tag_name(key).as_str()))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl TraceixSdk {
+    /// Extract the EXIF metadata of `filename` as an [`ExifReport`], from the API or
+    /// locally as `mode` selects.
+    ///
+    /// ```no_run
+    /// # use traceix_sdk::{ExtractionMode, TraceixSdk};
+    /// # fn main() -> Result<(), traceix_sdk::TraceixError> {
+    /// let sdk = TraceixSdk::new(None)?;
+    /// let report = sdk.exif_report("IMG_0042.jpg", ExtractionMode::Remote)?;
+    /// if let Some(gps) = report.gps {
+    ///     println!("taken at {gps}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn exif_report(
+        &self,
+        filename: impl AsRef<Path>,
+        mode: ExtractionMode,
+    ) -> Result<ExifReport, TraceixError> {
+        let filename = filename.as_ref();
+        match mode {
+            ExtractionMode::Remote => ExifReport::from_value(&self.exif_extraction(filename)?),
+            #[cfg(feature = "local-exif")]
+            ExtractionMode::Local => read_exif(filename),
+            #[cfg(feature = "local-exif")]
+            ExtractionMode::RemoteWithLocalFallback => match self.exif_extraction(filename) {
+                Err(e) if e.is_unavailable() => read_exif(filename),
+                result => ExifReport::from_value(&result?),
+            },
+        }
+    }
+}
+
+fn gps(tags: &BTreeMap<String, &Value>) -> Option<GpsCoordinates> {
+    let reference = |name: &str| {
+        tags.get(name)
+            .and_then(|v| value_text(v))
+            .and_then(|s| s.trim().chars().next())
+    };
+    let lat = coordinate(tags.get("gpslatitude")?, reference("gpslatituderef"))?;
+    let lon = coordinate(tags.get("gpslongitude")?, reference("gpslongituderef"))?;
+    if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lon) {
+        return None;
+    }
+
+    let altitude = tags
+        .get("gpsaltitude")
+        .and_then(|v| numbers(v).first().copied())
+        .map(|meters| {
+            let below = tags
+                .get("gpsaltitude")
+                .and_then(|v| value_text(v))
+                .is_some_and(|s| s.contains("Below"))
+                || tags.get("gpsaltituderef").is_some_and(|r| {
+                    r.as_u64() == Some(1)
+                        || value_text(r).is_some_and(|s| s.trim() == "1" || s.contains("Below"))
+                });
+            if below {
+                -meters.abs()
+            } else {
+                meters
+            }
+        });
+    Some(GpsCoordinates { lat, lon, altitude })
+}
+
+/// A latitude or longitude in any of the forms ExifTool prints: signed decimal,
+/// `48 deg 51' 29.52" N`, `48.858200 N`, `48, 51, 29.52` or raw rationals.
+fn coordinate(value: &Value, reference: Option<char>) -> Option<f64> {
+    let parts = numbers(value);
+    let (degrees, minutes, seconds) = match parts.as_slice() {
+        [d] => (*d, 0.0, 0.0),
+        [d, m] => (*d, *m, 0.0),
+        [d, m, s, ..] => (*d, *m, *s),
+        [] => return None,
+    };
+    // A hemisphere letter in the value itself wins over the separate reference tag.
+    let inline = value_text(value).and_then(|s| {
+        s.chars()
+            .rev()
+            .find(|c| c.is_ascii_alphabetic())
+            .filter(|c| matches!(c.to_ascii_uppercase(), 'N' | 'S' | 'E' | 'W'))
+    });
+    let reference = inline.or(reference).unwrap_or('N');
+    Some(GpsCoordinates::dms_to_decimal(
+        degrees, minutes, seconds, reference,
+    ))
+}
+
+/// The numbers in a value, reading `a/b` as a fraction.
+fn numbers(value: &Value) -> Vec<f64> {
+    match value {
+        Value::Number(n) => n.as_f64().into_iter().collect(),
+        Value::Array(items) => items.iter().flat_map(numbers).collect(),
+        Value::String(s) => s
+            .split(|c: char| !(c.is_ascii_digit() || matches!(c, '.' | '/' | '-')))
+            .filter(|token| token.chars().any(|c| c.is_ascii_digit()))
+            .filter_map(|token| match token.split_once('/') {
+                Some((a, b)) => {
+                    let b: f64 = b.parse().ok()?;
+                    (b != 0.0).then_some(a.parse::<f64>().ok()? / b)
+                }
+                None => token.parse().ok(),
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn find_tags(value: &Value) -> Option<&Map<String, Value>> {
+    match value {
+        Value::Array(items) if items.len() == 1 => find_tags(&items[0]),
+        Value::Object(object) => ["exif", "result", "data", "metadata"]
+            .iter()
+            .find_map(|key| find_tags(object.get(*key)?))
+            .or(Some(object)),
+        _ => None,
+    }
+}
+
+/// `EXIF:DateTimeOriginal` -> `datetimeoriginal`.
+fn tag_name(key: &str) -> String {
+    key.rsplit(':').next().unwrap_or(key).to_ascii_lowercase()
+}
+
+fn value_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Split a trailing `+02:00`, `-0500` or `Z` off a timestamp.
+fn split_offset(value: &str) -> (&str, Option<FixedOffset>) {
+    if let Some(datetime) = value.strip_suffix('Z') {
+        return (datetime, FixedOffset::east_opt(0));
+    }
+    match value.rfind(['+', '-']) {
+        // Only past the time part, so date separators aren't taken for a sign.
+        Some(at) if at > 10 => match parse_offset(&value[at..]) {
+            Some(offset) => (&value[..at], Some(offset)),
+            None => (value, None),
+        },
+        _ => (value, None),
+    }
+}
+
+/// Parse a UTC offset such as `+02:00`, `-0530` or `Z`.
+fn parse_offset(offset: &str) -> Option<FixedOffset> {
+    if offset == "Z" {
+        return FixedOffset::east_opt(0);
+    }
+    let (sign, rest) = match offset.as_bytes().first()? {
+        b'+' => (1, &offset[1..]),
+        b'-' => (-1, &offset[1..]),
+        _ => return None,
+    };
+    let digits: String = rest.chars().filter(|c| *c != ':').collect();
+    if digits.len() != 4 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let hours: i32 = digits[..2].parse().ok()?;
+    let minutes: i32 = digits[2..].parse().ok()?;
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
@@ -0,0 +1,278 @@
+// src/exif/strip.rs
+
+//! Removing identifying metadata from images before they leave the machine.
+
+use crate::TraceixError;
+use std::fs;
+use std::path::Path;
+use zeroize::Zeroizing;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// JPEG APP segments carrying metadata, by the identifier they start with.
+const JPEG_METADATA: &[&[u8]] = &[
+    b"Exif\0",
+    b"http://ns.adobe.com/xap/1.0/\0",
+    b"http://ns.adobe.com/xmp/extension/\0",
+    // IPTC, which holds bylines and locations.
+    b"Photoshop 3.0\0",
+];
+
+/// PNG chunks carrying metadata: EXIF, the text chunks XMP and comments are stored
+/// in, and the last-modified time.
+const PNG_METADATA: &[&[u8; 4]] = &[b"eXIf", b"tEXt", b"zTXt", b"iTXt", b"tIME"];
+
+/// Read the image at `path` and return a copy without its EXIF, XMP and IPTC metadata,
+/// so it can be submitted without revealing where it was taken or on which device.
+///
+/// JPEG, PNG and WebP images are rewritten; pixel data is copied unchanged, never
+/// re-encoded. The orientation tag goes with the rest of the EXIF, so a photo may
+/// display rotated. Anything after the end of a JPEG's primary image is dropped too,
+/// including the previews, gain maps and motion photos phones append there with
+/// their own EXIF. Files that aren't images are returned as they are. Fails with
+/// [`TraceixError::InvalidArgument`] for image formats whose metadata can't be
+/// removed safely, such as TIFF, camera RAW and HEIC.
+pub fn strip_exif(path: impl AsRef<Path>) -> Result<Vec<u8>, TraceixError> {
+    let path = path.as_ref();
+    strip_exif_bytes(&Zeroizing::new(fs::read(path)?)).map_err(|e| match e {
+        TraceixError::InvalidArgument(reason) => {
+            TraceixError::InvalidArgument(format!("{}: {reason}", path.display()))
+        }
+        e => e,
+    })
+}
+
+/// Like [`strip_exif`], for an image already in memory.
+pub fn strip_exif_bytes(data: &[u8]) -> Result<Vec<u8>, TraceixError> {
+    if data.starts_with(b"\xff\xd8") {
+        strip_jpeg(data)
+    } else if data.starts_with(PNG_SIGNATURE) {
+        strip_png(data)
+    } else if data.starts_with(b"RIFF") && data.get(8..12) == Some(b"WEBP") {
+        strip_webp(data)
+    } else if data.starts_with(b"II*\0") || data.starts_with(b"MM\0*") {
+        Err(unsupported("TIFF-based images (including camera RAW)"))
+    } else if data.get(4..8) == Some(b"ftyp")
+        && matches!(data.get(8..12), Some(b"heic" | b"heix" | b"mif1" | b"avif"))
+    {
+        Err(unsupported("HEIF/AVIF images"))
+    } else {
+        Ok(data.to_vec())
+    }
+}
+
+fn strip_jpeg(data: &[u8]) -> Result<Vec<u8>, TraceixError> {
+    let mut out = Vec::with_capacity(data.len());
+    out.extend_from_slice(&data[..2]);
+    let mut at = 2;
+    loop {
+        // Markers may be preceded by any number of 0xff fill bytes.
+        while data.get(at) == Some(&0xff) && data.get(at + 1) == Some(&0xff) {
+            at += 1;
+        }
+        let marker = match data.get(at..at + 2) {
+            Some([0xff, marker]) => *marker,
+            // Some writers leave trailing garbage after the image; keep it as is.
+            _ => {
+                out.extend_from_slice(data.get(at..).unwrap_or_default());
+                return Ok(out);
+            }
+        };
+        // Markers without a length field.
+        if marker == 0x01 || (0xd0..=0xd9).contains(&marker) {
+            out.extend_from_slice(&data[at..at + 2]);
+            at += 2;
+            // End of the primary image. Phones append further JPEGs after it (MPF
+            // previews, gain maps, motion photos), each with its own EXIF, so drop them.
+            if marker == 0xd9 {
+                return Ok(out);
+            }
+            continue;
+        }
+        let len = data
+            .get(at + 2..at + 4)
+            .map(|l| usize::from(u16::from_be_bytes([l[0], l[1]])))
+            .filter(|&len| len >= 2)
+            .ok_or_else(|| malformed("JPEG"))?;
+        let end = at + 2 + len;
+        let segment = data.get(at..end).ok_or_else(|| malformed("JPEG"))?;
+        let payload = &segment[4..];
+
+        let metadata = match marker {
+            0xe1 | 0xed => JPEG_METADATA.iter().any(|id| payload.starts_with(id)),
+            // The MPF index of the appended images, which are dropped.
+            0xe2 => payload.starts_with(b"MPF\0"),
+            // Comments, which cameras and editors fill with serial numbers and names.
+            0xfe => true,
+            _ => false,
+        };
+        if !metadata {
+            out.extend_from_slice(segment);
+        }
+        at = end;
+        // Start of scan: entropy-coded data follows, up to the next marker other than
+        // a stuffed 0xff or a restart marker. Progressive images have several scans.
+        if marker == 0xda {
+            let scan = entropy_coded_len(&data[at..]);
+            out.extend_from_slice(&data[at..at + scan]);
+            at += scan;
+            if at == data.len() {
+                return Ok(out);
+            }
+        }
+    }
+}
+
+/// Length of the entropy-coded data at the start of `data`, which ends at the first
+/// marker that isn't a stuffed `0xff 0x00` or a restart marker.
+fn entropy_coded_len(data: &[u8]) -> usize {
+    let mut at = 0;
+    while let Some(offset) = data[at..].iter().position(|&b| b == 0xff) {
+        at += offset;
+        match data.get(at + 1) {
+            Some(0x00 | 0xd0..=0xd7) => at += 2,
+            // Fill bytes before the marker.
+            Some(0xff) => at += 1,
+            Some(_) => return at,
+            None => return data.len(),
+        }
+    }
+    data.len()
+}
+
+fn strip_png(data: &[u8]) -> Result<Vec<u8>, TraceixError> {
+    let mut out = Vec::with_capacity(data.len());
+    out.extend_from_slice(PNG_SIGNATURE);
+    let mut at = PNG_SIGNATURE.len();
+    while at < data.len() {
+        let header = data.get(at..at + 8).ok_or_else(|| malformed("PNG"))?;
+        let len = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        // Length, type, data and CRC.
+        let end = at
+            .checked_add(12)
+            .and_then(|n| n.checked_add(len))
+            .filter(|&end| end <= data.len())
+            .ok_or_else(|| malformed("PNG"))?;
+        if !PNG_METADATA.iter().any(|kind| &header[4..8] == *kind) {
+            out.extend_from_slice(&data[at..end]);
+        }
+        at = end;
+        if &header[4..8] == b"IEND" {
+            break;
+        }
+    }
+    Ok(out)
+}
+
+fn strip_webp(data: &[u8]) -> Result<Vec<u8>, TraceixError> {
+    let mut chunks = Vec::with_capacity(data.len());
+    let mut at = 12;
+    while at + 8 <= data.len() {
+        let kind = &data[at..at + 4];
+        let len =
+            u32::from_le_bytes([data[at + 4], data[at + 5], data[at + 6], data[at + 7]]) as usize;
+        // Chunks are padded to an even length.
+        let end = at
+            .checked_add(8 + len + len % 2)
+            .filter(|&end| end <= data.len())
+            .ok_or_else(|| malformed("WebP"))?;
+        match kind {
+            b"EXIF" | b"XMP " => {}
+            b"VP8X" if len >= 1 => {
+                let start = chunks.len();
+                chunks.extend_from_slice(&data[at..end]);
+                // Clear the "has EXIF" and "has XMP" flags.
+                chunks[start + 8] &= !(0x08 | 0x04);
+            }
+            _ => chunks.extend_from_slice(&data[at..end]),
+        }
+        at = end;
+    }
+
+    let mut out = Vec::with_capacity(chunks.len() + 12);
+    out.extend_from_slice(b"RIFF");
+    out.extend_from_slice(&(chunks.len() as u32 + 4).to_le_bytes());
+    out.extend_from_slice(b"WEBP");
+    out.extend_from_slice(&chunks);
+    Ok(out)
+}
+
+fn unsupported(format: &str) -> TraceixError {
+    TraceixError::InvalidArgument(format!("can't strip metadata from {format}"))
+}
+
+fn malformed(format: &str) -> TraceixError {
+    TraceixError::InvalidArgument(format!("malformed {format} file"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A JPEG segment with `marker` and `payload`.
+    fn segment(marker: u8, payload: &[u8]) -> Vec<u8> {
+        let mut out = vec![0xff, marker];
+        out.extend_from_slice(&(payload.len() as u16 + 2).to_be_bytes());
+        out.extend_from_slice(payload);
+        out
+    }
+
+    /// A baseline JPEG with EXIF holding `location`, and a scan with stuffed and
+    /// restart bytes.
+    fn jpeg(location: &[u8]) -> Vec<u8> {
+        let mut exif = b"Exif\0\0GPS ".to_vec();
+        exif.extend_from_slice(location);
+        let mut out = vec![0xff, 0xd8];
+        out.extend(segment(0xe1, &exif));
+        out.extend(segment(0xdb, &[0; 65]));
+        out.extend(segment(0xda, &[1, 1, 0, 0, 63, 0]));
+        out.extend_from_slice(&[0x12, 0xff, 0x00, 0x34, 0xff, 0xd0, 0x56]);
+        out.extend_from_slice(&[0xff, 0xd9]);
+        out
+    }
+
+    fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+        haystack.windows(needle.len()).any(|w| w == needle)
+    }
+
+    #[test]
+    fn strips_exif_and_keeps_scan_data() {
+        let stripped = strip_exif_bytes(&jpeg(b"51.5N")).unwrap();
+        assert!(!contains(&stripped, b"Exif"));
+        assert!(contains(
+            &stripped,
+            &[0x12, 0xff, 0x00, 0x34, 0xff, 0xd0, 0x56]
+        ));
+        assert!(stripped.ends_with(&[0xff, 0xd9]));
+    }
+
+    #[test]
+    fn drops_jpegs_appended_after_the_primary_image() {
+        let mut mpf = b"MPF\0".to_vec();
+        mpf.extend_from_slice(&[0; 16]);
+        let mut primary = jpeg(b"51.5N");
+        primary.splice(2..2, segment(0xe2, &mpf));
+        let mut data = primary.clone();
+        data.extend(jpeg(b"48.8N"));
+
+        let stripped = strip_exif_bytes(&data).unwrap();
+        assert!(!contains(&stripped, b"GPS"));
+        assert!(!contains(&stripped, b"MPF"));
+        assert_eq!(stripped, strip_exif_bytes(&jpeg(b"51.5N")).unwrap());
+    }
+
+    #[test]
+    fn keeps_every_scan_of_a_progressive_jpeg() {
+        let mut data = vec![0xff, 0xd8];
+        data.extend(segment(0xda, &[1, 1, 0, 0, 0, 0]));
+        data.extend_from_slice(&[0xaa, 0xbb]);
+        data.extend(segment(0xc4, &[0; 17]));
+        data.extend(segment(0xda, &[1, 1, 0, 1, 63, 0]));
+        data.extend_from_slice(&[0xcc, 0xff, 0xff, 0xd9]);
+
+        let stripped = strip_exif_bytes(&data).unwrap();
+        assert!(contains(&stripped, &[0xaa, 0xbb]));
+        assert!(contains(&stripped, &[0xcc]));
+        assert!(stripped.ends_with(&[0xff, 0xd9]));
+    }
+}
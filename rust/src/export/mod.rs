@@ -0,0 +1,45 @@
+// src/export/mod.rs
+
+//! Converters from SDK results into formats consumed by downstream tooling.
+
+pub mod openioc;
+pub mod stix;
+pub mod text;
+
+use crate::hashing::to_hex;
+use sha2::{Digest, Sha256};
+
+/// A stable UUID-shaped identifier derived from `parts`, so re-exporting the same
+/// data produces the same ids.
+pub(crate) fn stable_id(parts: &[&str]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part.as_bytes());
+        hasher.update([0]);
+    }
+    let hex = to_hex(&hasher.finalize());
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+/// Escape text for inclusion in XML element content or attribute values.
+pub(crate) fn xml_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
@@ -0,0 +1,106 @@
+// src/export/openioc.rs
+
+//! OpenIOC 1.0 XML export.
+
+use super::{stable_id, xml_escape};
+use crate::ioc::{Ioc, IocSet};
+use chrono::{SecondsFormat, Utc};
+use std::fmt::Write;
+
+const NAMESPACE: &str = "http://schemas.mandiant.com/2010/ioc";
+
+/// Descriptive fields for the generated `<ioc>` document.
+#[derive(Clone, Debug)]
+pub struct OpenIocMetadata {
+    pub short_description: String,
+    pub description: String,
+    pub author: String,
+}
+
+impl Default for OpenIocMetadata {
+    fn default() -> Self {
+        Self {
+            short_description: "Traceix indicators".to_string(),
+            description: "Indicators extracted from Traceix analysis results".to_string(),
+            author: "Traceix SDK".to_string(),
+        }
+    }
+}
+
+/// Render `set` as an OpenIOC document whose indicators are OR-ed together.
+pub fn to_openioc(set: &IocSet, metadata: &OpenIocMetadata) -> String {
+    let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let values: Vec<String> = set.iter().map(Ioc::value).collect();
+    let refs: Vec<&str> = values.iter().map(String::as_str).collect();
+    let doc_id = stable_id(&refs);
+
+    let mut xml = String::new();
+    let _ = writeln!(xml, r#"<?xml version="1.0" encoding="utf-8"?>"#);
+    let _ = writeln!(
+        xml,
+        r#"<ioc xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns:xsd="http://www.w3.org/2001/XMLSchema" id="{doc_id}" last-modified="{now}" xmlns="{NAMESPACE}">"#
+    );
+    let _ = writeln!(
+        xml,
+        "  <short_description>{}</short_description>",
+        xml_escape(&metadata.short_description)
+    );
+    let _ = writeln!(
+        xml,
+        "  <description>{}</description>",
+        xml_escape(&metadata.description)
+    );
+    let _ = writeln!(
+        xml,
+        "  <authored_by>{}</authored_by>",
+        xml_escape(&metadata.author)
+    );
+    let _ = writeln!(xml, "  <authored_date>{now}</authored_date>");
+    let _ = writeln!(xml, "  <links />");
+    let _ = writeln!(xml, "  <definition>");
+    let _ = writeln!(
+        xml,
+        r#"    <Indicator operator="OR" id="{}">"#,
+        stable_id(&[&doc_id, "root"])
+    );
+
+    for ioc in set {
+        let (document, search, content_type) = context(ioc);
+        let value = ioc.value();
+        let _ = writeln!(
+            xml,
+            r#"      <IndicatorItem id="{}" condition="is">"#,
+            stable_id(&[search, &value])
+        );
+        let _ = writeln!(
+            xml,
+            r#"        <Context document="{document}" search="{search}" type="mir" />"#
+        );
+        let _ = writeln!(
+            xml,
+            r#"        <Content type="{content_type}">{}</Content>"#,
+            xml_escape(&value)
+        );
+        let _ = writeln!(xml, "      </IndicatorItem>");
+    }
+
+    let _ = writeln!(xml, "    </Indicator>");
+    let _ = writeln!(xml, "  </definition>");
+    let _ = writeln!(xml, "</ioc>");
+    xml
+}
+
+/// OpenIOC `(document, search, content type)` terms for an indicator.
+fn context(ioc: &Ioc) -> (&'static str, &'static str, &'static str) {
+    match ioc {
+        Ioc::Ip(_) => ("PortItem", "PortItem/remoteIP", "IP"),
+        Ioc::Domain(_) => ("Network", "Network/DNS", "string"),
+        Ioc::Url(_) => ("Network", "Network/URI", "string"),
+        Ioc::Mutex(_) => (
+            "ProcessItem",
+            "ProcessItem/HandleList/Handle/Name",
+            "string",
+        ),
+        Ioc::RegistryKey(_) => ("RegistryItem", "RegistryItem/KeyPath", "string"),
+    }
+}
@@ -0,0 +1,559 @@
+// src/export/stix.rs
+
+//! STIX 2.1 bundle export, for pushing a sample's analysis results into a threat
+//! intelligence platform.
+//!
+//! A bundle holds:
+//!
+//! - a `file` object with the sample's hashes, name and size, and its EXIF tags in the
+//!   `raster-image-ext` extension;
+//! - a `malware-analysis` object with the AI prediction's verdict;
+//! - when the verdict is malicious or suspicious, an indicator matching the sample's
+//!   SHA-256 and one for each network or host indicator in the prediction;
+//! - an `attack-pattern` for each ATT&CK technique CAPA matched, related to the sample's
+//!   indicator (or to the file when there is none).
+//!
+//! Ids are derived from the content they describe, so exporting the same sample again
+//! updates the objects already in the platform rather than duplicating them.
+//!
+//! ```
+//! # use traceix_sdk::export::stix::{to_stix_bundle, StixMetadata, StixSample};
+//! # use traceix_sdk::CapaReport;
+//! let capa = CapaReport::from_value(&serde_json::json!({
+//!     "rules": {
+//!         "inject thread": {
+//!             "meta": { "attack": [{ "tactic": "Defense Evasion",
+//!                                    "technique": "Process Injection", "id": "T1055" }] },
+//!         },
+//!     },
+//! }))?;
+//! let sample = StixSample {
+//!     sha256: "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08".into(),
+//!     name: Some("invoice.exe".into()),
+//!     prediction: Some(serde_json::json!({ "prediction": "malicious", "confidence": 0.97 })),
+//!     capa: Some(capa),
+//!     ..StixSample::default()
+//! };
+//! let bundle = to_stix_bundle(&sample, &StixMetadata::default())?;
+//! let types: Vec<&str> = bundle["objects"]
+//!     .as_array()
+//!     .unwrap()
+//!     .iter()
+//!     .map(|object| object["type"].as_str().unwrap())
+//!     .collect();
+//! assert_eq!(
+//!     types,
+//!     ["identity", "file", "malware-analysis", "indicator", "attack-pattern", "relationship"]
+//! );
+//! # Ok::<(), traceix_sdk::TraceixError>(())
+//! ```
+
+use crate::capa::CapaReport;
+use crate::exif::ExifReport;
+use crate::hashing::{to_hex, FileDigests};
+use crate::ioc::{Ioc, IocSet};
+use crate::TraceixError;
+use chrono::{SecondsFormat, Utc};
+use serde_json::{json, Map, Value};
+use sha1::{Digest, Sha1};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+/// Namespace of STIX deterministic identifiers (UUIDv5).
+const STIX_NAMESPACE: [u8; 16] = [
+    0x00, 0xab, 0xed, 0xb4, 0xaa, 0x42, 0x46, 0x6c, 0x9c, 0x01, 0xfe, 0xd2, 0x33, 0x15, 0xa9, 0xb7,
+];
+
+/// Prediction fields holding the verdict, in order of preference.
+const VERDICT_KEYS: &[&str] = &["verdict", "prediction", "label", "classification"];
+
+/// Descriptive fields for the generated bundle.
+#[derive(Clone, Debug)]
+pub struct StixMetadata {
+    /// Name of the `identity` every object is `created_by_ref`.
+    pub author: String,
+}
+
+impl Default for StixMetadata {
+    fn default() -> Self {
+        Self {
+            author: "Traceix SDK".to_string(),
+        }
+    }
+}
+
+/// The analysis results of one sample.
+#[derive(Clone, Debug, Default)]
+pub struct StixSample {
+    /// Hex SHA-256 of the sample. Required.
+    pub sha256: String,
+    pub md5: Option<String>,
+    pub sha1: Option<String>,
+    pub name: Option<String>,
+    pub size: Option<u64>,
+    /// The AI prediction result document.
+    pub prediction: Option<Value>,
+    pub capa: Option<CapaReport>,
+    pub exif: Option<ExifReport>,
+}
+
+impl StixSample {
+    /// The hashes, name and size of the file at `path`, with no results yet.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, TraceixError> {
+        let path = path.as_ref();
+        let digests = FileDigests::from_file(path)?;
+        Ok(Self {
+            sha256: digests.sha256,
+            md5: Some(digests.md5),
+            sha1: Some(digests.sha1),
+            name: path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned()),
+            size: Some(fs::metadata(path)?.len()),
+            ..Self::default()
+        })
+    }
+}
+
+/// Render the results in `sample` as a STIX 2.1 bundle.
+///
+/// Fails if `sample.sha256` is not a SHA-256 digest.
+pub fn to_stix_bundle(sample: &StixSample, metadata: &StixMetadata) -> Result<Value, TraceixError> {
+    let sha256 = sample.sha256.trim().to_ascii_lowercase();
+    if sha256.len() != 64 || !sha256.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(TraceixError::InvalidArgument(format!(
+            "{:?} is not a SHA-256 digest",
+            sample.sha256
+        )));
+    }
+    let now = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
+    let sdo = |kind: &str, key: &[&str]| {
+        let mut object = Map::new();
+        object.insert("type".into(), kind.into());
+        object.insert("spec_version".into(), "2.1".into());
+        object.insert("id".into(), sdo_id(kind, key).into());
+        object.insert("created".into(), now.clone().into());
+        object.insert("modified".into(), now.clone().into());
+        object
+    };
+    let mut objects = Vec::new();
+
+    let mut identity = sdo("identity", &[&metadata.author]);
+    identity.insert("name".into(), metadata.author.clone().into());
+    identity.insert("identity_class".into(), "system".into());
+    let identity_id = identity["id"].clone();
+    objects.push(Value::Object(identity));
+    let sdo = |kind: &str, key: &[&str]| {
+        let mut object = sdo(kind, key);
+        object.insert("created_by_ref".into(), identity_id.clone());
+        object
+    };
+
+    let file = file_object(sample, &sha256);
+    let file_id = file["id"].clone();
+    objects.push(file);
+
+    let verdict = sample.prediction.as_ref().map(Verdict::from_prediction);
+    if let Some(verdict) = &verdict {
+        let mut analysis = sdo("malware-analysis", &[&sha256]);
+        analysis.insert("product".into(), "traceix".into());
+        analysis.insert("result".into(), verdict.result.into());
+        if let Some(label) = &verdict.label {
+            analysis.insert("result_name".into(), label.clone().into());
+        }
+        analysis.insert("sample_ref".into(), file_id.clone());
+        objects.push(Value::Object(analysis));
+    }
+
+    let mut sample_indicator = None;
+    if let Some(verdict) = verdict
+        .as_ref()
+        .filter(|v| v.result != "benign" && v.result != "unknown")
+    {
+        let indicator_types = match verdict.result {
+            "malicious" => "malicious-activity",
+            _ => "anomalous-activity",
+        };
+        let mut indicator = |pattern: String, name: String| {
+            let mut object = sdo("indicator", &[&pattern]);
+            object.insert("name".into(), name.into());
+            object.insert("indicator_types".into(), json!([indicator_types]));
+            object.insert("pattern".into(), pattern.into());
+            object.insert("pattern_type".into(), "stix".into());
+            object.insert("valid_from".into(), now.clone().into());
+            if let Some(confidence) = verdict.confidence {
+                object.insert("confidence".into(), confidence.into());
+            }
+            objects.push(Value::Object(object));
+            objects.last().unwrap()["id"].clone()
+        };
+
+        let name = sample.name.as_deref().unwrap_or(&sha256);
+        sample_indicator = Some(indicator(
+            format!("[file:hashes.'SHA-256' = '{sha256}']"),
+            format!("{} file {name}", capitalize(verdict.result)),
+        ));
+        let iocs = sample
+            .prediction
+            .as_ref()
+            .map(IocSet::from_value)
+            .unwrap_or_default();
+        for ioc in &iocs {
+            let (pattern, kind) = ioc_pattern(ioc);
+            indicator(pattern, format!("{kind} seen in {name}"));
+        }
+    }
+
+    if let Some(capa) = &sample.capa {
+        let (source, relationship_type) = match &sample_indicator {
+            Some(indicator) => (indicator, "indicates"),
+            None => (&file_id, "related-to"),
+        };
+        let source = source.as_str().unwrap_or_default().to_string();
+        for (id, technique) in attack_techniques(capa) {
+            let mut pattern = sdo("attack-pattern", &[id]);
+            pattern.insert("name".into(), technique.name.into());
+            pattern.insert(
+                "external_references".into(),
+                json!([{
+                    "source_name": "mitre-attack",
+                    "external_id": id,
+                    "url": format!("https://attack.mitre.org/techniques/{}/", id.replace('.', "/")),
+                }]),
+            );
+            if !technique.tactics.is_empty() {
+                let phases: Vec<Value> = technique
+                    .tactics
+                    .iter()
+                    .map(
+                        |tactic| json!({ "kill_chain_name": "mitre-attack", "phase_name": tactic }),
+                    )
+                    .collect();
+                pattern.insert("kill_chain_phases".into(), phases.into());
+            }
+            let target = pattern["id"].as_str().unwrap_or_default().to_string();
+            objects.push(Value::Object(pattern));
+
+            let mut relationship = sdo("relationship", &[&source, relationship_type, &target]);
+            relationship.insert("relationship_type".into(), relationship_type.into());
+            relationship.insert("source_ref".into(), source.clone().into());
+            relationship.insert("target_ref".into(), target.into());
+            objects.push(Value::Object(relationship));
+        }
+    }
+
+    // Bundles are transient wrappers; each export gets its own id.
+    Ok(json!({
+        "type": "bundle",
+        "id": sdo_id("bundle", &[&sha256, &now]),
+        "objects": objects,
+    }))
+}
+
+/// The STIX `file` object for `sample`, with the deterministic id STIX prescribes for
+/// cyber-observables.
+fn file_object(sample: &StixSample, sha256: &str) -> Value {
+    let mut hashes = Map::new();
+    let normalize = |hash: &Option<String>| hash.as_deref().map(|h| h.trim().to_ascii_lowercase());
+    if let Some(md5) = normalize(&sample.md5) {
+        hashes.insert("MD5".into(), md5.into());
+    }
+    if let Some(sha1) = normalize(&sample.sha1) {
+        hashes.insert("SHA-1".into(), sha1.into());
+    }
+    hashes.insert("SHA-256".into(), sha256.into());
+
+    let mut contributing = Map::new();
+    // Only the first of MD5, SHA-1 and SHA-256 contributes to the id.
+    let (algorithm, hash) = hashes
+        .iter()
+        .min_by_key(|(name, _)| hash_rank(name))
+        .unwrap();
+    contributing.insert("hashes".into(), json!({ algorithm.as_str(): hash }));
+    if let Some(name) = &sample.name {
+        contributing.insert("name".into(), name.clone().into());
+    }
+    if let Some(tags) = sample
+        .exif
+        .as_ref()
+        .map(exif_tags)
+        .filter(|tags| !tags.is_empty())
+    {
+        contributing.insert(
+            "extensions".into(),
+            json!({ "raster-image-ext": { "exif_tags": tags } }),
+        );
+    }
+    let id = format!(
+        "file--{}",
+        uuid5(
+            &STIX_NAMESPACE,
+            canonical_json(&Value::Object(contributing.clone())).as_bytes()
+        )
+    );
+
+    let mut file = Map::new();
+    file.insert("type".into(), "file".into());
+    file.insert("spec_version".into(), "2.1".into());
+    file.insert("id".into(), id.into());
+    file.insert("hashes".into(), hashes.into());
+    if let Some(size) = sample.size {
+        file.insert("size".into(), size.into());
+    }
+    contributing.remove("hashes");
+    file.extend(contributing);
+    Value::Object(file)
+}
+
+fn hash_rank(algorithm: &str) -> u8 {
+    match algorithm {
+        "MD5" => 0,
+        "SHA-1" => 1,
+        _ => 2,
+    }
+}
+
+/// EXIF tags in the form of `raster-image-ext`, whose values are strings or integers.
+fn exif_tags(exif: &ExifReport) -> Map<String, Value> {
+    let mut tags = Map::new();
+    let typed = [
+        ("Make", exif.make.clone()),
+        ("Model", exif.model.clone()),
+        ("Software", exif.software.clone()),
+        ("DateTimeOriginal", exif.date_taken.map(|t| t.to_string())),
+        ("CreateDate", exif.date_digitized.map(|t| t.to_string())),
+        ("ModifyDate", exif.date_modified.map(|t| t.to_string())),
+        ("GPSLatitude", exif.gps.map(|gps| format!("{:.6}", gps.lat))),
+        (
+            "GPSLongitude",
+            exif.gps.map(|gps| format!("{:.6}", gps.lon)),
+        ),
+        (
+            "GPSAltitude",
+            exif.gps
+                .and_then(|gps| gps.altitude)
+                .map(|alt| format!("{alt:.1}")),
+        ),
+    ];
+    for (tag, value) in typed {
+        if let Some(value) = value {
+            tags.insert(tag.into(), value.into());
+        }
+    }
+    for (name, value) in &exif.other {
+        // STIX keys carry no ExifTool group prefix: `EXIF:LensModel` is `LensModel`.
+        let tag = name.rsplit(':').next().unwrap_or(name);
+        let value = match value {
+            Value::Null => continue,
+            Value::String(_) => value.clone(),
+            Value::Number(n) if n.is_i64() || n.is_u64() => value.clone(),
+            Value::Number(n) => n.to_string().into(),
+            other => other.to_string().into(),
+        };
+        tags.entry(tag).or_insert(value);
+    }
+    tags
+}
+
+/// The verdict of an AI prediction, in STIX's `malware-result-ov` vocabulary.
+struct Verdict {
+    result: &'static str,
+    /// The server's own label, e.g. `malicious` or `goodware`.
+    label: Option<String>,
+    /// Confidence from 0 to 100.
+    confidence: Option<u64>,
+}
+
+impl Verdict {
+    fn from_prediction(prediction: &Value) -> Self {
+        // The verdict may sit at the top level or in a `result` or `data` wrapper.
+        let candidates = [prediction, &prediction["result"], &prediction["data"]];
+        let label = candidates.iter().find_map(|doc| {
+            VERDICT_KEYS
+                .iter()
+                .find_map(|key| doc.get(key).and_then(Value::as_str))
+                .map(str::to_string)
+        });
+        let result = match (
+            &label,
+            candidates.iter().find_map(|doc| doc["malicious"].as_bool()),
+        ) {
+            (Some(label), _) => classify(label),
+            (None, Some(true)) => "malicious",
+            (None, Some(false)) => "benign",
+            (None, None) => "unknown",
+        };
+        let confidence = candidates.iter().find_map(|doc| {
+            ["confidence", "probability", "score"]
+                .iter()
+                .find_map(|key| doc.get(key).and_then(Value::as_f64))
+        });
+        Self {
+            result,
+            label,
+            // Fractions are scaled to STIX's 0-100 range.
+            confidence: confidence
+                .filter(|c| (0.0..=100.0).contains(c))
+                .map(|c| if c <= 1.0 { c * 100.0 } else { c }.round() as u64),
+        }
+    }
+}
+
+/// Map a verdict label onto `malware-result-ov`, matching whole words so "unsafe" isn't
+/// read as "safe".
+fn classify(label: &str) -> &'static str {
+    let label = label.to_ascii_lowercase();
+    let words: Vec<&str> = label
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect();
+    let any = |phrases: &[&str]| {
+        phrases.iter().any(|phrase| {
+            let phrase: Vec<&str> = phrase.split(' ').collect();
+            words.windows(phrase.len()).any(|window| window == phrase)
+        })
+    };
+    // Negated verdicts first, before the words they negate.
+    if any(&["not malicious", "non malicious", "not malware"]) {
+        "benign"
+    } else if any(&["unsafe", "not safe", "not clean", "not benign"]) {
+        "malicious"
+    } else if any(&["benign", "clean", "goodware", "safe"]) {
+        "benign"
+    } else if any(&["malicious", "malware"]) {
+        "malicious"
+    } else if any(&["suspicious", "grayware", "greyware", "pua"]) {
+        "suspicious"
+    } else {
+        "unknown"
+    }
+}
+
+/// A STIX pattern matching `ioc`, and a name for its kind.
+fn ioc_pattern(ioc: &Ioc) -> (String, &'static str) {
+    let (object, kind) = match ioc {
+        Ioc::Ip(ip) if ip.is_ipv4() => ("ipv4-addr:value", "IP address"),
+        Ioc::Ip(_) => ("ipv6-addr:value", "IP address"),
+        Ioc::Domain(_) => ("domain-name:value", "Domain"),
+        Ioc::Url(_) => ("url:value", "URL"),
+        Ioc::Mutex(_) => ("mutex:name", "Mutex"),
+        Ioc::RegistryKey(_) => ("windows-registry-key:key", "Registry key"),
+    };
+    let value = ioc.value().replace('\\', "\\\\").replace('\'', "\\'");
+    (format!("[{object} = '{value}']"), kind)
+}
+
+struct Technique {
+    name: String,
+    /// Kill chain phase names, e.g. `defense-evasion`.
+    tactics: BTreeSet<String>,
+}
+
+/// The ATT&CK techniques CAPA matched, by ID, with every tactic they were listed under.
+fn attack_techniques(capa: &CapaReport) -> BTreeMap<&str, Technique> {
+    let mut techniques: BTreeMap<&str, Technique> = BTreeMap::new();
+    for attack in capa.rules.iter().flat_map(|rule| &rule.attack) {
+        let technique = techniques.entry(&attack.id).or_insert_with(|| Technique {
+            name: [attack.subtechnique.as_deref(), Some(&attack.technique)]
+                .into_iter()
+                .flatten()
+                .find(|name| !name.is_empty())
+                .unwrap_or(&attack.id)
+                .to_string(),
+            tactics: BTreeSet::new(),
+        });
+        if !attack.tactic.is_empty() {
+            let tactic = attack.tactic.to_ascii_lowercase();
+            let phase = tactic.split_whitespace().collect::<Vec<_>>().join("-");
+            technique.tactics.insert(phase);
+        }
+    }
+    techniques
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+/// A deterministic id for a STIX domain object of type `kind` identified by `key`.
+fn sdo_id(kind: &str, key: &[&str]) -> String {
+    let name = std::iter::once(kind)
+        .chain(key.iter().copied())
+        .collect::<Vec<_>>()
+        .join("\0");
+    format!("{kind}--{}", uuid5(&STIX_NAMESPACE, name.as_bytes()))
+}
+
+/// A name-based UUID (RFC 4122 version 5).
+fn uuid5(namespace: &[u8; 16], name: &[u8]) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(namespace);
+    hasher.update(name);
+    let mut bytes = hasher.finalize()[..16].to_vec();
+    bytes[6] = (bytes[6] & 0x0f) | 0x50;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = to_hex(&bytes);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+/// JSON with object keys sorted and no whitespace (RFC 8785), as STIX hashes for
+/// deterministic ids. Written out here so it doesn't depend on how `serde_json` orders
+/// maps.
+fn canonical_json(value: &Value) -> String {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.encode_utf16().cmp(b.0.encode_utf16()));
+            let fields: Vec<String> = entries
+                .into_iter()
+                .map(|(key, value)| {
+                    format!("{}:{}", Value::from(key.as_str()), canonical_json(value))
+                })
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        }
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(canonical_json).collect();
+            format!("[{}]", items.join(","))
+        }
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_verdict_labels() {
+        for (label, expected) in [
+            ("Malicious", "malicious"),
+            ("malware.generic", "malicious"),
+            ("unsafe", "malicious"),
+            ("Not safe", "malicious"),
+            ("benign", "benign"),
+            ("SAFE", "benign"),
+            ("not malicious", "benign"),
+            ("non-malicious", "benign"),
+            ("suspicious", "suspicious"),
+            ("PUA:Win32/Toolbar", "suspicious"),
+            ("unclean-ish", "unknown"),
+            ("safeguard", "unknown"),
+            ("", "unknown"),
+        ] {
+            assert_eq!(classify(label), expected, "{label:?}");
+        }
+    }
+}
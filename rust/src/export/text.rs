@@ -0,0 +1,43 @@
+// src/export/text.rs
+
+//! Newline-delimited indicator lists, one file per indicator kind.
+
+use crate::ioc::{IocKind, IocSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const KINDS: [IocKind; 5] = [
+    IocKind::Ip,
+    IocKind::Domain,
+    IocKind::Url,
+    IocKind::Mutex,
+    IocKind::RegistryKey,
+];
+
+/// All indicators of `kind`, one per line (with a trailing newline when non-empty).
+pub fn to_lines(set: &IocSet, kind: IocKind) -> String {
+    set.of_kind(kind)
+        .map(|ioc| format!("{}\n", ioc.value()))
+        .collect()
+}
+
+/// Write one `<kind>.txt` list per indicator kind present in `set` into `dir`.
+///
+/// Returns the paths written. Kinds with no indicators are skipped.
+pub fn write_lists(set: &IocSet, dir: impl AsRef<Path>) -> io::Result<Vec<PathBuf>> {
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir)?;
+
+    let mut written = Vec::new();
+    for kind in KINDS {
+        let lines = to_lines(set, kind);
+        if lines.is_empty() {
+            continue;
+        }
+        let path = dir.join(format!("{}.txt", kind.as_str()));
+        fs::write(&path, lines)?;
+        written.push(path);
+    }
+    Ok(written)
+}
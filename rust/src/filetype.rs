@@ -0,0 +1,266 @@
+// src/filetype.rs
+
+//! File type detection from magic bytes, and the [`Preflight`] check that turns files
+//! away locally instead of uploading them only to have the API reject them.
+
+use crate::TraceixError;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// Bytes read from the start of a file to detect its type; enough to reach the tar
+/// header magic at offset 257 and a PDF header behind some leading junk.
+const HEADER_LEN: u64 = 1024;
+
+/// File types recognized by [`FileType::detect`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum FileType {
+    /// Windows PE or DOS `MZ` executable.
+    Pe,
+    Elf,
+    /// Mach-O executable, including universal (fat) binaries.
+    MachO,
+    JavaClass,
+    Jpeg,
+    Png,
+    Gif,
+    WebP,
+    Tiff,
+    /// HEIF container, e.g. HEIC or AVIF.
+    Heif,
+    Pdf,
+    /// ZIP archive, including Office Open XML documents, JARs and APKs.
+    Zip,
+    Gzip,
+    Bzip2,
+    Xz,
+    SevenZip,
+    Rar,
+    Tar,
+    Cab,
+    /// OLE compound file, e.g. a legacy Office document or an MSI package.
+    Ole,
+    Rtf,
+    /// Windows shortcut (`.lnk`).
+    Lnk,
+    /// Text starting with a `#!` interpreter line.
+    Script,
+    Unknown,
+}
+
+impl FileType {
+    /// Detect the type of a file from its first bytes; a [`HEADER_LEN`]-byte prefix is
+    /// enough.
+    pub fn detect(header: &[u8]) -> FileType {
+        let at = |offset: usize, magic: &[u8]| {
+            header.get(offset..).is_some_and(|h| h.starts_with(magic))
+        };
+        match header {
+            [b'M', b'Z', ..] => FileType::Pe,
+            [0x7f, b'E', b'L', b'F', ..] => FileType::Elf,
+            [0xfe, 0xed, 0xfa, 0xce | 0xcf, ..] | [0xce | 0xcf, 0xfa, 0xed, 0xfe, ..] => {
+                FileType::MachO
+            }
+            // Universal binaries and Java classes share a magic; a universal binary has
+            // a small architecture count where a class file has its version.
+            [0xca, 0xfe, 0xba, 0xbe, a, b, c, d, ..] => {
+                if u32::from_be_bytes([*a, *b, *c, *d]) < 45 {
+                    FileType::MachO
+                } else {
+                    FileType::JavaClass
+                }
+            }
+            [0xff, 0xd8, 0xff, ..] => FileType::Jpeg,
+            [0x89, b'P', b'N', b'G', ..] => FileType::Png,
+            _ if at(0, b"GIF87a") || at(0, b"GIF89a") => FileType::Gif,
+            _ if at(0, b"RIFF") && at(8, b"WEBP") => FileType::WebP,
+            _ if at(0, b"II*\0") || at(0, b"MM\0*") => FileType::Tiff,
+            _ if at(4, b"ftyp")
+                && [b"heic", b"heix", b"mif1", b"msf1", b"avif"]
+                    .iter()
+                    .any(|brand| at(8, *brand)) =>
+            {
+                FileType::Heif
+            }
+            _ if at(0, b"PK\x03\x04") || at(0, b"PK\x05\x06") || at(0, b"PK\x07\x08") => {
+                FileType::Zip
+            }
+            [0x1f, 0x8b, ..] => FileType::Gzip,
+            _ if at(0, b"BZh") => FileType::Bzip2,
+            _ if at(0, b"\xfd7zXZ\0") => FileType::Xz,
+            _ if at(0, b"7z\xbc\xaf\x27\x1c") => FileType::SevenZip,
+            _ if at(0, b"Rar!\x1a\x07") => FileType::Rar,
+            _ if at(257, b"ustar") => FileType::Tar,
+            _ if at(0, b"MSCF") => FileType::Cab,
+            _ if at(0, b"\xd0\xcf\x11\xe0\xa1\xb1\x1a\xe1") => FileType::Ole,
+            _ if at(0, b"{\\rtf") => FileType::Rtf,
+            _ if at(0, b"L\0\0\0\x01\x14\x02\0") => FileType::Lnk,
+            _ if at(0, b"#!") => FileType::Script,
+            // Readers accept a PDF header anywhere in the first kilobyte.
+            _ if header.windows(5).any(|w| w == b"%PDF-") => FileType::Pdf,
+            _ => FileType::Unknown,
+        }
+    }
+
+    /// Detect the type of the file at `path`, reading only its first bytes.
+    pub fn detect_file(path: impl AsRef<Path>) -> io::Result<FileType> {
+        let mut header = Vec::new();
+        File::open(path)?
+            .take(HEADER_LEN)
+            .read_to_end(&mut header)?;
+        Ok(FileType::detect(&header))
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FileType::Pe => "pe",
+            FileType::Elf => "elf",
+            FileType::MachO => "macho",
+            FileType::JavaClass => "java-class",
+            FileType::Jpeg => "jpeg",
+            FileType::Png => "png",
+            FileType::Gif => "gif",
+            FileType::WebP => "webp",
+            FileType::Tiff => "tiff",
+            FileType::Heif => "heif",
+            FileType::Pdf => "pdf",
+            FileType::Zip => "zip",
+            FileType::Gzip => "gzip",
+            FileType::Bzip2 => "bzip2",
+            FileType::Xz => "xz",
+            FileType::SevenZip => "7z",
+            FileType::Rar => "rar",
+            FileType::Tar => "tar",
+            FileType::Cab => "cab",
+            FileType::Ole => "ole",
+            FileType::Rtf => "rtf",
+            FileType::Lnk => "lnk",
+            FileType::Script => "script",
+            FileType::Unknown => "unknown",
+        }
+    }
+
+    /// The MIME type usually given to files of this type.
+    pub fn mime(&self) -> &'static str {
+        match self {
+            FileType::Pe => "application/vnd.microsoft.portable-executable",
+            FileType::Elf => "application/x-executable",
+            FileType::MachO => "application/x-mach-binary",
+            FileType::JavaClass => "application/java-vm",
+            FileType::Jpeg => "image/jpeg",
+            FileType::Png => "image/png",
+            FileType::Gif => "image/gif",
+            FileType::WebP => "image/webp",
+            FileType::Tiff => "image/tiff",
+            FileType::Heif => "image/heif",
+            FileType::Pdf => "application/pdf",
+            FileType::Zip => "application/zip",
+            FileType::Gzip => "application/gzip",
+            FileType::Bzip2 => "application/x-bzip2",
+            FileType::Xz => "application/x-xz",
+            FileType::SevenZip => "application/x-7z-compressed",
+            FileType::Rar => "application/vnd.rar",
+            FileType::Tar => "application/x-tar",
+            FileType::Cab => "application/vnd.ms-cab-compressed",
+            FileType::Ole => "application/x-ole-storage",
+            FileType::Rtf => "application/rtf",
+            FileType::Lnk => "application/x-ms-shortcut",
+            FileType::Script => "text/x-script",
+            FileType::Unknown => "application/octet-stream",
+        }
+    }
+
+    /// Whether files of this type are images, e.g. for EXIF extraction.
+    pub fn is_image(&self) -> bool {
+        matches!(
+            self,
+            FileType::Jpeg
+                | FileType::Png
+                | FileType::Gif
+                | FileType::WebP
+                | FileType::Tiff
+                | FileType::Heif
+        )
+    }
+
+    /// Whether files of this type are archives holding other files.
+    pub fn is_archive(&self) -> bool {
+        matches!(
+            self,
+            FileType::Zip
+                | FileType::Gzip
+                | FileType::Bzip2
+                | FileType::Xz
+                | FileType::SevenZip
+                | FileType::Rar
+                | FileType::Tar
+                | FileType::Cab
+        )
+    }
+}
+
+impl fmt::Display for FileType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Checks run on a file before it is uploaded, so one the API would refuse with
+/// `413 Payload Too Large` or `415 Unsupported Media Type` fails at once, without
+/// sending its bytes. Set it with
+/// [`TraceixSdkBuilder::preflight`](crate::TraceixSdkBuilder::preflight).
+///
+/// ```
+/// # use traceix_sdk::{FileType, Preflight};
+/// let preflight = Preflight::new()
+///     .max_size(256 * 1024 * 1024)
+///     .allow([FileType::Pe, FileType::Elf, FileType::MachO, FileType::Pdf]);
+/// # let _ = preflight;
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Preflight {
+    max_size: Option<u64>,
+    allowed: Option<Vec<FileType>>,
+}
+
+impl Preflight {
+    /// A check that accepts everything until limits are added.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reject files larger than `bytes`.
+    pub fn max_size(mut self, bytes: u64) -> Self {
+        self.max_size = Some(bytes);
+        self
+    }
+
+    /// Only accept files of these types; add [`FileType::Unknown`] to also accept files
+    /// whose type isn't recognized. Can be called repeatedly. Without it, every type is
+    /// accepted.
+    pub fn allow(mut self, types: impl IntoIterator<Item = FileType>) -> Self {
+        self.allowed.get_or_insert_with(Vec::new).extend(types);
+        self
+    }
+
+    /// Check the file at `path`, returning its detected type.
+    ///
+    /// Fails with [`TraceixError::FileTooLarge`] or
+    /// [`TraceixError::UnsupportedFileType`].
+    pub fn check(&self, path: impl AsRef<Path>) -> Result<FileType, TraceixError> {
+        let path = path.as_ref();
+        let size = path.metadata()?.len();
+        if let Some(limit) = self.max_size.filter(|&limit| size > limit) {
+            return Err(TraceixError::FileTooLarge { size, limit });
+        }
+        let file_type = FileType::detect_file(path)?;
+        if let Some(allowed) = &self.allowed {
+            if !allowed.contains(&file_type) {
+                return Err(TraceixError::UnsupportedFileType { file_type });
+            }
+        }
+        Ok(file_type)
+    }
+}
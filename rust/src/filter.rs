@@ -0,0 +1,169 @@
+// src/filter.rs
+
+use crate::hashing::{normalize_hex_digest, FileDigests};
+use crate::nsrl::Nsrl;
+use crate::TraceixError;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+/// A set of known hashes (MD5, SHA-1 and/or SHA-256), e.g. a known-good allowlist.
+#[derive(Clone, Debug, Default)]
+pub struct HashList {
+    hashes: HashSet<String>,
+}
+
+impl HashList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load a list with one hash per line. Blank lines and `#` comments are ignored,
+    /// and only the first comma/whitespace separated column of each line is used.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, TraceixError> {
+        let mut list = Self::new();
+        let text =
+            fs::read_to_string(path.as_ref()).map_err(|e| TraceixError::file(path.as_ref(), e))?;
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let hash = line
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .next()
+                .unwrap_or_default()
+                .trim_matches('"');
+            list.insert(hash).map_err(|e| {
+                TraceixError::InvalidArgument(format!(
+                    "{}:{}: {e}",
+                    path.as_ref().display(),
+                    number + 1
+                ))
+            })?;
+        }
+        Ok(list)
+    }
+
+    /// Add a hex digest. Fails if it isn't an MD5/SHA-1/SHA-256 digest.
+    pub fn insert(&mut self, hash: &str) -> Result<(), TraceixError> {
+        let (_, hash) = normalize_hex_digest(hash)?;
+        self.hashes.insert(hash);
+        Ok(())
+    }
+
+    pub fn contains(&self, hash: &str) -> bool {
+        self.hashes.contains(&hash.trim().to_ascii_lowercase())
+    }
+
+    /// The first of `digests` present in the list, if any.
+    pub fn matches(&self, digests: &FileDigests) -> Option<String> {
+        digests
+            .iter()
+            .find(|(_, hash)| self.hashes.contains(*hash))
+            .map(|(_, hash)| hash.to_string())
+    }
+
+    pub fn len(&self) -> usize {
+        self.hashes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+
+    pub fn extend(&mut self, other: HashList) {
+        self.hashes.extend(other.hashes);
+    }
+}
+
+/// Which list a file matched.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ListKind {
+    Allow,
+    Deny,
+}
+
+/// A file's match against a [`HashFilter`], with the hash that matched.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ListMatch {
+    pub kind: ListKind,
+    pub hash: String,
+}
+
+/// Allowlist/denylist consulted before batch items are processed.
+///
+/// Denylist matches take precedence. Allowlisted files are suppressed (not uploaded or
+/// reported) unless `suppress_allowlisted` is turned off, in which case they are
+/// processed and flagged like denylisted ones.
+#[derive(Clone, Debug)]
+pub struct HashFilter {
+    pub allow: HashList,
+    pub deny: HashList,
+    /// Consulted after the lists; NSRL hits count as allowlisted.
+    pub nsrl: Option<Arc<Nsrl>>,
+    pub suppress_allowlisted: bool,
+}
+
+impl Default for HashFilter {
+    fn default() -> Self {
+        Self {
+            allow: HashList::new(),
+            deny: HashList::new(),
+            nsrl: None,
+            suppress_allowlisted: true,
+        }
+    }
+}
+
+impl HashFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the hashes in `path` to the allowlist.
+    pub fn load_allowlist(mut self, path: impl AsRef<Path>) -> Result<Self, TraceixError> {
+        self.allow.extend(HashList::load(path)?);
+        Ok(self)
+    }
+
+    /// Add the hashes in `path` to the denylist.
+    pub fn load_denylist(mut self, path: impl AsRef<Path>) -> Result<Self, TraceixError> {
+        self.deny.extend(HashList::load(path)?);
+        Ok(self)
+    }
+
+    /// Treat files known to the NSRL as allowlisted.
+    pub fn with_nsrl(mut self, nsrl: Nsrl) -> Self {
+        self.nsrl = Some(Arc::new(nsrl));
+        self
+    }
+
+    /// Match precomputed digests against the lists, then the NSRL if configured.
+    pub fn check(&self, digests: &FileDigests) -> Result<Option<ListMatch>, TraceixError> {
+        if let Some(hash) = self.deny.matches(digests) {
+            return Ok(Some(ListMatch {
+                kind: ListKind::Deny,
+                hash,
+            }));
+        }
+        let mut hash = self.allow.matches(digests);
+        if let (None, Some(nsrl)) = (&hash, &self.nsrl) {
+            hash = nsrl.lookup(digests)?;
+        }
+        Ok(hash.map(|hash| ListMatch {
+            kind: ListKind::Allow,
+            hash,
+        }))
+    }
+
+    /// Hash the file at `path` and match it against the lists.
+    pub fn check_file(&self, path: impl AsRef<Path>) -> Result<Option<ListMatch>, TraceixError> {
+        if self.allow.is_empty() && self.deny.is_empty() && self.nsrl.is_none() {
+            return Ok(None);
+        }
+        let path = path.as_ref();
+        self.check(&FileDigests::from_file(path).map_err(|e| TraceixError::file(path, e))?)
+    }
+}
@@ -0,0 +1,254 @@
+// src/fuzzy.rs
+
+//! Fuzzy hashes (ssdeep and TLSH), which stay close for similar content, to find
+//! near-duplicates of a sample with
+//! [`TraceixSdk::similarity_search`](crate::TraceixSdk::similarity_search) rather than
+//! only exact SHA-256 matches. Computing them locally needs the `fuzzy-hash` feature.
+
+use crate::TraceixError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+#[cfg(feature = "fuzzy-hash")]
+use {
+    crate::hashing::for_each_chunk,
+    std::fs::File,
+    std::io::{self, Read},
+    std::path::Path,
+};
+#[cfg(feature = "blocking")]
+use {
+    crate::{Endpoint, TraceixSdk},
+    serde_json::Value,
+};
+
+/// Lengths in hex digits of the TLSH variants (bucket count and checksum size) in use.
+const TLSH_HEX_LENGTHS: [usize; 5] = [30, 70, 74, 134, 138];
+
+/// Fuzzy hash algorithms understood by the Traceix API.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum FuzzyHashType {
+    /// Context-triggered piecewise hash, e.g. `96:U57GjXnLt9co6pZwvLhJluvrs:Hj3BeoEcNJ0Ts`.
+    Ssdeep,
+    /// Trend Micro locality-sensitive hash, e.g. `T1` followed by 70 hex digits.
+    Tlsh,
+}
+
+impl FuzzyHashType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FuzzyHashType::Ssdeep => "ssdeep",
+            FuzzyHashType::Tlsh => "tlsh",
+        }
+    }
+}
+
+/// A validated ssdeep or TLSH hash, its algorithm detected from its shape.
+///
+/// ```
+/// # use traceix_sdk::{FuzzyHash, FuzzyHashType};
+/// let hash: FuzzyHash = "3:HEREar5MFUul0U0KMP:knl8lkKMP".parse()?;
+/// assert_eq!(hash.kind(), FuzzyHashType::Ssdeep);
+/// # Ok::<(), traceix_sdk::TraceixError>(())
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct FuzzyHash {
+    kind: FuzzyHashType,
+    value: String,
+}
+
+impl FuzzyHash {
+    /// Validate `hash` as either an ssdeep or a TLSH hash.
+    pub fn parse(hash: &str) -> Result<Self, TraceixError> {
+        let hash = hash.trim();
+        if hash.contains(':') {
+            Self::ssdeep(hash)
+        } else {
+            Self::tlsh(hash)
+        }
+    }
+
+    /// Validate an ssdeep hash: `blocksize:hash:hash`, the hashes in base64.
+    pub fn ssdeep(hash: &str) -> Result<Self, TraceixError> {
+        let hash = hash.trim();
+        let invalid = || {
+            TraceixError::InvalidArgument(format!(
+                "{hash:?} is not an ssdeep hash (expected blocksize:hash:hash)"
+            ))
+        };
+        let mut parts = hash.splitn(3, ':');
+        let (Some(block_size), Some(first), Some(second)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+        // ssdeep can append the file name as `,"name"`; it's not part of the hash.
+        let second = second.split_once(',').map_or(second, |(hash, _)| hash);
+        let is_base64 = |s: &str| {
+            s.bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'+' || b == b'/')
+        };
+        if block_size.is_empty()
+            || !block_size.bytes().all(|b| b.is_ascii_digit())
+            || !is_base64(first)
+            || !is_base64(second)
+        {
+            return Err(invalid());
+        }
+        Ok(Self {
+            kind: FuzzyHashType::Ssdeep,
+            value: format!("{block_size}:{first}:{second}"),
+        })
+    }
+
+    /// Validate a TLSH hash, with or without its `T1` version prefix.
+    pub fn tlsh(hash: &str) -> Result<Self, TraceixError> {
+        let hash = hash.trim();
+        let digits = hash
+            .strip_prefix("T1")
+            .or_else(|| hash.strip_prefix("t1"))
+            .unwrap_or(hash);
+        if !TLSH_HEX_LENGTHS.contains(&digits.len())
+            || !digits.bytes().all(|b| b.is_ascii_hexdigit())
+        {
+            return Err(TraceixError::InvalidArgument(format!(
+                "{hash:?} is neither an ssdeep nor a TLSH hash"
+            )));
+        }
+        Ok(Self {
+            kind: FuzzyHashType::Tlsh,
+            value: format!("T1{}", digits.to_ascii_uppercase()),
+        })
+    }
+
+    pub fn kind(&self) -> FuzzyHashType {
+        self.kind
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.value
+    }
+}
+
+impl fmt::Display for FuzzyHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.value)
+    }
+}
+
+impl FromStr for FuzzyHash {
+    type Err = TraceixError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl TryFrom<String> for FuzzyHash {
+    type Error = TraceixError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Self::parse(&s)
+    }
+}
+
+impl From<FuzzyHash> for String {
+    fn from(hash: FuzzyHash) -> Self {
+        hash.value
+    }
+}
+
+/// ssdeep and TLSH of the same content, computed by [`fuzzy_hash_file`].
+#[cfg(feature = "fuzzy-hash")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FuzzyDigests {
+    pub ssdeep: FuzzyHash,
+    /// `None` for inputs TLSH can't describe: under 50 bytes, or too uniform.
+    pub tlsh: Option<FuzzyHash>,
+}
+
+#[cfg(feature = "fuzzy-hash")]
+impl FuzzyDigests {
+    pub fn get(&self, kind: FuzzyHashType) -> Option<&FuzzyHash> {
+        match kind {
+            FuzzyHashType::Ssdeep => Some(&self.ssdeep),
+            FuzzyHashType::Tlsh => self.tlsh.as_ref(),
+        }
+    }
+}
+
+/// Compute the ssdeep and TLSH of everything read from `reader`, in a single pass.
+#[cfg(feature = "fuzzy-hash")]
+pub fn fuzzy_hash_reader(reader: impl Read) -> io::Result<FuzzyDigests> {
+    let mut ssdeep = fuzzyhash::FuzzyHash::default();
+    let mut tlsh = tlsh2::TlshDefaultBuilder::new();
+    for_each_chunk(reader, |chunk| {
+        ssdeep.update(chunk);
+        tlsh.update(chunk);
+    })?;
+    ssdeep.finalize();
+
+    let tlsh = tlsh.build().map(|tlsh| FuzzyHash {
+        kind: FuzzyHashType::Tlsh,
+        value: String::from_utf8_lossy(&tlsh.hash()).into_owned(),
+    });
+    Ok(FuzzyDigests {
+        ssdeep: FuzzyHash {
+            kind: FuzzyHashType::Ssdeep,
+            value: ssdeep.to_string(),
+        },
+        tlsh,
+    })
+}
+
+/// Compute the ssdeep and TLSH of the file at `path`.
+#[cfg(feature = "fuzzy-hash")]
+pub fn fuzzy_hash_file(path: impl AsRef<Path>) -> io::Result<FuzzyDigests> {
+    fuzzy_hash_reader(File::open(path)?)
+}
+
+#[cfg(feature = "blocking")]
+impl TraceixSdk {
+    /// Find samples whose ssdeep or TLSH hash is close to `hash`.
+    ///
+    /// ```no_run
+    /// # use traceix_sdk::{FuzzyHash, TraceixSdk};
+    /// # fn main() -> Result<(), traceix_sdk::TraceixError> {
+    /// let sdk = TraceixSdk::new(None)?;
+    /// let hash: FuzzyHash = "96:U57GjXnLt9co6pZwvLhJluvrszNgMFwO6MFG8SvkpjTWf:Hj3BeoEcNJ0TspgIG8SvkpjTg".parse()?;
+    /// println!("{}", sdk.similarity_search(&hash)?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn similarity_search(&self, hash: &FuzzyHash) -> Result<Value, TraceixError> {
+        self.post_json(
+            &Endpoint::SIMILARITY_SEARCH,
+            &serde_json::json!({ hash.kind().as_str(): hash.as_str() }),
+        )
+    }
+
+    /// Fuzzy-hash a local file and search for similar samples, without uploading it.
+    ///
+    /// Fails with [`TraceixError::InvalidArgument`] if the file has no TLSH (see
+    /// [`FuzzyDigests::tlsh`]) and `kind` is [`FuzzyHashType::Tlsh`].
+    #[cfg(feature = "fuzzy-hash")]
+    pub fn similarity_search_file(
+        &self,
+        path: impl AsRef<Path>,
+        kind: FuzzyHashType,
+    ) -> Result<Value, TraceixError> {
+        let path = path.as_ref();
+        let digests = fuzzy_hash_file(path)?;
+        let hash = digests.get(kind).ok_or_else(|| {
+            TraceixError::InvalidArgument(format!(
+                "{} has no {} hash (too small or too uniform)",
+                path.display(),
+                kind.as_str()
+            ))
+        })?;
+        self.similarity_search(hash)
+    }
+}
@@ -0,0 +1,288 @@
+// src/hashing.rs
+
+//! Streaming MD5/SHA-1/SHA-256 helpers, e.g. to look a sample up with
+//! [`TraceixSdk::hash_search`](crate::TraceixSdk::hash_search) without uploading it.
+//! Files are read in fixed-size chunks, never loaded whole into memory.
+
+use crate::TraceixError;
+use serde::{Deserialize, Serialize};
+use sha2::digest::DynDigest;
+use sha2::Digest;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+/// Digest algorithms understood by the Traceix API.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HashAlgorithm {
+    Md5,
+    Sha1,
+    Sha256,
+}
+
+impl HashAlgorithm {
+    /// Guess the algorithm from the length of a hex digest.
+    pub fn from_hex_len(len: usize) -> Option<HashAlgorithm> {
+        match len {
+            32 => Some(HashAlgorithm::Md5),
+            40 => Some(HashAlgorithm::Sha1),
+            64 => Some(HashAlgorithm::Sha256),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HashAlgorithm::Md5 => "md5",
+            HashAlgorithm::Sha1 => "sha1",
+            HashAlgorithm::Sha256 => "sha256",
+        }
+    }
+
+    fn hasher(&self) -> Box<dyn DynDigest + Send> {
+        match self {
+            HashAlgorithm::Md5 => Box::new(md5::Md5::default()),
+            HashAlgorithm::Sha1 => Box::new(sha1::Sha1::default()),
+            HashAlgorithm::Sha256 => Box::new(sha2::Sha256::default()),
+        }
+    }
+}
+
+/// A validated MD5, SHA-1 or SHA-256 hex digest, normalized to lowercase, whose
+/// algorithm is detected from its length.
+///
+/// ```
+/// # use traceix_sdk::hashing::{FileHash, HashAlgorithm};
+/// let hash: FileHash = " 44D88612FEA8A8F36DE82E1278ABB02F ".parse()?;
+/// assert_eq!(hash.algorithm(), HashAlgorithm::Md5);
+/// assert_eq!(hash.as_str(), "44d88612fea8a8f36de82e1278abb02f");
+/// assert!("not-a-hash".parse::<FileHash>().is_err());
+/// # Ok::<(), traceix_sdk::TraceixError>(())
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct FileHash {
+    algorithm: HashAlgorithm,
+    hex: String,
+}
+
+impl FileHash {
+    /// Validate `hash`, ignoring surrounding whitespace and letter case.
+    pub fn parse(hash: &str) -> Result<Self, TraceixError> {
+        let hash = hash.trim();
+        if let Some(c) = hash.chars().find(|c| !c.is_ascii_hexdigit()) {
+            return Err(TraceixError::InvalidArgument(format!(
+                "{hash:?} is not a hex digest: unexpected character {c:?}"
+            )));
+        }
+        let algorithm = HashAlgorithm::from_hex_len(hash.len()).ok_or_else(|| {
+            TraceixError::InvalidArgument(format!(
+                "{hash:?} has {} hex digits; expected 32 (MD5), 40 (SHA-1) or 64 (SHA-256)",
+                hash.len()
+            ))
+        })?;
+        Ok(Self {
+            algorithm,
+            hex: hash.to_ascii_lowercase(),
+        })
+    }
+
+    pub fn algorithm(&self) -> HashAlgorithm {
+        self.algorithm
+    }
+
+    /// The lowercase hex digest.
+    pub fn as_str(&self) -> &str {
+        &self.hex
+    }
+
+    /// Name of the request field the API expects this hash in, e.g. `sha256`.
+    pub fn field(&self) -> &'static str {
+        self.algorithm.as_str()
+    }
+
+    pub fn into_string(self) -> String {
+        self.hex
+    }
+}
+
+impl fmt::Display for FileHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.hex)
+    }
+}
+
+impl FromStr for FileHash {
+    type Err = TraceixError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl TryFrom<String> for FileHash {
+    type Error = TraceixError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Self::parse(&s)
+    }
+}
+
+impl From<FileHash> for String {
+    fn from(hash: FileHash) -> Self {
+        hash.hex
+    }
+}
+
+impl AsRef<str> for FileHash {
+    fn as_ref(&self) -> &str {
+        &self.hex
+    }
+}
+
+/// Validate a hex digest, detect its algorithm by length, and lowercase it.
+pub(crate) fn normalize_hex_digest(hash: &str) -> Result<(HashAlgorithm, String), TraceixError> {
+    let hash = FileHash::parse(hash)?;
+    Ok((hash.algorithm, hash.hex))
+}
+
+/// MD5, SHA-1 and SHA-256 of the same content, as lowercase hex.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FileDigests {
+    pub md5: String,
+    pub sha1: String,
+    pub sha256: String,
+}
+
+impl FileDigests {
+    /// Compute all three digests in a single streaming pass over `reader`.
+    pub fn from_reader(reader: impl Read) -> io::Result<Self> {
+        let mut md5 = md5::Md5::new();
+        let mut sha1 = sha1::Sha1::new();
+        let mut sha256 = sha2::Sha256::new();
+
+        for_each_chunk(reader, |chunk| {
+            Digest::update(&mut md5, chunk);
+            Digest::update(&mut sha1, chunk);
+            Digest::update(&mut sha256, chunk);
+        })?;
+
+        Ok(Self {
+            md5: to_hex(&Digest::finalize(md5)),
+            sha1: to_hex(&Digest::finalize(sha1)),
+            sha256: to_hex(&Digest::finalize(sha256)),
+        })
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_reader(File::open(path)?)
+    }
+
+    /// The digests as `(algorithm, hex)` pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (HashAlgorithm, &str)> {
+        [
+            (HashAlgorithm::Md5, self.md5.as_str()),
+            (HashAlgorithm::Sha1, self.sha1.as_str()),
+            (HashAlgorithm::Sha256, self.sha256.as_str()),
+        ]
+        .into_iter()
+    }
+}
+
+/// Feed `reader` to `f` in fixed-size chunks until EOF.
+pub(crate) fn for_each_chunk(mut reader: impl Read, mut f: impl FnMut(&[u8])) -> io::Result<()> {
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) => return Ok(()),
+            Ok(n) => f(&buf[..n]),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Hex digest of everything read from `reader`.
+pub fn hash_reader(reader: impl Read, algorithm: HashAlgorithm) -> io::Result<String> {
+    let mut hasher = algorithm.hasher();
+    for_each_chunk(reader, |chunk| hasher.update(chunk))?;
+    Ok(to_hex(&hasher.finalize()))
+}
+
+/// Hex digest of the file at `path`.
+pub fn hash_file(path: impl AsRef<Path>, algorithm: HashAlgorithm) -> io::Result<String> {
+    hash_reader(File::open(path)?, algorithm)
+}
+
+pub fn sha256_reader(reader: impl Read) -> io::Result<String> {
+    hash_reader(reader, HashAlgorithm::Sha256)
+}
+
+pub fn sha256_file(path: impl AsRef<Path>) -> io::Result<String> {
+    hash_file(path, HashAlgorithm::Sha256)
+}
+
+pub fn sha1_reader(reader: impl Read) -> io::Result<String> {
+    hash_reader(reader, HashAlgorithm::Sha1)
+}
+
+pub fn sha1_file(path: impl AsRef<Path>) -> io::Result<String> {
+    hash_file(path, HashAlgorithm::Sha1)
+}
+
+pub fn md5_reader(reader: impl Read) -> io::Result<String> {
+    hash_reader(reader, HashAlgorithm::Md5)
+}
+
+pub fn md5_file(path: impl AsRef<Path>) -> io::Result<String> {
+    hash_file(path, HashAlgorithm::Md5)
+}
+
+/// Lowercase hex encoding of a digest.
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// A writer that hashes everything passing through it on the way to `inner`.
+pub struct HashingWriter<W> {
+    inner: W,
+    hasher: Box<dyn DynDigest + Send>,
+    written: u64,
+}
+
+impl<W: Write> HashingWriter<W> {
+    pub fn new(inner: W, algorithm: HashAlgorithm) -> Self {
+        Self {
+            inner,
+            hasher: algorithm.hasher(),
+            written: 0,
+        }
+    }
+
+    /// Bytes written so far.
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+
+    /// Flush and return the inner writer with the hex digest of everything written.
+    pub fn finish(mut self) -> io::Result<(W, String)> {
+        self.inner.flush()?;
+        let digest = to_hex(&self.hasher.finalize());
+        Ok((self.inner, digest))
+    }
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.hasher.update(&buf[..n]);
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
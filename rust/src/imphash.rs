@@ -0,0 +1,924 @@
+// src/imphash.rs
+
+//! Import hashes (imphash) of Windows PE files: the MD5 of the imported DLL and function
+//! names in import-table order, as computed by `pefile` and shown by most intel
+//! platforms. Samples built from the same source tree usually share one, so it pivots to
+//! related samples with [`TraceixSdk::imphash_search`](crate::TraceixSdk::imphash_search).
+
+use md5::{Digest, Md5};
+use std::fs;
+use std::io;
+use std::path::Path;
+#[cfg(feature = "blocking")]
+use {
+    crate::{Endpoint, TraceixError, TraceixSdk},
+    serde_json::Value,
+};
+
+/// Upper bounds on what is read from the import table, so a corrupt or hostile file
+/// can't make parsing run away.
+const MAX_DLLS: usize = 4096;
+const MAX_FUNCTIONS: usize = 65536;
+const MAX_NAME: usize = 512;
+
+/// Compute the imphash of the PE image in `data`.
+///
+/// Returns `Ok(None)` for a valid PE that imports nothing, and an
+/// [`io::ErrorKind::InvalidData`] error if `data` isn't a PE file.
+pub fn imphash(data: &[u8]) -> io::Result<Option<String>> {
+    let names = import_names(data)?;
+    if names.is_empty() {
+        return Ok(None);
+    }
+    let digest = Md5::digest(names.join(",").as_bytes());
+    Ok(Some(
+        digest
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect::<String>(),
+    ))
+}
+
+/// Compute the imphash of the PE file at `path`. See [`imphash`].
+pub fn imphash_file(path: impl AsRef<Path>) -> io::Result<Option<String>> {
+    imphash(&fs::read(path)?)
+}
+
+/// The `dll.function` entries an imphash is made of, normalized the way `pefile` does:
+/// lowercase, with `.dll`/`.ocx`/`.sys` dropped from the DLL name.
+fn import_names(data: &[u8]) -> io::Result<Vec<String>> {
+    let pe = Pe::parse(data).ok_or_else(|| invalid("not a PE file"))?;
+    let Some(mut descriptor) = pe.import_directory() else {
+        return Ok(Vec::new());
+    };
+
+    let mut names = Vec::new();
+    for _ in 0..MAX_DLLS {
+        let Some(entry) = pe.slice(descriptor, 20) else {
+            return Err(invalid("import table runs past the end of the file"));
+        };
+        let original_thunks = u32_at(entry, 0);
+        let dll_name = u32_at(entry, 12);
+        let thunks = u32_at(entry, 16);
+        if dll_name == 0 && thunks == 0 {
+            break;
+        }
+        descriptor = descriptor
+            .checked_add(20)
+            .ok_or_else(|| invalid("import table runs past the end of the address space"))?;
+
+        let Some(dll) = pe.c_string(dll_name) else {
+            continue;
+        };
+        let dll = dll.to_ascii_lowercase();
+        let library = match dll.rsplit_once('.') {
+            Some((stem, "dll" | "ocx" | "sys")) => stem.to_string(),
+            _ => dll.clone(),
+        };
+
+        // Bound linkers leave resolved addresses in the IAT, so prefer the lookup table.
+        let mut thunk = if original_thunks != 0 {
+            original_thunks
+        } else {
+            thunks
+        };
+        for _ in 0..MAX_FUNCTIONS {
+            let Some(value) = pe.thunk(thunk) else {
+                break;
+            };
+            if value == 0 {
+                break;
+            }
+            thunk = thunk.checked_add(pe.thunk_size()).ok_or_else(|| {
+                invalid("import lookup table runs past the end of the address space")
+            })?;
+            let function = if value & pe.ordinal_flag() != 0 {
+                let ordinal = (value & 0xffff) as u16;
+                match ordinal_name(&dll, ordinal) {
+                    Some(name) => name.to_ascii_lowercase(),
+                    None => format!("ord{ordinal}"),
+                }
+            } else {
+                // Skip the two-byte hint in front of the name.
+                match u32::try_from(value & 0x7fff_ffff)
+                    .ok()
+                    .and_then(|rva| pe.c_string(rva.checked_add(2)?))
+                {
+                    Some(name) => name.to_ascii_lowercase(),
+                    None => continue,
+                }
+            };
+            names.push(format!("{library}.{function}"));
+        }
+    }
+    Ok(names)
+}
+
+/// The parts of a PE image needed to walk its import table.
+struct Pe<'a> {
+    data: &'a [u8],
+    pe32_plus: bool,
+    import_rva: u32,
+    /// `(virtual address, virtual size, raw offset, raw size)` of each section.
+    sections: Vec<(u32, u32, u32, u32)>,
+}
+
+impl<'a> Pe<'a> {
+    fn parse(data: &'a [u8]) -> Option<Self> {
+        if data.get(..2)? != b"MZ" {
+            return None;
+        }
+        let header = usize::try_from(u32_at(data.get(0x3c..0x40)?, 0)).ok()?;
+        if data.get(header..header.checked_add(4)?)? != b"PE\0\0" {
+            return None;
+        }
+        let coff = header + 4;
+        let section_count = usize::from(u16_at(data.get(coff..coff + 20)?, 2));
+        let optional_size = usize::from(u16_at(data.get(coff..coff + 20)?, 16));
+        let optional = coff + 20;
+        let optional_header = data.get(optional..optional.checked_add(optional_size)?)?;
+        let pe32_plus = match u16_at(optional_header.get(..2)?, 0) {
+            0x10b => false,
+            0x20b => true,
+            _ => return None,
+        };
+        // Data directory 1 is the import table.
+        let directories = if pe32_plus { 112 } else { 96 };
+        let import_rva = optional_header
+            .get(directories + 8..directories + 12)
+            .map_or(0, |d| u32_at(d, 0));
+
+        let table = optional + optional_size;
+        let sections = (0..section_count)
+            .map_while(|i| data.get(table + i * 40..table + (i + 1) * 40))
+            .map(|s| (u32_at(s, 12), u32_at(s, 8), u32_at(s, 20), u32_at(s, 16)))
+            .collect();
+        Some(Self {
+            data,
+            pe32_plus,
+            import_rva,
+            sections,
+        })
+    }
+
+    fn import_directory(&self) -> Option<u32> {
+        (self.import_rva != 0).then_some(self.import_rva)
+    }
+
+    /// Map a relative virtual address to a file offset.
+    fn offset(&self, rva: u32) -> Option<usize> {
+        let found = self.sections.iter().find(|&&(address, size, _, raw_size)| {
+            rva >= address && rva - address < size.max(raw_size)
+        });
+        let offset = match found {
+            Some(&(address, _, raw, _)) => raw.checked_add(rva - address)?,
+            // Headers and section-less images map one to one.
+            None => rva,
+        };
+        usize::try_from(offset).ok()
+    }
+
+    fn slice(&self, rva: u32, len: usize) -> Option<&'a [u8]> {
+        let start = self.offset(rva)?;
+        self.data.get(start..start.checked_add(len)?)
+    }
+
+    fn c_string(&self, rva: u32) -> Option<String> {
+        let start = self.offset(rva)?;
+        let bytes = self.data.get(start..)?;
+        let bytes = &bytes[..bytes.len().min(MAX_NAME)];
+        let end = bytes.iter().position(|&b| b == 0)?;
+        (end > 0).then(|| String::from_utf8_lossy(&bytes[..end]).into_owned())
+    }
+
+    fn thunk_size(&self) -> u32 {
+        if self.pe32_plus {
+            8
+        } else {
+            4
+        }
+    }
+
+    fn ordinal_flag(&self) -> u64 {
+        if self.pe32_plus {
+            1 << 63
+        } else {
+            1 << 31
+        }
+    }
+
+    fn thunk(&self, rva: u32) -> Option<u64> {
+        let bytes = self.slice(rva, self.thunk_size() as usize)?;
+        Some(if self.pe32_plus {
+            u64::from_le_bytes(bytes.try_into().ok()?)
+        } else {
+            u64::from(u32_at(bytes, 0))
+        })
+    }
+}
+
+/// Names for exports commonly imported by ordinal, as resolved by `pefile`'s
+/// `ordlookup` before hashing. Other ordinal imports hash as `ordN`.
+fn ordinal_name(dll: &str, ordinal: u16) -> Option<&'static str> {
+    let table = match dll {
+        "ws2_32.dll" | "wsock32.dll" => WS2_32_ORDINALS,
+        "oleaut32.dll" => OLEAUT32_ORDINALS,
+        _ => return None,
+    };
+    table
+        .binary_search_by_key(&ordinal, |&(ordinal, _)| ordinal)
+        .ok()
+        .map(|i| table[i].1)
+}
+
+/// `ordlookup/ws2_32.py`, also used for `wsock32.dll`. Sorted by ordinal.
+const WS2_32_ORDINALS: &[(u16, &str)] = &[
+    (1, "accept"),
+    (2, "bind"),
+    (3, "closesocket"),
+    (4, "connect"),
+    (5, "getpeername"),
+    (6, "getsockname"),
+    (7, "getsockopt"),
+    (8, "htonl"),
+    (9, "htons"),
+    (10, "ioctlsocket"),
+    (11, "inet_addr"),
+    (12, "inet_ntoa"),
+    (13, "listen"),
+    (14, "ntohl"),
+    (15, "ntohs"),
+    (16, "recv"),
+    (17, "recvfrom"),
+    (18, "select"),
+    (19, "send"),
+    (20, "sendto"),
+    (21, "setsockopt"),
+    (22, "shutdown"),
+    (23, "socket"),
+    (24, "GetAddrInfoW"),
+    (25, "GetNameInfoW"),
+    (26, "WSApSetPostRoutine"),
+    (27, "FreeAddrInfoW"),
+    (28, "WPUCompleteOverlappedRequest"),
+    (29, "WSAAccept"),
+    (30, "WSAAddressToStringA"),
+    (31, "WSAAddressToStringW"),
+    (32, "WSACloseEvent"),
+    (33, "WSAConnect"),
+    (34, "WSACreateEvent"),
+    (35, "WSADuplicateSocketA"),
+    (36, "WSADuplicateSocketW"),
+    (37, "WSAEnumNameSpaceProvidersA"),
+    (38, "WSAEnumNameSpaceProvidersW"),
+    (39, "WSAEnumNetworkEvents"),
+    (40, "WSAEnumProtocolsA"),
+    (41, "WSAEnumProtocolsW"),
+    (42, "WSAEventSelect"),
+    (43, "WSAGetOverlappedResult"),
+    (44, "WSAGetQOSByName"),
+    (45, "WSAGetServiceClassInfoA"),
+    (46, "WSAGetServiceClassInfoW"),
+    (47, "WSAGetServiceClassNameByClassIdA"),
+    (48, "WSAGetServiceClassNameByClassIdW"),
+    (49, "WSAHtonl"),
+    (50, "WSAHtons"),
+    (51, "gethostbyaddr"),
+    (52, "gethostbyname"),
+    (53, "getprotobyname"),
+    (54, "getprotobynumber"),
+    (55, "getservbyname"),
+    (56, "getservbyport"),
+    (57, "gethostname"),
+    (58, "WSAInstallServiceClassA"),
+    (59, "WSAInstallServiceClassW"),
+    (60, "WSAIoctl"),
+    (61, "WSAJoinLeaf"),
+    (62, "WSALookupServiceBeginA"),
+    (63, "WSALookupServiceBeginW"),
+    (64, "WSALookupServiceEnd"),
+    (65, "WSALookupServiceNextA"),
+    (66, "WSALookupServiceNextW"),
+    (67, "WSANSPIoctl"),
+    (68, "WSANtohl"),
+    (69, "WSANtohs"),
+    (70, "WSAProviderConfigChange"),
+    (71, "WSARecv"),
+    (72, "WSARecvDisconnect"),
+    (73, "WSARecvFrom"),
+    (74, "WSARemoveServiceClass"),
+    (75, "WSAResetEvent"),
+    (76, "WSASend"),
+    (77, "WSASendDisconnect"),
+    (78, "WSASendTo"),
+    (79, "WSASetEvent"),
+    (80, "WSASetServiceA"),
+    (81, "WSASetServiceW"),
+    (82, "WSASocketA"),
+    (83, "WSASocketW"),
+    (84, "WSAStringToAddressA"),
+    (85, "WSAStringToAddressW"),
+    (86, "WSAWaitForMultipleEvents"),
+    (87, "WSCDeinstallProvider"),
+    (88, "WSCEnableNSProvider"),
+    (89, "WSCEnumProtocols"),
+    (90, "WSCGetProviderPath"),
+    (91, "WSCInstallNameSpace"),
+    (92, "WSCInstallProvider"),
+    (93, "WSCUnInstallNameSpace"),
+    (94, "WSCUpdateProvider"),
+    (95, "WSCWriteNameSpaceOrder"),
+    (96, "WSCWriteProviderOrder"),
+    (97, "freeaddrinfo"),
+    (98, "getaddrinfo"),
+    (99, "getnameinfo"),
+    (101, "WSAAsyncSelect"),
+    (102, "WSAAsyncGetHostByAddr"),
+    (103, "WSAAsyncGetHostByName"),
+    (104, "WSAAsyncGetProtoByNumber"),
+    (105, "WSAAsyncGetProtoByName"),
+    (106, "WSAAsyncGetServByPort"),
+    (107, "WSAAsyncGetServByName"),
+    (108, "WSACancelAsyncRequest"),
+    (109, "WSASetBlockingHook"),
+    (110, "WSAUnhookBlockingHook"),
+    (111, "WSAGetLastError"),
+    (112, "WSASetLastError"),
+    (113, "WSACancelBlockingCall"),
+    (114, "WSAIsBlocking"),
+    (115, "WSAStartup"),
+    (116, "WSACleanup"),
+    (151, "__WSAFDIsSet"),
+    (500, "WEP"),
+];
+
+/// `ordlookup/oleaut32.py`. Sorted by ordinal.
+const OLEAUT32_ORDINALS: &[(u16, &str)] = &[
+    (2, "SysAllocString"),
+    (3, "SysReAllocString"),
+    (4, "SysAllocStringLen"),
+    (5, "SysReAllocStringLen"),
+    (6, "SysFreeString"),
+    (7, "SysStringLen"),
+    (8, "VariantInit"),
+    (9, "VariantClear"),
+    (10, "VariantCopy"),
+    (11, "VariantCopyInd"),
+    (12, "VariantChangeType"),
+    (13, "VariantTimeToDosDateTime"),
+    (14, "DosDateTimeToVariantTime"),
+    (15, "SafeArrayCreate"),
+    (16, "SafeArrayDestroy"),
+    (17, "SafeArrayGetDim"),
+    (18, "SafeArrayGetElemsize"),
+    (19, "SafeArrayGetUBound"),
+    (20, "SafeArrayGetLBound"),
+    (21, "SafeArrayLock"),
+    (22, "SafeArrayUnlock"),
+    (23, "SafeArrayAccessData"),
+    (24, "SafeArrayUnaccessData"),
+    (25, "SafeArrayGetElement"),
+    (26, "SafeArrayPutElement"),
+    (27, "SafeArrayCopy"),
+    (28, "DispGetParam"),
+    (29, "DispGetIDsOfNames"),
+    (30, "DispInvoke"),
+    (31, "CreateDispTypeInfo"),
+    (32, "CreateStdDispatch"),
+    (33, "RegisterActiveObject"),
+    (34, "RevokeActiveObject"),
+    (35, "GetActiveObject"),
+    (36, "SafeArrayAllocDescriptor"),
+    (37, "SafeArrayAllocData"),
+    (38, "SafeArrayDestroyDescriptor"),
+    (39, "SafeArrayDestroyData"),
+    (40, "SafeArrayRedim"),
+    (41, "SafeArrayAllocDescriptorEx"),
+    (42, "SafeArrayCreateEx"),
+    (43, "SafeArrayCreateVectorEx"),
+    (44, "SafeArraySetRecordInfo"),
+    (45, "SafeArrayGetRecordInfo"),
+    (46, "VarParseNumFromStr"),
+    (47, "VarNumFromParseNum"),
+    (48, "VarI2FromUI1"),
+    (49, "VarI2FromI4"),
+    (50, "VarI2FromR4"),
+    (51, "VarI2FromR8"),
+    (52, "VarI2FromCy"),
+    (53, "VarI2FromDate"),
+    (54, "VarI2FromStr"),
+    (55, "VarI2FromDisp"),
+    (56, "VarI2FromBool"),
+    (57, "SafeArraySetIID"),
+    (58, "VarI4FromUI1"),
+    (59, "VarI4FromI2"),
+    (60, "VarI4FromR4"),
+    (61, "VarI4FromR8"),
+    (62, "VarI4FromCy"),
+    (63, "VarI4FromDate"),
+    (64, "VarI4FromStr"),
+    (65, "VarI4FromDisp"),
+    (66, "VarI4FromBool"),
+    (67, "SafeArrayGetIID"),
+    (68, "VarR4FromUI1"),
+    (69, "VarR4FromI2"),
+    (70, "VarR4FromI4"),
+    (71, "VarR4FromR8"),
+    (72, "VarR4FromCy"),
+    (73, "VarR4FromDate"),
+    (74, "VarR4FromStr"),
+    (75, "VarR4FromDisp"),
+    (76, "VarR4FromBool"),
+    (77, "SafeArrayGetVartype"),
+    (78, "VarR8FromUI1"),
+    (79, "VarR8FromI2"),
+    (80, "VarR8FromI4"),
+    (81, "VarR8FromR4"),
+    (82, "VarR8FromCy"),
+    (83, "VarR8FromDate"),
+    (84, "VarR8FromStr"),
+    (85, "VarR8FromDisp"),
+    (86, "VarR8FromBool"),
+    (87, "VarFormat"),
+    (88, "VarDateFromUI1"),
+    (89, "VarDateFromI2"),
+    (90, "VarDateFromI4"),
+    (91, "VarDateFromR4"),
+    (92, "VarDateFromR8"),
+    (93, "VarDateFromCy"),
+    (94, "VarDateFromStr"),
+    (95, "VarDateFromDisp"),
+    (96, "VarDateFromBool"),
+    (97, "VarFormatDateTime"),
+    (98, "VarCyFromUI1"),
+    (99, "VarCyFromI2"),
+    (100, "VarCyFromI4"),
+    (101, "VarCyFromR4"),
+    (102, "VarCyFromR8"),
+    (103, "VarCyFromDate"),
+    (104, "VarCyFromStr"),
+    (105, "VarCyFromDisp"),
+    (106, "VarCyFromBool"),
+    (107, "VarFormatNumber"),
+    (108, "VarBstrFromUI1"),
+    (109, "VarBstrFromI2"),
+    (110, "VarBstrFromI4"),
+    (111, "VarBstrFromR4"),
+    (112, "VarBstrFromR8"),
+    (113, "VarBstrFromCy"),
+    (114, "VarBstrFromDate"),
+    (115, "VarBstrFromDisp"),
+    (116, "VarBstrFromBool"),
+    (117, "VarFormatPercent"),
+    (118, "VarBoolFromUI1"),
+    (119, "VarBoolFromI2"),
+    (120, "VarBoolFromI4"),
+    (121, "VarBoolFromR4"),
+    (122, "VarBoolFromR8"),
+    (123, "VarBoolFromDate"),
+    (124, "VarBoolFromCy"),
+    (125, "VarBoolFromStr"),
+    (126, "VarBoolFromDisp"),
+    (127, "VarFormatCurrency"),
+    (128, "VarWeekdayName"),
+    (129, "VarMonthName"),
+    (130, "VarUI1FromI2"),
+    (131, "VarUI1FromI4"),
+    (132, "VarUI1FromR4"),
+    (133, "VarUI1FromR8"),
+    (134, "VarUI1FromCy"),
+    (135, "VarUI1FromDate"),
+    (136, "VarUI1FromStr"),
+    (137, "VarUI1FromDisp"),
+    (138, "VarUI1FromBool"),
+    (139, "VarFormatFromTokens"),
+    (140, "VarTokenizeFormatString"),
+    (141, "VarAdd"),
+    (142, "VarAnd"),
+    (143, "VarDiv"),
+    (144, "DllCanUnloadNow"),
+    (145, "DllGetClassObject"),
+    (146, "DispCallFunc"),
+    (147, "VariantChangeTypeEx"),
+    (148, "SafeArrayPtrOfIndex"),
+    (149, "SysStringByteLen"),
+    (150, "SysAllocStringByteLen"),
+    (151, "DllRegisterServer"),
+    (152, "VarEqv"),
+    (153, "VarIdiv"),
+    (154, "VarImp"),
+    (155, "VarMod"),
+    (156, "VarMul"),
+    (157, "VarOr"),
+    (158, "VarPow"),
+    (159, "VarSub"),
+    (160, "CreateTypeLib"),
+    (161, "LoadTypeLib"),
+    (162, "LoadRegTypeLib"),
+    (163, "RegisterTypeLib"),
+    (164, "QueryPathOfRegTypeLib"),
+    (165, "LHashValOfNameSys"),
+    (166, "LHashValOfNameSysA"),
+    (167, "VarXor"),
+    (168, "VarAbs"),
+    (169, "VarFix"),
+    (170, "OaBuildVersion"),
+    (171, "ClearCustData"),
+    (172, "VarInt"),
+    (173, "VarNeg"),
+    (174, "VarNot"),
+    (175, "VarRound"),
+    (176, "VarCmp"),
+    (177, "VarDecAdd"),
+    (178, "VarDecDiv"),
+    (179, "VarDecMul"),
+    (180, "CreateTypeLib2"),
+    (181, "VarDecSub"),
+    (182, "VarDecAbs"),
+    (183, "LoadTypeLibEx"),
+    (184, "SystemTimeToVariantTime"),
+    (185, "VariantTimeToSystemTime"),
+    (186, "UnRegisterTypeLib"),
+    (187, "VarDecFix"),
+    (188, "VarDecInt"),
+    (189, "VarDecNeg"),
+    (190, "VarDecFromUI1"),
+    (191, "VarDecFromI2"),
+    (192, "VarDecFromI4"),
+    (193, "VarDecFromR4"),
+    (194, "VarDecFromR8"),
+    (195, "VarDecFromDate"),
+    (196, "VarDecFromCy"),
+    (197, "VarDecFromStr"),
+    (198, "VarDecFromDisp"),
+    (199, "VarDecFromBool"),
+    (200, "GetErrorInfo"),
+    (201, "SetErrorInfo"),
+    (202, "CreateErrorInfo"),
+    (203, "VarDecRound"),
+    (204, "VarDecCmp"),
+    (205, "VarI2FromI1"),
+    (206, "VarI2FromUI2"),
+    (207, "VarI2FromUI4"),
+    (208, "VarI2FromDec"),
+    (209, "VarI4FromI1"),
+    (210, "VarI4FromUI2"),
+    (211, "VarI4FromUI4"),
+    (212, "VarI4FromDec"),
+    (213, "VarR4FromI1"),
+    (214, "VarR4FromUI2"),
+    (215, "VarR4FromUI4"),
+    (216, "VarR4FromDec"),
+    (217, "VarR8FromI1"),
+    (218, "VarR8FromUI2"),
+    (219, "VarR8FromUI4"),
+    (220, "VarR8FromDec"),
+    (221, "VarDateFromI1"),
+    (222, "VarDateFromUI2"),
+    (223, "VarDateFromUI4"),
+    (224, "VarDateFromDec"),
+    (225, "VarCyFromI1"),
+    (226, "VarCyFromUI2"),
+    (227, "VarCyFromUI4"),
+    (228, "VarCyFromDec"),
+    (229, "VarBstrFromI1"),
+    (230, "VarBstrFromUI2"),
+    (231, "VarBstrFromUI4"),
+    (232, "VarBstrFromDec"),
+    (233, "VarBoolFromI1"),
+    (234, "VarBoolFromUI2"),
+    (235, "VarBoolFromUI4"),
+    (236, "VarBoolFromDec"),
+    (237, "VarUI1FromI1"),
+    (238, "VarUI1FromUI2"),
+    (239, "VarUI1FromUI4"),
+    (240, "VarUI1FromDec"),
+    (241, "VarDecFromI1"),
+    (242, "VarDecFromUI2"),
+    (243, "VarDecFromUI4"),
+    (244, "VarI1FromUI1"),
+    (245, "VarI1FromI2"),
+    (246, "VarI1FromI4"),
+    (247, "VarI1FromR4"),
+    (248, "VarI1FromR8"),
+    (249, "VarI1FromDate"),
+    (250, "VarI1FromCy"),
+    (251, "VarI1FromStr"),
+    (252, "VarI1FromDisp"),
+    (253, "VarI1FromBool"),
+    (254, "VarI1FromUI2"),
+    (255, "VarI1FromUI4"),
+    (256, "VarI1FromDec"),
+    (257, "VarUI2FromUI1"),
+    (258, "VarUI2FromI2"),
+    (259, "VarUI2FromI4"),
+    (260, "VarUI2FromR4"),
+    (261, "VarUI2FromR8"),
+    (262, "VarUI2FromDate"),
+    (263, "VarUI2FromCy"),
+    (264, "VarUI2FromStr"),
+    (265, "VarUI2FromDisp"),
+    (266, "VarUI2FromBool"),
+    (267, "VarUI2FromI1"),
+    (268, "VarUI2FromUI4"),
+    (269, "VarUI2FromDec"),
+    (270, "VarUI4FromUI1"),
+    (271, "VarUI4FromI2"),
+    (272, "VarUI4FromI4"),
+    (273, "VarUI4FromR4"),
+    (274, "VarUI4FromR8"),
+    (275, "VarUI4FromDate"),
+    (276, "VarUI4FromCy"),
+    (277, "VarUI4FromStr"),
+    (278, "VarUI4FromDisp"),
+    (279, "VarUI4FromBool"),
+    (280, "VarUI4FromI1"),
+    (281, "VarUI4FromUI2"),
+    (282, "VarUI4FromDec"),
+    (283, "BSTR_UserSize"),
+    (284, "BSTR_UserMarshal"),
+    (285, "BSTR_UserUnmarshal"),
+    (286, "BSTR_UserFree"),
+    (287, "VARIANT_UserSize"),
+    (288, "VARIANT_UserMarshal"),
+    (289, "VARIANT_UserUnmarshal"),
+    (290, "VARIANT_UserFree"),
+    (291, "LPSAFEARRAY_UserSize"),
+    (292, "LPSAFEARRAY_UserMarshal"),
+    (293, "LPSAFEARRAY_UserUnmarshal"),
+    (294, "LPSAFEARRAY_UserFree"),
+    (295, "LPSAFEARRAY_Size"),
+    (296, "LPSAFEARRAY_Marshal"),
+    (297, "LPSAFEARRAY_Unmarshal"),
+    (298, "VarDecCmpR8"),
+    (299, "VarCyAdd"),
+    (300, "DllUnregisterServer"),
+    (301, "OACreateTypeLib2"),
+    (303, "VarCyMul"),
+    (304, "VarCyMulI4"),
+    (305, "VarCySub"),
+    (306, "VarCyAbs"),
+    (307, "VarCyFix"),
+    (308, "VarCyInt"),
+    (309, "VarCyNeg"),
+    (310, "VarCyRound"),
+    (311, "VarCyCmp"),
+    (312, "VarCyCmpR8"),
+    (313, "VarBstrCat"),
+    (314, "VarBstrCmp"),
+    (315, "VarR8Pow"),
+    (316, "VarR4CmpR8"),
+    (317, "VarR8Round"),
+    (318, "VarCat"),
+    (319, "VarDateFromUdateEx"),
+    (322, "GetRecordInfoFromGuids"),
+    (323, "GetRecordInfoFromTypeInfo"),
+    (325, "SetVarConversionLocaleSetting"),
+    (326, "GetVarConversionLocaleSetting"),
+    (327, "SetOaNoCache"),
+    (329, "VarCyMulI8"),
+    (330, "VarDateFromUdate"),
+    (331, "VarUdateFromDate"),
+    (332, "GetAltMonthNames"),
+    (333, "VarI8FromUI1"),
+    (334, "VarI8FromI2"),
+    (335, "VarI8FromR4"),
+    (336, "VarI8FromR8"),
+    (337, "VarI8FromCy"),
+    (338, "VarI8FromDate"),
+    (339, "VarI8FromStr"),
+    (340, "VarI8FromDisp"),
+    (341, "VarI8FromBool"),
+    (342, "VarI8FromI1"),
+    (343, "VarI8FromUI2"),
+    (344, "VarI8FromUI4"),
+    (345, "VarI8FromDec"),
+    (346, "VarI2FromI8"),
+    (347, "VarI2FromUI8"),
+    (348, "VarI4FromI8"),
+    (349, "VarI4FromUI8"),
+    (360, "VarR4FromI8"),
+    (361, "VarR4FromUI8"),
+    (362, "VarR8FromI8"),
+    (363, "VarR8FromUI8"),
+    (364, "VarDateFromI8"),
+    (365, "VarDateFromUI8"),
+    (366, "VarCyFromI8"),
+    (367, "VarCyFromUI8"),
+    (368, "VarBstrFromI8"),
+    (369, "VarBstrFromUI8"),
+    (370, "VarBoolFromI8"),
+    (371, "VarBoolFromUI8"),
+    (372, "VarUI1FromI8"),
+    (373, "VarUI1FromUI8"),
+    (374, "VarDecFromI8"),
+    (375, "VarDecFromUI8"),
+    (376, "VarI1FromI8"),
+    (377, "VarI1FromUI8"),
+    (378, "VarUI2FromI8"),
+    (379, "VarUI2FromUI8"),
+    (401, "OleLoadPictureEx"),
+    (402, "OleLoadPictureFileEx"),
+    (411, "SafeArrayCreateVector"),
+    (412, "SafeArrayCopyData"),
+    (413, "VectorFromBstr"),
+    (414, "BstrFromVector"),
+    (415, "OleIconToCursor"),
+    (416, "OleCreatePropertyFrameIndirect"),
+    (417, "OleCreatePropertyFrame"),
+    (418, "OleLoadPicture"),
+    (419, "OleCreatePictureIndirect"),
+    (420, "OleCreateFontIndirect"),
+    (421, "OleTranslateColor"),
+    (422, "OleLoadPictureFile"),
+    (423, "OleSavePictureFile"),
+    (424, "OleLoadPicturePath"),
+    (425, "VarUI4FromI8"),
+    (426, "VarUI4FromUI8"),
+    (427, "VarI8FromUI8"),
+    (428, "VarUI8FromI8"),
+    (429, "VarUI8FromUI1"),
+    (430, "VarUI8FromI2"),
+    (431, "VarUI8FromR4"),
+    (432, "VarUI8FromR8"),
+    (433, "VarUI8FromCy"),
+    (434, "VarUI8FromDate"),
+    (435, "VarUI8FromStr"),
+    (436, "VarUI8FromDisp"),
+    (437, "VarUI8FromBool"),
+    (438, "VarUI8FromI1"),
+    (439, "VarUI8FromUI2"),
+    (440, "VarUI8FromUI4"),
+    (441, "VarUI8FromDec"),
+    (442, "RegisterTypeLibForUser"),
+    (443, "UnRegisterTypeLibForUser"),
+];
+
+fn u16_at(bytes: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes([bytes[at], bytes[at + 1]])
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(feature = "blocking")]
+impl TraceixSdk {
+    /// Find samples sharing an import hash, e.g. one computed with [`imphash_file`].
+    pub fn imphash_search(&self, imphash: &str) -> Result<Value, TraceixError> {
+        let imphash = imphash.trim();
+        if imphash.len() != 32 || !imphash.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(TraceixError::InvalidArgument(format!(
+                "{imphash:?} is not an imphash (expected 32 hex digits)"
+            )));
+        }
+        self.post_json(
+            &Endpoint::IMPHASH_SEARCH,
+            &serde_json::json!({ "imphash": imphash.to_ascii_lowercase() }),
+        )
+    }
+
+    /// Compute a local PE file's imphash and search for samples sharing it, without
+    /// uploading the file.
+    ///
+    /// ```no_run
+    /// # use traceix_sdk::TraceixSdk;
+    /// # fn main() -> Result<(), traceix_sdk::TraceixError> {
+    /// let sdk = TraceixSdk::new(None)?;
+    /// let related = sdk.imphash_search_file("dropper.exe")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Fails with [`TraceixError::InvalidArgument`] if the file imports nothing.
+    pub fn imphash_search_file(&self, path: impl AsRef<Path>) -> Result<Value, TraceixError> {
+        let path = path.as_ref();
+        let imphash = imphash_file(path)?.ok_or_else(|| {
+            TraceixError::InvalidArgument(format!("{} has no imports", path.display()))
+        })?;
+        self.imphash_search(&imphash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// File offset of the single section's raw data.
+    const RAW: usize = 0x200;
+
+    fn put(data: &mut [u8], at: usize, bytes: &[u8]) {
+        data[at..at + bytes.len()].copy_from_slice(bytes);
+    }
+
+    /// A PE32 image with a 0x1000-byte section mapped at `va`, the import table at
+    /// `import_rva`, and `SizeOfOptionalHeader` set to `optional_size`.
+    fn pe32(va: u32, import_rva: u32, optional_size: u16) -> Vec<u8> {
+        let mut data = vec![0; RAW + 0x1000];
+        put(&mut data, 0, b"MZ");
+        put(&mut data, 0x3c, &0x40u32.to_le_bytes());
+        put(&mut data, 0x40, b"PE\0\0");
+        let coff = 0x44;
+        put(&mut data, coff + 2, &1u16.to_le_bytes());
+        put(&mut data, coff + 16, &optional_size.to_le_bytes());
+        let optional = coff + 20;
+        put(&mut data, optional, &0x10bu16.to_le_bytes());
+        put(&mut data, optional + 104, &import_rva.to_le_bytes());
+        let section = optional + 224;
+        put(&mut data, section + 8, &0x1000u32.to_le_bytes());
+        put(&mut data, section + 12, &va.to_le_bytes());
+        put(&mut data, section + 16, &0x1000u32.to_le_bytes());
+        put(&mut data, section + 20, &(RAW as u32).to_le_bytes());
+        data
+    }
+
+    /// Write `bytes` at `rva` in the section mapped at `va`.
+    fn put_rva(data: &mut [u8], va: u32, rva: u32, bytes: &[u8]) {
+        put(data, RAW + (rva - va) as usize, bytes);
+    }
+
+    fn descriptor(lookup: u32, name: u32) -> Vec<u8> {
+        let mut entry = vec![0; 20];
+        put(&mut entry, 0, &lookup.to_le_bytes());
+        put(&mut entry, 12, &name.to_le_bytes());
+        put(&mut entry, 16, &lookup.to_le_bytes());
+        entry
+    }
+
+    /// Imports `KERNEL32.dll!CreateFileA` by name and `WS2_32.dll` ordinal 115.
+    fn sample() -> Vec<u8> {
+        let va = 0x1000;
+        let mut data = pe32(va, va, 224);
+        put_rva(&mut data, va, 0x1000, &descriptor(0x1040, 0x10a0));
+        put_rva(&mut data, va, 0x1014, &descriptor(0x1050, 0x10b0));
+        put_rva(&mut data, va, 0x1040, &0x1080u32.to_le_bytes());
+        put_rva(&mut data, va, 0x1050, &0x8000_0073u32.to_le_bytes());
+        put_rva(&mut data, va, 0x1082, b"CreateFileA\0");
+        put_rva(&mut data, va, 0x10a0, b"KERNEL32.dll\0");
+        put_rva(&mut data, va, 0x10b0, b"WS2_32.dll\0");
+        data
+    }
+
+    #[test]
+    fn hashes_named_and_ordinal_imports() {
+        let data = sample();
+        assert_eq!(
+            import_names(&data).unwrap(),
+            ["kernel32.createfilea", "ws2_32.wsastartup"]
+        );
+        assert_eq!(
+            imphash(&data).unwrap().as_deref(),
+            Some("838a0195b160f53f93097cc8c6b8c14c")
+        );
+    }
+
+    #[test]
+    fn rejects_files_that_are_not_pe() {
+        assert!(imphash(b"").is_err());
+        assert!(imphash(b"MZ").is_err());
+        assert!(imphash(&[0; 512]).is_err());
+    }
+
+    #[test]
+    fn rejects_optional_headers_too_short_for_the_magic() {
+        for size in [0, 1] {
+            assert!(imphash(&pe32(0x1000, 0x1000, size)).is_err());
+        }
+    }
+
+    #[test]
+    fn truncated_files_fail_without_panicking() {
+        let data = sample();
+        for len in 0..data.len() {
+            let _ = imphash(&data[..len]);
+        }
+    }
+
+    #[test]
+    fn import_table_at_the_end_of_the_address_space_is_rejected() {
+        let va = 0xffff_f000;
+        let mut data = pe32(va, 0xffff_ffec, 224);
+        put_rva(
+            &mut data,
+            va,
+            0xffff_ffec,
+            &descriptor(0xffff_f200, 0xffff_f100),
+        );
+        put_rva(&mut data, va, 0xffff_f100, b"a.dll\0");
+        assert!(imphash(&data).is_err());
+    }
+
+    #[test]
+    fn lookup_table_at_the_end_of_the_address_space_is_rejected() {
+        let va = 0xffff_f000;
+        let mut data = pe32(va, va, 224);
+        put_rva(&mut data, va, va, &descriptor(0xffff_fffc, 0xffff_f100));
+        put_rva(&mut data, va, 0xffff_f100, b"a.dll\0");
+        put_rva(&mut data, va, 0xffff_fffc, &0x8000_0001u32.to_le_bytes());
+        assert!(imphash(&data).is_err());
+    }
+}
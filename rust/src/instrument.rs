@@ -0,0 +1,159 @@
+// src/instrument.rs
+
+use crate::endpoint::Endpoint;
+use std::time::Duration;
+
+/// Record a finished HTTP request. `status` is `None` when no response was received.
+///
+/// Emits through the `metrics` facade when the `metrics` feature is enabled, so any
+/// installed recorder (statsd, Prometheus, ...) picks it up:
+/// - `traceix_requests_total` counter, labelled `endpoint`, `class`, `status`
+/// - `traceix_request_duration_seconds` histogram, labelled `endpoint`
+#[cfg(feature = "metrics")]
+pub(crate) fn record_request(endpoint: &Endpoint, status: Option<u16>, elapsed: Duration) {
+    let status = status.map_or_else(|| "error".to_string(), |s| s.to_string());
+    metrics::counter!(
+        "traceix_requests_total",
+        "endpoint" => endpoint.name,
+        "class" => endpoint.class.as_str(),
+        "status" => status,
+    )
+    .increment(1);
+    metrics::histogram!(
+        "traceix_request_duration_seconds",
+        "endpoint" => endpoint.name,
+    )
+    .record(elapsed.as_secs_f64());
+}
+
+#[cfg(not(feature = "metrics"))]
+pub(crate) fn record_request(_endpoint: &Endpoint, _status: Option<u16>, _elapsed: Duration) {}
+
+/// Record the outcome of a batch item (`ok`, `error`, `suppressed`, `dead_lettered`)
+/// as the `traceix_batch_items_total` counter.
+#[cfg(all(feature = "blocking", feature = "metrics"))]
+pub(crate) fn record_batch_item(outcome: &'static str) {
+    metrics::counter!("traceix_batch_items_total", "outcome" => outcome).increment(1);
+}
+
+#[cfg(all(feature = "blocking", not(feature = "metrics")))]
+pub(crate) fn record_batch_item(_outcome: &'static str) {}
+
+/// The `traceix.request` span for a call to `endpoint`. Only the endpoint and URL are
+/// recorded; headers, and with them the API key, never reach the span.
+///
+/// The span carries `endpoint`, `class`, `url`, and, once the call finishes, `status`
+/// and `attempts`.
+#[cfg(feature = "tracing")]
+fn call_span(endpoint: &Endpoint, url: &url::Url) -> tracing::Span {
+    tracing::info_span!(
+        "traceix.request",
+        endpoint = endpoint.name,
+        class = endpoint.class.as_str(),
+        url = %url,
+        status = tracing::field::Empty,
+        attempts = tracing::field::Empty,
+    )
+}
+
+/// The `traceix.request` span covering one blocking SDK call, entered for all of its
+/// attempts.
+#[cfg(all(feature = "blocking", feature = "tracing"))]
+pub(crate) type CallSpan = tracing::span::EnteredSpan;
+
+#[cfg(all(feature = "blocking", not(feature = "tracing")))]
+pub(crate) struct CallSpan;
+
+/// Open and enter the span for a blocking call to `endpoint`, named `traceix.request`
+/// with the `tracing` feature.
+#[cfg(all(feature = "blocking", feature = "tracing"))]
+pub(crate) fn enter_call(endpoint: &Endpoint, url: &url::Url) -> CallSpan {
+    call_span(endpoint, url).entered()
+}
+
+#[cfg(all(feature = "blocking", not(feature = "tracing")))]
+pub(crate) fn enter_call(_endpoint: &Endpoint, _url: &url::Url) -> CallSpan {
+    CallSpan
+}
+
+/// The `traceix.request` span covering one async SDK call. An entered span can't be
+/// held across `.await`, so [`in_call`] enters it each time the call is polled.
+#[cfg(all(feature = "async", feature = "tracing"))]
+pub(crate) type AsyncCallSpan = tracing::Span;
+
+#[cfg(all(feature = "async", not(feature = "tracing")))]
+pub(crate) struct AsyncCallSpan;
+
+/// Open the span for an async call to `endpoint`, as [`enter_call`] does for blocking
+/// ones.
+#[cfg(all(feature = "async", feature = "tracing"))]
+pub(crate) fn async_call(endpoint: &Endpoint, url: &url::Url) -> AsyncCallSpan {
+    call_span(endpoint, url)
+}
+
+#[cfg(all(feature = "async", not(feature = "tracing")))]
+pub(crate) fn async_call(_endpoint: &Endpoint, _url: &url::Url) -> AsyncCallSpan {
+    AsyncCallSpan
+}
+
+/// Run `call` inside `span`, so the events of every attempt are attributed to it.
+#[cfg(all(feature = "async", feature = "tracing"))]
+pub(crate) async fn in_call<F: std::future::Future>(span: &AsyncCallSpan, call: F) -> F::Output {
+    use tracing::Instrument;
+    call.instrument(span.clone()).await
+}
+
+#[cfg(all(feature = "async", not(feature = "tracing")))]
+pub(crate) async fn in_call<F: std::future::Future>(_span: &AsyncCallSpan, call: F) -> F::Output {
+    call.await
+}
+
+/// Emit a `debug` event for one attempt of the current call.
+#[cfg(feature = "tracing")]
+pub(crate) fn trace_attempt(
+    attempt: u32,
+    status: Option<u16>,
+    elapsed: Duration,
+    error: Option<&crate::TraceixError>,
+) {
+    let elapsed_ms = elapsed.as_secs_f64() * 1000.0;
+    match error {
+        None => tracing::debug!(attempt, status, elapsed_ms, "request succeeded"),
+        Some(error) => tracing::debug!(attempt, status, elapsed_ms, %error, "request failed"),
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn trace_attempt(
+    _attempt: u32,
+    _status: Option<u16>,
+    _elapsed: Duration,
+    _error: Option<&crate::TraceixError>,
+) {
+}
+
+/// Emit a `warn` event before sleeping for a retry.
+#[cfg(feature = "tracing")]
+pub(crate) fn trace_retry(attempt: u32, delay: Duration, error: &crate::TraceixError) {
+    tracing::warn!(
+        attempt,
+        delay_ms = delay.as_millis() as u64,
+        %error,
+        "retrying request"
+    );
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn trace_retry(_attempt: u32, _delay: Duration, _error: &crate::TraceixError) {}
+
+/// Record the final `status` and attempt count on the current call's span.
+#[cfg(feature = "tracing")]
+pub(crate) fn finish_call(span: &tracing::Span, attempts: u32, status: Option<u16>) {
+    span.record("attempts", attempts);
+    if let Some(status) = status {
+        span.record("status", status);
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn finish_call<S>(_span: &S, _attempts: u32, _status: Option<u16>) {}
@@ -0,0 +1,284 @@
+// src/ioc.rs
+
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::OnceLock;
+
+/// Kinds of indicator the extractor recognizes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum IocKind {
+    Ip,
+    Domain,
+    Url,
+    Mutex,
+    RegistryKey,
+}
+
+impl IocKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IocKind::Ip => "ip",
+            IocKind::Domain => "domain",
+            IocKind::Url => "url",
+            IocKind::Mutex => "mutex",
+            IocKind::RegistryKey => "registry_key",
+        }
+    }
+}
+
+/// A single indicator of compromise, normalized for deduplication.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Ioc {
+    Ip(IpAddr),
+    /// Lowercased host name.
+    Domain(String),
+    Url(String),
+    Mutex(String),
+    RegistryKey(String),
+}
+
+impl Ioc {
+    pub fn kind(&self) -> IocKind {
+        match self {
+            Ioc::Ip(_) => IocKind::Ip,
+            Ioc::Domain(_) => IocKind::Domain,
+            Ioc::Url(_) => IocKind::Url,
+            Ioc::Mutex(_) => IocKind::Mutex,
+            Ioc::RegistryKey(_) => IocKind::RegistryKey,
+        }
+    }
+
+    /// The indicator value as plain text.
+    pub fn value(&self) -> String {
+        match self {
+            Ioc::Ip(ip) => ip.to_string(),
+            Ioc::Domain(v) | Ioc::Url(v) | Ioc::Mutex(v) | Ioc::RegistryKey(v) => v.clone(),
+        }
+    }
+}
+
+/// A deduplicated, ordered set of indicators extracted from analysis results.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct IocSet {
+    iocs: BTreeSet<Ioc>,
+}
+
+impl IocSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Extract indicators from a prediction, CAPA, or EXIF result document.
+    pub fn from_value(value: &Value) -> Self {
+        let mut set = Self::new();
+        set.extend_from_value(value);
+        set
+    }
+
+    /// Walk `value` and add every indicator found in its strings.
+    ///
+    /// Strings under keys mentioning `mutex`/`mutant` are taken as mutex names;
+    /// everything else is scanned for URLs, IPs, and registry keys. Domains come from
+    /// URL hosts and from fields named for them (`domain`, `host`, `dns`, `fqdn`), never
+    /// from free text, where API and rule names such as `kernel32.VirtualAlloc` or
+    /// `System.Net.WebClient` would otherwise read as host names.
+    pub fn extend_from_value(&mut self, value: &Value) {
+        self.walk(None, value);
+    }
+
+    pub fn insert(&mut self, ioc: Ioc) -> bool {
+        self.iocs.insert(ioc)
+    }
+
+    /// Merge another set into this one.
+    pub fn merge(&mut self, other: IocSet) {
+        self.iocs.extend(other.iocs);
+    }
+
+    pub fn len(&self) -> usize {
+        self.iocs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.iocs.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Ioc> {
+        self.iocs.iter()
+    }
+
+    /// Indicators of a single kind, e.g. every domain for a DNS blocklist.
+    pub fn of_kind(&self, kind: IocKind) -> impl Iterator<Item = &Ioc> {
+        self.iocs.iter().filter(move |ioc| ioc.kind() == kind)
+    }
+
+    fn walk(&mut self, key: Option<&str>, value: &Value) {
+        match value {
+            Value::Object(map) => {
+                for (k, v) in map {
+                    self.walk(Some(k), v);
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    self.walk(key, item);
+                }
+            }
+            Value::String(s) => self.scan(key, s),
+            _ => {}
+        }
+    }
+
+    fn scan(&mut self, key: Option<&str>, text: &str) {
+        let key = key.map(str::to_ascii_lowercase).unwrap_or_default();
+        if key.contains("mutex") || key.contains("mutant") {
+            let name = text.trim();
+            if !name.is_empty() {
+                self.insert(Ioc::Mutex(name.to_string()));
+            }
+            return;
+        }
+
+        // Registry keys and URLs first, blanking them out so their components aren't
+        // rescanned as loose domains (a URL's host is still recorded explicitly).
+        let mut rest = text.to_string();
+        for m in registry_re().find_iter(text) {
+            self.insert(Ioc::RegistryKey(
+                m.as_str().trim_end_matches('\\').to_string(),
+            ));
+            rest = rest.replace(m.as_str(), " ");
+        }
+
+        for m in url_re().find_iter(text) {
+            let raw = m
+                .as_str()
+                .trim_end_matches(['.', ',', ';', ')', ']', '\'', '"']);
+            if let Ok(url) = url::Url::parse(raw) {
+                match url.host() {
+                    Some(url::Host::Domain(host)) => {
+                        self.insert(Ioc::Domain(host.to_ascii_lowercase()));
+                    }
+                    Some(url::Host::Ipv4(ip)) => {
+                        self.insert(Ioc::Ip(IpAddr::V4(ip)));
+                    }
+                    Some(url::Host::Ipv6(ip)) => {
+                        self.insert(Ioc::Ip(IpAddr::V6(ip)));
+                    }
+                    None => {}
+                }
+                self.insert(Ioc::Url(raw.to_string()));
+            }
+            rest = rest.replace(m.as_str(), " ");
+        }
+
+        for m in ipv4_re().find_iter(&rest) {
+            if let Ok(ip) = m.as_str().parse::<Ipv4Addr>() {
+                if is_routable_v4(&ip) {
+                    self.insert(Ioc::Ip(IpAddr::V4(ip)));
+                }
+            }
+        }
+
+        for token in rest.split(|c: char| c.is_whitespace() || matches!(c, ',' | ';' | '"')) {
+            if token.matches(':').count() >= 2 {
+                if let Ok(ip) = token.trim_matches(['[', ']']).parse::<Ipv6Addr>() {
+                    if !ip.is_loopback() && !ip.is_unspecified() {
+                        self.insert(Ioc::Ip(IpAddr::V6(ip)));
+                    }
+                }
+            }
+        }
+
+        if !is_host_key(&key) {
+            return;
+        }
+        for m in domain_re().find_iter(&rest) {
+            let domain = m.as_str().to_ascii_lowercase();
+            let tld = domain.rsplit('.').next().unwrap_or_default();
+            if !NON_TLD_EXTENSIONS.contains(&tld) {
+                self.insert(Ioc::Domain(domain));
+            }
+        }
+    }
+}
+
+impl IntoIterator for IocSet {
+    type Item = Ioc;
+    type IntoIter = std::collections::btree_set::IntoIter<Ioc>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iocs.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a IocSet {
+    type Item = &'a Ioc;
+    type IntoIter = std::collections::btree_set::Iter<'a, Ioc>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iocs.iter()
+    }
+}
+
+impl FromIterator<Ioc> for IocSet {
+    fn from_iter<I: IntoIterator<Item = Ioc>>(iter: I) -> Self {
+        Self {
+            iocs: iter.into_iter().collect(),
+        }
+    }
+}
+
+/// File extensions that look like TLDs in `name.ext` strings but are almost always file names.
+const NON_TLD_EXTENSIONS: &[&str] = &[
+    "exe", "dll", "sys", "drv", "ocx", "cpl", "scr", "bat", "cmd", "ps1", "vbs", "txt", "log",
+    "ini", "cfg", "dat", "bin", "tmp", "bak", "jpg", "jpeg", "png", "gif", "bmp", "pdf", "doc",
+    "docx", "xls", "xlsx", "ppt", "pptx", "rtf", "json", "xml", "html", "htm", "js", "py", "so",
+    "dylib", "lnk", "pdb", "manifest", "mui",
+];
+
+/// Whether a field named `key` (lowercased) holds host names rather than free text.
+fn is_host_key(key: &str) -> bool {
+    ["domain", "host", "dns", "fqdn"]
+        .iter()
+        .any(|name| key.contains(name))
+}
+
+fn is_routable_v4(ip: &Ipv4Addr) -> bool {
+    !(ip.is_loopback() || ip.is_unspecified() || ip.is_broadcast() || ip.is_link_local())
+}
+
+fn url_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r#"(?i)\b(?:https?|ftp)://[^\s"'<>]+"#).unwrap())
+}
+
+fn ipv4_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\b(?:\d{1,3}\.){3}\d{1,3}\b").unwrap())
+}
+
+fn domain_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?i)\b(?:[a-z0-9](?:[a-z0-9-]{0,61}[a-z0-9])?\.)+[a-z][a-z0-9-]{1,23}\b")
+            .unwrap()
+    })
+}
+
+fn registry_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r"(?i)\b(?:HKEY_LOCAL_MACHINE|HKEY_CURRENT_USER|HKEY_CLASSES_ROOT|HKEY_USERS|HKEY_CURRENT_CONFIG|HKLM|HKCU|HKCR|HKU|HKCC)\\[^\s,;]+",
+        )
+        .unwrap()
+    })
+}
@@ -0,0 +1,317 @@
+// src/ipfs/cache.rs
+
+use crate::cid::Cid;
+use crate::datasets::DatasetListOptions;
+use crate::hashing::sha256_file;
+use crate::{TraceixError, TraceixSdk};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+const INDEX_FILE: &str = "index.json";
+
+/// A dataset held in a [`DatasetCache`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct CachedDataset {
+    /// The dataset's CID as a CIDv1, whichever form it was listed or added under; also
+    /// the name of its entry in the cache directory.
+    pub cid: String,
+    pub name: Option<String>,
+    pub bytes: u64,
+    pub cached_at: DateTime<Utc>,
+    /// When the files were last checked against the hashes recorded at download.
+    pub verified_at: DateTime<Utc>,
+    /// SHA-256 of each file, keyed by its `/`-separated path inside the dataset. A
+    /// dataset that is a single file has one entry with an empty path.
+    pub files: BTreeMap<String, String>,
+}
+
+/// A dataset that [`DatasetCache::sync`] or [`DatasetCache::revalidate`] could not
+/// download.
+#[derive(Debug)]
+pub struct FailedDataset {
+    pub cid: String,
+    pub error: TraceixError,
+}
+
+/// The outcome of [`DatasetCache::sync`] or [`DatasetCache::revalidate`].
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct CacheSyncReport {
+    /// Datasets downloaded, or repaired by `revalidate`.
+    pub downloaded: Vec<CachedDataset>,
+    /// Datasets already present and intact.
+    pub unchanged: usize,
+    pub failed: Vec<FailedDataset>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct CacheIndex {
+    datasets: BTreeMap<String, CachedDataset>,
+    /// Newest creation time seen by a complete sync, per selection.
+    synced_until: BTreeMap<String, DateTime<Utc>>,
+}
+
+/// A local mirror of public IPFS datasets, one entry per CID under a directory.
+///
+/// The cache keeps an index of what it holds, so [`DatasetCache::sync`] only downloads
+/// datasets published since the last sync and [`DatasetCache::add`] skips datasets
+/// already present. [`DatasetCache::revalidate`] checks every file against the SHA-256
+/// recorded when it was downloaded and repairs whatever has changed. A cache should be
+/// used by one process at a time.
+///
+/// ```no_run
+/// # use traceix_sdk::{DatasetCache, DatasetListOptions, TraceixSdk};
+/// # fn main() -> Result<(), traceix_sdk::TraceixError> {
+/// let sdk = TraceixSdk::new(None)?;
+/// let mut cache = DatasetCache::open("/var/cache/traceix/datasets")?;
+/// let selection = DatasetListOptions {
+///     tag: Some("ransomware".into()),
+///     ..DatasetListOptions::default()
+/// };
+/// let report = cache.sync(&sdk, &selection)?;
+/// for dataset in &report.downloaded {
+///     println!("new: {} in {}", dataset.cid, cache.path(&dataset.cid)?.display());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct DatasetCache {
+    dir: PathBuf,
+    index: CacheIndex,
+}
+
+impl DatasetCache {
+    /// Open the cache in `dir`, creating it if needed.
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, TraceixError> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        let index = match File::open(dir.join(INDEX_FILE)) {
+            Ok(file) => serde_json::from_reader(BufReader::new(file)).map_err(|e| {
+                TraceixError::InvalidArgument(format!("invalid dataset cache index: {e}"))
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => CacheIndex::default(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self { dir, index })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Where the dataset `cid` is, or would be, stored: a file or a directory.
+    pub fn path(&self, cid: &str) -> Result<PathBuf, TraceixError> {
+        Ok(self.dir.join(key(cid)?))
+    }
+
+    /// The dataset `cid`, if the cache holds it. Any form of the CID may be given.
+    pub fn get(&self, cid: &str) -> Option<&CachedDataset> {
+        self.index.datasets.get(&key(cid).ok()?)
+    }
+
+    pub fn contains(&self, cid: &str) -> bool {
+        self.get(cid).is_some()
+    }
+
+    /// Every dataset in the cache, ordered by CID.
+    pub fn datasets(&self) -> impl Iterator<Item = &CachedDataset> {
+        self.index.datasets.values()
+    }
+
+    /// Download the dataset `cid` into the cache unless it is already there.
+    pub fn add(&mut self, sdk: &TraceixSdk, cid: &str) -> Result<&CachedDataset, TraceixError> {
+        let cid = key(cid)?;
+        if !self.index.datasets.contains_key(&cid) {
+            self.mirror(sdk, &cid, None)?;
+        }
+        Ok(&self.index.datasets[&cid])
+    }
+
+    /// Delete the dataset `cid` from the cache. Returns whether it was there.
+    pub fn remove(&mut self, cid: &str) -> Result<bool, TraceixError> {
+        let cid = key(cid)?;
+        if self.index.datasets.remove(&cid).is_none() {
+            return Ok(false);
+        }
+        let path = self.dir.join(&cid);
+        if path.is_dir() {
+            fs::remove_dir_all(&path)?;
+        } else if path.exists() {
+            fs::remove_file(&path)?;
+        }
+        self.save()?;
+        Ok(true)
+    }
+
+    /// Download every dataset matching `selection` that the cache doesn't hold yet.
+    ///
+    /// Only datasets published after the newest one seen by the last complete sync of
+    /// the same selection (its `name` and `tag`) are listed, so a regular sync doesn't
+    /// walk the whole catalog. A dataset that fails to download is reported in
+    /// [`CacheSyncReport::failed`] and retried by the next sync; only a failure to list
+    /// the catalog fails the call.
+    pub fn sync(
+        &mut self,
+        sdk: &TraceixSdk,
+        selection: &DatasetListOptions,
+    ) -> Result<CacheSyncReport, TraceixError> {
+        let selection_key = format!("name={:?} tag={:?}", selection.name, selection.tag);
+        let mut options = selection.clone();
+        options.page = 0;
+        options.created_after = options
+            .created_after
+            .max(self.index.synced_until.get(&selection_key).copied());
+
+        let mut report = CacheSyncReport::default();
+        let mut newest = None;
+        for dataset in sdk.iter_datasets(options) {
+            let dataset = dataset?;
+            newest = newest.max(dataset.created_at);
+            let cid = match key(&dataset.cid) {
+                Ok(cid) => cid,
+                Err(error) => {
+                    report.failed.push(FailedDataset {
+                        cid: dataset.cid,
+                        error,
+                    });
+                    continue;
+                }
+            };
+            if self.index.datasets.contains_key(&cid) {
+                report.unchanged += 1;
+                continue;
+            }
+            match self.mirror(sdk, &cid, dataset.name) {
+                Ok(()) => report.downloaded.push(self.index.datasets[&cid].clone()),
+                Err(error) => report.failed.push(FailedDataset { cid, error }),
+            }
+        }
+
+        if let Some(newest) = newest.filter(|_| report.failed.is_empty()) {
+            self.index.synced_until.insert(selection_key, newest);
+            self.save()?;
+        }
+        Ok(report)
+    }
+
+    /// Check every cached file against the SHA-256 recorded when it was downloaded, and
+    /// download again any dataset with files that are missing or changed. Intact files
+    /// of a damaged dataset are kept.
+    pub fn revalidate(&mut self, sdk: &TraceixSdk) -> Result<CacheSyncReport, TraceixError> {
+        let mut report = CacheSyncReport::default();
+        let cids: Vec<String> = self.index.datasets.keys().cloned().collect();
+        for cid in cids {
+            let path = self.dir.join(&cid);
+            let entry = &self.index.datasets[&cid];
+            let mut damaged = false;
+            for (name, sha256) in &entry.files {
+                let file = if name.is_empty() {
+                    path.clone()
+                } else {
+                    path.join(name)
+                };
+                if sha256_file(&file).ok().as_ref() != Some(sha256) {
+                    let _ = fs::remove_file(&file);
+                    damaged = true;
+                }
+            }
+            if !damaged {
+                self.index
+                    .datasets
+                    .get_mut(&cid)
+                    .expect("listed")
+                    .verified_at = Utc::now();
+                report.unchanged += 1;
+                continue;
+            }
+            let name = entry.name.clone();
+            match self.mirror(sdk, &cid, name) {
+                Ok(()) => report.downloaded.push(self.index.datasets[&cid].clone()),
+                Err(error) => report.failed.push(FailedDataset { cid, error }),
+            }
+        }
+        self.save()?;
+        Ok(report)
+    }
+
+    /// Download `cid` (a cache key) into the cache, hash what was written, and
+    /// record it in the index.
+    fn mirror(
+        &mut self,
+        sdk: &TraceixSdk,
+        cid: &str,
+        name: Option<String>,
+    ) -> Result<(), TraceixError> {
+        let path = self.dir.join(cid);
+        let download = sdk.download_dataset(cid, &path)?;
+        let mut files = BTreeMap::new();
+        hash_tree(&path, "", &mut files)?;
+        let now = Utc::now();
+        let cached_at = self
+            .index
+            .datasets
+            .get(cid)
+            .map_or(now, |previous| previous.cached_at);
+        self.index.datasets.insert(
+            cid.to_string(),
+            CachedDataset {
+                cid: cid.to_string(),
+                name,
+                bytes: download.bytes,
+                cached_at,
+                verified_at: now,
+                files,
+            },
+        );
+        self.save()
+    }
+
+    /// Write the index next to its final location, then move it into place so a crash
+    /// never leaves a truncated file behind.
+    fn save(&self) -> Result<(), TraceixError> {
+        let path = self.dir.join(INDEX_FILE);
+        let tmp = path.with_extension("part");
+        {
+            let mut writer = BufWriter::new(File::create(&tmp)?);
+            serde_json::to_writer_pretty(&mut writer, &self.index)
+                .map_err(|e| TraceixError::Io(e.into()))?;
+            writer.flush()?;
+        }
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+}
+
+/// The cache key of `cid`: the CIDv1 in canonical form.
+fn key(cid: &str) -> Result<String, TraceixError> {
+    Ok(Cid::parse(cid)?.to_v1().to_string())
+}
+
+/// Record the SHA-256 of every file under `path`, keyed by `/`-separated paths
+/// starting with `prefix`.
+fn hash_tree(
+    path: &Path,
+    prefix: &str,
+    files: &mut BTreeMap<String, String>,
+) -> Result<(), TraceixError> {
+    if !path.is_dir() {
+        files.insert(prefix.to_string(), sha256_file(path)?);
+        return Ok(());
+    }
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let child = if prefix.is_empty() {
+            name
+        } else {
+            format!("{prefix}/{name}")
+        };
+        hash_tree(&entry.path(), &child, files)?;
+    }
+    Ok(())
+}
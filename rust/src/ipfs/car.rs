@@ -0,0 +1,617 @@
+// src/ipfs/car.rs
+
+//! Content Addressed aRchives: a header naming the root CIDs, then each block prefixed
+//! by its CID. See <https://ipld.io/specs/transport/car/>.
+
+use super::dag;
+use super::download::{DatasetDownloadOptions, Fetcher, Source, MAX_BLOCK_SIZE};
+use super::DatasetDownload;
+use crate::cid::{Cid, DAG_PB, RAW};
+use crate::{TraceixError, TraceixSdk};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// CBOR tag marking a CID in DAG-CBOR.
+const CID_TAG: u64 = 42;
+/// Size of the CARv2 header that follows its pragma.
+const CAR_V2_HEADER_LEN: usize = 40;
+
+/// A CAR file written by [`TraceixSdk::export_car`].
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct CarExport {
+    pub path: PathBuf,
+    pub root: String,
+    pub blocks: usize,
+    /// Size of the CAR file.
+    pub bytes: u64,
+}
+
+/// A CAR file opened with [`TraceixSdk::import_car`], every block of which has been
+/// checked against its CID.
+///
+/// Blocks are read from the file when needed rather than held in memory.
+pub struct CarArchive {
+    path: PathBuf,
+    roots: Vec<Cid>,
+    /// Offset and length of each block's data, keyed by its CID as a CIDv1.
+    blocks: HashMap<Cid, (u64, usize)>,
+    file: Mutex<File>,
+}
+
+impl CarArchive {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The CIDs the archive was made for.
+    pub fn roots(&self) -> &[Cid] {
+        &self.roots
+    }
+
+    pub fn block_count(&self) -> usize {
+        self.blocks.len()
+    }
+
+    pub fn contains(&self, cid: &Cid) -> bool {
+        self.blocks.contains_key(&cid.to_v1())
+    }
+
+    /// Write the dataset of an archive with a single root to `dest`, as
+    /// [`TraceixSdk::download_dataset`] would.
+    pub fn extract(&self, dest: impl AsRef<Path>) -> Result<DatasetDownload, TraceixError> {
+        match self.roots.as_slice() {
+            [root] => self.extract_root(root, dest),
+            roots => Err(TraceixError::InvalidArgument(format!(
+                "{} has {} roots; pick one with extract_root",
+                self.path.display(),
+                roots.len()
+            ))),
+        }
+    }
+
+    /// Write the dataset `root` from the archive to `dest`. Fails if the archive lacks
+    /// any of its blocks.
+    pub fn extract_root(
+        &self,
+        root: &Cid,
+        dest: impl AsRef<Path>,
+    ) -> Result<DatasetDownload, TraceixError> {
+        let options = DatasetDownloadOptions::default();
+        Fetcher::new(Source::Car(self), &options).save(root, dest.as_ref())
+    }
+
+    pub(super) fn block(&self, cid: &Cid) -> Result<Vec<u8>, TraceixError> {
+        let &(offset, len) = self.blocks.get(&cid.to_v1()).ok_or_else(|| {
+            TraceixError::InvalidArgument(format!(
+                "{} doesn't contain block {cid}",
+                self.path.display()
+            ))
+        })?;
+        let mut data = vec![0; len];
+        {
+            let mut file = self.file.lock().unwrap();
+            file.seek(SeekFrom::Start(offset))?;
+            file.read_exact(&mut data)?;
+        }
+        // The file may have changed since it was opened.
+        cid.verify(&data)?;
+        Ok(data)
+    }
+
+    fn open(path: &Path) -> Result<Self, TraceixError> {
+        let invalid = |reason: String| {
+            TraceixError::InvalidArgument(format!(
+                "{} is not a valid CAR file: {reason}",
+                path.display()
+            ))
+        };
+        let mut reader = CountingReader {
+            inner: BufReader::new(File::open(path)?),
+            position: 0,
+        };
+
+        let mut header = read_header(&mut reader).map_err(invalid)?;
+        let mut end = None;
+        if header.version == 2 {
+            // A CARv2 wraps a CARv1 payload, located by the header after the pragma.
+            let mut fixed = [0u8; CAR_V2_HEADER_LEN];
+            reader.read_exact(&mut fixed)?;
+            let field =
+                |i: usize| u64::from_le_bytes(fixed[16 + i * 8..24 + i * 8].try_into().unwrap());
+            let (data_offset, data_size) = (field(0), field(1));
+            let skip = data_offset
+                .checked_sub(reader.position)
+                .ok_or_else(|| invalid("CARv2 payload overlaps its header".into()))?;
+            io::copy(&mut (&mut reader).take(skip), &mut io::sink())?;
+            let payload_end = data_offset
+                .checked_add(data_size)
+                .ok_or_else(|| invalid("CARv2 payload size is out of range".into()))?;
+            end = Some(payload_end);
+            header = read_header(&mut reader).map_err(invalid)?;
+        }
+        if header.version != 1 {
+            return Err(invalid(format!("unsupported version {}", header.version)));
+        }
+
+        let mut blocks = HashMap::new();
+        while end.is_none_or(|end| reader.position < end) {
+            let Some(len) = read_varint_from(&mut reader)? else {
+                break;
+            };
+            if len > MAX_BLOCK_SIZE + 1024 {
+                return Err(invalid(format!("section of {len} bytes is too large")));
+            }
+            let start = reader.position;
+            let mut section = vec![0; len as usize];
+            reader.read_exact(&mut section)?;
+            let (cid, cid_len) = Cid::read_prefix(&section)
+                .map_err(|e| invalid(format!("bad CID at byte {start}: {e}")))?;
+            cid.verify(&section[cid_len..])?;
+            blocks.insert(
+                cid.to_v1(),
+                (start + cid_len as u64, section.len() - cid_len),
+            );
+        }
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            roots: header.roots,
+            blocks,
+            file: Mutex::new(File::open(path)?),
+        })
+    }
+}
+
+impl TraceixSdk {
+    /// Save the dataset `cid` as a CARv1 file at `path`, for archival or to carry it to
+    /// an air-gapped network.
+    ///
+    /// Every block of the dataset is fetched and verified as by
+    /// [`TraceixSdk::download_dataset`], then written once in depth-first order. The
+    /// file is written to `path.part` and renamed once complete. CAR files can be read
+    /// back with [`TraceixSdk::import_car`] or by any IPFS implementation, e.g.
+    /// `ipfs dag import`.
+    pub fn export_car(&self, cid: &str, path: impl AsRef<Path>) -> Result<CarExport, TraceixError> {
+        let root = Cid::parse(cid)?;
+        let path = path.as_ref();
+        let partial = crate::download::partial_path(path);
+        let written = self.write_car(&root, &partial);
+        let blocks = match written {
+            Ok(blocks) => blocks,
+            Err(e) => {
+                let _ = fs::remove_file(&partial);
+                return Err(e);
+            }
+        };
+        fs::rename(&partial, path)?;
+        Ok(CarExport {
+            path: path.to_path_buf(),
+            root: root.to_string(),
+            blocks,
+            bytes: fs::metadata(path)?.len(),
+        })
+    }
+
+    fn write_car(&self, root: &Cid, path: &Path) -> Result<usize, TraceixError> {
+        let mut out = BufWriter::new(File::create(path)?);
+        let header = car_header(root);
+        write_varint_to(&mut out, header.len() as u64)?;
+        out.write_all(&header)?;
+
+        let mut seen = HashSet::new();
+        let mut stack = vec![root.clone()];
+        while let Some(cid) = stack.pop() {
+            // Identity CIDs carry their data inline and are never stored as blocks.
+            if cid.inline_data().is_some() || !seen.insert(cid.clone()) {
+                continue;
+            }
+            let block = self.ipfs_block(&cid)?;
+            let cid_bytes = cid.to_bytes();
+            write_varint_to(&mut out, (cid_bytes.len() + block.len()) as u64)?;
+            out.write_all(&cid_bytes)?;
+            out.write_all(&block)?;
+            match cid.codec() {
+                DAG_PB => {
+                    let node = dag::decode_node(&block)?;
+                    stack.extend(node.links.into_iter().rev().map(|link| link.cid));
+                }
+                RAW => {}
+                codec => {
+                    return Err(TraceixError::UnexpectedResponse(format!(
+                        "{cid} uses codec 0x{codec:x}, whose links can't be followed"
+                    )))
+                }
+            }
+        }
+        out.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+        Ok(seen.len())
+    }
+
+    /// Open a CAR file, e.g. one written by [`TraceixSdk::export_car`] on another
+    /// machine, checking every block in it against its CID. CARv1 and CARv2 files are
+    /// read. No request is made; use [`CarArchive::extract`] to write the dataset out.
+    ///
+    /// ```no_run
+    /// # use traceix_sdk::TraceixSdk;
+    /// # fn main() -> Result<(), traceix_sdk::TraceixError> {
+    /// let sdk = TraceixSdk::new(None)?;
+    /// // On the connected side:
+    /// let cid = "bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi";
+    /// sdk.export_car(cid, "/media/transfer/dataset.car")?;
+    /// // On the air-gapped side:
+    /// let archive = sdk.import_car("/media/transfer/dataset.car")?;
+    /// let dataset = archive.extract("datasets/latest")?;
+    /// println!("{} files", dataset.files);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn import_car(&self, path: impl AsRef<Path>) -> Result<CarArchive, TraceixError> {
+        CarArchive::open(path.as_ref())
+    }
+}
+
+/// Tracks how far into the file reading has got.
+struct CountingReader<R> {
+    inner: R,
+    position: u64,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+struct CarHeader {
+    version: u64,
+    roots: Vec<Cid>,
+}
+
+fn read_header(reader: &mut impl Read) -> Result<CarHeader, String> {
+    let len = read_varint_from(reader)
+        .map_err(|e| e.to_string())?
+        .ok_or("empty file")?;
+    if len > 1024 * 1024 {
+        return Err(format!("header of {len} bytes is too large"));
+    }
+    let mut bytes = vec![0; len as usize];
+    reader.read_exact(&mut bytes).map_err(|e| e.to_string())?;
+    let Cbor::Map(entries) = read_cbor(&bytes, &mut 0, 0)? else {
+        return Err("header is not a map".into());
+    };
+    let mut header = CarHeader {
+        version: 0,
+        roots: Vec::new(),
+    };
+    for (key, value) in entries {
+        match (key, value) {
+            (Cbor::Text(key), Cbor::Uint(version)) if key == "version" => header.version = version,
+            (Cbor::Text(key), Cbor::Array(roots)) if key == "roots" => {
+                for root in roots {
+                    match root {
+                        // Binary CIDs in DAG-CBOR start with the identity multibase prefix.
+                        Cbor::Tag(CID_TAG, inner) => match *inner {
+                            Cbor::Bytes(bytes) if bytes.first() == Some(&0) => header
+                                .roots
+                                .push(Cid::from_bytes(&bytes[1..]).map_err(|e| e.to_string())?),
+                            _ => return Err("malformed root CID".into()),
+                        },
+                        _ => return Err("root is not a CID".into()),
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(header)
+}
+
+/// The DAG-CBOR header `{"roots": [root], "version": 1}`.
+fn car_header(root: &Cid) -> Vec<u8> {
+    let mut cid = vec![0];
+    cid.extend(root.to_bytes());
+    let mut out = vec![0xa2];
+    cbor_head(&mut out, 3, 5);
+    out.extend(b"roots");
+    cbor_head(&mut out, 4, 1);
+    cbor_head(&mut out, 6, CID_TAG);
+    cbor_head(&mut out, 2, cid.len() as u64);
+    out.extend(cid);
+    cbor_head(&mut out, 3, 7);
+    out.extend(b"version");
+    cbor_head(&mut out, 0, 1);
+    out
+}
+
+/// The subset of CBOR found in CAR headers.
+enum Cbor {
+    Uint(u64),
+    Bytes(Vec<u8>),
+    Text(String),
+    Array(Vec<Cbor>),
+    Map(Vec<(Cbor, Cbor)>),
+    Tag(u64, Box<Cbor>),
+    Simple,
+}
+
+fn cbor_head(out: &mut Vec<u8>, major: u8, value: u64) {
+    let major = major << 5;
+    match value {
+        0..=23 => out.push(major | value as u8),
+        24..=0xff => out.extend([major | 24, value as u8]),
+        0x100..=0xffff => {
+            out.push(major | 25);
+            out.extend((value as u16).to_be_bytes());
+        }
+        0x10000..=0xffff_ffff => {
+            out.push(major | 26);
+            out.extend((value as u32).to_be_bytes());
+        }
+        _ => {
+            out.push(major | 27);
+            out.extend(value.to_be_bytes());
+        }
+    }
+}
+
+fn read_cbor(bytes: &[u8], at: &mut usize, depth: usize) -> Result<Cbor, String> {
+    if depth > 16 {
+        return Err("header is nested too deeply".into());
+    }
+    let truncated = || "truncated header".to_string();
+    let initial = *bytes.get(*at).ok_or_else(truncated)?;
+    *at += 1;
+    let (major, info) = (initial >> 5, initial & 0x1f);
+    let width = match info {
+        0..=23 => 0,
+        24 => 1,
+        25 => 2,
+        26 => 4,
+        27 => 8,
+        _ => return Err("indefinite-length CBOR isn't allowed in DAG-CBOR".into()),
+    };
+    let value = if width == 0 {
+        u64::from(info)
+    } else {
+        let raw = bytes.get(*at..*at + width).ok_or_else(truncated)?;
+        *at += width;
+        raw.iter().fold(0, |n, &b| (n << 8) | u64::from(b))
+    };
+    let mut take = |len: u64| -> Result<&[u8], String> {
+        let end = at.checked_add(len as usize).ok_or_else(truncated)?;
+        let slice = bytes.get(*at..end).ok_or_else(truncated)?;
+        *at = end;
+        Ok(slice)
+    };
+    Ok(match major {
+        0 => Cbor::Uint(value),
+        1 => Cbor::Simple,
+        2 => Cbor::Bytes(take(value)?.to_vec()),
+        3 => Cbor::Text(String::from_utf8_lossy(take(value)?).into_owned()),
+        4 => Cbor::Array(
+            (0..value)
+                .map(|_| read_cbor(bytes, at, depth + 1))
+                .collect::<Result<_, _>>()?,
+        ),
+        5 => Cbor::Map(
+            (0..value)
+                .map(|_| {
+                    Ok((
+                        read_cbor(bytes, at, depth + 1)?,
+                        read_cbor(bytes, at, depth + 1)?,
+                    ))
+                })
+                .collect::<Result<_, String>>()?,
+        ),
+        6 => Cbor::Tag(value, Box::new(read_cbor(bytes, at, depth + 1)?)),
+        _ => Cbor::Simple,
+    })
+}
+
+/// Read a varint from `reader`; `None` at a clean end of file.
+fn read_varint_from(reader: &mut impl Read) -> io::Result<Option<u64>> {
+    let mut value = 0u64;
+    for (i, shift) in (0..64).step_by(7).enumerate() {
+        let mut byte = [0u8];
+        if reader.read(&mut byte)? == 0 {
+            return match i {
+                0 => Ok(None),
+                _ => Err(io::ErrorKind::UnexpectedEof.into()),
+            };
+        }
+        value |= u64::from(byte[0] & 0x7f) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(Some(value));
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        "varint is too long",
+    ))
+}
+
+fn write_varint_to(out: &mut impl Write, mut value: u64) -> io::Result<()> {
+    while value >= 0x80 {
+        out.write_all(&[value as u8 | 0x80])?;
+        value >>= 7;
+    }
+    out.write_all(&[value as u8])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hashing::to_hex;
+    use sha2::{Digest, Sha256};
+
+    fn raw_cid(data: &[u8]) -> Cid {
+        Cid::parse(&format!("f01551220{}", to_hex(&Sha256::digest(data)))).unwrap()
+    }
+
+    /// A CARv1 file rooted at `root` holding `blocks`.
+    fn car_v1(root: &Cid, blocks: &[(&Cid, &[u8])]) -> Vec<u8> {
+        let mut out = Vec::new();
+        let header = car_header(root);
+        write_varint_to(&mut out, header.len() as u64).unwrap();
+        out.extend(header);
+        for (cid, data) in blocks {
+            let cid = cid.to_bytes();
+            write_varint_to(&mut out, (cid.len() + data.len()) as u64).unwrap();
+            out.extend(cid);
+            out.extend(*data);
+        }
+        out
+    }
+
+    /// `payload` wrapped in a CARv2 whose header gives `data_size` as its length.
+    fn car_v2(payload: &[u8], data_size: u64) -> Vec<u8> {
+        let mut out = vec![0x0a, 0xa1, 0x67];
+        out.extend(b"version");
+        out.push(0x02);
+        let data_offset = (out.len() + CAR_V2_HEADER_LEN) as u64;
+        out.extend([0; 16]);
+        out.extend(data_offset.to_le_bytes());
+        out.extend(data_size.to_le_bytes());
+        out.extend(0u64.to_le_bytes());
+        out.extend(payload);
+        out
+    }
+
+    fn open(name: &str, bytes: &[u8]) -> Result<CarArchive, TraceixError> {
+        let path = std::env::temp_dir().join(format!(
+            "traceix-car-test-{}-{name}.car",
+            std::process::id()
+        ));
+        fs::write(&path, bytes).unwrap();
+        let archive = CarArchive::open(&path);
+        fs::remove_file(&path).unwrap();
+        archive
+    }
+
+    #[test]
+    fn reads_back_a_written_car() {
+        let (hello, world) = (b"hello".as_slice(), b"world".as_slice());
+        let (hello_cid, world_cid) = (raw_cid(hello), raw_cid(world));
+        let bytes = car_v1(&hello_cid, &[(&hello_cid, hello), (&world_cid, world)]);
+
+        let archive = open("v1", &bytes).unwrap();
+        assert_eq!(archive.roots(), std::slice::from_ref(&hello_cid));
+        assert_eq!(archive.block_count(), 2);
+        assert_eq!(archive.block(&hello_cid).unwrap(), hello);
+        assert_eq!(archive.block(&world_cid).unwrap(), world);
+        assert!(!archive.contains(&raw_cid(b"other")));
+    }
+
+    #[test]
+    fn reads_the_payload_of_a_car_v2() {
+        let data = b"hello".as_slice();
+        let cid = raw_cid(data);
+        let payload = car_v1(&cid, &[(&cid, data)]);
+        let bytes = car_v2(&payload, payload.len() as u64);
+
+        let archive = open("v2", &bytes).unwrap();
+        assert_eq!(archive.roots(), std::slice::from_ref(&cid));
+        assert_eq!(archive.block(&cid).unwrap(), data);
+    }
+
+    #[test]
+    fn rejects_a_car_v2_payload_size_that_overflows() {
+        let cid = raw_cid(b"hello");
+        let payload = car_v1(&cid, &[(&cid, b"hello")]);
+        let bytes = car_v2(&payload, u64::MAX);
+
+        assert!(matches!(
+            open("overflow", &bytes),
+            Err(TraceixError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_blocks_that_dont_match_their_cid() {
+        let cid = raw_cid(b"hello");
+        let bytes = car_v1(&cid, &[(&cid, b"jello")]);
+
+        assert!(matches!(
+            open("mismatch", &bytes),
+            Err(TraceixError::IntegrityMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_malformed_headers() {
+        for (name, header) in [
+            ("empty", Vec::new()),
+            ("not-a-map", vec![0x01, 0x01]),
+            ("truncated", vec![0x05, 0xa2, 0x65]),
+            ("version", {
+                let mut out = vec![0x0a, 0xa1, 0x67];
+                out.extend(b"version");
+                out.push(0x03);
+                out
+            }),
+        ] {
+            assert!(
+                matches!(open(name, &header), Err(TraceixError::InvalidArgument(_))),
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn truncated_cars_fail_cleanly() {
+        let data = b"hello".as_slice();
+        let cid = raw_cid(data);
+        let bytes = car_v1(&cid, &[(&cid, data)]);
+        // Ending right after the header is a valid archive with no blocks.
+        let header_end = car_v1(&cid, &[]).len();
+        for len in 0..bytes.len() {
+            match open("truncated", &bytes[..len]) {
+                Ok(archive) if len == header_end => assert_eq!(archive.block_count(), 0),
+                result => assert!(result.is_err(), "{len} bytes"),
+            }
+        }
+    }
+
+    #[test]
+    fn car_header_round_trips() {
+        let cid = raw_cid(b"hello");
+        let mut bytes = Vec::new();
+        let header = car_header(&cid);
+        write_varint_to(&mut bytes, header.len() as u64).unwrap();
+        bytes.extend(header);
+
+        let header = read_header(&mut bytes.as_slice()).unwrap();
+        assert_eq!(header.version, 1);
+        assert_eq!(header.roots, [cid]);
+    }
+
+    #[test]
+    fn varints_round_trip() {
+        for value in [
+            0,
+            1,
+            0x7f,
+            0x80,
+            0x3fff,
+            0x4000,
+            u64::from(u32::MAX),
+            u64::MAX,
+        ] {
+            let mut bytes = Vec::new();
+            write_varint_to(&mut bytes, value).unwrap();
+            assert_eq!(
+                read_varint_from(&mut bytes.as_slice()).unwrap(),
+                Some(value)
+            );
+        }
+        assert_eq!(read_varint_from(&mut [].as_slice()).unwrap(), None);
+        assert!(read_varint_from(&mut [0x80].as_slice()).is_err());
+        assert!(read_varint_from(&mut [0xff; 10].as_slice()).is_err());
+    }
+}
@@ -0,0 +1,286 @@
+// src/ipfs/dag.rs
+
+//! Just enough protobuf to read dag-pb nodes and the UnixFS data inside them.
+
+use crate::cid::{read_varint, Cid};
+use crate::TraceixError;
+
+/// A dag-pb node: its links, then its data.
+pub(crate) struct PbNode {
+    pub(crate) links: Vec<PbLink>,
+    pub(crate) data: Vec<u8>,
+}
+
+pub(crate) struct PbLink {
+    pub(crate) cid: Cid,
+    pub(crate) name: Option<String>,
+}
+
+/// What a UnixFS node represents.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum UnixFsKind {
+    Raw,
+    Directory,
+    File,
+    Metadata,
+    Symlink,
+    HamtShard,
+}
+
+pub(crate) struct UnixFs {
+    pub(crate) kind: UnixFsKind,
+    pub(crate) data: Vec<u8>,
+    pub(crate) filesize: Option<u64>,
+    /// Content size under each link, in link order.
+    pub(crate) blocksizes: Vec<u64>,
+}
+
+/// A protobuf field: its number and its value, either a varint or a byte string.
+enum Field<'a> {
+    Varint(u32, u64),
+    Bytes(u32, &'a [u8]),
+}
+
+fn fields(bytes: &[u8]) -> impl Iterator<Item = Result<Field<'_>, TraceixError>> {
+    let mut at = 0;
+    std::iter::from_fn(move || {
+        if at >= bytes.len() {
+            return None;
+        }
+        Some(next_field(bytes, &mut at))
+    })
+}
+
+fn next_field<'a>(bytes: &'a [u8], at: &mut usize) -> Result<Field<'a>, TraceixError> {
+    let malformed = || TraceixError::UnexpectedResponse("malformed dag-pb block".into());
+    let key = read_varint(bytes, at).ok_or_else(malformed)?;
+    let number = (key >> 3) as u32;
+    match key & 7 {
+        0 => Ok(Field::Varint(
+            number,
+            read_varint(bytes, at).ok_or_else(malformed)?,
+        )),
+        2 => {
+            let len = read_varint(bytes, at).ok_or_else(malformed)? as usize;
+            let value = bytes
+                .get(*at..at.saturating_add(len))
+                .ok_or_else(malformed)?;
+            *at += len;
+            Ok(Field::Bytes(number, value))
+        }
+        // Fixed-width fields aren't used by dag-pb or UnixFS, but are skipped correctly.
+        1 => {
+            *at += 8;
+            Ok(Field::Varint(number, 0))
+        }
+        5 => {
+            *at += 4;
+            Ok(Field::Varint(number, 0))
+        }
+        _ => Err(malformed()),
+    }
+}
+
+pub(crate) fn decode_node(bytes: &[u8]) -> Result<PbNode, TraceixError> {
+    let mut node = PbNode {
+        links: Vec::new(),
+        data: Vec::new(),
+    };
+    for field in fields(bytes) {
+        match field? {
+            Field::Bytes(1, data) => node.data = data.to_vec(),
+            Field::Bytes(2, link) => node.links.push(decode_link(link)?),
+            _ => {}
+        }
+    }
+    Ok(node)
+}
+
+fn decode_link(bytes: &[u8]) -> Result<PbLink, TraceixError> {
+    let mut cid = None;
+    let mut name = None;
+    for field in fields(bytes) {
+        match field? {
+            Field::Bytes(1, hash) => cid = Some(Cid::from_bytes(hash)?),
+            Field::Bytes(2, text) => name = Some(String::from_utf8_lossy(text).into_owned()),
+            _ => {}
+        }
+    }
+    Ok(PbLink {
+        cid: cid
+            .ok_or_else(|| TraceixError::UnexpectedResponse("dag-pb link without hash".into()))?,
+        name,
+    })
+}
+
+pub(crate) fn decode_unixfs(bytes: &[u8]) -> Result<UnixFs, TraceixError> {
+    let mut unixfs = UnixFs {
+        kind: UnixFsKind::Raw,
+        data: Vec::new(),
+        filesize: None,
+        blocksizes: Vec::new(),
+    };
+    let mut kind = None;
+    for field in fields(bytes) {
+        match field? {
+            Field::Varint(1, value) => kind = Some(value),
+            Field::Bytes(2, data) => unixfs.data = data.to_vec(),
+            Field::Varint(3, size) => unixfs.filesize = Some(size),
+            Field::Varint(4, size) => unixfs.blocksizes.push(size),
+            // Packed encoding of the same repeated field.
+            Field::Bytes(4, packed) => {
+                let mut at = 0;
+                while at < packed.len() {
+                    let size = read_varint(packed, &mut at).ok_or_else(|| {
+                        TraceixError::UnexpectedResponse("malformed UnixFS blocksizes".into())
+                    })?;
+                    unixfs.blocksizes.push(size);
+                }
+            }
+            _ => {}
+        }
+    }
+    unixfs.kind = match kind {
+        Some(0) => UnixFsKind::Raw,
+        Some(1) => UnixFsKind::Directory,
+        Some(2) => UnixFsKind::File,
+        Some(3) => UnixFsKind::Metadata,
+        Some(4) => UnixFsKind::Symlink,
+        Some(5) => UnixFsKind::HamtShard,
+        other => {
+            return Err(TraceixError::UnexpectedResponse(format!(
+                "unknown UnixFS node type {other:?}"
+            )))
+        }
+    };
+    Ok(unixfs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn varint(out: &mut Vec<u8>, mut value: u64) {
+        while value >= 0x80 {
+            out.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        out.push(value as u8);
+    }
+
+    fn bytes_field(out: &mut Vec<u8>, number: u64, value: &[u8]) {
+        varint(out, number << 3 | 2);
+        varint(out, value.len() as u64);
+        out.extend(value);
+    }
+
+    fn varint_field(out: &mut Vec<u8>, number: u64, value: u64) {
+        varint(out, number << 3);
+        varint(out, value);
+    }
+
+    fn cid() -> Cid {
+        Cid::parse("bafkreibm6jg3ux5qumhcn2b3flc3tyu6dmlb4xa7u5bf44yegnrjhc4yeq").unwrap()
+    }
+
+    /// A dag-pb file node linking to `cid()` under `name`, with `data` as its UnixFS data.
+    fn node(name: &str, data: &[u8]) -> Vec<u8> {
+        let mut link = Vec::new();
+        bytes_field(&mut link, 1, &cid().to_bytes());
+        bytes_field(&mut link, 2, name.as_bytes());
+        varint_field(&mut link, 3, 5);
+        let mut node = Vec::new();
+        bytes_field(&mut node, 2, &link);
+        bytes_field(&mut node, 1, data);
+        node
+    }
+
+    fn unixfs_file(data: &[u8], blocksizes: &[u64]) -> Vec<u8> {
+        let mut out = Vec::new();
+        varint_field(&mut out, 1, 2);
+        bytes_field(&mut out, 2, data);
+        varint_field(
+            &mut out,
+            3,
+            data.len() as u64 + blocksizes.iter().sum::<u64>(),
+        );
+        for &size in blocksizes {
+            varint_field(&mut out, 4, size);
+        }
+        out
+    }
+
+    #[test]
+    fn decodes_links_and_data() {
+        let unixfs = unixfs_file(b"head", &[5]);
+        let decoded = decode_node(&node("part", &unixfs)).unwrap();
+        assert_eq!(decoded.links.len(), 1);
+        assert_eq!(decoded.links[0].cid, cid());
+        assert_eq!(decoded.links[0].name.as_deref(), Some("part"));
+        assert_eq!(decoded.data, unixfs);
+
+        let unixfs = decode_unixfs(&decoded.data).unwrap();
+        assert_eq!(unixfs.kind, UnixFsKind::File);
+        assert_eq!(unixfs.data, b"head");
+        assert_eq!(unixfs.filesize, Some(9));
+        assert_eq!(unixfs.blocksizes, [5]);
+    }
+
+    #[test]
+    fn decodes_packed_blocksizes() {
+        let mut packed = Vec::new();
+        for size in [262144, 262144, 17] {
+            varint(&mut packed, size);
+        }
+        let mut bytes = Vec::new();
+        varint_field(&mut bytes, 1, 2);
+        bytes_field(&mut bytes, 4, &packed);
+        assert_eq!(
+            decode_unixfs(&bytes).unwrap().blocksizes,
+            [262144, 262144, 17]
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_nodes() {
+        let mut no_hash = Vec::new();
+        bytes_field(&mut no_hash, 2, b"name");
+        let mut without_hash = Vec::new();
+        bytes_field(&mut without_hash, 2, &no_hash);
+        let mut bad_cid = Vec::new();
+        bytes_field(&mut bad_cid, 1, &[0x01, 0x55]);
+        let mut with_bad_cid = Vec::new();
+        bytes_field(&mut with_bad_cid, 2, &bad_cid);
+
+        for (name, bytes) in [
+            ("length past the end", vec![0x0a, 0x05, b'a']),
+            ("unterminated varint", vec![0x08, 0x80]),
+            ("group wire type", vec![0x0b]),
+            ("link without hash", without_hash),
+            ("link with bad CID", with_bad_cid),
+        ] {
+            assert!(decode_node(&bytes).is_err(), "{name}");
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_unixfs() {
+        let mut unknown = Vec::new();
+        varint_field(&mut unknown, 1, 9);
+        assert!(decode_unixfs(&unknown).is_err());
+        assert!(decode_unixfs(&[]).is_err());
+
+        let mut bad_packed = Vec::new();
+        varint_field(&mut bad_packed, 1, 2);
+        bytes_field(&mut bad_packed, 4, &[0x80]);
+        assert!(decode_unixfs(&bad_packed).is_err());
+    }
+
+    #[test]
+    fn truncated_nodes_never_panic() {
+        let bytes = node("part", &unixfs_file(b"head", &[5]));
+        for len in 0..bytes.len() {
+            let _ = decode_node(&bytes[..len]).and_then(|node| decode_unixfs(&node.data));
+        }
+    }
+}
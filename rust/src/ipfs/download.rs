@@ -0,0 +1,500 @@
+// src/ipfs/download.rs
+
+use super::car::CarArchive;
+use super::dag::{self, PbNode, UnixFsKind};
+#[cfg(feature = "ipfs-node")]
+use super::node;
+use super::GatewayHealth;
+use crate::cid::{Cid, DAG_PB, RAW};
+use crate::download::partial_path;
+use crate::{build_url, Endpoint, TraceixError, TraceixSdk};
+use reqwest::blocking::Request;
+use reqwest::header::ACCEPT;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+use url::Url;
+
+/// Largest block accepted from a gateway. Real blocks are at most 1-2 MiB.
+pub(super) const MAX_BLOCK_SIZE: u64 = 4 * 1024 * 1024;
+
+/// How far a dataset download has got.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DatasetProgress {
+    /// Bytes on disk so far, including any resumed from an earlier attempt.
+    pub bytes_written: u64,
+    /// Total size, when the dataset is a single file.
+    pub total: Option<u64>,
+}
+
+type DatasetProgressCallback = Arc<dyn Fn(DatasetProgress) + Send + Sync>;
+
+/// Options for [`TraceixSdk::download_dataset_with_options`].
+#[derive(Clone, Default)]
+pub struct DatasetDownloadOptions {
+    progress: Option<DatasetProgressCallback>,
+}
+
+impl DatasetDownloadOptions {
+    /// Call `callback` after each block is written.
+    pub fn on_progress(
+        mut self,
+        callback: impl Fn(DatasetProgress) + Send + Sync + 'static,
+    ) -> Self {
+        self.progress = Some(Arc::new(callback));
+        self
+    }
+}
+
+impl fmt::Debug for DatasetDownloadOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DatasetDownloadOptions")
+            .field("progress", &self.progress.is_some())
+            .finish()
+    }
+}
+
+/// A dataset saved by [`TraceixSdk::download_dataset`].
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct DatasetDownload {
+    /// The file, or the directory for a dataset of several files.
+    pub path: PathBuf,
+    pub cid: String,
+    pub bytes: u64,
+    pub files: usize,
+}
+
+/// Where a [`Fetcher`] gets blocks from.
+#[derive(Clone, Copy)]
+pub(super) enum Source<'a> {
+    Network(&'a TraceixSdk),
+    Car(&'a CarArchive),
+}
+
+/// Walks a UnixFS DAG, fetching and verifying each block.
+pub(super) struct Fetcher<'a> {
+    source: Source<'a>,
+    options: &'a DatasetDownloadOptions,
+    written: u64,
+    total: Option<u64>,
+    files: usize,
+}
+
+impl<'a> Fetcher<'a> {
+    pub(super) fn new(source: Source<'a>, options: &'a DatasetDownloadOptions) -> Self {
+        Self {
+            source,
+            options,
+            written: 0,
+            total: None,
+            files: 0,
+        }
+    }
+
+    fn block(&self, cid: &Cid) -> Result<Vec<u8>, TraceixError> {
+        if let Some(data) = cid.inline_data() {
+            return Ok(data.to_vec());
+        }
+        match self.source {
+            Source::Network(sdk) => sdk.ipfs_block(cid),
+            Source::Car(car) => car.block(cid),
+        }
+    }
+
+    /// Save the dataset rooted at `root` to `dest`.
+    pub(super) fn save(mut self, root: &Cid, dest: &Path) -> Result<DatasetDownload, TraceixError> {
+        let block = self.block(root)?;
+        match self.entry(root, block)? {
+            Entry::Directory(node) => self.save_dir(node, dest)?,
+            Entry::File(block, size) => {
+                self.total = Some(size);
+                self.save_file(root, block, size, dest)?;
+            }
+        }
+        Ok(DatasetDownload {
+            path: dest.to_path_buf(),
+            cid: root.to_string(),
+            bytes: self.written,
+            files: self.files,
+        })
+    }
+
+    fn progress(&self) {
+        if let Some(callback) = &self.options.progress {
+            callback(DatasetProgress {
+                bytes_written: self.written,
+                total: self.total,
+            });
+        }
+    }
+
+    fn emit(&mut self, data: &[u8], out: &mut impl Write) -> Result<(), TraceixError> {
+        if !data.is_empty() {
+            out.write_all(data)?;
+            self.written += data.len() as u64;
+            self.progress();
+        }
+        Ok(())
+    }
+
+    /// Save the file rooted at `cid` to `dest`, continuing from `dest.part` if an
+    /// earlier attempt left one.
+    fn save_file(
+        &mut self,
+        cid: &Cid,
+        block: Vec<u8>,
+        size: u64,
+        dest: &Path,
+    ) -> Result<(), TraceixError> {
+        let partial = partial_path(dest);
+        let mut resumed = fs::metadata(&partial).map(|m| m.len()).unwrap_or(0);
+        if resumed > size {
+            resumed = 0;
+            File::create(&partial)?;
+        }
+        if resumed > 0 {
+            self.written += resumed;
+            self.progress();
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&partial)?;
+        let mut out = BufWriter::new(file);
+        self.write_node(cid, Some(block), resumed, &mut out)?;
+        out.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+
+        let len = fs::metadata(&partial)?.len();
+        if len != size {
+            // The blocks were verified, so the DAG itself is inconsistent; starting over
+            // won't help, but a stale partial file shouldn't linger either.
+            let _ = fs::remove_file(&partial);
+            return Err(TraceixError::UnexpectedResponse(format!(
+                "{cid} declares {size} bytes but its blocks hold {len}"
+            )));
+        }
+        fs::rename(&partial, dest)?;
+        self.files += 1;
+        Ok(())
+    }
+
+    /// Write the content under `cid`, leaving out the first `skip` bytes. Returns the
+    /// size of the content, skipped or not.
+    fn write_node(
+        &mut self,
+        cid: &Cid,
+        block: Option<Vec<u8>>,
+        mut skip: u64,
+        out: &mut impl Write,
+    ) -> Result<u64, TraceixError> {
+        let block = match block {
+            Some(block) => block,
+            None => self.block(cid)?,
+        };
+        let (data, links, blocksizes) = match cid.codec() {
+            RAW => (block, Vec::new(), Vec::new()),
+            DAG_PB => {
+                let node = dag::decode_node(&block)?;
+                let unixfs = dag::decode_unixfs(&node.data)?;
+                if !matches!(unixfs.kind, UnixFsKind::File | UnixFsKind::Raw) {
+                    return Err(TraceixError::UnexpectedResponse(format!(
+                        "{cid} is a {:?} node inside a file",
+                        unixfs.kind
+                    )));
+                }
+                (unixfs.data, node.links, unixfs.blocksizes)
+            }
+            codec => return Err(unsupported_codec(cid, codec)),
+        };
+
+        let mut len = data.len() as u64;
+        let from = skip.min(len);
+        self.emit(&data[from as usize..], out)?;
+        skip -= from;
+        for (i, link) in links.iter().enumerate() {
+            // Children that were fully written by an earlier attempt aren't fetched again.
+            let child = match blocksizes.get(i) {
+                Some(&size) if skip >= size => size,
+                _ => self.write_node(&link.cid, None, skip, out)?,
+            };
+            skip = skip.saturating_sub(child);
+            len += child;
+        }
+        Ok(len)
+    }
+
+    /// Save the directory `node` to `dest`. Files already present at their full size
+    /// are kept, so an interrupted download picks up where it stopped.
+    fn save_dir(&mut self, node: PbNode, dest: &Path) -> Result<(), TraceixError> {
+        fs::create_dir_all(dest)?;
+        for link in node.links {
+            let name = link.name.unwrap_or_default();
+            if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\']) {
+                return Err(TraceixError::UnexpectedResponse(format!(
+                    "unsafe entry name {name:?} in dataset"
+                )));
+            }
+            let target = dest.join(&name);
+            let block = self.block(&link.cid)?;
+            match self.entry(&link.cid, block)? {
+                Entry::Directory(node) => self.save_dir(node, &target)?,
+                Entry::File(block, size) => {
+                    let complete = !partial_path(&target).exists()
+                        && fs::metadata(&target).is_ok_and(|m| m.is_file() && m.len() == size);
+                    if complete {
+                        self.written += size;
+                        self.files += 1;
+                        self.progress();
+                    } else {
+                        self.save_file(&link.cid, block, size, &target)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Work out what the block for `cid` is.
+    fn entry(&self, cid: &Cid, block: Vec<u8>) -> Result<Entry, TraceixError> {
+        match cid.codec() {
+            RAW => {
+                let size = block.len() as u64;
+                Ok(Entry::File(block, size))
+            }
+            DAG_PB => {
+                let node = dag::decode_node(&block)?;
+                let unixfs = dag::decode_unixfs(&node.data)?;
+                match unixfs.kind {
+                    UnixFsKind::Directory => Ok(Entry::Directory(node)),
+                    UnixFsKind::File | UnixFsKind::Raw => {
+                        let size = unixfs.filesize.unwrap_or_else(|| {
+                            unixfs.data.len() as u64 + unixfs.blocksizes.iter().sum::<u64>()
+                        });
+                        Ok(Entry::File(block, size))
+                    }
+                    kind => Err(TraceixError::UnexpectedResponse(format!(
+                        "{cid} is a UnixFS {kind:?} node, which can't be downloaded"
+                    ))),
+                }
+            }
+            codec => Err(unsupported_codec(cid, codec)),
+        }
+    }
+}
+
+/// Send the requests `build` makes for the block `cid` to `url` and check what comes back
+/// is that block.
+pub(super) fn fetch_block<F>(
+    sdk: &TraceixSdk,
+    url: &Url,
+    build: F,
+    cid: &Cid,
+) -> Result<Vec<u8>, TraceixError>
+where
+    F: FnMut() -> Result<Request, TraceixError>,
+{
+    let response = sdk.send_request(&Endpoint::IPFS_BLOCK, url, None, build)?;
+    let mut data = Vec::new();
+    response.take(MAX_BLOCK_SIZE + 1).read_to_end(&mut data)?;
+    if data.len() as u64 > MAX_BLOCK_SIZE {
+        return Err(TraceixError::UnexpectedResponse(format!(
+            "block {cid} is larger than {MAX_BLOCK_SIZE} bytes"
+        )));
+    }
+    cid.verify(&data)?;
+    Ok(data)
+}
+
+enum Entry {
+    Directory(PbNode),
+    /// The root block of a file, and the file's size.
+    File(Vec<u8>, u64),
+}
+
+fn unsupported_codec(cid: &Cid, codec: u64) -> TraceixError {
+    TraceixError::UnexpectedResponse(format!("{cid} uses unsupported codec 0x{codec:x}"))
+}
+
+impl TraceixSdk {
+    /// The configured IPFS gateways, healthiest first, with the latency and failures
+    /// seen so far. This is the order the next block will be requested in.
+    pub fn ipfs_gateway_health(&self) -> Vec<GatewayHealth> {
+        self.ipfs_gateways.health()
+    }
+
+    /// Download the public IPFS dataset `cid` to `dest`.
+    ///
+    /// Blocks are fetched from the configured gateways (see
+    /// [`TraceixSdkBuilder::ipfs_gateway`](crate::TraceixSdkBuilder::ipfs_gateway)) and
+    /// each one is checked against the hash in its CID. A block that a gateway can't
+    /// serve, or serves wrongly, is fetched from the next; the error from the last
+    /// gateway tried is returned if none succeed. With the `ipfs-node` feature and an
+    /// [`ipfs_node`](crate::TraceixSdkBuilder::ipfs_node) configured, blocks come from
+    /// that node instead. A file is
+    /// written to `dest.part` and renamed once complete; calling `download_dataset`
+    /// again after an interruption continues from the partial file. A dataset that is a
+    /// directory is saved as a directory at `dest`, keeping files already downloaded.
+    pub fn download_dataset(
+        &self,
+        cid: &str,
+        dest: impl AsRef<Path>,
+    ) -> Result<DatasetDownload, TraceixError> {
+        self.download_dataset_with_options(cid, dest, &DatasetDownloadOptions::default())
+    }
+
+    /// Like [`TraceixSdk::download_dataset`], with explicit options.
+    ///
+    /// ```no_run
+    /// # use traceix_sdk::{DatasetDownloadOptions, TraceixSdk};
+    /// # fn main() -> Result<(), traceix_sdk::TraceixError> {
+    /// let sdk = TraceixSdk::new(None)?;
+    /// let options = DatasetDownloadOptions::default().on_progress(|p| {
+    ///     eprint!("\r{} of {:?} bytes", p.bytes_written, p.total);
+    /// });
+    /// let cid = "bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi";
+    /// let saved = sdk.download_dataset_with_options(cid, "datasets/latest", &options)?;
+    /// println!("{} files, {} bytes", saved.files, saved.bytes);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn download_dataset_with_options(
+        &self,
+        cid: &str,
+        dest: impl AsRef<Path>,
+        options: &DatasetDownloadOptions,
+    ) -> Result<DatasetDownload, TraceixError> {
+        let root = Cid::parse(cid)?;
+        Fetcher::new(Source::Network(self), options).save(&root, dest.as_ref())
+    }
+
+    /// Fetch the block `cid` from the IPFS node or the healthiest gateway that has it,
+    /// and check it against `cid`.
+    pub(super) fn ipfs_block(&self, cid: &Cid) -> Result<Vec<u8>, TraceixError> {
+        #[cfg(feature = "ipfs-node")]
+        if let Some(node) = &self.ipfs_node {
+            let url = node::rpc_url(node, "block/get", cid, &[]);
+            return fetch_block(
+                self,
+                &url,
+                || Ok(self.client.post(url.clone()).build()?),
+                cid,
+            );
+        }
+        let gateways = &self.ipfs_gateways;
+        let mut last_error = None;
+        for index in gateways.ranked() {
+            let started = Instant::now();
+            match self.gateway_block(gateways.url(index), cid) {
+                Ok(data) => {
+                    gateways.record_success(index, started.elapsed());
+                    return Ok(data);
+                }
+                // Another gateway wouldn't do any better.
+                Err(e) if e.planned_request().is_some() || e.is_cancelled() => return Err(e),
+                Err(e) => {
+                    gateways.record_failure(index);
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error
+            .unwrap_or_else(|| TraceixError::InvalidArgument("no IPFS gateways configured".into())))
+    }
+
+    /// Fetch one block from `gateway`.
+    fn gateway_block(&self, gateway: &Url, cid: &Cid) -> Result<Vec<u8>, TraceixError> {
+        let mut url = build_url(gateway, &format!("/ipfs/{cid}"));
+        url.set_query(Some("format=raw"));
+        // Gateways are third parties: the request goes out without the API key.
+        let build = || {
+            Ok(self
+                .client
+                .get(url.clone())
+                .header(ACCEPT, "application/vnd.ipld.raw")
+                .build()?)
+        };
+        fetch_block(self, &url, build, cid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hashing::to_hex;
+    use crate::{CancellationToken, Transport};
+    use reqwest::blocking::Response;
+    use sha2::{Digest, Sha256};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const BLOCK: &[u8] = b"hello";
+
+    /// A gateway serving `BLOCK` for every request, counting them.
+    #[derive(Clone, Default)]
+    struct Gateway(Arc<AtomicUsize>);
+
+    impl Transport for Gateway {
+        fn execute(&self, _request: Request) -> Result<Response, TraceixError> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            let response = http::Response::builder()
+                .status(200)
+                .body(BLOCK.to_vec())
+                .unwrap();
+            Ok(Response::from(response))
+        }
+    }
+
+    fn sdk(gateway: &Gateway) -> TraceixSdk {
+        TraceixSdk::builder()
+            .api_key("test")
+            .ipfs_gateway("https://gateway.example")
+            .capture_requests(8)
+            .transport(gateway.clone())
+            .build()
+            .unwrap()
+    }
+
+    fn cid() -> String {
+        format!("f01551220{}", to_hex(&Sha256::digest(BLOCK)))
+    }
+
+    #[test]
+    fn blocks_are_sent_like_api_requests() {
+        let gateway = Gateway::default();
+        let sdk = sdk(&gateway);
+        let root = Cid::parse(&cid()).unwrap();
+
+        assert_eq!(sdk.ipfs_block(&root).unwrap(), BLOCK);
+        let captured = sdk.captured_requests();
+        assert_eq!(captured.len(), 1);
+        let request = &captured[0].request;
+        assert_eq!(request.endpoint, Some(Endpoint::IPFS_BLOCK.name));
+        assert!(request
+            .url
+            .as_str()
+            .starts_with("https://gateway.example/ipfs/"));
+        assert!(!request
+            .headers
+            .iter()
+            .any(|(name, _)| name.eq_ignore_ascii_case("x-api-key")));
+    }
+
+    #[test]
+    fn cancelled_downloads_stop_fetching() {
+        let gateway = Gateway::default();
+        let token = CancellationToken::new();
+        token.cancel();
+        let sdk = sdk(&gateway).with_cancellation(&token);
+        let dest =
+            std::env::temp_dir().join(format!("traceix-ipfs-cancel-test-{}", std::process::id()));
+
+        let error = sdk.download_dataset(&cid(), &dest).unwrap_err();
+        assert!(error.is_cancelled(), "{error}");
+        assert_eq!(gateway.0.load(Ordering::SeqCst), 0);
+        assert!(!dest.exists());
+    }
+}
@@ -0,0 +1,111 @@
+// src/ipfs/gateways.rs
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use url::Url;
+
+/// How long a gateway is ranked last after a failure. Doubles with each further
+/// failure in a row, up to [`MAX_PENALTY`].
+const BASE_PENALTY: Duration = Duration::from_secs(30);
+const MAX_PENALTY: Duration = Duration::from_secs(10 * 60);
+/// Weight of the newest sample in the latency average.
+const LATENCY_WEIGHT: f64 = 0.3;
+
+/// What the SDK currently knows about an IPFS gateway, from
+/// [`TraceixSdk::ipfs_gateway_health`](crate::TraceixSdk::ipfs_gateway_health).
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct GatewayHealth {
+    pub url: Url,
+    /// Moving average of the time taken to fetch a block. `None` until it has served one.
+    pub latency: Option<Duration>,
+    /// Failures since the last successful fetch.
+    pub consecutive_failures: u32,
+    /// `false` while the gateway is penalized for a recent failure. It is still tried,
+    /// after every available gateway.
+    pub available: bool,
+}
+
+#[derive(Default)]
+struct Health {
+    latency: Option<Duration>,
+    consecutive_failures: u32,
+    penalized_until: Option<Instant>,
+}
+
+/// The configured gateways, with the health each has shown so far. Shared by clones of
+/// the client.
+pub(crate) struct GatewayPool {
+    gateways: Vec<(Url, Mutex<Health>)>,
+}
+
+impl GatewayPool {
+    pub(crate) fn new(gateways: Vec<Url>) -> Self {
+        Self {
+            gateways: gateways
+                .into_iter()
+                .map(|url| (url, Mutex::new(Health::default())))
+                .collect(),
+        }
+    }
+
+    /// Gateway indices, healthiest first: available gateways before penalized ones,
+    /// then untried gateways, then by latency. Ties keep the configured order.
+    pub(crate) fn ranked(&self) -> Vec<usize> {
+        let now = Instant::now();
+        let mut order: Vec<(usize, (bool, Option<Duration>))> = self
+            .gateways
+            .iter()
+            .enumerate()
+            .map(|(i, (_, health))| {
+                let health = health.lock().unwrap();
+                let penalized = health.penalized_until.is_some_and(|until| until > now);
+                (i, (penalized, health.latency))
+            })
+            .collect();
+        order.sort_by_key(|&(_, key)| key);
+        order.into_iter().map(|(i, _)| i).collect()
+    }
+
+    pub(crate) fn url(&self, index: usize) -> &Url {
+        &self.gateways[index].0
+    }
+
+    pub(crate) fn record_success(&self, index: usize, elapsed: Duration) {
+        let mut health = self.gateways[index].1.lock().unwrap();
+        health.latency = Some(match health.latency {
+            Some(average) => {
+                average.mul_f64(1.0 - LATENCY_WEIGHT) + elapsed.mul_f64(LATENCY_WEIGHT)
+            }
+            None => elapsed,
+        });
+        health.consecutive_failures = 0;
+        health.penalized_until = None;
+    }
+
+    pub(crate) fn record_failure(&self, index: usize) {
+        let mut health = self.gateways[index].1.lock().unwrap();
+        health.consecutive_failures = health.consecutive_failures.saturating_add(1);
+        let penalty = BASE_PENALTY
+            .saturating_mul(1 << (health.consecutive_failures - 1).min(16))
+            .min(MAX_PENALTY);
+        health.penalized_until = Some(Instant::now() + penalty);
+    }
+
+    pub(crate) fn health(&self) -> Vec<GatewayHealth> {
+        let now = Instant::now();
+        self.ranked()
+            .into_iter()
+            .map(|i| {
+                let (url, health) = &self.gateways[i];
+                let health = health.lock().unwrap();
+                GatewayHealth {
+                    url: url.clone(),
+                    latency: health.latency,
+                    consecutive_failures: health.consecutive_failures,
+                    available: health.penalized_until.is_none_or(|until| until <= now),
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,45 @@
+// src/ipfs/mod.rs
+
+//! Fetching public datasets from IPFS.
+//!
+//! Content is fetched from an HTTP gateway one block at a time in the trustless
+//! `application/vnd.ipld.raw` format, and each block is checked against the hash in its
+//! CID before it is used. A gateway can therefore fail a download but can't alter it.
+//!
+//! Several gateways can be configured. A block that one gateway fails to serve, or
+//! serves wrongly, is fetched from the next, and gateways are ranked by the latency and
+//! failures seen so far so later fetches go to the healthiest first.
+//!
+//! Datasets can also be saved as CAR files with
+//! [`TraceixSdk::export_car`](crate::TraceixSdk::export_car), and read back offline with
+//! [`TraceixSdk::import_car`](crate::TraceixSdk::import_car).
+//!
+//! A [`DatasetCache`] keeps a local mirror of chosen datasets up to date without
+//! downloading the catalog again on every run.
+//!
+//! With the `ipfs-node` feature, a local Kubo node can be used instead: blocks are read
+//! through its RPC API, and datasets can be pinned on it to keep them available.
+
+mod cache;
+mod car;
+mod dag;
+mod download;
+mod gateways;
+#[cfg(feature = "ipfs-node")]
+mod node;
+
+pub use cache::{CacheSyncReport, CachedDataset, DatasetCache, FailedDataset};
+pub use car::{CarArchive, CarExport};
+pub use download::{DatasetDownload, DatasetDownloadOptions, DatasetProgress};
+pub use gateways::GatewayHealth;
+pub(crate) use gateways::GatewayPool;
+#[cfg(feature = "ipfs-node")]
+pub use node::DEFAULT_IPFS_NODE;
+
+/// Gateways used by [`TraceixSdk::download_dataset`](crate::TraceixSdk::download_dataset)
+/// when none are configured, in order of preference.
+pub const DEFAULT_IPFS_GATEWAYS: &[&str] = &[
+    "https://trustless-gateway.link",
+    "https://ipfs.io",
+    "https://dweb.link",
+];
@@ -0,0 +1,93 @@
+// src/ipfs/node.rs
+
+use crate::cid::Cid;
+use crate::{build_url, Endpoint, TraceixError, TraceixSdk};
+use reqwest::blocking::Response;
+use serde_json::Value;
+use std::time::Duration;
+use url::Url;
+
+/// Address of the Kubo RPC API on a default install.
+pub const DEFAULT_IPFS_NODE: &str = "http://127.0.0.1:5001";
+
+/// Pinning fetches the whole dataset into the node, so it may take far longer than the
+/// client's usual timeout.
+const PIN_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+/// The URL of a call to the Kubo RPC `command` about `cid`.
+pub(super) fn rpc_url(node: &Url, command: &str, cid: &Cid, args: &[(&str, &str)]) -> Url {
+    let mut url = build_url(node, &format!("/api/v0/{command}"));
+    url.query_pairs_mut()
+        .append_pair("arg", &cid.to_string())
+        .extend_pairs(args);
+    url
+}
+
+impl TraceixSdk {
+    fn ipfs_node(&self) -> Result<&Url, TraceixError> {
+        self.ipfs_node.as_ref().ok_or_else(|| {
+            TraceixError::InvalidArgument(
+                "no IPFS node configured; set one with TraceixSdkBuilder::ipfs_node".into(),
+            )
+        })
+    }
+
+    /// Send an RPC call for `endpoint` to `url` on the node, the way API calls are sent.
+    fn node_rpc(
+        &self,
+        endpoint: &Endpoint,
+        url: &Url,
+        timeout: Option<Duration>,
+    ) -> Result<Response, TraceixError> {
+        self.send_request(endpoint, url, None, || {
+            let mut request = self.client.post(url.clone());
+            if let Some(timeout) = timeout {
+                request = request.timeout(timeout);
+            }
+            Ok(request.build()?)
+        })
+    }
+
+    /// Pin the public dataset `cid` recursively on the configured IPFS node, so the node
+    /// keeps a full copy and serves it to the network even if others drop it. Returns
+    /// once the whole dataset has been fetched, which for a large dataset can take a
+    /// while.
+    ///
+    /// ```no_run
+    /// # use traceix_sdk::{TraceixSdk, DEFAULT_IPFS_NODE};
+    /// # fn main() -> Result<(), traceix_sdk::TraceixError> {
+    /// let sdk = TraceixSdk::builder().ipfs_node(DEFAULT_IPFS_NODE).build()?;
+    /// let cid = "bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi";
+    /// sdk.pin_dataset(cid)?;
+    /// // Now served by the local node rather than a public gateway.
+    /// sdk.download_dataset(cid, "datasets/latest")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn pin_dataset(&self, cid: &str) -> Result<Cid, TraceixError> {
+        let cid = Cid::parse(cid)?;
+        let url = rpc_url(self.ipfs_node()?, "pin/add", &cid, &[("recursive", "true")]);
+        let response: Value = self
+            .node_rpc(&Endpoint::IPFS_PIN, &url, Some(PIN_TIMEOUT))?
+            .json()?;
+        let pinned = response
+            .get("Pins")
+            .and_then(Value::as_array)
+            .is_some_and(|pins| !pins.is_empty());
+        if !pinned {
+            return Err(TraceixError::UnexpectedResponse(format!(
+                "IPFS node didn't pin {cid}: {response}"
+            )));
+        }
+        Ok(cid)
+    }
+
+    /// Remove the pin on `cid` from the configured IPFS node, letting it garbage-collect
+    /// the dataset.
+    pub fn unpin_dataset(&self, cid: &str) -> Result<(), TraceixError> {
+        let cid = Cid::parse(cid)?;
+        let url = rpc_url(self.ipfs_node()?, "pin/rm", &cid, &[("recursive", "true")]);
+        self.node_rpc(&Endpoint::IPFS_UNPIN, &url, None)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,304 @@
+// src/large_upload.rs
+
+use crate::{
+    hashing, upload_fields, upload_file_name, Endpoint, Submission, TraceixError, TraceixSdk,
+    UploadOptions,
+};
+use reqwest::blocking::Body;
+use reqwest::header::{CONTENT_RANGE, CONTENT_TYPE};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+use std::env;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// Default size of each part sent by [`TraceixSdk::upload_large`].
+pub const DEFAULT_PART_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Options for [`TraceixSdk::upload_large_with_options`].
+#[derive(Clone, Debug)]
+pub struct LargeUploadOptions {
+    /// Bytes per part. Only takes effect for new sessions; a resumed upload keeps the
+    /// part size it started with.
+    pub part_size: u64,
+    /// Number of parts sent at the same time.
+    pub parallelism: usize,
+    /// Where resume state is kept. Defaults to a file named after the sample's SHA-256
+    /// in the system temp directory.
+    pub state_file: Option<PathBuf>,
+    pub upload: UploadOptions,
+}
+
+impl Default for LargeUploadOptions {
+    fn default() -> Self {
+        Self {
+            part_size: DEFAULT_PART_SIZE,
+            parallelism: 4,
+            state_file: None,
+            upload: UploadOptions::default(),
+        }
+    }
+}
+
+/// Progress of a chunked upload, persisted after every part.
+#[derive(Debug, Serialize, Deserialize)]
+struct SessionState {
+    session_id: String,
+    sha256: String,
+    size: u64,
+    part_size: u64,
+    completed: BTreeSet<u64>,
+}
+
+impl SessionState {
+    fn parts(&self) -> u64 {
+        self.size.div_ceil(self.part_size).max(1)
+    }
+
+    fn load(path: &Path) -> Option<Self> {
+        let file = File::open(path).ok()?;
+        serde_json::from_reader(file).ok()
+    }
+
+    /// Write the state next to its final location, then move it into place so a crash
+    /// never leaves a truncated file behind.
+    fn save(&self, path: &Path) -> Result<(), TraceixError> {
+        let partial = path.with_extension("partial");
+        fs::write(
+            &partial,
+            serde_json::to_vec(self).expect("state serializes"),
+        )?;
+        fs::rename(&partial, path)?;
+        Ok(())
+    }
+}
+
+impl TraceixSdk {
+    /// Upload a very large sample, e.g. a memory dump or disk image, for AI prediction
+    /// in resumable parts.
+    ///
+    /// The file is split into parts that are sent several at a time within an upload
+    /// session. Which parts have arrived is saved to a state file after each one, so
+    /// calling `upload_large` again for the same file after a crash or network failure
+    /// only sends the missing parts. The state file is removed once the upload completes.
+    pub fn upload_large(&self, filename: impl AsRef<Path>) -> Result<Submission, TraceixError> {
+        self.upload_large_with_options(filename, &LargeUploadOptions::default())
+    }
+
+    /// Like [`TraceixSdk::upload_large`], with explicit options.
+    ///
+    /// ```no_run
+    /// # use std::path::PathBuf;
+    /// # use traceix_sdk::{LargeUploadOptions, TraceixSdk};
+    /// # fn main() -> Result<(), traceix_sdk::TraceixError> {
+    /// let sdk = TraceixSdk::new(None)?;
+    /// let options = LargeUploadOptions {
+    ///     part_size: 64 * 1024 * 1024,
+    ///     parallelism: 8,
+    ///     state_file: Some(PathBuf::from("/var/lib/triage/memdump.upload")),
+    ///     ..LargeUploadOptions::default()
+    /// };
+    /// let submission = sdk.upload_large_with_options("/evidence/memdump.raw", &options)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn upload_large_with_options(
+        &self,
+        filename: impl AsRef<Path>,
+        options: &LargeUploadOptions,
+    ) -> Result<Submission, TraceixError> {
+        let filename = filename.as_ref();
+        if options.upload.strip_metadata {
+            return Err(TraceixError::InvalidArgument(
+                "metadata can't be stripped from uploads sent in parts".into(),
+            ));
+        }
+        if options.part_size == 0 {
+            return Err(TraceixError::InvalidArgument(
+                "part size must be positive".into(),
+            ));
+        }
+        if let Some(preflight) = &self.preflight {
+            preflight.check(filename)?;
+        }
+        let size = fs::metadata(filename)?.len();
+        let sha256 = hashing::sha256_file(filename)?;
+        let state_file = options
+            .state_file
+            .clone()
+            .unwrap_or_else(|| env::temp_dir().join(format!("traceix-upload-{sha256}.json")));
+
+        let mut state = match SessionState::load(&state_file) {
+            Some(state) if state.sha256 == sha256 && state.size == size => {
+                match self.session_parts(&state.session_id) {
+                    Ok(completed) => SessionState { completed, ..state },
+                    // The server forgot the session, e.g. because it expired.
+                    Err(e) if e.status() == Some(StatusCode::NOT_FOUND) => {
+                        self.create_session(filename, &sha256, size, options)?
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+            _ => self.create_session(filename, &sha256, size, options)?,
+        };
+        state.save(&state_file)?;
+
+        let pending: Vec<u64> = (0..state.parts())
+            .filter(|part| !state.completed.contains(part))
+            .collect();
+        self.with_priority(options.upload.priority).send_parts(
+            filename,
+            &mut state,
+            &state_file,
+            pending,
+            options.parallelism,
+        )?;
+
+        let response = self.post_json(
+            &Endpoint::UPLOAD_SESSION_COMPLETE,
+            &serde_json::json!({ "session_id": state.session_id, "sha256": sha256 }),
+        )?;
+        let submission = Submission::from_response(self, response)?;
+        let _ = fs::remove_file(&state_file);
+        Ok(submission)
+    }
+
+    fn create_session(
+        &self,
+        filename: &Path,
+        sha256: &str,
+        size: u64,
+        options: &LargeUploadOptions,
+    ) -> Result<SessionState, TraceixError> {
+        let mut body: Map<String, Value> = upload_fields(&options.upload)
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), Value::String(value)))
+            .collect();
+        body.insert("filename".into(), upload_file_name(filename).into());
+        body.insert("sha256".into(), sha256.into());
+        body.insert("size".into(), size.into());
+        body.insert("part_size".into(), options.part_size.into());
+
+        let response = self.post_json(&Endpoint::UPLOAD_SESSION_CREATE, &Value::Object(body))?;
+        let session_id = response
+            .get("session_id")
+            .and_then(Value::as_str)
+            .filter(|id| !id.is_empty())
+            .ok_or_else(|| {
+                TraceixError::UnexpectedResponse(format!("no session_id in response {response}"))
+            })?;
+        Ok(SessionState {
+            session_id: session_id.to_string(),
+            sha256: sha256.to_string(),
+            size,
+            // The server may insist on its own part size.
+            part_size: response
+                .get("part_size")
+                .and_then(Value::as_u64)
+                .filter(|&n| n > 0)
+                .unwrap_or(options.part_size),
+            completed: BTreeSet::new(),
+        })
+    }
+
+    /// The part numbers the server already holds for `session_id`.
+    fn session_parts(&self, session_id: &str) -> Result<BTreeSet<u64>, TraceixError> {
+        let response = self.post_json(
+            &Endpoint::UPLOAD_SESSION_STATUS,
+            &serde_json::json!({ "session_id": session_id }),
+        )?;
+        let parts = response
+            .get("parts")
+            .and_then(Value::as_array)
+            .ok_or_else(|| {
+                TraceixError::UnexpectedResponse(format!("no parts in response {response}"))
+            })?;
+        Ok(parts.iter().filter_map(Value::as_u64).collect())
+    }
+
+    /// Send `pending` parts on up to `parallelism` threads, recording each one in the
+    /// state file as it completes. Stops at the first part that fails for good.
+    fn send_parts(
+        &self,
+        filename: &Path,
+        state: &mut SessionState,
+        state_file: &Path,
+        pending: Vec<u64>,
+        parallelism: usize,
+    ) -> Result<(), TraceixError> {
+        let queue = Mutex::new(pending.into_iter());
+        let shared = Mutex::new(&mut *state);
+        let failed = AtomicBool::new(false);
+        let errors = Mutex::new(Vec::new());
+
+        thread::scope(|scope| {
+            for _ in 0..parallelism.max(1) {
+                scope.spawn(|| loop {
+                    if failed.load(Ordering::Relaxed) {
+                        return;
+                    }
+                    let Some(part) = queue.lock().unwrap().next() else {
+                        return;
+                    };
+                    let (session_id, size, part_size) = {
+                        let state = shared.lock().unwrap();
+                        (state.session_id.clone(), state.size, state.part_size)
+                    };
+                    let result = self
+                        .send_part(filename, &session_id, part, size, part_size)
+                        .and_then(|()| {
+                            let mut state = shared.lock().unwrap();
+                            state.completed.insert(part);
+                            state.save(state_file)
+                        });
+                    if let Err(e) = result {
+                        failed.store(true, Ordering::Relaxed);
+                        errors.lock().unwrap().push(e);
+                        return;
+                    }
+                });
+            }
+        });
+
+        match errors.into_inner().unwrap().into_iter().next() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    fn send_part(
+        &self,
+        filename: &Path,
+        session_id: &str,
+        part: u64,
+        size: u64,
+        part_size: u64,
+    ) -> Result<(), TraceixError> {
+        let start = part * part_size;
+        let len = part_size.min(size - start.min(size));
+        let range = if len == 0 {
+            format!("bytes */{size}")
+        } else {
+            format!("bytes {}-{}/{}", start, start + len - 1, size)
+        };
+
+        self.send(&Endpoint::UPLOAD_SESSION_PART, Some(filename), |request| {
+            let mut file = File::open(filename)?;
+            file.seek(SeekFrom::Start(start))?;
+            let reader = self.body_reader(self.buffers.reader(file.take(len)));
+            Ok(request
+                .header("x-upload-session", session_id)
+                .header("x-part-number", part.to_string())
+                .header(CONTENT_TYPE, "application/octet-stream")
+                .header(CONTENT_RANGE, range.as_str())
+                .body(Body::sized(reader, len)))
+        })?;
+        Ok(())
+    }
+}
@@ -1,167 +1,969 @@
 // src/lib.rs
 
-use reqwest::blocking::{Client};
-use reqwest::blocking::multipart;
-use reqwest::header::{HeaderMap, HeaderValue, USER_AGENT};
 use serde_json::Value;
-use std::env;
-use std::fs::File;
-use std::path::Path;
-use std::{fmt, io};
-
-#[derive(Debug)]
-pub enum TraceixError {
-    NoApiKey,
-    InvalidSearchType,
-    NoUuidProvided,
-    Http(reqwest::Error),
-    Io(io::Error),
+
+/// Declare items that only exist with the default `blocking` feature, which provides
+/// [`TraceixSdk`] and everything built on its blocking HTTP client.
+macro_rules! cfg_blocking {
+    ($($item:item)*) => {
+        $(
+            #[cfg(feature = "blocking")]
+            $item
+        )*
+    };
+}
+
+cfg_blocking! {
+    use reqwest::blocking::multipart;
+    use reqwest::blocking::{Client, Request, RequestBuilder, Response};
+    use serde::de::DeserializeOwned;
+    use pagination::{Page, Paginator};
+    use progress::ProgressReader;
+    use std::fs::File;
+    use std::io::Cursor;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+    use zeroize::Zeroizing;
 }
 
-impl fmt::Display for TraceixError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            TraceixError::NoApiKey => write!(f, "You did not provide an API key"),
-            TraceixError::InvalidSearchType => write!(f, "Search must be of type capa or exif"),
-            TraceixError::NoUuidProvided => {
-                write!(f, "You did not provide a UUID required by the endpoint")
+#[cfg(any(feature = "blocking", feature = "async"))]
+use {
+    reqwest::header::{HeaderMap, HeaderValue, USER_AGENT},
+    secret::ApiKey,
+    std::env,
+    std::path::Path,
+    url::Url,
+};
+
+#[cfg(feature = "async")]
+mod async_client;
+#[cfg(any(feature = "blocking", feature = "async"))]
+mod builder;
+pub mod capa;
+pub mod cid;
+pub mod diff;
+mod dry_run;
+mod endpoint;
+mod error;
+pub mod exif;
+#[cfg(feature = "export")]
+pub mod export;
+pub mod filetype;
+pub mod filter;
+pub mod fuzzy;
+pub mod hashing;
+pub mod imphash;
+#[cfg(any(feature = "blocking", feature = "async"))]
+mod instrument;
+pub mod ioc;
+#[cfg(any(feature = "blocking", feature = "async"))]
+mod limits;
+pub mod nsrl;
+mod observer;
+mod options;
+pub mod pagination;
+mod progress;
+#[cfg(any(feature = "blocking", feature = "async"))]
+mod proxy;
+#[cfg(any(feature = "blocking", feature = "async"))]
+mod retry;
+#[cfg(any(feature = "blocking", feature = "async"))]
+mod secret;
+mod status;
+#[cfg(all(
+    any(feature = "blocking", feature = "async"),
+    any(feature = "default-tls", feature = "rustls")
+))]
+mod tls;
+
+cfg_blocking! {
+    mod alerts;
+    mod api;
+    #[cfg(feature = "archives")]
+    mod archive;
+    pub mod batch;
+    mod buffer_pool;
+    mod cancel;
+    mod capture;
+    #[cfg(feature = "vcr")]
+    mod cassette;
+    mod datasets;
+    pub mod dead_letter;
+    mod dedupe;
+    mod directory;
+    mod download;
+    pub mod ipfs;
+    mod large_upload;
+    mod middleware;
+    #[cfg(feature = "mock")]
+    mod mock;
+    mod notifications;
+    #[cfg(feature = "encoded-uploads")]
+    mod payload;
+    pub mod queue;
+    mod report;
+    mod rescan;
+    mod results;
+    mod resume;
+    #[cfg(feature = "s3")]
+    pub mod s3;
+    mod saved_search;
+    mod search;
+    mod submission;
+    mod submit;
+    mod throttle;
+    mod transport;
+    mod uploads;
+    #[cfg(feature = "signed-results")]
+    mod verify;
+    #[cfg(feature = "watch")]
+    pub mod watcher;
+    mod watchlist;
+}
+
+#[cfg(feature = "async")]
+pub use async_client::AsyncTraceixSdk;
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub use builder::{TraceixSdkBuilder, DEFAULT_TIMEOUT, DEFAULT_UPLOAD_CHUNK_SIZE};
+pub use capa::CapaReport;
+pub use cid::{Cid, CidVersion};
+pub use dry_run::{PlannedBody, PlannedRequest};
+pub use endpoint::{Endpoint, EndpointClass, DEFAULT_BASE_URL};
+pub use error::{RateLimitStatus, TraceixError};
+pub use exif::{ExifReport, ExtractionMode};
+pub use filetype::{FileType, Preflight};
+pub use fuzzy::{FuzzyHash, FuzzyHashType};
+pub use hashing::{FileDigests, FileHash, HashAlgorithm, HashingWriter};
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub use limits::RateLimit;
+pub use observer::{GiveUpEvent, Observer, RequestEvent, ResponseEvent, RetryEvent};
+pub use options::{Analyses, AnalysisSelection, Priority, UploadOptions};
+pub use progress::{ProgressCallback, UploadProgress};
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub use retry::{RetryPolicy, DEFAULT_MAX_RETRY_AFTER};
+pub use status::{AnalysisStatus, JobStatus};
+
+cfg_blocking! {
+    pub use alerts::{AlertPayload, AlertRule, AlertTrigger, NewAlertRule};
+    pub use api::TraceixApi;
+    #[cfg(feature = "archives")]
+    pub use archive::{
+        ArchiveMember, ArchiveOptions, ArchiveSummary, MemberSkipReason, SkippedMember,
+    };
+    pub use cancel::CancellationToken;
+    pub use capture::CapturedRequest;
+    #[cfg(feature = "vcr")]
+    pub use cassette::Cassette;
+    pub use datasets::{
+        DatasetEvent, DatasetListOptions, DatasetPages, DatasetSummary, DatasetWatch,
+        DEFAULT_DATASET_PAGE_SIZE,
+    };
+    pub use dedupe::UploadOutcome;
+    pub use directory::{
+        DirectorySummary, FailedFile, SkipReason, SkippedFile, SubmitOptions, SubmittedFile,
+    };
+    pub use download::Download;
+    pub use ipfs::{
+        CacheSyncReport, CachedDataset, CarArchive, CarExport, DatasetCache, DatasetDownload,
+        DatasetDownloadOptions, DatasetProgress, FailedDataset, GatewayHealth,
+        DEFAULT_IPFS_GATEWAYS,
+    };
+    #[cfg(feature = "ipfs-node")]
+    pub use ipfs::DEFAULT_IPFS_NODE;
+    pub use large_upload::{LargeUploadOptions, DEFAULT_PART_SIZE};
+    pub use middleware::Middleware;
+    #[cfg(feature = "mock")]
+    pub use mock::{Expectation, MockCall, MockTraceix};
+    pub use notifications::NotificationSettings;
+    #[cfg(feature = "encrypted-queue")]
+    pub use queue::EncryptedQueueStore;
+    pub use queue::{DirQueueStore, FlushResult, QueueStore, QueuedSample};
+    pub use report::{ReportFormat, ShareLink, ShareScope};
+    pub use rescan::RescanComparison;
+    pub use saved_search::SavedSearch;
+    pub use search::{HashSearch, HashSearchResponse, MAX_HASHES_PER_SEARCH};
+    pub use submission::{AnalysisResult, PollOptions, Submission, DEFAULT_ANALYSIS_TIMEOUT};
+    pub use submit::SubmitBuilder;
+    pub use transport::Transport;
+    pub use uploads::{
+        DeletedUpload, ListOptions, UploadPages, UploadSummary, DEFAULT_LIST_PAGE_SIZE,
+    };
+    pub use watchlist::WatchlistEntry;
+}
+
+/// Decode a JSON response body into a typed model.
+#[cfg(feature = "blocking")]
+fn decode_response<T: DeserializeOwned>(body: Value) -> Result<T, TraceixError> {
+    serde_json::from_value(body.clone())
+        .map_err(|e| TraceixError::UnexpectedResponse(format!("{e} in {body}")))
+}
+
+/// The file name sent with an upload.
+///
+/// Names that aren't valid UTF-8 (common on seized media) are transmitted as a
+/// sanitized lossy copy, with undecodable and control characters replaced by `_`.
+#[cfg(any(feature = "blocking", feature = "async"))]
+fn upload_file_name(path: &Path) -> String {
+    let Some(name) = path.file_name() else {
+        return "file".to_string();
+    };
+    if let Some(name) = name.to_str() {
+        return name.to_string();
+    }
+
+    let sanitized: String = name
+        .to_string_lossy()
+        .chars()
+        .map(|c| {
+            if c == char::REPLACEMENT_CHARACTER || c.is_control() || c == '"' {
+                '_'
+            } else {
+                c
             }
-            TraceixError::Http(e) => write!(f, "HTTP error: {e}"),
-            TraceixError::Io(e) => write!(f, "IO error: {e}"),
-        }
+        })
+        .collect();
+
+    if sanitized.is_empty() {
+        "file".to_string()
+    } else {
+        sanitized
     }
 }
 
-impl std::error::Error for TraceixError {}
+/// Responses from [`TraceixSdk::full_upload`]; `None` for analyses that weren't selected.
+#[derive(Clone, Debug, Default)]
+pub struct FullUpload {
+    pub prediction: Option<Value>,
+    pub capa: Option<Value>,
+    pub exif: Option<Value>,
+}
+
+/// Turn an error status into [`TraceixError::Api`], keeping the body the server sent.
+#[cfg(feature = "blocking")]
+fn check_response(resp: Response) -> Result<Response, TraceixError> {
+    let status = resp.status();
+    if !status.is_client_error() && !status.is_server_error() {
+        return Ok(resp);
+    }
+    let headers = resp.headers().clone();
+    let body = resp.bytes().unwrap_or_default();
+    Err(TraceixError::api(status, &headers, &body))
+}
 
-impl From<reqwest::Error> for TraceixError {
-    fn from(err: reqwest::Error) -> Self {
-        TraceixError::Http(err)
+/// Multipart text fields carried by `options`, shared by the blocking and async clients.
+#[cfg(any(feature = "blocking", feature = "async"))]
+fn upload_fields(options: &UploadOptions) -> Vec<(&'static str, String)> {
+    let mut fields = vec![("priority", options.priority.as_str().to_string())];
+    if let Some(upload_id) = &options.upload_id {
+        fields.push(("upload_id", upload_id.clone()));
     }
+    if !options.tags.is_empty() {
+        fields.push(("tags", options.tags.join(",")));
+    }
+    if let Some(description) = &options.description {
+        fields.push(("description", description.clone()));
+    }
+    if let Some(case_id) = &options.case_id {
+        fields.push(("case_id", case_id.clone()));
+    }
+    if let Some(source) = &options.source {
+        fields.push(("source", source.clone()));
+    }
+    if options.private {
+        fields.push(("private", "true".to_string()));
+    }
+    fields
+}
+
+/// Multipart body for an upload: the file part plus the fields carried by `options`.
+#[cfg(feature = "blocking")]
+fn upload_form(part: multipart::Part, options: &UploadOptions) -> multipart::Form {
+    upload_fields(options).into_iter().fold(
+        multipart::Form::new().part("file", part),
+        |form, (name, value)| form.text(name, value),
+    )
+}
+
+/// Version reported in the `User-Agent` header.
+#[cfg(any(feature = "blocking", feature = "async"))]
+const SDK_VERSION: &str = "0.0.0.1";
+
+/// Page size used when a listing is fetched in full on the caller's behalf.
+#[cfg(feature = "blocking")]
+const LIST_ALL_PAGE_SIZE: usize = 100;
+
+/// Whether TRACEIX_DISABLE_TELEMETRY leaves platform details in the user agent.
+#[cfg(any(feature = "blocking", feature = "async"))]
+fn telemetry_from_env() -> bool {
+    !env::var("TRACEIX_DISABLE_TELEMETRY")
+        .map(|v| v == "1")
+        .unwrap_or(false)
 }
 
-impl From<io::Error> for TraceixError {
-    fn from(err: io::Error) -> Self {
-        TraceixError::Io(err)
+#[cfg(any(feature = "blocking", feature = "async"))]
+fn build_user_agent(telemetry: bool, suffix: Option<&str>) -> String {
+    let mut ua = format!("Traceix/{}", SDK_VERSION);
+    if telemetry {
+        // Not exactly the same as Python's platform+python_version,
+        // but gives OS/arch + crate version.
+        let os = std::env::consts::OS;
+        let arch = std::env::consts::ARCH;
+        let crate_version = env!("CARGO_PKG_VERSION");
+        ua.push_str(&format!(" ({}-{} v{})", os, arch, crate_version));
     }
+    if let Some(suffix) = suffix.filter(|s| !s.is_empty()) {
+        ua.push(' ');
+        ua.push_str(suffix);
+    }
+
+    ua
+}
+
+/// Headers sent with every request: the API key and the user agent.
+///
+/// The header keeps its own copy of the key for the life of the client; it is marked
+/// sensitive, and the clients leave their headers out of `Debug` output.
+#[cfg(any(feature = "blocking", feature = "async"))]
+fn build_headers(api_key: &ApiKey, user_agent: &str) -> Result<HeaderMap, TraceixError> {
+    let mut headers = HeaderMap::new();
+
+    let mut key = HeaderValue::from_str(api_key.expose()).map_err(|_| {
+        TraceixError::InvalidArgument("API key is not a valid header value".to_string())
+    })?;
+    key.set_sensitive(true);
+    headers.insert("x-api-key", key);
+    headers.insert(
+        USER_AGENT,
+        HeaderValue::from_str(user_agent).map_err(|_| {
+            TraceixError::InvalidArgument(format!("invalid user agent {user_agent:?}"))
+        })?,
+    );
+
+    Ok(headers)
+}
+
+/// Resolve an endpoint path against the configured base URL.
+#[cfg(any(feature = "blocking", feature = "async"))]
+fn build_url(base_url: &Url, path: &str) -> Url {
+    // Append segment by segment so a base with a path prefix (e.g. an on-prem
+    // mirror under /traceix/) is kept, and each segment is percent-encoded.
+    let mut url = base_url.clone();
+    url.path_segments_mut()
+        .expect("base url validated at construction")
+        .pop_if_empty()
+        .extend(path.split('/').filter(|segment| !segment.is_empty()));
+    url
 }
 
-#[derive(Clone, Copy, Debug)]
+/// Validate a configured base URL.
+#[cfg(any(feature = "blocking", feature = "async"))]
+fn parse_base_url(raw: &str) -> Result<Url, TraceixError> {
+    let invalid = |reason: String| TraceixError::InvalidBaseUrl {
+        url: raw.to_string(),
+        reason,
+    };
+
+    let url = Url::parse(raw).map_err(|e| invalid(e.to_string()))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(invalid(format!("unsupported scheme {:?}", url.scheme())));
+    }
+    if url.cannot_be_a_base() {
+        return Err(invalid("URL cannot be used as a base".to_string()));
+    }
+    if url.query().is_some() || url.fragment().is_some() {
+        return Err(invalid("URL must not have a query or fragment".to_string()));
+    }
+
+    Ok(url)
+}
+
+/// The API root to use: `base_url` if given, otherwise TRACEIX_BASE_URL, otherwise
+/// [`DEFAULT_BASE_URL`].
+#[cfg(any(feature = "blocking", feature = "async"))]
+fn resolve_base_url(base_url: Option<&str>) -> Result<Url, TraceixError> {
+    match base_url {
+        Some(url) => parse_base_url(url),
+        None => match env::var("TRACEIX_BASE_URL") {
+            Ok(url) if !url.trim().is_empty() => parse_base_url(url.trim()),
+            _ => parse_base_url(DEFAULT_BASE_URL),
+        },
+    }
+}
+
+/// The API key to use: `api_key` if given, otherwise TRACEIX_API_KEY.
+#[cfg(any(feature = "blocking", feature = "async"))]
+fn resolve_api_key(api_key: Option<ApiKey>) -> Result<ApiKey, TraceixError> {
+    let key = match api_key {
+        Some(k) if !k.expose().is_empty() => k,
+        _ => ApiKey::new(env::var("TRACEIX_API_KEY").map_err(|_| TraceixError::NoApiKey)?),
+    };
+
+    if key.expose().is_empty() {
+        return Err(TraceixError::NoApiKey);
+    }
+    Ok(key)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum SearchType {
     Capa,
     Exif,
 }
 
+#[cfg(feature = "blocking")]
+#[derive(Clone)]
 pub struct TraceixSdk {
-    api_key: String,
-    base_url: String,
+    headers: HeaderMap,
+    base_url: Url,
     client: Client,
+    limits: Arc<limits::Limits>,
+    buffers: Arc<buffer_pool::BufferPool>,
+    throttle: Option<Arc<throttle::Throttle>>,
+    preflight: Option<Arc<Preflight>>,
+    cancel: Option<CancellationToken>,
+    priority: Priority,
+    dry_run: bool,
+    capture: Option<Arc<capture::RequestLog>>,
+    ipfs_gateways: Arc<ipfs::GatewayPool>,
+    #[cfg(feature = "ipfs-node")]
+    ipfs_node: Option<Url>,
+    observers: observer::Observers,
+    retry: Arc<RetryPolicy>,
+    timeout: Duration,
+    connect_timeout: Option<Duration>,
+    transport: Arc<dyn Transport>,
+    middleware: middleware::MiddlewareChain,
+    #[cfg(feature = "signed-results")]
+    verifier: Option<Arc<verify::ResultVerifier>>,
 }
 
+/// Shows where the client points; the headers, which hold the API key, are left out.
+#[cfg(feature = "blocking")]
+impl std::fmt::Debug for TraceixSdk {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TraceixSdk")
+            .field("base_url", &self.base_url.as_str())
+            .field("api_key", &"<redacted>")
+            .field("timeout", &self.timeout)
+            .field("connect_timeout", &self.connect_timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "blocking")]
 impl TraceixSdk {
-    pub const SDK_VERSION: &'static str = "0.0.0.1";
+    pub const SDK_VERSION: &'static str = SDK_VERSION;
 
     /// Initialize the SDK. If `api_key` is `None`, it will read TRACEIX_API_KEY from the environment.
+    /// The API root is taken from TRACEIX_BASE_URL when set.
     pub fn new(api_key: Option<String>) -> Result<Self, TraceixError> {
-        let key = match api_key {
-            Some(k) if !k.is_empty() => k,
-            _ => env::var("TRACEIX_API_KEY").map_err(|_| TraceixError::NoApiKey)?,
-        };
-
-        if key.is_empty() {
-            return Err(TraceixError::NoApiKey);
+        let mut builder = TraceixSdkBuilder::new();
+        if let Some(key) = api_key {
+            builder = builder.api_key(key);
         }
+        builder.build()
+    }
 
-        let client = Client::builder().build()?;
+    /// Start configuring an SDK instance.
+    pub fn builder() -> TraceixSdkBuilder {
+        TraceixSdkBuilder::new()
+    }
 
-        Ok(Self {
-            api_key: key,
-            base_url: "https://ai.perkinsfund.org".to_string(),
-            client,
-        })
+    /// The most recent requests, oldest first, when the client was built with
+    /// [`capture_requests`](TraceixSdkBuilder::capture_requests); empty otherwise.
+    /// Handles derived from this one share its capture.
+    pub fn captured_requests(&self) -> Vec<CapturedRequest> {
+        self.capture
+            .as_ref()
+            .map(|log| log.entries())
+            .unwrap_or_default()
     }
 
-    fn build_user_agent(&self) -> String {
-        let telemetry_disabled = env::var("TRACEIX_DISABLE_TELEMETRY")
-            .map(|v| v == "1")
-            .unwrap_or(false);
+    /// A handle sharing this client, limits, and observers, but retrying with `policy`,
+    /// e.g. to disable retries for one non-idempotent call.
+    pub fn with_retry_policy(&self, policy: RetryPolicy) -> TraceixSdk {
+        TraceixSdk {
+            retry: Arc::new(policy),
+            ..self.clone()
+        }
+    }
+
+    /// A handle sharing this client, limits, and observers, but giving each request
+    /// `timeout` to complete instead of the builder's
+    /// [`timeout`](TraceixSdkBuilder::timeout), e.g. to bound one slow call.
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use traceix_sdk::TraceixSdk;
+    /// # fn main() -> Result<(), traceix_sdk::TraceixError> {
+    /// let sdk = TraceixSdk::new(None)?;
+    /// match sdk.with_timeout(Duration::from_secs(5)).ai_prediction("sample.exe") {
+    ///     Err(e) if e.is_timeout() => eprintln!("gave up: {e}"),
+    ///     other => println!("{:?}", other?),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_timeout(&self, timeout: Duration) -> TraceixSdk {
+        TraceixSdk {
+            timeout,
+            ..self.clone()
+        }
+    }
 
-        let mut ua = format!("Traceix/{}", Self::SDK_VERSION);
-        if !telemetry_disabled {
-            // Not exactly the same as Python's platform+python_version,
-            // but gives OS/arch + crate version.
-            let os = std::env::consts::OS;
-            let arch = std::env::consts::ARCH;
-            let crate_version = env!("CARGO_PKG_VERSION");
-            ua.push_str(&format!(" ({}-{} v{})", os, arch, crate_version));
+    /// A handle sharing this client, limits, and observers, whose operations stop with
+    /// [`TraceixError::Cancelled`] once `token` is cancelled: requests waiting on the
+    /// server are abandoned, upload bodies stop streaming, downloads stop being written,
+    /// and retry and polling waits end early. Operations already started on other
+    /// handles are unaffected.
+    ///
+    /// The blocking HTTP client can't interrupt a request it is waiting on, so an
+    /// abandoned request lives on in a background thread, holding its connection until
+    /// the server responds or the [timeout](TraceixSdkBuilder::timeout) expires. Its
+    /// response is then discarded.
+    ///
+    /// ```no_run
+    /// # use std::thread;
+    /// # use traceix_sdk::{Analyses, CancellationToken, TraceixSdk};
+    /// # fn main() -> Result<(), traceix_sdk::TraceixError> {
+    /// let sdk = TraceixSdk::new(None)?;
+    /// let token = CancellationToken::new();
+    /// let worker = {
+    ///     let sdk = sdk.with_cancellation(&token);
+    ///     thread::spawn(move || sdk.full_upload("disk.img", Analyses::all()))
+    /// };
+    /// // Later, e.g. when the user clicks "Cancel":
+    /// token.cancel();
+    /// match worker.join().unwrap() {
+    ///     Err(e) if e.is_cancelled() => println!("cancelled"),
+    ///     other => println!("{:?}", other?.prediction),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_cancellation(&self, token: &CancellationToken) -> TraceixSdk {
+        TraceixSdk {
+            cancel: Some(token.clone()),
+            ..self.clone()
         }
+    }
 
-        ua
+    /// A handle whose requests wait for client-side limits at `priority`.
+    pub(crate) fn with_priority(&self, priority: Priority) -> TraceixSdk {
+        TraceixSdk {
+            priority,
+            ..self.clone()
+        }
     }
 
     fn build_headers(&self) -> HeaderMap {
-        let mut headers = HeaderMap::new();
+        self.headers.clone()
+    }
 
-        headers.insert(
-            "x-api-key",
-            HeaderValue::from_str(&self.api_key).expect("invalid api key for header"),
-        );
-        headers.insert(
-            USER_AGENT,
-            HeaderValue::from_str(&self.build_user_agent()).expect("invalid user agent"),
-        );
+    fn build_url(&self, path: &str) -> Url {
+        build_url(&self.base_url, path)
+    }
 
-        headers
+    /// Wrap a request body so it is read no faster than the configured
+    /// [`max_upload_rate`](TraceixSdkBuilder::max_upload_rate) and stops on cancellation.
+    fn body_reader<R: std::io::Read>(
+        &self,
+        inner: R,
+    ) -> cancel::CancellableReader<throttle::ThrottledReader<R>> {
+        cancel::CancellableReader::new(
+            throttle::ThrottledReader::new(inner, self.throttle.clone()),
+            self.cancel.clone(),
+        )
     }
 
-    fn build_url(&self, path: &str) -> String {
-        // Python: f"{self.base_url}/{path}"
-        // Their paths start with "/", so this effectively becomes base_url + path.
-        format!("{}{}", self.base_url, path)
+    /// Sleep between retries or polls, returning early if the handle is cancelled.
+    pub(crate) fn pause(&self, duration: Duration) -> Result<(), TraceixError> {
+        match &self.cancel {
+            Some(token) => token.sleep(duration),
+            None => {
+                thread::sleep(duration);
+                Ok(())
+            }
+        }
     }
 
-    fn build_file_form(&self, filename: &str) -> Result<multipart::Form, TraceixError> {
-        let file = File::open(filename)?;
-        let name = Path::new(filename)
-            .file_name()
-            .and_then(|s| s.to_str())
-            .unwrap_or("file")
-            .to_string();
+    fn build_file_form(
+        &self,
+        filename: &Path,
+        options: &UploadOptions,
+    ) -> Result<multipart::Form, TraceixError> {
+        if let Some(preflight) = &self.preflight {
+            preflight.check(filename)?;
+        }
+        let part = if options.strip_metadata {
+            let data = Zeroizing::new(exif::strip_exif(filename)?);
+            let len = data.len() as u64;
+            let reader = ProgressReader::new(
+                self.body_reader(Cursor::new(data)),
+                len,
+                options.progress.clone(),
+            );
+            multipart::Part::reader_with_length(reader, len)
+        } else {
+            let file = File::open(filename)?;
+            let len = file.metadata()?.len();
+            let reader = ProgressReader::new(
+                self.body_reader(self.buffers.reader(file)),
+                len,
+                options.progress.clone(),
+            );
+            multipart::Part::reader_with_length(reader, len)
+        };
+        let name = upload_file_name(filename);
 
-        let part = multipart::Part::reader(file)
+        let part = part
             .file_name(name)
             .mime_str("application/octet-stream")
             .map_err(TraceixError::Http)?;
 
-        Ok(multipart::Form::new().part("file", part))
+        Ok(upload_form(part, options))
+    }
+
+    /// Send a POST to `endpoint`, letting `build` attach the body, and decode the JSON response.
+    ///
+    /// Any failure is wrapped in [`TraceixError::Context`] naming the endpoint,
+    /// the URL, and `source` (the local file involved, if any).
+    fn execute<F>(
+        &self,
+        endpoint: &Endpoint,
+        source: Option<&Path>,
+        build: F,
+    ) -> Result<Value, TraceixError>
+    where
+        F: FnMut(RequestBuilder) -> Result<RequestBuilder, TraceixError>,
+    {
+        let resp = self.send(endpoint, source, build)?;
+        let url = resp.url().clone();
+        resp.json().map_err(|e| {
+            TraceixError::from(e)
+                .timed_out(Some(self.timeout), None)
+                .with_context(endpoint, &url, source)
+        })
+    }
+
+    /// Like [`TraceixSdk::execute`], but hands back the successful response undecoded
+    /// so large bodies can be streamed.
+    ///
+    /// Failed attempts are retried according to the SDK's [`RetryPolicy`]; `build` is
+    /// called again for every attempt so bodies read from files are reopened.
+    fn send<F>(
+        &self,
+        endpoint: &Endpoint,
+        source: Option<&Path>,
+        mut build: F,
+    ) -> Result<Response, TraceixError>
+    where
+        F: FnMut(RequestBuilder) -> Result<RequestBuilder, TraceixError>,
+    {
+        let url = self.build_url(endpoint.path);
+        self.send_request(endpoint, &url, source, || {
+            let request = self.client.post(url.clone()).headers(self.build_headers());
+            Ok(build(request)?.build()?)
+        })
+    }
+
+    /// Send the request `build` makes for `endpoint` to `url`, with the middleware,
+    /// limits, observers, capture, metrics, tracing, cancellation and retries of every
+    /// SDK call. Used directly for requests that aren't to the API, such as IPFS
+    /// gateways, which `build` leaves without the API key.
+    pub(crate) fn send_request<F>(
+        &self,
+        endpoint: &Endpoint,
+        url: &Url,
+        source: Option<&Path>,
+        mut build: F,
+    ) -> Result<Response, TraceixError>
+    where
+        F: FnMut() -> Result<Request, TraceixError>,
+    {
+        let span = instrument::enter_call(endpoint, url);
+
+        let mut send = |attempt: u32| -> Result<Response, TraceixError> {
+            let mut request = build()?;
+            let timeout = *request.timeout_mut().get_or_insert(self.timeout);
+            self.middleware.request(endpoint, &mut request)?;
+            if self.dry_run {
+                let planned = PlannedRequest::new(Some(endpoint.name), &request, source);
+                return Err(TraceixError::DryRun(Box::new(planned)));
+            }
+            let planned = self
+                .capture
+                .as_ref()
+                .map(|_| PlannedRequest::new(Some(endpoint.name), &request, source));
+            let permit = self.limits.acquire(endpoint.class, self.priority);
+            self.observers.request(&RequestEvent {
+                endpoint,
+                url,
+                source,
+            });
+
+            let started = Instant::now();
+            let sent_at = chrono::Utc::now();
+            let result = match &self.cancel {
+                Some(token) => {
+                    let transport = Arc::clone(&self.transport);
+                    // A cancelled request carries on in the background until it
+                    // finishes, and keeps its concurrency slot until then.
+                    token.run(move || {
+                        let _permit = permit;
+                        transport.execute(request)
+                    })
+                }
+                None => {
+                    let _permit = permit;
+                    self.transport.execute(request)
+                }
+            }
+            .map_err(|e| e.timed_out(Some(timeout), self.connect_timeout))
+            .and_then(check_response)
+            .and_then(|mut resp| {
+                self.middleware.response(endpoint, &mut resp)?;
+                Ok(resp)
+            });
+            let elapsed = started.elapsed();
+            let result = match (&self.capture, planned) {
+                (Some(log), Some(planned)) => log.record(planned, sent_at, elapsed, result),
+                _ => result,
+            };
+            let status = match &result {
+                Ok(resp) => Some(resp.status()),
+                Err(e) => e.status(),
+            };
+            instrument::record_request(endpoint, status.map(|s| s.as_u16()), elapsed);
+            instrument::trace_attempt(
+                attempt,
+                status.map(|s| s.as_u16()),
+                elapsed,
+                result.as_ref().err(),
+            );
+
+            self.observers.response(&ResponseEvent {
+                endpoint,
+                url,
+                source,
+                status,
+                elapsed,
+                error: result.as_ref().err(),
+            });
+            result
+        };
+
+        let mut attempt = 1;
+        loop {
+            let error = match send(attempt) {
+                Ok(resp) => {
+                    instrument::finish_call(&span, attempt, Some(resp.status().as_u16()));
+                    return Ok(resp);
+                }
+                Err(e) => e,
+            };
+            if !self.retry.should_retry(endpoint, &error, attempt) {
+                instrument::finish_call(&span, attempt, error.status().map(|s| s.as_u16()));
+                if attempt > 1 {
+                    self.observers.giveup(&GiveUpEvent {
+                        endpoint: Some(endpoint),
+                        source,
+                        attempts: attempt,
+                        error: &error,
+                    });
+                }
+                return Err(error.with_context(endpoint, url, source));
+            }
+
+            let delay = self.retry.delay(attempt, &error);
+            instrument::trace_retry(attempt, delay, &error);
+            self.observers.retry(&RetryEvent {
+                endpoint: Some(endpoint),
+                source,
+                attempt,
+                delay,
+                error: &error,
+            });
+            self.pause(delay)
+                .map_err(|e| e.with_context(endpoint, url, source))?;
+            attempt += 1;
+        }
+    }
+
+    fn upload_file(
+        &self,
+        endpoint: &Endpoint,
+        filename: &Path,
+        options: &UploadOptions,
+    ) -> Result<Value, TraceixError> {
+        let sdk = self.with_priority(options.priority);
+        sdk.execute(endpoint, Some(filename), |mut request| {
+            if let Some(timeout) = options.timeout {
+                request = request.timeout(timeout);
+            }
+            Ok(request.multipart(self.build_file_form(filename, options)?))
+        })
+    }
+
+    /// Upload an already-built file part for AI prediction. For sources that aren't local files.
+    pub(crate) fn upload_part(
+        &self,
+        part: multipart::Part,
+        options: &UploadOptions,
+    ) -> Result<Submission, TraceixError> {
+        // The part streams from a one-shot reader, so it can only be sent once.
+        let sdk = self
+            .with_retry_policy(RetryPolicy::none())
+            .with_priority(options.priority);
+        let mut part = Some(part);
+        let response = sdk.execute(&Endpoint::AI_PREDICTION, None, |mut request| {
+            if let Some(timeout) = options.timeout {
+                request = request.timeout(timeout);
+            }
+            let part = part.take().expect("retries are disabled");
+            Ok(request.multipart(upload_form(part, options)))
+        })?;
+        Submission::from_response(self, response)
+    }
+
+    fn post_json(&self, endpoint: &Endpoint, body: &Value) -> Result<Value, TraceixError> {
+        self.execute(endpoint, None, |request| Ok(request.json(body)))
     }
 
-    /// Full upload: prediction, CAPA extraction, and EXIF extraction.
+    /// Every item of the listing at `endpoint`, whose responses carry a page of items in
+    /// `field`, requested a page at a time until the server has no more.
+    fn list_all<T: DeserializeOwned>(
+        &self,
+        endpoint: &Endpoint,
+        field: &str,
+    ) -> Result<Vec<T>, TraceixError> {
+        Paginator::new(LIST_ALL_PAGE_SIZE, |cursor, page_size| {
+            let mut body = serde_json::Map::new();
+            let offset = pagination::insert_cursor(&mut body, cursor, page_size);
+            let mut response = self.post_json(endpoint, &Value::Object(body))?;
+            let next_token = response
+                .get("next_token")
+                .and_then(Value::as_str)
+                .map(str::to_string);
+            let items = match response.get_mut(field) {
+                Some(items) => decode_response(items.take())?,
+                None => {
+                    return Err(TraceixError::UnexpectedResponse(format!(
+                        "no {field} in response {response}"
+                    )))
+                }
+            };
+            Ok(Page::from_response(items, next_token, offset, page_size))
+        })
+        .collect()
+    }
+
+    /// Like [`TraceixSdk::post_json`], but also reports whether the response body was
+    /// signed by the configured result verification key.
+    fn post_json_verified(
+        &self,
+        endpoint: &Endpoint,
+        body: &Value,
+    ) -> Result<(Value, bool), TraceixError> {
+        let resp = self.send(endpoint, None, |request| Ok(request.json(body)))?;
+        let url = resp.url().clone();
+        #[cfg(feature = "signed-results")]
+        let signature = resp.headers().get(verify::RESULT_SIGNATURE_HEADER).cloned();
+        let bytes = resp
+            .bytes()
+            .map_err(|e| TraceixError::from(e).with_context(endpoint, &url, None))?;
+        let value = serde_json::from_slice(&bytes).map_err(|e| {
+            TraceixError::UnexpectedResponse(format!("{e} in response body"))
+                .with_context(endpoint, &url, None)
+        })?;
+
+        #[cfg(feature = "signed-results")]
+        let verified = self
+            .verifier
+            .as_ref()
+            .is_some_and(|verifier| verifier.verify(signature.as_ref(), &bytes));
+        #[cfg(not(feature = "signed-results"))]
+        let verified = false;
+
+        Ok((value, verified))
+    }
+
+    /// Full upload: runs the selected analyses (e.g. `Analyses::PREDICTION | Analyses::CAPA`).
     pub fn full_upload(
         &self,
-        filename: &str,
-    ) -> Result<(Value, Value, Value), TraceixError> {
-        let ai_data = self.ai_prediction(filename)?;
-        let capa_status = self.capa_extraction(filename)?;
-        let exif_data = self.exif_extraction(filename)?;
-        Ok((ai_data, capa_status, exif_data))
+        filename: impl AsRef<Path>,
+        analyses: Analyses,
+    ) -> Result<FullUpload, TraceixError> {
+        self.full_upload_with_options(filename, analyses, &UploadOptions::default())
     }
 
-    /// Sends a request to the prediction endpoint.
-    pub fn ai_prediction(&self, filename: &str) -> Result<Value, TraceixError> {
-        let url = self.build_url("/api/traceix/v1/upload");
-        let headers = self.build_headers();
-        let form = self.build_file_form(filename)?;
+    /// Like [`TraceixSdk::full_upload`], selecting analyses with named flags. Endpoints
+    /// that weren't selected are not called and their fields in [`FullUpload`] are `None`.
+    ///
+    /// ```no_run
+    /// # use traceix_sdk::{AnalysisSelection, TraceixSdk};
+    /// # fn main() -> Result<(), traceix_sdk::TraceixError> {
+    /// let sdk = TraceixSdk::new(None)?;
+    /// let upload = sdk.full_upload_with(
+    ///     "sample.exe",
+    ///     AnalysisSelection {
+    ///         prediction: true,
+    ///         ..AnalysisSelection::default()
+    ///     },
+    /// )?;
+    /// assert!(upload.capa.is_none() && upload.exif.is_none());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn full_upload_with(
+        &self,
+        filename: impl AsRef<Path>,
+        selection: AnalysisSelection,
+    ) -> Result<FullUpload, TraceixError> {
+        self.full_upload(filename, selection.into())
+    }
 
-        let resp = self
-            .client
-            .post(&url)
-            .headers(headers)
-            .multipart(form)
-            .send()?
-            .error_for_status()?;
+    /// Full upload with explicit upload options (e.g. priority).
+    pub fn full_upload_with_options(
+        &self,
+        filename: impl AsRef<Path>,
+        analyses: Analyses,
+        options: &UploadOptions,
+    ) -> Result<FullUpload, TraceixError> {
+        if analyses.is_empty() {
+            return Err(TraceixError::InvalidArgument(
+                "no analyses selected".to_string(),
+            ));
+        }
 
-        Ok(resp.json()?)
+        let filename = filename.as_ref();
+        let mut upload = FullUpload::default();
+        if analyses.contains(Analyses::PREDICTION) {
+            upload.prediction = Some(self.ai_prediction_with_options(filename, options)?);
+        }
+        if analyses.contains(Analyses::CAPA) {
+            upload.capa = Some(self.capa_extraction_with_options(filename, options)?);
+        }
+        if analyses.contains(Analyses::EXIF) {
+            upload.exif = Some(self.exif_extraction_with_options(filename, options)?);
+        }
+        Ok(upload)
+    }
+
+    /// Sends a request to the prediction endpoint.
+    pub fn ai_prediction(&self, filename: impl AsRef<Path>) -> Result<Value, TraceixError> {
+        self.ai_prediction_with_options(filename, &UploadOptions::default())
+    }
+
+    /// Sends a request to the prediction endpoint with explicit upload options.
+    pub fn ai_prediction_with_options(
+        &self,
+        filename: impl AsRef<Path>,
+        options: &UploadOptions,
+    ) -> Result<Value, TraceixError> {
+        self.upload_file(&Endpoint::AI_PREDICTION, filename.as_ref(), options)
     }
 
     /// Check the status of a provided UUID.
@@ -170,137 +972,107 @@ impl TraceixSdk {
             return Err(TraceixError::NoUuidProvided);
         }
 
-        let url = self.build_url("/api/v1/traceix/status");
-        let headers = self.build_headers();
-        let body = serde_json::json!({ "uuid": uuid });
+        self.post_json(
+            &Endpoint::CHECK_STATUS,
+            &serde_json::json!({ "uuid": uuid }),
+        )
+    }
 
-        let resp = self
-            .client
-            .post(&url)
-            .headers(headers)
-            .json(&body)
-            .send()?
-            .error_for_status()?;
+    /// Cancel a queued or running analysis by UUID.
+    pub fn cancel(&self, uuid: &str) -> Result<Value, TraceixError> {
+        if uuid.is_empty() {
+            return Err(TraceixError::NoUuidProvided);
+        }
 
-        Ok(resp.json()?)
+        self.post_json(&Endpoint::CANCEL, &serde_json::json!({ "uuid": uuid }))
     }
 
     /// Search by file hash (capa or exif).
+    ///
+    /// `file_hash` may be an MD5, SHA-1 or SHA-256 hex digest, or a [`FileHash`]; it is
+    /// validated first and sent in the field for its algorithm.
     pub fn hash_search(
         &self,
-        file_hash: &str,
+        file_hash: impl AsRef<str>,
         search_type: SearchType,
     ) -> Result<Value, TraceixError> {
-        let path = match search_type {
-            SearchType::Capa => "/api/traceix/v1/capa/search",
-            SearchType::Exif => "/api/traceix/v1/exif/search",
+        let endpoint = match search_type {
+            SearchType::Capa => &Endpoint::CAPA_SEARCH,
+            SearchType::Exif => &Endpoint::EXIF_SEARCH,
         };
+        let hash = FileHash::parse(file_hash.as_ref())?;
 
-        let url = self.build_url(path);
-        let mut headers = self.build_headers();
-        headers.insert(
-            "content-type",
-            HeaderValue::from_static("application/json"),
-        );
-
-        let body = serde_json::json!({ "sha256": file_hash });
-
-        let resp = self
-            .client
-            .post(&url)
-            .headers(headers)
-            .json(&body)
-            .send()?
-            .error_for_status()?;
+        self.post_json(
+            endpoint,
+            &serde_json::json!({ hash.field(): hash.as_str() }),
+        )
+    }
 
-        Ok(resp.json()?)
+    /// Hash a local file with SHA-256 and search for it, without uploading the file.
+    pub fn hash_search_file(
+        &self,
+        path: impl AsRef<Path>,
+        search_type: SearchType,
+    ) -> Result<Value, TraceixError> {
+        let path = path.as_ref();
+        let sha256 = hashing::sha256_file(path).map_err(|e| TraceixError::file(path, e))?;
+        self.hash_search(&sha256, search_type)
     }
 
     /// Extract the CAPA capabilities from the filename.
-    pub fn capa_extraction(&self, filename: &str) -> Result<Value, TraceixError> {
-        let url = self.build_url("/api/traceix/v1/capa");
-        let headers = self.build_headers();
-        let form = self.build_file_form(filename)?;
-
-        let resp = self
-            .client
-            .post(&url)
-            .headers(headers)
-            .multipart(form)
-            .send()?
-            .error_for_status()?;
+    pub fn capa_extraction(&self, filename: impl AsRef<Path>) -> Result<Value, TraceixError> {
+        self.capa_extraction_with_options(filename, &UploadOptions::default())
+    }
 
-        Ok(resp.json()?)
+    /// Extract the CAPA capabilities with explicit upload options.
+    pub fn capa_extraction_with_options(
+        &self,
+        filename: impl AsRef<Path>,
+        options: &UploadOptions,
+    ) -> Result<Value, TraceixError> {
+        self.upload_file(&Endpoint::CAPA_EXTRACTION, filename.as_ref(), options)
     }
 
     /// Extract EXIF metadata from the filename.
-    pub fn exif_extraction(&self, filename: &str) -> Result<Value, TraceixError> {
-        let url = self.build_url("/api/traceix/v1/exif");
-        let headers = self.build_headers();
-        let form = self.build_file_form(filename)?;
-
-        let resp = self
-            .client
-            .post(&url)
-            .headers(headers)
-            .multipart(form)
-            .send()?
-            .error_for_status()?;
+    pub fn exif_extraction(&self, filename: impl AsRef<Path>) -> Result<Value, TraceixError> {
+        self.exif_extraction_with_options(filename, &UploadOptions::default())
+    }
 
-        Ok(resp.json()?)
+    /// Extract EXIF metadata with explicit upload options.
+    pub fn exif_extraction_with_options(
+        &self,
+        filename: impl AsRef<Path>,
+        options: &UploadOptions,
+    ) -> Result<Value, TraceixError> {
+        self.upload_file(&Endpoint::EXIF_EXTRACTION, filename.as_ref(), options)
     }
 
-    /// List all public IPFS datasets currently available.
+    /// List all public IPFS datasets currently available. For typed, filtered results
+    /// a page at a time, use [`TraceixSdk::list_datasets`].
     ///
     /// Note: in Python you *could* skip the API key, but here we still send headers.
     pub fn list_all_ipfs_datasets(&self) -> Result<Value, TraceixError> {
-        let url = self.build_url("/api/traceix/v1/ipfs/listall");
-        let headers = self.build_headers();
-
-        let resp = self
-            .client
-            .post(&url)
-            .headers(headers)
-            .send()?
-            .error_for_status()?;
-
-        Ok(resp.json()?)
+        self.execute(&Endpoint::IPFS_LIST_ALL, None, Ok)
     }
 
     /// Get a public IPFS dataset by CID.
+    ///
+    /// The CID is validated first, so a malformed one fails with
+    /// [`TraceixError::InvalidArgument`] saying what is wrong without a request being
+    /// made. It is sent in its canonical form (see [`Cid`]).
     pub fn get_public_ipfs_dataset(&self, cid: &str) -> Result<Value, TraceixError> {
-        let url = self.build_url("/api/traceix/v1/ipfs/search");
-        let headers = self.build_headers();
-        let body = serde_json::json!({ "cid": cid });
-
-        let resp = self
-            .client
-            .post(&url)
-            .headers(headers)
-            .json(&body)
-            .send()?
-            .error_for_status()?;
-
-        Ok(resp.json()?)
+        let cid = Cid::parse(cid)?;
+        self.post_json(
+            &Endpoint::IPFS_SEARCH,
+            &serde_json::json!({ "cid": cid.to_string() }),
+        )
     }
 
     /// Search by file hash to see if the dataset has been uploaded to the public domain.
-    pub fn search_ipfs_dataset_by_hash(
-        &self,
-        file_hash: &str,
-    ) -> Result<Value, TraceixError> {
-        let url = self.build_url("/api/traceix/v1/ipfs/find");
-        let headers = self.build_headers();
-        let body = serde_json::json!({ "sha_hash": file_hash });
-
-        let resp = self
-            .client
-            .post(&url)
-            .headers(headers)
-            .json(&body)
-            .send()?
-            .error_for_status()?;
-
-        Ok(resp.json()?)
+    pub fn search_ipfs_dataset_by_hash(&self, file_hash: &str) -> Result<Value, TraceixError> {
+        self.post_json(
+            &Endpoint::IPFS_FIND,
+            &serde_json::json!({ "sha_hash": file_hash }),
+        )
     }
 }
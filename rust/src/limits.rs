@@ -0,0 +1,404 @@
+// src/limits.rs
+
+use crate::endpoint::EndpointClass;
+use crate::options::Priority;
+use std::collections::HashMap;
+#[cfg(feature = "blocking")]
+use std::sync::Arc;
+use std::sync::{Condvar, Mutex};
+#[cfg(feature = "blocking")]
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Number of [`Priority`] levels, indexed by `Priority as usize`.
+const PRIORITIES: usize = 3;
+
+/// How often async callers check for a free concurrency slot; blocking callers are
+/// woken as soon as one is released.
+#[cfg(feature = "async")]
+const ASYNC_POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// Whether a waiter at `level` must let a higher-priority waiter go first.
+fn outranked(waiting: &[usize; PRIORITIES], level: usize) -> bool {
+    waiting[level + 1..].iter().any(|&n| n > 0)
+}
+
+/// A request rate: at most `requests` per `per`, allowing bursts up to `requests`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RateLimit {
+    pub requests: u32,
+    pub per: Duration,
+}
+
+impl RateLimit {
+    pub fn new(requests: u32, per: Duration) -> Self {
+        Self {
+            requests: requests.max(1),
+            per,
+        }
+    }
+
+    pub fn per_second(requests: u32) -> Self {
+        Self::new(requests, Duration::from_secs(1))
+    }
+
+    pub fn per_minute(requests: u32) -> Self {
+        Self::new(requests, Duration::from_secs(60))
+    }
+}
+
+/// Token bucket enforcing a [`RateLimit`].
+#[derive(Debug)]
+struct TokenBucket {
+    limit: RateLimit,
+    state: Mutex<BucketState>,
+}
+
+#[derive(Debug)]
+struct BucketState {
+    tokens: f64,
+    last: Instant,
+    /// Callers waiting for a token, by priority.
+    waiting: [usize; PRIORITIES],
+}
+
+impl TokenBucket {
+    fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            state: Mutex::new(BucketState {
+                tokens: f64::from(limit.requests),
+                last: Instant::now(),
+                waiting: [0; PRIORITIES],
+            }),
+        }
+    }
+
+    /// Block until a token is available and no higher-priority caller is waiting for
+    /// one, then take it.
+    #[cfg(feature = "blocking")]
+    fn acquire(&self, priority: Priority) {
+        let level = priority as usize;
+        self.state.lock().unwrap().waiting[level] += 1;
+        loop {
+            let wait = {
+                let mut state = self.state.lock().unwrap();
+                match self.take(&mut state, level) {
+                    Ok(()) => {
+                        state.waiting[level] -= 1;
+                        return;
+                    }
+                    Err(wait) => wait,
+                }
+            };
+            thread::sleep(wait);
+        }
+    }
+
+    /// Like [`acquire`](Self::acquire), sleeping on the async runtime instead of the
+    /// thread. Dropping the future gives up the caller's place in the queue.
+    #[cfg(feature = "async")]
+    async fn acquire_async(&self, priority: Priority) {
+        let level = priority as usize;
+        self.state.lock().unwrap().waiting[level] += 1;
+        let _waiting = BucketWaiter {
+            bucket: self,
+            level,
+        };
+        loop {
+            let wait = match self.take(&mut self.state.lock().unwrap(), level) {
+                Ok(()) => return,
+                Err(wait) => wait,
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Refill the bucket and take a token for a caller at `level`, or say how long to
+    /// wait before trying again.
+    fn take(&self, state: &mut BucketState, level: usize) -> Result<(), Duration> {
+        let capacity = f64::from(self.limit.requests);
+        let per_token = self.limit.per.as_secs_f64() / capacity;
+        let now = Instant::now();
+        if per_token > 0.0 {
+            state.tokens = (state.tokens
+                + now.duration_since(state.last).as_secs_f64() / per_token)
+                .min(capacity);
+        } else {
+            state.tokens = capacity;
+        }
+        state.last = now;
+
+        let outranked = outranked(&state.waiting, level);
+        if !outranked && state.tokens >= 1.0 {
+            state.tokens -= 1.0;
+            return Ok(());
+        }
+        let refill = Duration::from_secs_f64((1.0 - state.tokens).max(0.0) * per_token);
+        if outranked {
+            // Check again once the higher-priority caller has had its turn.
+            Err(refill.max(Duration::from_millis(1)))
+        } else {
+            Err(refill)
+        }
+    }
+}
+
+/// Counts an async caller as waiting for a token until it is served or gives up.
+#[cfg(feature = "async")]
+struct BucketWaiter<'a> {
+    bucket: &'a TokenBucket,
+    level: usize,
+}
+
+#[cfg(feature = "async")]
+impl Drop for BucketWaiter<'_> {
+    fn drop(&mut self) {
+        self.bucket.state.lock().unwrap().waiting[self.level] -= 1;
+    }
+}
+
+/// A counting semaphore bounding in-flight requests for one endpoint class. Free slots
+/// go to the highest-priority waiter first.
+#[derive(Debug)]
+struct Semaphore {
+    state: Mutex<SemaphoreState>,
+    released: Condvar,
+}
+
+#[derive(Debug)]
+struct SemaphoreState {
+    available: usize,
+    waiting: [usize; PRIORITIES],
+}
+
+impl Semaphore {
+    fn new(permits: usize) -> Self {
+        Self {
+            state: Mutex::new(SemaphoreState {
+                available: permits,
+                waiting: [0; PRIORITIES],
+            }),
+            released: Condvar::new(),
+        }
+    }
+
+    #[cfg(feature = "blocking")]
+    fn acquire(&self, priority: Priority) -> Permit<'_> {
+        let level = priority as usize;
+        let mut state = self.state.lock().unwrap();
+        state.waiting[level] += 1;
+        while state.available == 0 || outranked(&state.waiting, level) {
+            state = self.released.wait(state).unwrap();
+        }
+        state.waiting[level] -= 1;
+        state.available -= 1;
+        if state.available > 0 {
+            // A lower-priority waiter may have been held back only by this one.
+            self.released.notify_all();
+        }
+        Permit {
+            semaphore: Some(self),
+        }
+    }
+
+    /// Like [`acquire`](Self::acquire), polling on the async runtime instead of blocking
+    /// the thread. Dropping the future gives up the caller's place in the queue.
+    #[cfg(feature = "async")]
+    async fn acquire_async(&self, priority: Priority) -> Permit<'_> {
+        let level = priority as usize;
+        self.state.lock().unwrap().waiting[level] += 1;
+        let waiting = SemaphoreWaiter {
+            semaphore: self,
+            level,
+        };
+        loop {
+            {
+                let mut state = self.state.lock().unwrap();
+                if state.available > 0 && !outranked(&state.waiting, level) {
+                    state.available -= 1;
+                    break;
+                }
+            }
+            tokio::time::sleep(ASYNC_POLL_INTERVAL).await;
+        }
+        drop(waiting);
+        Permit {
+            semaphore: Some(self),
+        }
+    }
+
+    fn release(&self) {
+        self.state.lock().unwrap().available += 1;
+        self.released.notify_all();
+    }
+}
+
+/// Counts an async caller as waiting for a slot until it gets one or gives up, then
+/// wakes blocked callers it may have been holding back.
+#[cfg(feature = "async")]
+struct SemaphoreWaiter<'a> {
+    semaphore: &'a Semaphore,
+    level: usize,
+}
+
+#[cfg(feature = "async")]
+impl Drop for SemaphoreWaiter<'_> {
+    fn drop(&mut self) {
+        self.semaphore.state.lock().unwrap().waiting[self.level] -= 1;
+        self.semaphore.released.notify_all();
+    }
+}
+
+/// Held for the duration of a request; frees the slot on drop.
+pub(crate) struct Permit<'a> {
+    semaphore: Option<&'a Semaphore>,
+}
+
+impl Permit<'_> {
+    /// Keep the slot taken after this permit is dropped, for an [`OwnedPermit`] to free.
+    #[cfg(feature = "blocking")]
+    fn detach(mut self) {
+        self.semaphore = None;
+    }
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        if let Some(semaphore) = self.semaphore {
+            semaphore.release();
+        }
+    }
+}
+
+/// Like [`Permit`], but keeping the limits alive itself rather than borrowing them, so
+/// it can move to the thread a cancellable request runs on and free the slot only once
+/// the request there has finished.
+#[cfg(feature = "blocking")]
+pub(crate) struct OwnedPermit {
+    limits: Arc<Limits>,
+    /// The class whose concurrency slot is held, if it has a limit.
+    held: Option<EndpointClass>,
+}
+
+#[cfg(feature = "blocking")]
+impl Drop for OwnedPermit {
+    fn drop(&mut self) {
+        if let Some(semaphore) = self
+            .held
+            .and_then(|class| self.limits.concurrency.get(&class))
+        {
+            semaphore.release();
+        }
+    }
+}
+
+/// Client-side request limits, shared by every clone of an SDK instance.
+///
+/// A request first waits for a concurrency slot in its endpoint class, then for a token
+/// from its class rate limit, then from the global one, so the stricter per-endpoint
+/// caps and the global cap hold at the same time. At each step, waiting requests are
+/// served in [`Priority`] order, so urgent uploads overtake bulk ones already queued.
+#[derive(Debug, Default)]
+pub(crate) struct Limits {
+    concurrency: HashMap<EndpointClass, Semaphore>,
+    rates: HashMap<EndpointClass, TokenBucket>,
+    global_rate: Option<TokenBucket>,
+}
+
+impl Limits {
+    pub(crate) fn new(
+        concurrency: &HashMap<EndpointClass, usize>,
+        rates: &HashMap<EndpointClass, RateLimit>,
+        global_rate: Option<RateLimit>,
+    ) -> Self {
+        Self {
+            concurrency: concurrency
+                .iter()
+                .map(|(class, permits)| (*class, Semaphore::new(*permits)))
+                .collect(),
+            rates: rates
+                .iter()
+                .map(|(class, limit)| (*class, TokenBucket::new(*limit)))
+                .collect(),
+            global_rate: global_rate.map(TokenBucket::new),
+        }
+    }
+
+    /// Block until a request of `class` sent at `priority` may start. Unlimited classes
+    /// return immediately.
+    #[cfg(feature = "blocking")]
+    pub(crate) fn acquire(
+        self: &Arc<Self>,
+        class: EndpointClass,
+        priority: Priority,
+    ) -> OwnedPermit {
+        let held = self.concurrency.get(&class).map(|semaphore| {
+            semaphore.acquire(priority).detach();
+            class
+        });
+        let permit = OwnedPermit {
+            limits: Arc::clone(self),
+            held,
+        };
+        if let Some(bucket) = self.rates.get(&class) {
+            bucket.acquire(priority);
+        }
+        if let Some(bucket) = &self.global_rate {
+            bucket.acquire(priority);
+        }
+        permit
+    }
+
+    /// Wait, without blocking the async runtime, until a request of `class` sent at
+    /// `priority` may start. Blocking and async callers share the same slots and tokens.
+    #[cfg(feature = "async")]
+    pub(crate) async fn acquire_async(
+        &self,
+        class: EndpointClass,
+        priority: Priority,
+    ) -> Permit<'_> {
+        let permit = match self.concurrency.get(&class) {
+            Some(semaphore) => semaphore.acquire_async(priority).await,
+            None => Permit { semaphore: None },
+        };
+        if let Some(bucket) = self.rates.get(&class) {
+            bucket.acquire_async(priority).await;
+        }
+        if let Some(bucket) = &self.global_rate {
+            bucket.acquire_async(priority).await;
+        }
+        permit
+    }
+}
+
+#[cfg(all(test, feature = "blocking"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_permit_moved_to_another_thread_holds_its_slot_there() {
+        let limits = Arc::new(Limits::new(
+            &HashMap::from([(EndpointClass::Upload, 1)]),
+            &HashMap::new(),
+            None,
+        ));
+        let permit = limits.acquire(EndpointClass::Upload, Priority::Normal);
+        let request = thread::spawn(move || {
+            let _permit = permit;
+            thread::sleep(Duration::from_millis(50));
+            Instant::now()
+        });
+
+        let _second = limits.acquire(EndpointClass::Upload, Priority::Normal);
+        let acquired = Instant::now();
+        assert!(acquired >= request.join().unwrap());
+    }
+
+    #[test]
+    fn unlimited_classes_dont_wait() {
+        let limits = Arc::new(Limits::default());
+        let _first = limits.acquire(EndpointClass::Upload, Priority::Normal);
+        let _second = limits.acquire(EndpointClass::Upload, Priority::Normal);
+    }
+}
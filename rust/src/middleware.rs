@@ -0,0 +1,142 @@
+// src/middleware.rs
+
+use crate::{Endpoint, TraceixError};
+use reqwest::blocking::{Request, Response};
+use std::fmt;
+use std::sync::Arc;
+
+/// Hooks that may change every request the SDK sends and inspect every response,
+/// e.g. to add a corporate auth header or sign requests. Register with
+/// [`TraceixSdkBuilder::middleware`](crate::TraceixSdkBuilder::middleware).
+///
+/// `on_request` runs in registration order, right before the request goes to the
+/// transport and again for every retry; `on_response` runs in reverse order on
+/// successful responses. Returning an error aborts the call with it. For read-only
+/// logging, an [`Observer`](crate::Observer) is simpler.
+///
+/// The same hooks run for [`AsyncTraceixSdk`](crate::AsyncTraceixSdk) requests, see
+/// [`TraceixSdkBuilder::build_async`](crate::TraceixSdkBuilder::build_async).
+///
+/// ```no_run
+/// # use reqwest::blocking::Request;
+/// # use reqwest::header::HeaderValue;
+/// # use traceix_sdk::{Endpoint, Middleware, TraceixError, TraceixSdk};
+/// struct CorporateAuth(HeaderValue);
+///
+/// impl Middleware for CorporateAuth {
+///     fn on_request(&self, _: &Endpoint, request: &mut Request) -> Result<(), TraceixError> {
+///         request.headers_mut().insert("x-corp-auth", self.0.clone());
+///         Ok(())
+///     }
+/// }
+///
+/// # fn main() -> Result<(), TraceixError> {
+/// let sdk = TraceixSdk::builder()
+///     .middleware(CorporateAuth(HeaderValue::from_static("token")))
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+pub trait Middleware: Send + Sync {
+    fn on_request(&self, _endpoint: &Endpoint, _request: &mut Request) -> Result<(), TraceixError> {
+        Ok(())
+    }
+
+    fn on_response(
+        &self,
+        _endpoint: &Endpoint,
+        _response: &mut Response,
+    ) -> Result<(), TraceixError> {
+        Ok(())
+    }
+}
+
+/// The middleware registered on an SDK instance, in registration order.
+#[derive(Clone, Default)]
+pub(crate) struct MiddlewareChain(Vec<Arc<dyn Middleware>>);
+
+impl MiddlewareChain {
+    pub(crate) fn push(&mut self, middleware: Arc<dyn Middleware>) {
+        self.0.push(middleware);
+    }
+
+    pub(crate) fn request(
+        &self,
+        endpoint: &Endpoint,
+        request: &mut Request,
+    ) -> Result<(), TraceixError> {
+        self.0
+            .iter()
+            .try_for_each(|m| m.on_request(endpoint, request))
+    }
+
+    pub(crate) fn response(
+        &self,
+        endpoint: &Endpoint,
+        response: &mut Response,
+    ) -> Result<(), TraceixError> {
+        self.0
+            .iter()
+            .rev()
+            .try_for_each(|m| m.on_response(endpoint, response))
+    }
+}
+
+#[cfg(feature = "async")]
+impl MiddlewareChain {
+    /// Run the request hooks on an async request through a blocking mirror of it, then
+    /// carry their changes to the method, URL, headers, timeout, and in-memory body
+    /// back. Streamed bodies are left as they are.
+    pub(crate) fn request_async(
+        &self,
+        endpoint: &Endpoint,
+        request: &mut reqwest::Request,
+    ) -> Result<(), TraceixError> {
+        if self.0.is_empty() {
+            return Ok(());
+        }
+        let mut mirror = Request::new(request.method().clone(), request.url().clone());
+        *mirror.headers_mut() = request.headers().clone();
+        *mirror.timeout_mut() = request.timeout().copied();
+        let body = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map(<[u8]>::to_vec);
+        *mirror.body_mut() = body.clone().map(Into::into);
+
+        self.request(endpoint, &mut mirror)?;
+
+        *request.method_mut() = mirror.method().clone();
+        *request.url_mut() = mirror.url().clone();
+        *request.headers_mut() = mirror.headers().clone();
+        *request.timeout_mut() = mirror.timeout().copied();
+        let changed = mirror.body().and_then(|body| body.as_bytes());
+        if body.is_some() && changed != body.as_deref() {
+            *request.body_mut() = changed.map(|bytes| bytes.to_vec().into());
+        }
+        Ok(())
+    }
+
+    /// Run the response hooks on a buffered copy of an async response.
+    pub(crate) fn response_async(
+        &self,
+        endpoint: &Endpoint,
+        status: reqwest::StatusCode,
+        headers: &reqwest::header::HeaderMap,
+        body: &bytes::Bytes,
+    ) -> Result<(), TraceixError> {
+        if self.0.is_empty() {
+            return Ok(());
+        }
+        let mut copy = http::Response::new(body.clone());
+        *copy.status_mut() = status;
+        *copy.headers_mut() = headers.clone();
+        self.response(endpoint, &mut Response::from(copy))
+    }
+}
+
+impl fmt::Debug for MiddlewareChain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MiddlewareChain({})", self.0.len())
+    }
+}
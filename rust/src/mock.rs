@@ -0,0 +1,295 @@
+// src/mock.rs
+
+use crate::{Endpoint, SearchType, TraceixApi, TraceixError, UploadOptions};
+use serde_json::Value;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// An in-memory [`TraceixApi`] for unit-testing code that embeds the SDK.
+///
+/// Program it with [`Expectation`]s: each answers calls to one endpoint, optionally
+/// only for a given file name or argument, and may require an exact number of calls.
+/// Calls no expectation answers fail with [`TraceixError::InvalidArgument`].
+/// [`MockTraceix::verify`] then checks every call count and that nothing unexpected
+/// was called.
+///
+/// ```
+/// # use serde_json::json;
+/// # use std::path::Path;
+/// # use traceix_sdk::{Endpoint, Expectation, MockTraceix, TraceixApi};
+/// let mock = MockTraceix::new();
+/// mock.expect(
+///     Expectation::new(&Endpoint::AI_PREDICTION)
+///         .filename("invoice.exe")
+///         .times(1)
+///         .returns(json!({ "uuid": "u1" })),
+/// );
+/// mock.expect(Expectation::new(&Endpoint::CHECK_STATUS).returns(json!({ "status": "completed" })));
+///
+/// let api: &dyn TraceixApi = &mock;
+/// let upload = api.ai_prediction(Path::new("/evidence/invoice.exe")).unwrap();
+/// assert_eq!(api.check_status(upload["uuid"].as_str().unwrap()).unwrap()["status"], "completed");
+/// mock.verify();
+/// ```
+#[derive(Default)]
+pub struct MockTraceix {
+    expectations: Mutex<Vec<Expectation>>,
+    calls: Mutex<Vec<MockCall>>,
+}
+
+/// A canned answer for calls to one endpoint, registered with [`MockTraceix::expect`].
+#[must_use = "an expectation does nothing until passed to `MockTraceix::expect`"]
+pub struct Expectation {
+    endpoint: &'static str,
+    filename: Option<PathBuf>,
+    argument: Option<String>,
+    times: Option<usize>,
+    response: MockResponse,
+    matched: usize,
+}
+
+#[derive(Clone)]
+enum MockResponse {
+    Value(Value),
+    Error(Arc<dyn Fn() -> TraceixError + Send + Sync>),
+}
+
+/// A call made to a [`MockTraceix`].
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct MockCall {
+    /// Name of the endpoint the call would have gone to.
+    pub endpoint: &'static str,
+    /// The file uploaded, for upload calls.
+    pub filename: Option<PathBuf>,
+    /// The UUID, hash or CID passed, for calls that take one.
+    pub argument: Option<String>,
+    pub options: Option<UploadOptions>,
+    /// Whether an expectation answered the call.
+    pub expected: bool,
+}
+
+impl Expectation {
+    /// Answer calls to `endpoint` with an empty JSON object.
+    pub fn new(endpoint: &Endpoint) -> Self {
+        Self {
+            endpoint: endpoint.name,
+            filename: None,
+            argument: None,
+            times: None,
+            response: MockResponse::Value(Value::Object(Default::default())),
+            matched: 0,
+        }
+    }
+
+    /// Only answer uploads of this file: a full path, or a file name matching the last
+    /// component of the uploaded path.
+    pub fn filename(mut self, filename: impl Into<PathBuf>) -> Self {
+        self.filename = Some(filename.into());
+        self
+    }
+
+    /// Only answer calls with this UUID, hash or CID.
+    pub fn argument(mut self, argument: impl Into<String>) -> Self {
+        self.argument = Some(argument.into());
+        self
+    }
+
+    /// Require exactly `times` matching calls. Further calls are left to later
+    /// expectations. Without it, any number of calls is fine, including none.
+    pub fn times(mut self, times: usize) -> Self {
+        self.times = Some(times);
+        self
+    }
+
+    /// Answer with `response`.
+    pub fn returns(mut self, response: Value) -> Self {
+        self.response = MockResponse::Value(response);
+        self
+    }
+
+    /// Answer with the error `error` builds, e.g. to test retry or fallback paths.
+    pub fn fails(mut self, error: impl Fn() -> TraceixError + Send + Sync + 'static) -> Self {
+        self.response = MockResponse::Error(Arc::new(error));
+        self
+    }
+
+    fn matches(&self, call: &MockCall) -> bool {
+        let filename_matches = match (&self.filename, &call.filename) {
+            (None, _) => true,
+            (Some(expected), Some(path)) => {
+                path == expected || path.file_name() == Some(expected.as_os_str())
+            }
+            (Some(_), None) => false,
+        };
+        self.endpoint == call.endpoint
+            && filename_matches
+            && (self.argument.is_none() || self.argument == call.argument)
+            && self.times.is_none_or(|times| self.matched < times)
+    }
+}
+
+impl fmt::Debug for Expectation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Expectation")
+            .field("endpoint", &self.endpoint)
+            .field("filename", &self.filename)
+            .field("argument", &self.argument)
+            .field("times", &self.times)
+            .field("matched", &self.matched)
+            .finish_non_exhaustive()
+    }
+}
+
+impl MockTraceix {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an expectation. Calls are answered by the first expectation, in the order
+    /// they were added, that matches and hasn't used up its `times`.
+    pub fn expect(&self, expectation: Expectation) -> &Self {
+        self.expectations.lock().unwrap().push(expectation);
+        self
+    }
+
+    /// Every call made so far, in order.
+    pub fn calls(&self) -> Vec<MockCall> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    /// Panic unless every expectation with [`times`](Expectation::times) got exactly that
+    /// many calls and every call was answered by an expectation.
+    pub fn verify(&self) {
+        let mut problems = Vec::new();
+        for expectation in self.expectations.lock().unwrap().iter() {
+            if let Some(times) = expectation.times {
+                if expectation.matched != times {
+                    problems.push(format!(
+                        "expected {times} call(s) matching {expectation:?}, got {}",
+                        expectation.matched
+                    ));
+                }
+            }
+        }
+        for call in self
+            .calls
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|call| !call.expected)
+        {
+            problems.push(format!("unexpected call {call:?}"));
+        }
+        assert!(
+            problems.is_empty(),
+            "MockTraceix expectations not met:\n{}",
+            problems.join("\n")
+        );
+    }
+
+    fn call(
+        &self,
+        endpoint: &Endpoint,
+        filename: Option<&Path>,
+        argument: Option<&str>,
+        options: Option<&UploadOptions>,
+    ) -> Result<Value, TraceixError> {
+        let mut call = MockCall {
+            endpoint: endpoint.name,
+            filename: filename.map(Path::to_path_buf),
+            argument: argument.map(str::to_string),
+            options: options.cloned(),
+            expected: false,
+        };
+        let response = {
+            let mut expectations = self.expectations.lock().unwrap();
+            expectations
+                .iter_mut()
+                .find(|expectation| expectation.matches(&call))
+                .map(|expectation| {
+                    expectation.matched += 1;
+                    expectation.response.clone()
+                })
+        };
+        call.expected = response.is_some();
+        let unexpected = format!("no expectation answers {call:?}");
+        self.calls.lock().unwrap().push(call);
+
+        match response {
+            Some(MockResponse::Value(value)) => Ok(value),
+            Some(MockResponse::Error(error)) => Err(error()),
+            None => Err(TraceixError::InvalidArgument(unexpected)),
+        }
+    }
+}
+
+impl TraceixApi for MockTraceix {
+    fn ai_prediction_with_options(
+        &self,
+        filename: &Path,
+        options: &UploadOptions,
+    ) -> Result<Value, TraceixError> {
+        self.call(
+            &Endpoint::AI_PREDICTION,
+            Some(filename),
+            None,
+            Some(options),
+        )
+    }
+
+    fn capa_extraction_with_options(
+        &self,
+        filename: &Path,
+        options: &UploadOptions,
+    ) -> Result<Value, TraceixError> {
+        self.call(
+            &Endpoint::CAPA_EXTRACTION,
+            Some(filename),
+            None,
+            Some(options),
+        )
+    }
+
+    fn exif_extraction_with_options(
+        &self,
+        filename: &Path,
+        options: &UploadOptions,
+    ) -> Result<Value, TraceixError> {
+        self.call(
+            &Endpoint::EXIF_EXTRACTION,
+            Some(filename),
+            None,
+            Some(options),
+        )
+    }
+
+    fn check_status(&self, uuid: &str) -> Result<Value, TraceixError> {
+        self.call(&Endpoint::CHECK_STATUS, None, Some(uuid), None)
+    }
+
+    fn cancel(&self, uuid: &str) -> Result<Value, TraceixError> {
+        self.call(&Endpoint::CANCEL, None, Some(uuid), None)
+    }
+
+    fn hash_search(&self, file_hash: &str, search_type: SearchType) -> Result<Value, TraceixError> {
+        let endpoint = match search_type {
+            SearchType::Capa => &Endpoint::CAPA_SEARCH,
+            SearchType::Exif => &Endpoint::EXIF_SEARCH,
+        };
+        self.call(endpoint, None, Some(file_hash), None)
+    }
+
+    fn list_all_ipfs_datasets(&self) -> Result<Value, TraceixError> {
+        self.call(&Endpoint::IPFS_LIST_ALL, None, None, None)
+    }
+
+    fn get_public_ipfs_dataset(&self, cid: &str) -> Result<Value, TraceixError> {
+        self.call(&Endpoint::IPFS_SEARCH, None, Some(cid), None)
+    }
+
+    fn search_ipfs_dataset_by_hash(&self, file_hash: &str) -> Result<Value, TraceixError> {
+        self.call(&Endpoint::IPFS_FIND, None, Some(file_hash), None)
+    }
+}
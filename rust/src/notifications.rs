@@ -0,0 +1,43 @@
+// src/notifications.rs
+
+use crate::{decode_response, Endpoint, TraceixError, TraceixSdk};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// Account-level notification preferences.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotificationSettings {
+    /// Send an email when an analysis completes.
+    #[serde(default)]
+    pub email_on_completion: bool,
+    /// Address for email notifications. The account address is used when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    /// Call `webhook_url` when the verdict of a submission changes.
+    #[serde(default)]
+    pub webhook_on_verdict_change: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook_url: Option<Url>,
+}
+
+impl TraceixSdk {
+    /// Fetch the account's current notification settings.
+    pub fn notification_settings(&self) -> Result<NotificationSettings, TraceixError> {
+        decode_response(self.post_json(&Endpoint::NOTIFICATIONS, &serde_json::json!({}))?)
+    }
+
+    /// Replace the account's notification settings, returning them as stored by the server.
+    pub fn update_notification_settings(
+        &self,
+        settings: &NotificationSettings,
+    ) -> Result<NotificationSettings, TraceixError> {
+        if settings.webhook_on_verdict_change && settings.webhook_url.is_none() {
+            return Err(TraceixError::InvalidArgument(
+                "webhook_on_verdict_change requires a webhook_url".to_string(),
+            ));
+        }
+
+        let body = serde_json::to_value(settings).expect("settings serialize to JSON");
+        decode_response(self.post_json(&Endpoint::NOTIFICATIONS_UPDATE, &body)?)
+    }
+}
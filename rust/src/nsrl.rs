@@ -0,0 +1,173 @@
+// src/nsrl.rs
+
+use crate::filter::HashList;
+use crate::hashing::FileDigests;
+use crate::TraceixError;
+#[cfg(feature = "blocking")]
+use crate::{TraceixSdk, DEFAULT_TIMEOUT};
+#[cfg(feature = "blocking")]
+use reqwest::{blocking::Client, StatusCode};
+#[cfg(feature = "blocking")]
+use serde_json::Value;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+#[cfg(feature = "blocking")]
+use url::Url;
+
+/// Default hash lookup service, which serves the NSRL RDS over HTTP.
+pub const DEFAULT_NSRL_API_URL: &str = "https://hashlookup.circl.lu/";
+
+/// Known-good file hashes from the NIST National Software Reference Library.
+///
+/// Files found in the RDS are stock OS/application files and can be skipped before
+/// upload. Plug it into a batch through [`HashFilter::with_nsrl`](crate::filter::HashFilter::with_nsrl).
+#[derive(Clone, Debug)]
+pub enum Nsrl {
+    /// Hashes loaded from a local RDS export.
+    Local(HashList),
+    /// A hashlookup-compatible API, queried per file.
+    #[cfg(feature = "blocking")]
+    Api { url: Url, client: Client },
+}
+
+impl Nsrl {
+    /// Load a legacy RDS `NSRLFile.txt` (quoted CSV with `SHA-1` and `MD5` columns),
+    /// or a plain list with one hash per line.
+    pub fn load_rds(path: impl AsRef<Path>) -> Result<Self, TraceixError> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| TraceixError::file(path, e))?;
+        let mut lines = BufReader::new(file)
+            .lines()
+            .map(|line| line.map_err(|e| TraceixError::file(path, e)));
+
+        let header = match lines.next() {
+            Some(line) => line?,
+            None => return Ok(Nsrl::Local(HashList::new())),
+        };
+        if !header.starts_with("\"SHA-1\"") {
+            return HashList::load(path).map(Nsrl::Local);
+        }
+
+        let columns: Vec<String> = split_csv(&header);
+        let sha1 = columns.iter().position(|c| c == "SHA-1");
+        let md5 = columns.iter().position(|c| c == "MD5");
+
+        let mut list = HashList::new();
+        for line in lines {
+            let fields = split_csv(&line?);
+            for index in [sha1, md5].into_iter().flatten() {
+                if let Some(hash) = fields.get(index).filter(|h| !h.is_empty()) {
+                    list.insert(hash)?;
+                }
+            }
+        }
+        Ok(Nsrl::Local(list))
+    }
+
+    /// Query the public hashlookup service at [`DEFAULT_NSRL_API_URL`].
+    #[cfg(feature = "blocking")]
+    pub fn api() -> Result<Self, TraceixError> {
+        Self::api_at(DEFAULT_NSRL_API_URL)
+    }
+
+    /// Query a self-hosted hashlookup-compatible service, with the SDK's
+    /// [`DEFAULT_TIMEOUT`] and no proxy or TLS settings. Use [`Nsrl::api_with`] or
+    /// [`TraceixSdk::nsrl_api_at`] when the service is only reachable through them.
+    #[cfg(feature = "blocking")]
+    pub fn api_at(url: &str) -> Result<Self, TraceixError> {
+        Self::api_with(url, Client::builder().timeout(DEFAULT_TIMEOUT).build()?)
+    }
+
+    /// Query a hashlookup-compatible service at `url` through `client`.
+    #[cfg(feature = "blocking")]
+    pub fn api_with(url: &str, client: Client) -> Result<Self, TraceixError> {
+        let url = Url::parse(url).map_err(|e| TraceixError::InvalidBaseUrl {
+            url: url.to_string(),
+            reason: e.to_string(),
+        })?;
+        Ok(Nsrl::Api { url, client })
+    }
+
+    /// The first of `digests` known to the NSRL, if any.
+    pub fn lookup(&self, digests: &FileDigests) -> Result<Option<String>, TraceixError> {
+        match self {
+            Nsrl::Local(list) => Ok(list.matches(digests)),
+            #[cfg(feature = "blocking")]
+            Nsrl::Api { url, client } => {
+                let endpoint = lookup_url(url, &digests.sha1)?;
+                let resp = client
+                    .get(endpoint)
+                    .header("accept", "application/json")
+                    .send()?;
+                if resp.status() == StatusCode::NOT_FOUND {
+                    return Ok(None);
+                }
+                let body: Value = resp.error_for_status()?.json()?;
+                // hashlookup also indexes other sources; only treat clean entries as known-good.
+                let malicious = body.get("KnownMalicious").is_some();
+                Ok((!malicious).then(|| digests.sha1.clone()))
+            }
+        }
+    }
+
+    /// Hash the file at `path` and look it up.
+    pub fn lookup_file(&self, path: impl AsRef<Path>) -> Result<Option<String>, TraceixError> {
+        self.lookup(&FileDigests::from_file(path.as_ref())?)
+    }
+}
+
+/// Query the hashlookup service through this client's timeouts, proxy and TLS
+/// settings.
+#[cfg(feature = "blocking")]
+impl TraceixSdk {
+    /// Query the public hashlookup service at [`DEFAULT_NSRL_API_URL`].
+    pub fn nsrl_api(&self) -> Result<Nsrl, TraceixError> {
+        self.nsrl_api_at(DEFAULT_NSRL_API_URL)
+    }
+
+    /// Query a self-hosted hashlookup-compatible service.
+    pub fn nsrl_api_at(&self, url: &str) -> Result<Nsrl, TraceixError> {
+        Nsrl::api_with(url, self.client.clone())
+    }
+}
+
+/// The SHA-1 lookup under `base`. Its path is treated as a directory even without a
+/// trailing `/`, so a service mounted under a prefix keeps it.
+#[cfg(feature = "blocking")]
+fn lookup_url(base: &Url, sha1: &str) -> Result<Url, TraceixError> {
+    let mut base = base.clone();
+    if !base.path().ends_with('/') {
+        let path = format!("{}/", base.path());
+        base.set_path(&path);
+    }
+    base.join(&format!("lookup/sha1/{sha1}"))
+        .map_err(|e| TraceixError::InvalidArgument(e.to_string()))
+}
+
+fn split_csv(line: &str) -> Vec<String> {
+    line.split(',')
+        .map(|field| field.trim().trim_matches('"').to_string())
+        .collect()
+}
+
+#[cfg(all(test, feature = "blocking"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup_url_keeps_the_base_path() {
+        for base in [
+            "https://nsrl.example/hashlookup",
+            "https://nsrl.example/hashlookup/",
+        ] {
+            let url = lookup_url(&Url::parse(base).unwrap(), "abc").unwrap();
+            assert_eq!(
+                url.as_str(),
+                "https://nsrl.example/hashlookup/lookup/sha1/abc"
+            );
+        }
+        let url = lookup_url(&Url::parse(DEFAULT_NSRL_API_URL).unwrap(), "abc").unwrap();
+        assert_eq!(url.as_str(), "https://hashlookup.circl.lu/lookup/sha1/abc");
+    }
+}
@@ -0,0 +1,103 @@
+// src/observer.rs
+
+use crate::{Endpoint, TraceixError};
+use reqwest::StatusCode;
+use std::path::Path;
+use std::time::Duration;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use std::{fmt, sync::Arc};
+use url::Url;
+
+/// A request about to be sent.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct RequestEvent<'a> {
+    pub endpoint: &'a Endpoint,
+    pub url: &'a Url,
+    /// Local file the request reads from, if any.
+    pub source: Option<&'a Path>,
+}
+
+/// A request that finished, successfully or not.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct ResponseEvent<'a> {
+    pub endpoint: &'a Endpoint,
+    pub url: &'a Url,
+    pub source: Option<&'a Path>,
+    /// HTTP status, or `None` if no response was received.
+    pub status: Option<StatusCode>,
+    pub elapsed: Duration,
+    pub error: Option<&'a TraceixError>,
+}
+
+/// An operation that failed and is about to be tried again.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct RetryEvent<'a> {
+    /// The endpoint being retried, when the retry is for a single request.
+    pub endpoint: Option<&'a Endpoint>,
+    pub source: Option<&'a Path>,
+    /// The attempt that just failed, starting at 1.
+    pub attempt: u32,
+    pub delay: Duration,
+    pub error: &'a TraceixError,
+}
+
+/// An operation that failed on its last allowed attempt.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct GiveUpEvent<'a> {
+    pub endpoint: Option<&'a Endpoint>,
+    pub source: Option<&'a Path>,
+    pub attempts: u32,
+    pub error: &'a TraceixError,
+}
+
+/// Read-only hooks notified about every SDK operation, e.g. for custom logging,
+/// billing attribution, or anomaly detection. Register with
+/// [`TraceixSdkBuilder::observer`](crate::TraceixSdkBuilder::observer).
+///
+/// Observers cannot change requests; every method defaults to doing nothing.
+/// They are called synchronously on the requesting thread, so keep them cheap.
+pub trait Observer: Send + Sync {
+    fn on_request(&self, _event: &RequestEvent<'_>) {}
+    fn on_response(&self, _event: &ResponseEvent<'_>) {}
+    fn on_retry(&self, _event: &RetryEvent<'_>) {}
+    fn on_giveup(&self, _event: &GiveUpEvent<'_>) {}
+}
+
+/// The observers registered on an SDK instance.
+#[cfg(any(feature = "blocking", feature = "async"))]
+#[derive(Clone, Default)]
+pub(crate) struct Observers(Vec<Arc<dyn Observer>>);
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+impl Observers {
+    pub(crate) fn push(&mut self, observer: Arc<dyn Observer>) {
+        self.0.push(observer);
+    }
+
+    pub(crate) fn request(&self, event: &RequestEvent<'_>) {
+        self.0.iter().for_each(|o| o.on_request(event));
+    }
+
+    pub(crate) fn response(&self, event: &ResponseEvent<'_>) {
+        self.0.iter().for_each(|o| o.on_response(event));
+    }
+
+    pub(crate) fn retry(&self, event: &RetryEvent<'_>) {
+        self.0.iter().for_each(|o| o.on_retry(event));
+    }
+
+    pub(crate) fn giveup(&self, event: &GiveUpEvent<'_>) {
+        self.0.iter().for_each(|o| o.on_giveup(event));
+    }
+}
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+impl fmt::Debug for Observers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Observers({})", self.0.len())
+    }
+}
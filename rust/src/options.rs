@@ -0,0 +1,144 @@
+// src/options.rs
+
+use crate::progress::{ProgressCallback, UploadProgress};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// How urgently a submission should be processed.
+///
+/// Ordered from lowest to highest, so `Priority::Urgent > Priority::Bulk`.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum Priority {
+    /// Large background jobs that can wait behind everything else.
+    Bulk,
+    /// The default for interactive and scripted submissions.
+    #[default]
+    Normal,
+    /// One-off samples an analyst is actively waiting on.
+    Urgent,
+}
+
+impl Priority {
+    /// The value sent to the API in the `priority` form field.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Priority::Bulk => "bulk",
+            Priority::Normal => "normal",
+            Priority::Urgent => "urgent",
+        }
+    }
+}
+
+bitflags::bitflags! {
+    /// Which analyses [`TraceixSdk::full_upload`](crate::TraceixSdk::full_upload) runs for a file.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub struct Analyses: u8 {
+        const PREDICTION = 1;
+        const CAPA = 1 << 1;
+        const EXIF = 1 << 2;
+    }
+}
+
+impl Default for Analyses {
+    fn default() -> Self {
+        Analyses::all()
+    }
+}
+
+/// Which analyses [`TraceixSdk::full_upload_with`](crate::TraceixSdk::full_upload_with)
+/// runs, as named flags. Equivalent to [`Analyses`]; the default selects nothing, so
+/// struct update syntax reads as a list of what to run.
+///
+/// ```
+/// # use traceix_sdk::{AnalysisSelection, Analyses};
+/// let selection = AnalysisSelection {
+///     capa: true,
+///     ..AnalysisSelection::default()
+/// };
+/// assert_eq!(Analyses::from(selection), Analyses::CAPA);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct AnalysisSelection {
+    pub prediction: bool,
+    pub capa: bool,
+    pub exif: bool,
+}
+
+impl From<AnalysisSelection> for Analyses {
+    fn from(selection: AnalysisSelection) -> Self {
+        let mut analyses = Analyses::empty();
+        analyses.set(Analyses::PREDICTION, selection.prediction);
+        analyses.set(Analyses::CAPA, selection.capa);
+        analyses.set(Analyses::EXIF, selection.exif);
+        analyses
+    }
+}
+
+impl From<Analyses> for AnalysisSelection {
+    fn from(analyses: Analyses) -> Self {
+        Self {
+            prediction: analyses.contains(Analyses::PREDICTION),
+            capa: analyses.contains(Analyses::CAPA),
+            exif: analyses.contains(Analyses::EXIF),
+        }
+    }
+}
+
+/// Options carried on file uploads.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UploadOptions {
+    pub priority: Priority,
+    /// Client-chosen identifier for the transfer. When set, an interrupted
+    /// upload can be continued with [`TraceixSdk::resume_upload`](crate::TraceixSdk::resume_upload).
+    pub upload_id: Option<String>,
+    /// Free-form labels attached to the submission, e.g. a case number.
+    pub tags: Vec<String>,
+    /// Free-text note on where the sample came from or why it was submitted.
+    pub description: Option<String>,
+    /// Identifier of the investigation the sample belongs to, so results can be traced
+    /// back to it.
+    pub case_id: Option<String>,
+    /// Where the sample was collected, e.g. `email-gateway` or a hostname.
+    pub source: Option<String>,
+    /// Keep the sample and its results out of public datasets and searches.
+    pub private: bool,
+    /// Send a copy of image files with their EXIF, XMP and IPTC metadata removed, so
+    /// GPS positions and device identifiers never reach the server. See
+    /// [`exif::strip_exif`](crate::exif::strip_exif) for the formats handled.
+    pub strip_metadata: bool,
+    /// Overall timeout for each upload request, overriding the client default.
+    pub timeout: Option<Duration>,
+    /// Notified as the file is streamed to the server by the blocking client. Not serialized.
+    #[serde(skip)]
+    pub progress: Option<ProgressCallback>,
+}
+
+impl UploadOptions {
+    pub fn with_priority(priority: Priority) -> Self {
+        Self {
+            priority,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_upload_id(upload_id: impl Into<String>) -> Self {
+        Self {
+            upload_id: Some(upload_id.into()),
+            ..Self::default()
+        }
+    }
+
+    /// Report upload progress to `callback`, e.g. to drive a progress bar.
+    pub fn on_progress(
+        mut self,
+        callback: impl Fn(UploadProgress) + Send + Sync + 'static,
+    ) -> Self {
+        self.progress = Some(ProgressCallback::new(callback));
+        self
+    }
+}
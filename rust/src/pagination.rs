@@ -0,0 +1,266 @@
+// src/pagination.rs
+
+use crate::TraceixError;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "blocking")]
+use serde_json::{Map, Value};
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+
+/// Position of the next page to fetch from a listing endpoint.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Cursor {
+    /// Offset-based pagination (`offset` / `limit`).
+    Offset(u64),
+    /// Opaque continuation token issued by the server.
+    Token(String),
+}
+
+/// One page of results plus the cursor of the following page, if any.
+#[derive(Clone, Debug)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next: Option<Cursor>,
+}
+
+impl<T> Page<T> {
+    /// Build a page for an offset-based endpoint. A short page is treated as the last one.
+    pub fn from_offset(items: Vec<T>, offset: u64, page_size: usize) -> Self {
+        let next = if items.len() < page_size || items.is_empty() {
+            None
+        } else {
+            Some(Cursor::Offset(offset + items.len() as u64))
+        };
+        Self { items, next }
+    }
+
+    /// Build a page for a token-based endpoint.
+    pub fn from_token(items: Vec<T>, next_token: Option<String>) -> Self {
+        Self {
+            items,
+            next: next_token.filter(|t| !t.is_empty()).map(Cursor::Token),
+        }
+    }
+
+    /// Build a page from a listing response requested with [`insert_cursor`], which
+    /// returned `offset`.
+    #[cfg(feature = "blocking")]
+    pub(crate) fn from_response(
+        items: Vec<T>,
+        next_token: Option<String>,
+        offset: Option<u64>,
+        page_size: usize,
+    ) -> Self {
+        // Servers that issue continuation tokens use them; otherwise page by offset. A
+        // page longer than requested comes from a server that doesn't page at all.
+        match (next_token, offset) {
+            (Some(token), _) => Self::from_token(items, Some(token)),
+            (None, Some(offset)) if items.len() <= page_size => {
+                Self::from_offset(items, offset, page_size)
+            }
+            (None, _) => Self::from_token(items, None),
+        }
+    }
+}
+
+/// Add the paging fields selecting `cursor` to a listing request `body`. Returns the
+/// offset the page starts at, unless it is selected by continuation token.
+#[cfg(feature = "blocking")]
+pub(crate) fn insert_cursor(
+    body: &mut Map<String, Value>,
+    cursor: Option<&Cursor>,
+    page_size: usize,
+) -> Option<u64> {
+    body.insert("limit".into(), page_size.into());
+    match cursor {
+        Some(Cursor::Token(token)) => {
+            body.insert("next_token".into(), token.clone().into());
+            None
+        }
+        Some(Cursor::Offset(offset)) => {
+            body.insert("offset".into(), (*offset).into());
+            Some(*offset)
+        }
+        None => {
+            body.insert("offset".into(), 0.into());
+            Some(0)
+        }
+    }
+}
+
+/// Opaque, serializable position in a listing, exact to the item.
+///
+/// Obtained from [`Paginator::resume_token`] and fed back through
+/// [`Paginator::resume_from`], e.g. by a nightly sync job that stops mid-catalog.
+/// Its string form (via `Display` / `FromStr`) is safe to store as-is.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResumeToken {
+    page: Option<Cursor>,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    skip: usize,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    finished: bool,
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+impl ResumeToken {
+    /// Whether the listing had been fully consumed when the token was taken.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
+
+impl fmt::Display for ResumeToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let json = serde_json::to_string(self).map_err(|_| fmt::Error)?;
+        f.write_str(&json)
+    }
+}
+
+impl FromStr for ResumeToken {
+    type Err = TraceixError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_str(s)
+            .map_err(|e| TraceixError::InvalidArgument(format!("invalid resume token: {e}")))
+    }
+}
+
+/// Lazily walks a paginated listing, one page request at a time.
+///
+/// `fetch` is called with the cursor of the page to load (`None` for the first
+/// page) and the configured page size. Iterating yields individual items; use
+/// [`Paginator::next_page`] to work a page at a time instead.
+///
+/// [`Paginator::cursor`] always points at the next page that has *not* been
+/// fetched yet, so persisting it after a page has been fully consumed and
+/// passing it to [`Paginator::resume`] later continues exactly where the
+/// previous run stopped. To stop in the middle of a page, persist
+/// [`Paginator::resume_token`] instead.
+pub struct Paginator<T, F> {
+    fetch: F,
+    page_size: usize,
+    buffer: VecDeque<T>,
+    next: Option<Cursor>,
+    // Cursor the buffered page was fetched with, and how many of its items were yielded.
+    current: Option<Cursor>,
+    consumed: usize,
+    // Items to drop from the next fetched page when resuming mid-page.
+    skip: usize,
+    started: bool,
+    done: bool,
+}
+
+impl<T, F> Paginator<T, F>
+where
+    F: FnMut(Option<&Cursor>, usize) -> Result<Page<T>, TraceixError>,
+{
+    /// Start from the first page.
+    pub fn new(page_size: usize, fetch: F) -> Self {
+        Self {
+            fetch,
+            page_size: page_size.max(1),
+            buffer: VecDeque::new(),
+            next: None,
+            current: None,
+            consumed: 0,
+            skip: 0,
+            started: false,
+            done: false,
+        }
+    }
+
+    /// Continue from a previously saved cursor.
+    pub fn resume(cursor: Cursor, page_size: usize, fetch: F) -> Self {
+        let mut paginator = Self::new(page_size, fetch);
+        paginator.next = Some(cursor);
+        paginator.started = true;
+        paginator
+    }
+
+    /// Continue from a [`ResumeToken`], skipping items already yielded from its page.
+    pub fn resume_from(token: ResumeToken, page_size: usize, fetch: F) -> Self {
+        let mut paginator = Self::new(page_size, fetch);
+        paginator.started = token.page.is_some() || token.finished;
+        paginator.next = token.page;
+        paginator.skip = token.skip;
+        paginator
+    }
+
+    /// An item-exact position to resume from later, even in the middle of a page.
+    pub fn resume_token(&self) -> ResumeToken {
+        if !self.buffer.is_empty() {
+            return ResumeToken {
+                page: self.current.clone(),
+                skip: self.consumed,
+                finished: false,
+            };
+        }
+        ResumeToken {
+            page: self.next.clone(),
+            skip: self.skip,
+            finished: self.started && self.next.is_none(),
+        }
+    }
+
+    /// The cursor of the next page to be fetched, or `None` if the listing is exhausted.
+    pub fn cursor(&self) -> Option<&Cursor> {
+        self.next.as_ref()
+    }
+
+    pub fn page_size(&self) -> usize {
+        self.page_size
+    }
+
+    /// Fetch the next whole page, including any items still buffered from the current one.
+    pub fn next_page(&mut self) -> Option<Result<Vec<T>, TraceixError>> {
+        if !self.buffer.is_empty() {
+            return Some(Ok(self.buffer.drain(..).collect()));
+        }
+        if self.done || (self.started && self.next.is_none()) {
+            return None;
+        }
+
+        match (self.fetch)(self.next.as_ref(), self.page_size) {
+            Ok(page) => {
+                self.started = true;
+                self.current = std::mem::replace(&mut self.next, page.next);
+                let mut items = page.items;
+                self.consumed = self.skip.min(items.len());
+                items.drain(..self.consumed);
+                self.skip = 0;
+                Some(Ok(items))
+            }
+            Err(e) => {
+                // Leave the cursor in place so the caller can resume after the failure.
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl<T, F> Iterator for Paginator<T, F>
+where
+    F: FnMut(Option<&Cursor>, usize) -> Result<Page<T>, TraceixError>,
+{
+    type Item = Result<T, TraceixError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.buffer.pop_front() {
+                self.consumed += 1;
+                return Some(Ok(item));
+            }
+            match self.next_page()? {
+                Ok(items) => self.buffer.extend(items),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
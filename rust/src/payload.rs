@@ -0,0 +1,139 @@
+// src/payload.rs
+
+use crate::{Submission, TraceixError, TraceixSdk, UploadOptions};
+use base64::engine::general_purpose::STANDARD;
+use base64::read::DecoderReader;
+use reqwest::blocking::multipart::Part;
+use std::io::{self, Read};
+
+/// Longest `data:` header accepted before the `,` that starts the payload.
+const MAX_DATA_URI_HEADER: usize = 1024;
+
+impl TraceixSdk {
+    /// Upload base64-encoded content for AI prediction under `filename`.
+    ///
+    /// The input is decoded as it is streamed into the request body, so no temp file or
+    /// full decoded copy is created. Line breaks and other whitespace (as in MIME bodies)
+    /// are ignored.
+    ///
+    /// ```no_run
+    /// # use traceix_sdk::{TraceixSdk, UploadOptions};
+    /// # fn main() -> Result<(), traceix_sdk::TraceixError> {
+    /// let sdk = TraceixSdk::new(None)?;
+    /// let encoded = std::io::Cursor::new("TVqQAAMAAAAEAAAA//8AALgAAAAAAAAAQAAAAAAAAAA=");
+    /// let submission = sdk.upload_base64("attachment.exe", encoded, &UploadOptions::default())?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn upload_base64<R>(
+        &self,
+        filename: &str,
+        encoded: R,
+        options: &UploadOptions,
+    ) -> Result<Submission, TraceixError>
+    where
+        R: Read + Send + 'static,
+    {
+        self.upload_decoded(filename, encoded, "application/octet-stream", options)
+    }
+
+    /// Upload the content of a base64 `data:` URI (e.g. `data:image/png;base64,iVBOR...`)
+    /// for AI prediction under `filename`, decoding it as it streams.
+    ///
+    /// The URI's media type is sent as the part's content type.
+    pub fn upload_data_uri<R>(
+        &self,
+        filename: &str,
+        mut uri: R,
+        options: &UploadOptions,
+    ) -> Result<Submission, TraceixError>
+    where
+        R: Read + Send + 'static,
+    {
+        let mime = read_data_uri_header(&mut uri)?;
+        self.upload_decoded(filename, uri, &mime, options)
+    }
+
+    fn upload_decoded<R>(
+        &self,
+        filename: &str,
+        encoded: R,
+        mime: &str,
+        options: &UploadOptions,
+    ) -> Result<Submission, TraceixError>
+    where
+        R: Read + Send + 'static,
+    {
+        let decoded = DecoderReader::new(SkipWhitespace(encoded), &STANDARD);
+        let part = Part::reader(self.body_reader(decoded))
+            .file_name(filename.to_string())
+            .mime_str(mime)
+            .map_err(TraceixError::Http)?;
+        self.upload_part(part, options)
+    }
+}
+
+/// Consume `data:[<mediatype>][;params];base64,` from `reader`, returning the media type.
+fn read_data_uri_header(reader: &mut impl Read) -> Result<String, TraceixError> {
+    let invalid =
+        |reason: &str| TraceixError::InvalidArgument(format!("invalid data URI: {reason}"));
+
+    let mut header = Vec::new();
+    let mut byte = [0u8; 1];
+    loop {
+        if reader.read(&mut byte)? == 0 {
+            return Err(invalid("missing ','"));
+        }
+        if byte[0] == b',' {
+            break;
+        }
+        if header.len() >= MAX_DATA_URI_HEADER {
+            return Err(invalid("header too long"));
+        }
+        header.push(byte[0]);
+    }
+
+    let header = String::from_utf8(header).map_err(|_| invalid("header is not UTF-8"))?;
+    let header = header.trim_start();
+    let params = header
+        .get(..5)
+        .filter(|scheme| scheme.eq_ignore_ascii_case("data:"))
+        .map(|_| &header[5..])
+        .ok_or_else(|| invalid("missing data: scheme"))?;
+
+    let mut parts = params.split(';');
+    let mime = parts.next().unwrap_or_default().trim();
+    if !parts.any(|p| p.trim().eq_ignore_ascii_case("base64")) {
+        return Err(invalid("only base64 data URIs are supported"));
+    }
+
+    Ok(if mime.is_empty() {
+        "application/octet-stream".to_string()
+    } else {
+        mime.to_string()
+    })
+}
+
+/// Drops ASCII whitespace from the underlying stream.
+struct SkipWhitespace<R>(R);
+
+impl<R: Read> Read for SkipWhitespace<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let n = self.0.read(buf)?;
+            if n == 0 {
+                return Ok(0);
+            }
+            let mut kept = 0;
+            for i in 0..n {
+                if !buf[i].is_ascii_whitespace() {
+                    buf[kept] = buf[i];
+                    kept += 1;
+                }
+            }
+            if kept > 0 {
+                return Ok(kept);
+            }
+        }
+    }
+}
@@ -0,0 +1,87 @@
+// src/progress.rs
+
+use std::fmt;
+#[cfg(feature = "blocking")]
+use std::io::{self, Read};
+use std::sync::Arc;
+
+/// How much of an upload body has been handed to the connection.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct UploadProgress {
+    pub bytes_sent: u64,
+    /// Size of the file being uploaded.
+    pub total: u64,
+}
+
+impl UploadProgress {
+    /// Progress between 0.0 and 1.0; an empty file counts as done.
+    pub fn fraction(&self) -> f64 {
+        if self.total == 0 {
+            1.0
+        } else {
+            (self.bytes_sent as f64 / self.total as f64).min(1.0)
+        }
+    }
+}
+
+/// A callback receiving [`UploadProgress`] updates while a file is streamed, set with
+/// [`UploadOptions::on_progress`](crate::UploadOptions::on_progress).
+///
+/// It runs on the uploading thread after every chunk read from disk, so it should be
+/// cheap; forward to a channel to render progress elsewhere. A retried upload starts
+/// again from zero.
+#[derive(Clone)]
+pub struct ProgressCallback(Arc<dyn Fn(UploadProgress) + Send + Sync>);
+
+impl ProgressCallback {
+    pub fn new(callback: impl Fn(UploadProgress) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(callback))
+    }
+
+    pub fn call(&self, progress: UploadProgress) {
+        (self.0)(progress)
+    }
+}
+
+impl fmt::Debug for ProgressCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ProgressCallback")
+    }
+}
+
+/// Reports every read from `inner` to an optional [`ProgressCallback`].
+#[cfg(feature = "blocking")]
+pub(crate) struct ProgressReader<R> {
+    inner: R,
+    progress: UploadProgress,
+    callback: Option<ProgressCallback>,
+}
+
+#[cfg(feature = "blocking")]
+impl<R: Read> ProgressReader<R> {
+    pub(crate) fn new(inner: R, total: u64, callback: Option<ProgressCallback>) -> Self {
+        Self {
+            inner,
+            progress: UploadProgress {
+                bytes_sent: 0,
+                total,
+            },
+            callback,
+        }
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl<R: Read> Read for ProgressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        if let Some(callback) = &self.callback {
+            if n > 0 {
+                self.progress.bytes_sent += n as u64;
+                callback.call(self.progress);
+            }
+        }
+        Ok(n)
+    }
+}
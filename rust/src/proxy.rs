@@ -0,0 +1,77 @@
+// src/proxy.rs
+
+use crate::builder::HttpClientBuilder;
+use crate::TraceixError;
+use reqwest::{NoProxy, Proxy};
+use std::env;
+use std::fmt;
+
+/// Proxy configuration collected by [`TraceixSdkBuilder`](crate::TraceixSdkBuilder).
+#[derive(Clone, Default)]
+pub(crate) struct ProxySettings {
+    pub(crate) url: Option<String>,
+    pub(crate) credentials: Option<(String, String)>,
+    pub(crate) no_proxy: Option<Vec<String>>,
+    pub(crate) disabled: bool,
+}
+
+impl ProxySettings {
+    /// Configure `client` to use the proxy.
+    ///
+    /// An explicit URL wins over `HTTPS_PROXY`/`ALL_PROXY` (upper or lower case), and an
+    /// explicit bypass list over `NO_PROXY`. With neither, `reqwest`'s own proxy detection
+    /// (including `HTTP_PROXY` for plain-HTTP base URLs) is left in place.
+    pub(crate) fn apply<B: HttpClientBuilder>(&self, client: B) -> Result<B, TraceixError> {
+        if self.disabled {
+            return Ok(client.no_proxy());
+        }
+        let Some(url) = self.url.clone().or_else(proxy_from_env) else {
+            return Ok(client);
+        };
+
+        let mut proxy = Proxy::all(url.as_str()).map_err(|e| {
+            TraceixError::InvalidArgument(format!("invalid proxy {}: {e}", redact(&url)))
+        })?;
+        if let Some((username, password)) = &self.credentials {
+            proxy = proxy.basic_auth(username, password);
+        }
+        let no_proxy = match &self.no_proxy {
+            Some(hosts) => NoProxy::from_string(&hosts.join(",")),
+            None => NoProxy::from_env(),
+        };
+        Ok(client.proxy(proxy.no_proxy(no_proxy)))
+    }
+}
+
+/// The first non-empty proxy variable that applies to HTTPS requests.
+fn proxy_from_env() -> Option<String> {
+    ["HTTPS_PROXY", "https_proxy", "ALL_PROXY", "all_proxy"]
+        .iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|value| !value.trim().is_empty())
+}
+
+/// `url` with any password replaced, for error messages and `Debug`.
+fn redact(url: &str) -> String {
+    match url::Url::parse(url) {
+        Ok(mut parsed) if parsed.password().is_some() => {
+            let _ = parsed.set_password(Some("***"));
+            parsed.to_string()
+        }
+        _ => url.to_string(),
+    }
+}
+
+impl fmt::Debug for ProxySettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProxySettings")
+            .field("url", &self.url.as_deref().map(redact))
+            .field(
+                "credentials",
+                &self.credentials.as_ref().map(|(user, _)| (user, "***")),
+            )
+            .field("no_proxy", &self.no_proxy)
+            .field("disabled", &self.disabled)
+            .finish()
+    }
+}
@@ -0,0 +1,400 @@
+// src/queue.rs
+
+//! A local queue of samples to upload later, e.g. from a triage laptop that collects
+//! samples offline and submits them once it is back on a network.
+//!
+//! [`TraceixSdk::enqueue`] copies a file into a [`QueueStore`] and
+//! [`TraceixSdk::flush_queue`] uploads everything queued. Queued samples are live
+//! malware or sensitive documents, so a store on a machine that could be lost should
+//! be an [`EncryptedQueueStore`] (with the `encrypted-queue` feature).
+
+use crate::{upload_file_name, Submission, TraceixError, TraceixSdk, UploadOptions};
+#[cfg(feature = "encrypted-queue")]
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng, Payload},
+    Aes256Gcm, Key, Nonce,
+};
+use chrono::{DateTime, Utc};
+use reqwest::blocking::multipart::Part;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use zeroize::Zeroizing;
+
+/// A sample waiting in a [`QueueStore`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct QueuedSample {
+    /// Identifier within the store; sorts in queueing order.
+    pub id: String,
+    /// File name the sample is uploaded under.
+    pub filename: String,
+    pub size: u64,
+    pub options: UploadOptions,
+    pub queued_at: DateTime<Utc>,
+}
+
+/// A queued entry together with the outcome of its upload by
+/// [`TraceixSdk::flush_queue`].
+#[non_exhaustive]
+pub struct FlushResult {
+    /// Identifier of the entry within the store.
+    pub id: String,
+    /// The queued sample; `None` when its metadata couldn't be read, e.g. a corrupted
+    /// entry or one encrypted with another key. `result` then holds that error.
+    pub sample: Option<QueuedSample>,
+    pub result: Result<Submission, TraceixError>,
+}
+
+/// Where queued samples are kept until they are uploaded.
+pub trait QueueStore: Send + Sync {
+    /// Store `data` under `sample.id`.
+    fn put(&self, sample: &QueuedSample, data: &[u8]) -> Result<(), TraceixError>;
+    /// Ids of every queued sample, oldest first.
+    fn ids(&self) -> Result<Vec<String>, TraceixError>;
+    /// The queued sample with id `id`.
+    fn get(&self, id: &str) -> Result<QueuedSample, TraceixError>;
+    /// Every queued sample, oldest first. Fails if any of them can't be read.
+    fn list(&self) -> Result<Vec<QueuedSample>, TraceixError> {
+        self.ids()?.iter().map(|id| self.get(id)).collect()
+    }
+    /// The content of a queued sample.
+    fn read(&self, id: &str) -> Result<Vec<u8>, TraceixError>;
+    /// Drop a sample from the queue, e.g. once it has been uploaded.
+    fn remove(&self, id: &str) -> Result<(), TraceixError>;
+}
+
+/// A [`QueueStore`] keeping each sample as plain files in a directory: `<id>.json` for
+/// its metadata and `<id>.sample` for its content.
+#[derive(Clone, Debug)]
+pub struct DirQueueStore {
+    dir: PathBuf,
+}
+
+impl DirQueueStore {
+    /// Use `dir` for the queue, creating it if needed.
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self, TraceixError> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+}
+
+impl QueueStore for DirQueueStore {
+    fn put(&self, sample: &QueuedSample, data: &[u8]) -> Result<(), TraceixError> {
+        let meta = serde_json::to_vec(sample).expect("sample serializes");
+        // The metadata goes last, so a sample only shows up once its content is there.
+        write_atomic(&file(&self.dir, &sample.id, "sample")?, data)?;
+        write_atomic(&file(&self.dir, &sample.id, "json")?, &meta)
+    }
+
+    fn ids(&self) -> Result<Vec<String>, TraceixError> {
+        list_ids(&self.dir, "json")
+    }
+
+    fn get(&self, id: &str) -> Result<QueuedSample, TraceixError> {
+        parse_meta(id, &fs::read(file(&self.dir, id, "json")?)?)
+    }
+
+    fn read(&self, id: &str) -> Result<Vec<u8>, TraceixError> {
+        Ok(fs::read(file(&self.dir, id, "sample")?)?)
+    }
+
+    fn remove(&self, id: &str) -> Result<(), TraceixError> {
+        remove_entry(&self.dir, id, &["json", "sample"])
+    }
+}
+
+/// A [`QueueStore`] encrypting every sample and its metadata with AES-256-GCM, so the
+/// queue directory reveals neither the samples nor their names without the key.
+///
+/// The key never touches the disk; keep it in the OS keychain or derive it from an
+/// operator passphrase. Entries written with another key fail to read with
+/// [`TraceixError::InvalidArgument`].
+///
+/// ```no_run
+/// # use traceix_sdk::{EncryptedQueueStore, TraceixSdk, UploadOptions};
+/// # fn main() -> Result<(), traceix_sdk::TraceixError> {
+/// # let key = [0u8; 32];
+/// let store = EncryptedQueueStore::open("/var/lib/triage/queue", &key)?;
+/// let sdk = TraceixSdk::new(None)?;
+/// sdk.enqueue(&store, "/media/usb/dropper.exe", &UploadOptions::default())?;
+/// // ... later, back online ...
+/// for flushed in sdk.flush_queue(&store)? {
+///     if let Err(e) = flushed.result {
+///         eprintln!("{} stays queued: {e}", flushed.id);
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "encrypted-queue")]
+pub struct EncryptedQueueStore {
+    dir: PathBuf,
+    cipher: Aes256Gcm,
+}
+
+#[cfg(feature = "encrypted-queue")]
+impl EncryptedQueueStore {
+    /// Length of a nonce, stored in front of each ciphertext.
+    const NONCE_LEN: usize = 12;
+
+    /// Use `dir` for the queue, creating it if needed, with a 256-bit `key`.
+    pub fn open(dir: impl Into<PathBuf>, key: &[u8; 32]) -> Result<Self, TraceixError> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir,
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key)),
+        })
+    }
+
+    /// A new random key from the operating system's generator.
+    pub fn generate_key() -> [u8; 32] {
+        Aes256Gcm::generate_key(OsRng).into()
+    }
+
+    /// Encrypt `plain` for the `kind` file of entry `id`. Both are authenticated, so a
+    /// file can't be swapped into another entry or role undetected.
+    fn seal(&self, id: &str, kind: &str, plain: &[u8]) -> Vec<u8> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let aad = format!("{id}.{kind}");
+        let sealed = self
+            .cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: plain,
+                    aad: aad.as_bytes(),
+                },
+            )
+            .expect("AES-GCM encryption of in-memory data");
+        [nonce.as_slice(), &sealed].concat()
+    }
+
+    fn open_file(&self, id: &str, kind: &str) -> Result<Vec<u8>, TraceixError> {
+        let sealed = fs::read(file(&self.dir, id, kind)?)?;
+        let undecryptable = || {
+            TraceixError::InvalidArgument(format!(
+                "can't decrypt queue entry {id}: wrong key or corrupted file"
+            ))
+        };
+        if sealed.len() < Self::NONCE_LEN {
+            return Err(undecryptable());
+        }
+        let (nonce, ciphertext) = sealed.split_at(Self::NONCE_LEN);
+        let aad = format!("{id}.{kind}");
+        self.cipher
+            .decrypt(
+                Nonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: aad.as_bytes(),
+                },
+            )
+            .map_err(|_| undecryptable())
+    }
+}
+
+#[cfg(feature = "encrypted-queue")]
+impl QueueStore for EncryptedQueueStore {
+    fn put(&self, sample: &QueuedSample, data: &[u8]) -> Result<(), TraceixError> {
+        let meta = serde_json::to_vec(sample).expect("sample serializes");
+        write_atomic(
+            &file(&self.dir, &sample.id, "data")?,
+            &self.seal(&sample.id, "data", data),
+        )?;
+        write_atomic(
+            &file(&self.dir, &sample.id, "meta")?,
+            &self.seal(&sample.id, "meta", &meta),
+        )
+    }
+
+    fn ids(&self) -> Result<Vec<String>, TraceixError> {
+        list_ids(&self.dir, "meta")
+    }
+
+    fn get(&self, id: &str) -> Result<QueuedSample, TraceixError> {
+        parse_meta(id, &self.open_file(id, "meta")?)
+    }
+
+    fn read(&self, id: &str) -> Result<Vec<u8>, TraceixError> {
+        self.open_file(id, "data")
+    }
+
+    fn remove(&self, id: &str) -> Result<(), TraceixError> {
+        remove_entry(&self.dir, id, &["meta", "data"])
+    }
+}
+
+impl TraceixSdk {
+    /// Copy the file at `path` into `store` to upload later with `options`.
+    pub fn enqueue(
+        &self,
+        store: &dyn QueueStore,
+        path: impl AsRef<Path>,
+        options: &UploadOptions,
+    ) -> Result<QueuedSample, TraceixError> {
+        let path = path.as_ref();
+        let data = fs::read(path)?;
+        let sample = QueuedSample {
+            id: new_id(),
+            filename: upload_file_name(path),
+            size: data.len() as u64,
+            options: options.clone(),
+            queued_at: Utc::now(),
+        };
+        store.put(&sample, &data)?;
+        Ok(sample)
+    }
+
+    /// Upload every sample in `store` for AI prediction, oldest first, removing each
+    /// one that was accepted. Samples that fail stay queued for the next flush, as do
+    /// entries that can't be read, which are reported with no `sample`; only a store
+    /// that can't be listed fails the whole call.
+    pub fn flush_queue(&self, store: &dyn QueueStore) -> Result<Vec<FlushResult>, TraceixError> {
+        let ids = store.ids()?;
+        Ok(ids
+            .into_iter()
+            .map(|id| match store.get(&id) {
+                Ok(sample) => {
+                    let result = self.upload_queued(store, &sample);
+                    FlushResult {
+                        id,
+                        sample: Some(sample),
+                        result,
+                    }
+                }
+                Err(e) => FlushResult {
+                    id,
+                    sample: None,
+                    result: Err(e),
+                },
+            })
+            .collect())
+    }
+
+    fn upload_queued(
+        &self,
+        store: &dyn QueueStore,
+        sample: &QueuedSample,
+    ) -> Result<Submission, TraceixError> {
+        let data = Zeroizing::new(store.read(&sample.id)?);
+        let len = data.len() as u64;
+        let part = Part::reader_with_length(self.body_reader(Cursor::new(data)), len)
+            .file_name(sample.filename.clone())
+            .mime_str("application/octet-stream")
+            .map_err(TraceixError::Http)?;
+        let submission = self.upload_part(part, &sample.options)?;
+        store.remove(&sample.id)?;
+        Ok(submission)
+    }
+}
+
+/// A new entry id: the time in nanoseconds, then the process and a counter to keep ids
+/// from concurrent writers apart. Fixed width, so ids sort in queueing order.
+fn new_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = Utc::now().timestamp_nanos_opt().unwrap_or_default();
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed) % 10_000;
+    format!("{nanos:020}-{:010}-{count:04}", std::process::id())
+}
+
+/// Path of the `kind` file of entry `id`, refusing ids that would leave the directory.
+fn file(dir: &Path, id: &str, kind: &str) -> Result<PathBuf, TraceixError> {
+    if id.is_empty() || !id.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-') {
+        return Err(TraceixError::InvalidArgument(format!(
+            "invalid queue entry id {id:?}"
+        )));
+    }
+    Ok(dir.join(format!("{id}.{kind}")))
+}
+
+fn parse_meta(id: &str, meta: &[u8]) -> Result<QueuedSample, TraceixError> {
+    serde_json::from_slice(meta)
+        .map_err(|e| TraceixError::InvalidArgument(format!("invalid queue entry {id}: {e}")))
+}
+
+/// Ids of the entries in `dir` with a `kind` file, sorted.
+fn list_ids(dir: &Path, kind: &str) -> Result<Vec<String>, TraceixError> {
+    let suffix = format!(".{kind}");
+    let mut ids = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let name = entry?.file_name();
+        if let Some(id) = name.to_str().and_then(|n| n.strip_suffix(&suffix)) {
+            ids.push(id.to_string());
+        }
+    }
+    ids.sort();
+    Ok(ids)
+}
+
+fn remove_entry(dir: &Path, id: &str, kinds: &[&str]) -> Result<(), TraceixError> {
+    for kind in kinds {
+        match fs::remove_file(file(dir, id, kind)?) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Write next to `path`, then move into place so readers never see a partial file.
+fn write_atomic(path: &Path, data: &[u8]) -> Result<(), TraceixError> {
+    let partial = path.with_extension("partial");
+    fs::write(&partial, data)?;
+    fs::rename(&partial, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Transport;
+    use reqwest::blocking::{Request, Response};
+
+    struct Accept;
+
+    impl Transport for Accept {
+        fn execute(&self, _request: Request) -> Result<Response, TraceixError> {
+            let response = http::Response::builder()
+                .status(200)
+                .body(r#"{"uuid":"0000-1111"}"#)
+                .unwrap();
+            Ok(Response::from(response))
+        }
+    }
+
+    #[test]
+    fn unreadable_entries_fail_alone() {
+        let dir = std::env::temp_dir().join(format!("traceix-queue-test-{}", new_id()));
+        let store = DirQueueStore::open(&dir).unwrap();
+        let sdk = TraceixSdk::builder()
+            .api_key("test")
+            .transport(Accept)
+            .build()
+            .unwrap();
+
+        let sample = dir.join("sample.bin");
+        fs::write(&sample, b"MZ").unwrap();
+        let queued = sdk
+            .enqueue(&store, &sample, &UploadOptions::default())
+            .unwrap();
+        fs::write(dir.join("00000000000000000000-corrupt.json"), b"{not json").unwrap();
+
+        let flushed = sdk.flush_queue(&store).unwrap();
+        assert_eq!(flushed.len(), 2);
+        assert_eq!(flushed[0].id, "00000000000000000000-corrupt");
+        assert!(flushed[0].sample.is_none());
+        assert!(matches!(
+            flushed[0].result,
+            Err(TraceixError::InvalidArgument(_))
+        ));
+        assert_eq!(flushed[1].id, queued.id);
+        assert!(flushed[1].result.is_ok());
+        assert_eq!(store.ids().unwrap(), ["00000000000000000000-corrupt"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
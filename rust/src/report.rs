@@ -0,0 +1,119 @@
+// src/report.rs
+
+use crate::download::stream_to_file;
+use crate::{decode_response, Download, Endpoint, HashAlgorithm, TraceixError, TraceixSdk};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+use url::Url;
+
+/// What a share link grants access to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum ShareScope {
+    /// The human-readable web report only.
+    #[default]
+    Report,
+    /// The web report plus the raw prediction/CAPA/EXIF artifacts.
+    ReportAndArtifacts,
+}
+
+/// Formats a server-generated report can be downloaded in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum ReportFormat {
+    #[default]
+    Pdf,
+    Html,
+}
+
+impl ReportFormat {
+    /// Conventional file extension for the format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ReportFormat::Pdf => "pdf",
+            ReportFormat::Html => "html",
+        }
+    }
+}
+
+/// A time-limited link to a submission's web report that can be handed to third parties.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ShareLink {
+    pub url: Url,
+    pub scope: ShareScope,
+    /// Expiry timestamp as reported by the server.
+    #[serde(default)]
+    pub expires_at: Option<String>,
+}
+
+impl TraceixSdk {
+    /// The canonical web UI URL of the human-readable report for `uuid`.
+    ///
+    /// Confirms the submission exists via the status endpoint before returning.
+    pub fn report_url(&self, uuid: &str) -> Result<Url, TraceixError> {
+        // Errors (including not-found) surface from the status lookup.
+        self.check_status(uuid)?;
+
+        let mut url = self.build_url("/traceix/report");
+        url.path_segments_mut()
+            .expect("base url validated at construction")
+            .push(uuid);
+        Ok(url)
+    }
+
+    /// Create a time-limited share link for the report of `uuid`, valid for `expiry`.
+    ///
+    /// Anyone holding the link can view what `scope` allows without an API key.
+    pub fn create_share_link(
+        &self,
+        uuid: &str,
+        expiry: Duration,
+        scope: ShareScope,
+    ) -> Result<ShareLink, TraceixError> {
+        if uuid.is_empty() {
+            return Err(TraceixError::NoUuidProvided);
+        }
+        if expiry.as_secs() == 0 {
+            return Err(TraceixError::InvalidArgument(
+                "share link expiry must be at least one second".to_string(),
+            ));
+        }
+
+        let body = self.post_json(
+            &Endpoint::SHARE_LINK,
+            &serde_json::json!({
+                "uuid": uuid,
+                "expires_in": expiry.as_secs(),
+                "scope": scope,
+            }),
+        )?;
+
+        decode_response(body)
+    }
+
+    /// Download the server-generated report for `uuid` in `format`, streaming it to `dest`.
+    ///
+    /// The returned [`Download`] carries the SHA-256 of the written file, handy for
+    /// recording alongside case files.
+    pub fn download_report(
+        &self,
+        uuid: &str,
+        format: ReportFormat,
+        dest: impl AsRef<Path>,
+    ) -> Result<Download, TraceixError> {
+        if uuid.is_empty() {
+            return Err(TraceixError::NoUuidProvided);
+        }
+
+        let dest = dest.as_ref();
+        let body = serde_json::json!({ "uuid": uuid, "format": format });
+        let resp = self.send(&Endpoint::REPORT_DOWNLOAD, Some(dest), |request| {
+            Ok(request.json(&body))
+        })?;
+
+        stream_to_file(resp, dest, HashAlgorithm::Sha256, None, self.cancel.clone())
+    }
+}
@@ -0,0 +1,116 @@
+// src/rescan.rs
+
+use crate::diff::ResultDiff;
+use crate::hashing::normalize_hex_digest;
+use crate::{Endpoint, FileHash, Submission, TraceixError, TraceixSdk, DEFAULT_ANALYSIS_TIMEOUT};
+use serde::Serialize;
+use serde_json::Value;
+use std::time::SystemTime;
+
+/// The outcome of re-analyzing a sample and comparing it with the stored result.
+#[derive(Clone, Debug, Serialize)]
+pub struct RescanComparison {
+    pub original_uuid: String,
+    pub rescan_uuid: String,
+    pub original: Value,
+    pub rescan: Value,
+    pub diff: ResultDiff,
+    /// When the comparison was made, for documenting how verdicts evolve over time.
+    pub compared_at: SystemTime,
+}
+
+impl TraceixSdk {
+    /// Ask the server to re-run analysis on a sample it already holds.
+    pub(crate) fn request_rescan(&self, body: &Value) -> Result<Submission, TraceixError> {
+        let response = self.post_json(&Endpoint::RESCAN, body)?;
+        Submission::from_response(self, response)
+    }
+
+    /// Re-run analysis on a sample the server already holds, without re-uploading it.
+    ///
+    /// `hash_or_uuid` is either the UUID of an earlier submission or the sample's
+    /// MD5/SHA-1/SHA-256 hash. The returned [`Submission`] tracks the new job.
+    pub fn rescan(&self, hash_or_uuid: &str) -> Result<Submission, TraceixError> {
+        self.request_rescan(&rescan_target(hash_or_uuid)?)
+    }
+
+    /// Re-run analysis on an earlier submission without re-uploading the sample, e.g.
+    /// after the models were updated. The returned [`Submission`] tracks the new job,
+    /// which has its own UUID.
+    ///
+    /// ```no_run
+    /// # use traceix_sdk::TraceixSdk;
+    /// # fn main() -> Result<(), traceix_sdk::TraceixError> {
+    /// let sdk = TraceixSdk::new(None)?;
+    /// let job = sdk.reanalyze("0b5c7c1e-3f0d-4a4e-9d3b-2f8a6a1c9e10")?;
+    /// println!("{}", job.result()?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn reanalyze(&self, uuid: &str) -> Result<Submission, TraceixError> {
+        let uuid = uuid.trim();
+        if uuid.is_empty() {
+            return Err(TraceixError::NoUuidProvided);
+        }
+        if !is_uuid(uuid) {
+            return Err(TraceixError::InvalidArgument(format!(
+                "{uuid:?} is not a UUID"
+            )));
+        }
+        self.request_rescan(&serde_json::json!({ "uuid": uuid }))
+    }
+
+    /// Like [`TraceixSdk::reanalyze`], naming the sample by its SHA-256 (or MD5/SHA-1)
+    /// hash instead of an earlier submission.
+    pub fn reanalyze_hash(&self, hash: &str) -> Result<Submission, TraceixError> {
+        let (algorithm, hash) = normalize_hex_digest(hash)?;
+        self.request_rescan(&serde_json::json!({ algorithm.as_str(): hash }))
+    }
+
+    /// Re-analyze the sample behind `uuid` and diff the fresh result against the stored one.
+    ///
+    /// Waits up to [`DEFAULT_ANALYSIS_TIMEOUT`] for the re-scan to finish.
+    pub fn compare_with_rescan(&self, uuid: &str) -> Result<RescanComparison, TraceixError> {
+        let original = self.get_result(uuid)?;
+
+        let rescan = self.request_rescan(&serde_json::json!({ "uuid": uuid }))?;
+        rescan.result_timeout(DEFAULT_ANALYSIS_TIMEOUT)?;
+        let fresh = self.get_result(rescan.uuid())?;
+
+        Ok(RescanComparison {
+            original_uuid: uuid.to_string(),
+            rescan_uuid: rescan.uuid().to_string(),
+            diff: ResultDiff::between(&original, &fresh),
+            original,
+            rescan: fresh,
+            compared_at: SystemTime::now(),
+        })
+    }
+}
+
+/// Build the rescan request body, telling hashes and UUIDs apart by shape.
+fn rescan_target(hash_or_uuid: &str) -> Result<Value, TraceixError> {
+    let target = hash_or_uuid.trim();
+    if target.is_empty() {
+        return Err(TraceixError::NoUuidProvided);
+    }
+    if is_uuid(target) {
+        return Ok(serde_json::json!({ "uuid": target }));
+    }
+
+    let hash = FileHash::parse(target).map_err(|_| {
+        TraceixError::InvalidArgument(format!(
+            "{target:?} is neither a UUID nor an MD5/SHA-1/SHA-256 hash"
+        ))
+    })?;
+    Ok(serde_json::json!({ hash.field(): hash.as_str() }))
+}
+
+/// Whether `s` is a UUID in its canonical 8-4-4-4-12 hex form.
+fn is_uuid(s: &str) -> bool {
+    s.len() == 36
+        && s.char_indices().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        })
+}
@@ -0,0 +1,49 @@
+// src/results.rs
+
+use crate::{CapaReport, Endpoint, ExifReport, TraceixError, TraceixSdk};
+use serde_json::Value;
+
+impl TraceixSdk {
+    /// Fetch the stored result of a finished analysis, e.g. to collect it long after
+    /// submitting. Holds every artifact the job produced.
+    pub fn get_result(&self, uuid: &str) -> Result<Value, TraceixError> {
+        self.fetch_artifact(uuid, None)
+    }
+
+    /// Fetch only the AI prediction of a finished analysis.
+    pub fn get_prediction(&self, uuid: &str) -> Result<Value, TraceixError> {
+        self.fetch_artifact(uuid, Some("prediction"))
+    }
+
+    /// Fetch only the CAPA capabilities of a finished analysis.
+    pub fn get_capa_result(&self, uuid: &str) -> Result<Value, TraceixError> {
+        self.fetch_artifact(uuid, Some("capa"))
+    }
+
+    /// Fetch the CAPA capabilities of a finished analysis as a [`CapaReport`].
+    pub fn get_capa_report(&self, uuid: &str) -> Result<CapaReport, TraceixError> {
+        CapaReport::from_value(&self.get_capa_result(uuid)?)
+    }
+
+    /// Fetch only the EXIF metadata of a finished analysis.
+    pub fn get_exif_result(&self, uuid: &str) -> Result<Value, TraceixError> {
+        self.fetch_artifact(uuid, Some("exif"))
+    }
+
+    /// Fetch the EXIF metadata of a finished analysis as an [`ExifReport`].
+    pub fn get_exif_report(&self, uuid: &str) -> Result<ExifReport, TraceixError> {
+        ExifReport::from_value(&self.get_exif_result(uuid)?)
+    }
+
+    fn fetch_artifact(&self, uuid: &str, artifact: Option<&str>) -> Result<Value, TraceixError> {
+        if uuid.is_empty() {
+            return Err(TraceixError::NoUuidProvided);
+        }
+
+        let mut body = serde_json::json!({ "uuid": uuid });
+        if let Some(artifact) = artifact {
+            body["artifact"] = artifact.into();
+        }
+        self.post_json(&Endpoint::RESULT, &body)
+    }
+}
@@ -0,0 +1,66 @@
+// src/resume.rs
+
+use crate::{Endpoint, TraceixError, TraceixSdk};
+use reqwest::blocking::Body;
+use reqwest::header::{HeaderValue, CONTENT_RANGE, CONTENT_TYPE};
+use serde_json::Value;
+use std::fs::File;
+use std::io::{Seek, SeekFrom};
+use std::path::Path;
+
+impl TraceixSdk {
+    /// Ask the server how many bytes it has received for an interrupted upload.
+    pub fn upload_offset(&self, upload_id: &str) -> Result<u64, TraceixError> {
+        let body = self.post_json(
+            &Endpoint::UPLOAD_OFFSET,
+            &serde_json::json!({ "upload_id": upload_id }),
+        )?;
+
+        body.get("offset")
+            .or_else(|| body.get("received"))
+            .and_then(Value::as_u64)
+            .ok_or_else(|| {
+                TraceixError::UnexpectedResponse(format!("no offset in response {body}"))
+            })
+    }
+
+    /// Continue an interrupted prediction upload from the offset the server already holds.
+    ///
+    /// The original upload must have been started with
+    /// [`UploadOptions::upload_id`](crate::UploadOptions::upload_id) set to `upload_id`.
+    /// Only the remaining bytes of `filename` are sent.
+    pub fn resume_upload(
+        &self,
+        upload_id: &str,
+        filename: impl AsRef<Path>,
+    ) -> Result<Value, TraceixError> {
+        let filename = filename.as_ref();
+        let id_header = HeaderValue::from_str(upload_id).map_err(|_| {
+            TraceixError::InvalidArgument(format!("invalid upload id {upload_id:?}"))
+        })?;
+        let offset = self.upload_offset(upload_id)?;
+
+        self.execute(&Endpoint::UPLOAD_RESUME, Some(filename), |request| {
+            let mut file = File::open(filename)?;
+            let total = file.metadata()?.len();
+            let offset = offset.min(total);
+            file.seek(SeekFrom::Start(offset))?;
+
+            // `bytes */total` tells the server nothing is left to send but the upload is complete.
+            let range = if offset < total {
+                format!("bytes {}-{}/{}", offset, total - 1, total)
+            } else {
+                format!("bytes */{total}")
+            };
+
+            Ok(request
+                .header("x-upload-id", id_header.clone())
+                .header(CONTENT_TYPE, "application/octet-stream")
+                .header(CONTENT_RANGE, range)
+                .body(Body::sized(
+                    self.body_reader(self.buffers.reader(file)),
+                    total - offset,
+                )))
+        })
+    }
+}
@@ -0,0 +1,240 @@
+// src/retry.rs
+
+use crate::{Endpoint, TraceixError};
+use reqwest::StatusCode;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// How failed requests are retried.
+///
+/// Only transport failures (connection refused or reset, timeouts) and responses with
+/// one of the retryable status codes are retried; other errors surface immediately.
+/// Uploads and other requests that create something are only retried after a failure
+/// to connect or a `503 Service Unavailable`, never after a timeout, a failure while
+/// sending, or another error status, when the server may already have acted on them;
+/// see [`RetryPolicy::retry_non_idempotent`].
+/// The delay before attempt `n + 1` is `initial_backoff * multiplier^(n - 1)`, capped
+/// at `max_backoff`, and with jitter enabled a random value between half and all of it.
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # use traceix_sdk::RetryPolicy;
+/// # #[cfg(feature = "blocking")]
+/// # fn main() -> Result<(), traceix_sdk::TraceixError> {
+/// # use traceix_sdk::TraceixSdk;
+/// let sdk = TraceixSdk::builder()
+///     .retry_policy(
+///         RetryPolicy::default()
+///             .max_attempts(5)
+///             .backoff(Duration::from_millis(250), Duration::from_secs(10)),
+///     )
+///     .build()?;
+///
+/// // One call that must not be retried.
+/// let status = sdk.with_retry_policy(RetryPolicy::none()).check_status("uuid")?;
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "blocking"))]
+/// # fn main() {}
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: f64,
+    jitter: bool,
+    retry_connection_errors: bool,
+    retryable_statuses: Vec<u16>,
+    respect_rate_limits: bool,
+    max_retry_after: Duration,
+    retry_non_idempotent: bool,
+}
+
+/// Longest `Retry-After` wait honoured by default, see [`RetryPolicy::max_retry_after`].
+pub const DEFAULT_MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+impl Default for RetryPolicy {
+    /// Three attempts with jittered backoff from 500ms, retrying connection errors,
+    /// timeouts, and 502/503/504 responses.
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: true,
+            retry_connection_errors: true,
+            retryable_statuses: vec![502, 503, 504],
+            respect_rate_limits: false,
+            max_retry_after: DEFAULT_MAX_RETRY_AFTER,
+            retry_non_idempotent: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that sends every request exactly once.
+    pub fn none() -> Self {
+        Self::default().max_attempts(1)
+    }
+
+    /// Total attempts per request, including the first. At least 1.
+    pub fn max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    /// Delay before the first retry, and the cap for later ones.
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max.max(initial);
+        self
+    }
+
+    /// Factor the delay grows by after each failed attempt. Defaults to 2.
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.0);
+        self
+    }
+
+    /// Randomize delays so many clients failing together don't retry in lockstep.
+    pub fn jitter(mut self, enabled: bool) -> Self {
+        self.jitter = enabled;
+        self
+    }
+
+    /// Whether to retry requests that failed without a response, e.g. on a reset connection.
+    pub fn retry_connection_errors(mut self, enabled: bool) -> Self {
+        self.retry_connection_errors = enabled;
+        self
+    }
+
+    /// Replace the HTTP status codes that are retried.
+    pub fn retryable_statuses(mut self, statuses: impl IntoIterator<Item = u16>) -> Self {
+        self.retryable_statuses = statuses.into_iter().collect();
+        self
+    }
+
+    /// Also retry `429 Too Many Requests`, first sleeping for as long as the server's
+    /// `Retry-After` (or `X-RateLimit-Reset`) header asks. Off by default, so rate-limit
+    /// errors surface immediately with [`TraceixError::retry_after`] set.
+    pub fn respect_rate_limits(mut self, enabled: bool) -> Self {
+        self.respect_rate_limits = enabled;
+        self
+    }
+
+    /// Longest wait a rate-limited request sleeps through with
+    /// [`respect_rate_limits`](Self::respect_rate_limits). When the server asks for
+    /// more, the error surfaces instead, with [`TraceixError::retry_after`] set.
+    /// Defaults to [`DEFAULT_MAX_RETRY_AFTER`].
+    pub fn max_retry_after(mut self, max: Duration) -> Self {
+        self.max_retry_after = max;
+        self
+    }
+
+    /// Also retry uploads and other non-idempotent requests after a timeout, a failure
+    /// while sending, or a retryable status other than 503. The server may then have received the first attempt, so
+    /// only enable this when a duplicate submission is harmless. Off by default.
+    pub fn retry_non_idempotent(mut self, enabled: bool) -> Self {
+        self.retry_non_idempotent = enabled;
+        self
+    }
+
+    /// Whether `error` from attempt number `attempt` at `endpoint` should be retried.
+    pub(crate) fn should_retry(
+        &self,
+        endpoint: &Endpoint,
+        error: &TraceixError,
+        attempt: u32,
+    ) -> bool {
+        if attempt >= self.max_attempts {
+            return false;
+        }
+        if self.respect_rate_limits && error.is_rate_limited() {
+            return error
+                .retry_after()
+                .is_none_or(|wait| wait <= self.max_retry_after);
+        }
+        // Without a response, only a failed connection proves the server never saw the
+        // request; with one, only a 503 says it wasn't acted on.
+        let resend = endpoint.idempotent || self.retry_non_idempotent;
+        let retry_status = |status: StatusCode| {
+            self.retryable_statuses.contains(&status.as_u16())
+                && (resend || status == StatusCode::SERVICE_UNAVAILABLE)
+        };
+        match error.root() {
+            TraceixError::Api { status, .. } => retry_status(*status),
+            TraceixError::Timeout { source, .. } => {
+                self.retry_connection_errors && (resend || source.is_connect())
+            }
+            TraceixError::Http(e) => match e.status() {
+                Some(status) => retry_status(status),
+                None if e.is_connect() => self.retry_connection_errors,
+                None => {
+                    self.retry_connection_errors && resend && (e.is_timeout() || e.is_request())
+                }
+            },
+            _ => false,
+        }
+    }
+
+    /// How long to wait after `error` on attempt number `attempt` (starting at 1).
+    pub(crate) fn delay(&self, attempt: u32, error: &TraceixError) -> Duration {
+        if self.respect_rate_limits && error.is_rate_limited() {
+            if let Some(wait) = error.retry_after() {
+                return wait.min(self.max_retry_after);
+            }
+        }
+
+        let exponent = attempt.saturating_sub(1).min(32) as i32;
+        let backoff = self.initial_backoff.as_secs_f64() * self.multiplier.powi(exponent);
+        let backoff = backoff.min(self.max_backoff.as_secs_f64());
+        if !self.jitter {
+            return Duration::from_secs_f64(backoff);
+        }
+        Duration::from_secs_f64(backoff / 2.0 + backoff / 2.0 * random_fraction())
+    }
+}
+
+/// A random value in `[0, 1)`, good enough to spread out retries.
+fn random_fraction() -> f64 {
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderMap;
+
+    fn api_error(status: u16) -> TraceixError {
+        let status = StatusCode::from_u16(status).unwrap();
+        TraceixError::api(status, &HeaderMap::new(), b"{}")
+    }
+
+    #[test]
+    fn uploads_are_not_resubmitted_after_a_gateway_timeout() {
+        let policy = RetryPolicy::default();
+        assert!(!policy.should_retry(&Endpoint::AI_PREDICTION, &api_error(504), 1));
+        assert!(!policy.should_retry(&Endpoint::AI_PREDICTION, &api_error(502), 1));
+        assert!(policy.should_retry(&Endpoint::AI_PREDICTION, &api_error(503), 1));
+    }
+
+    #[test]
+    fn idempotent_requests_retry_every_retryable_status() {
+        let policy = RetryPolicy::default();
+        for status in [502, 503, 504] {
+            assert!(policy.should_retry(&Endpoint::CHECK_STATUS, &api_error(status), 1));
+        }
+        assert!(!policy.should_retry(&Endpoint::CHECK_STATUS, &api_error(500), 1));
+        assert!(!policy.should_retry(&Endpoint::CHECK_STATUS, &api_error(504), 3));
+    }
+
+    #[test]
+    fn opting_in_resubmits_uploads_after_a_gateway_timeout() {
+        let policy = RetryPolicy::default().retry_non_idempotent(true);
+        assert!(policy.should_retry(&Endpoint::AI_PREDICTION, &api_error(504), 1));
+    }
+}
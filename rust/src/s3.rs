@@ -0,0 +1,216 @@
+// src/s3.rs
+
+use crate::hashing::to_hex;
+use crate::{PlannedRequest, Submission, TraceixError, TraceixSdk, UploadOptions};
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use reqwest::blocking::multipart::Part;
+use sha2::{Digest, Sha256};
+use std::env;
+use url::Url;
+
+const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+
+/// Credentials and location of an S3-compatible object store.
+#[derive(Clone)]
+pub struct S3Config {
+    pub region: String,
+    /// Custom endpoint (MinIO, Ceph, R2, ...). `None` means AWS S3 in `region`.
+    pub endpoint: Option<Url>,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+    /// Address buckets as `endpoint/bucket/key` rather than `bucket.endpoint/key`.
+    /// Most self-hosted stores need this.
+    pub path_style: bool,
+}
+
+impl S3Config {
+    pub fn new(
+        region: impl Into<String>,
+        access_key_id: impl Into<String>,
+        secret_access_key: impl Into<String>,
+    ) -> Self {
+        Self {
+            region: region.into(),
+            endpoint: None,
+            access_key_id: access_key_id.into(),
+            secret_access_key: secret_access_key.into(),
+            session_token: None,
+            path_style: false,
+        }
+    }
+
+    /// Read the standard `AWS_*` environment variables.
+    ///
+    /// Uses `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY`, `AWS_SESSION_TOKEN`,
+    /// `AWS_REGION`/`AWS_DEFAULT_REGION` (default `us-east-1`), and `AWS_ENDPOINT_URL`,
+    /// which switches to path-style addressing.
+    pub fn from_env() -> Result<Self, TraceixError> {
+        let var = |name: &str| env::var(name).ok().filter(|v| !v.is_empty());
+        let missing = |name: &str| TraceixError::InvalidArgument(format!("{name} is not set"));
+
+        let region = var("AWS_REGION")
+            .or_else(|| var("AWS_DEFAULT_REGION"))
+            .unwrap_or_else(|| "us-east-1".to_string());
+        let mut config = Self::new(
+            region,
+            var("AWS_ACCESS_KEY_ID").ok_or_else(|| missing("AWS_ACCESS_KEY_ID"))?,
+            var("AWS_SECRET_ACCESS_KEY").ok_or_else(|| missing("AWS_SECRET_ACCESS_KEY"))?,
+        );
+        config.session_token = var("AWS_SESSION_TOKEN");
+        if let Some(endpoint) = var("AWS_ENDPOINT_URL") {
+            config = config.endpoint(&endpoint)?;
+        }
+        Ok(config)
+    }
+
+    /// Use a custom S3-compatible endpoint with path-style addressing.
+    pub fn endpoint(mut self, endpoint: &str) -> Result<Self, TraceixError> {
+        let url = Url::parse(endpoint).map_err(|e| TraceixError::InvalidBaseUrl {
+            url: endpoint.to_string(),
+            reason: e.to_string(),
+        })?;
+        self.endpoint = Some(url);
+        self.path_style = true;
+        Ok(self)
+    }
+
+    fn object_url(&self, bucket: &str, key: &str) -> Result<Url, TraceixError> {
+        let key = encode_key(key);
+        let raw = match (&self.endpoint, self.path_style) {
+            (Some(endpoint), true) => {
+                format!("{}/{bucket}/{key}", endpoint.as_str().trim_end_matches('/'))
+            }
+            (Some(endpoint), false) => {
+                let host = endpoint.host_str().unwrap_or_default();
+                let port = endpoint.port().map(|p| format!(":{p}")).unwrap_or_default();
+                format!("{}://{bucket}.{host}{port}/{key}", endpoint.scheme())
+            }
+            (None, true) => format!("https://s3.{}.amazonaws.com/{bucket}/{key}", self.region),
+            (None, false) => format!("https://{bucket}.s3.{}.amazonaws.com/{key}", self.region),
+        };
+        Url::parse(&raw).map_err(|e| TraceixError::InvalidArgument(format!("{raw}: {e}")))
+    }
+}
+
+/// Split `s3://bucket/key` into its bucket and key.
+fn parse_s3_uri(uri: &str) -> Result<(&str, &str), TraceixError> {
+    uri.strip_prefix("s3://")
+        .and_then(|rest| rest.split_once('/'))
+        .filter(|(bucket, key)| !bucket.is_empty() && !key.is_empty())
+        .ok_or_else(|| {
+            TraceixError::InvalidArgument(format!("{uri:?} is not an s3://bucket/key URI"))
+        })
+}
+
+/// Percent-encode an object key for a SigV4 canonical URI, keeping `/` separators.
+fn encode_key(key: &str) -> String {
+    let mut encoded = String::with_capacity(key.len());
+    for byte in key.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+fn hmac(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+impl TraceixSdk {
+    /// Upload an object from S3-compatible storage for AI prediction, streaming it
+    /// straight from the store into the upload body without touching local disk.
+    ///
+    /// `uri` is `s3://bucket/key`; the file name sent is the last segment of the key.
+    pub fn upload_s3(
+        &self,
+        uri: &str,
+        config: &S3Config,
+        options: &UploadOptions,
+    ) -> Result<Submission, TraceixError> {
+        let (bucket, key) = parse_s3_uri(uri)?;
+        let url = config.object_url(bucket, key)?;
+        let host = match url.port() {
+            Some(port) => format!("{}:{port}", url.host_str().unwrap_or_default()),
+            None => url.host_str().unwrap_or_default().to_string(),
+        };
+
+        // AWS Signature Version 4 for a GET with an unsigned payload.
+        let now = Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let scope = format!("{date}/{}/s3/aws4_request", config.region);
+
+        let mut headers = vec![
+            ("host", host),
+            ("x-amz-content-sha256", UNSIGNED_PAYLOAD.to_string()),
+            ("x-amz-date", amz_date.clone()),
+        ];
+        if let Some(token) = &config.session_token {
+            headers.push(("x-amz-security-token", token.clone()));
+        }
+        let canonical_headers: String = headers
+            .iter()
+            .map(|(name, value)| format!("{name}:{}\n", value.trim()))
+            .collect();
+        let signed_headers = headers
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join(";");
+
+        let canonical_request = format!(
+            "GET\n{}\n\n{canonical_headers}\n{signed_headers}\n{UNSIGNED_PAYLOAD}",
+            url.path()
+        );
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+            to_hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let key_date = hmac(
+            format!("AWS4{}", config.secret_access_key).as_bytes(),
+            &date,
+        );
+        let key_region = hmac(&key_date, &config.region);
+        let key_service = hmac(&key_region, "s3");
+        let signing_key = hmac(&key_service, "aws4_request");
+        let signature = to_hex(&hmac(&signing_key, &string_to_sign));
+
+        let mut request = self.client.get(url).header(
+            "authorization",
+            format!(
+                "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+                config.access_key_id
+            ),
+        );
+        for (name, value) in headers.into_iter().filter(|(name, _)| *name != "host") {
+            request = request.header(name, value);
+        }
+        let request = request.build()?;
+        if self.dry_run {
+            return Err(TraceixError::DryRun(Box::new(PlannedRequest::new(
+                None, &request, None,
+            ))));
+        }
+        let object = self.client.execute(request)?.error_for_status()?;
+
+        let filename = key.rsplit('/').next().unwrap_or(key).to_string();
+        let part = match object.content_length() {
+            Some(len) => Part::reader_with_length(self.body_reader(object), len),
+            None => Part::reader(self.body_reader(object)),
+        }
+        .file_name(filename)
+        .mime_str("application/octet-stream")
+        .map_err(TraceixError::Http)?;
+
+        self.upload_part(part, options)
+    }
+}
@@ -0,0 +1,49 @@
+// src/saved_search.rs
+
+use crate::{decode_response, Endpoint, TraceixError, TraceixSdk};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A named query stored on the server.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SavedSearch {
+    pub name: String,
+    /// The query document, exactly as it was saved.
+    pub query: Value,
+    #[serde(default)]
+    pub created_at: Option<String>,
+}
+
+impl TraceixSdk {
+    /// Store `query` under `name`, replacing any existing search with that name.
+    pub fn save_search(&self, name: &str, query: &Value) -> Result<SavedSearch, TraceixError> {
+        check_name(name)?;
+        decode_response(self.post_json(
+            &Endpoint::SAVED_SEARCH_SAVE,
+            &serde_json::json!({ "name": name, "query": query }),
+        )?)
+    }
+
+    /// List the account's saved searches, fetching every page.
+    pub fn list_saved_searches(&self) -> Result<Vec<SavedSearch>, TraceixError> {
+        self.list_all(&Endpoint::SAVED_SEARCH_LIST, "searches")
+    }
+
+    /// Execute a saved search by name and return its results.
+    pub fn run_saved_search(&self, name: &str) -> Result<Value, TraceixError> {
+        check_name(name)?;
+        self.post_json(
+            &Endpoint::SAVED_SEARCH_RUN,
+            &serde_json::json!({ "name": name }),
+        )
+    }
+}
+
+fn check_name(name: &str) -> Result<(), TraceixError> {
+    if name.trim().is_empty() {
+        return Err(TraceixError::InvalidArgument(
+            "saved search name is empty".to_string(),
+        ));
+    }
+    Ok(())
+}
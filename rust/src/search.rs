@@ -0,0 +1,244 @@
+// src/search.rs
+
+use crate::hashing::normalize_hex_digest;
+use crate::{Endpoint, HashAlgorithm, SearchType, TraceixError, TraceixSdk};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// Most hashes [`TraceixSdk::hash_search_many`] puts in a single request.
+pub const MAX_HASHES_PER_SEARCH: usize = 100;
+
+/// The response to one request issued by [`HashSearch::send`].
+#[derive(Clone, Debug)]
+pub struct HashSearchResponse {
+    pub search_type: SearchType,
+    pub algorithm: HashAlgorithm,
+    /// The normalized hashes included in this request.
+    pub hashes: Vec<String>,
+    pub response: Value,
+}
+
+/// A hash search over several hashes and result types, built by [`TraceixSdk::search_hashes`].
+///
+/// Hashes are grouped by algorithm and each group is sent to every selected endpoint in
+/// a single request, instead of one call per hash and type.
+///
+/// ```no_run
+/// # use traceix_sdk::{SearchType, TraceixSdk};
+/// # fn main() -> Result<(), traceix_sdk::TraceixError> {
+/// let sdk = TraceixSdk::new(None)?;
+/// let responses = sdk
+///     .search_hashes()
+///     .hash("44d88612fea8a8f36de82e1278abb02f")
+///     .hash("275a021bbfb6489e54d471899f7db9d1663fc695ec2fe2a2c4538aabf651fd0f")
+///     .search_type(SearchType::Capa)
+///     .max_results(25)
+///     .send()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+#[must_use = "a search does nothing until `send` is called"]
+pub struct HashSearch<'a> {
+    sdk: &'a TraceixSdk,
+    hashes: Vec<String>,
+    algorithm: Option<HashAlgorithm>,
+    search_types: Vec<SearchType>,
+    max_results: Option<usize>,
+}
+
+impl<'a> HashSearch<'a> {
+    pub fn hash(mut self, hash: impl Into<String>) -> Self {
+        self.hashes.push(hash.into());
+        self
+    }
+
+    pub fn hashes<I, S>(mut self, hashes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.hashes.extend(hashes.into_iter().map(Into::into));
+        self
+    }
+
+    /// Require every hash to be of this type. By default it is inferred per hash from its length.
+    pub fn hash_type(mut self, algorithm: HashAlgorithm) -> Self {
+        self.algorithm = Some(algorithm);
+        self
+    }
+
+    /// Add a result type to search. Defaults to both CAPA and EXIF when none is given.
+    pub fn search_type(mut self, search_type: SearchType) -> Self {
+        if !self.search_types.contains(&search_type) {
+            self.search_types.push(search_type);
+        }
+        self
+    }
+
+    /// Cap the number of results returned per request.
+    pub fn max_results(mut self, max: usize) -> Self {
+        self.max_results = Some(max);
+        self
+    }
+
+    /// Run the search, returning one response per result type and hash algorithm.
+    pub fn send(self) -> Result<Vec<HashSearchResponse>, TraceixError> {
+        if self.hashes.is_empty() {
+            return Err(TraceixError::InvalidArgument(
+                "no hashes to search for".to_string(),
+            ));
+        }
+
+        let mut groups: Vec<(HashAlgorithm, Vec<String>)> = Vec::new();
+        for hash in &self.hashes {
+            let (algorithm, hash) = normalize_hex_digest(hash)?;
+            if let Some(expected) = self.algorithm.filter(|a| *a != algorithm) {
+                return Err(TraceixError::InvalidArgument(format!(
+                    "{hash} is not a {} digest",
+                    expected.as_str()
+                )));
+            }
+            match groups.iter_mut().find(|(a, _)| *a == algorithm) {
+                Some((_, hashes)) if !hashes.contains(&hash) => hashes.push(hash),
+                Some(_) => {}
+                None => groups.push((algorithm, vec![hash])),
+            }
+        }
+
+        let search_types = if self.search_types.is_empty() {
+            vec![SearchType::Capa, SearchType::Exif]
+        } else {
+            self.search_types
+        };
+
+        let mut responses = Vec::new();
+        for search_type in search_types {
+            let endpoint = match search_type {
+                SearchType::Capa => &Endpoint::CAPA_SEARCH,
+                SearchType::Exif => &Endpoint::EXIF_SEARCH,
+            };
+            for (algorithm, hashes) in &groups {
+                let mut body = Map::new();
+                // A single hash keeps the plain string form of `hash_search`.
+                let value = match hashes.as_slice() {
+                    [hash] => Value::from(hash.as_str()),
+                    _ => Value::from(hashes.clone()),
+                };
+                body.insert(algorithm.as_str().to_string(), value);
+                if let Some(max) = self.max_results {
+                    body.insert("max_results".to_string(), Value::from(max));
+                }
+
+                responses.push(HashSearchResponse {
+                    search_type,
+                    algorithm: *algorithm,
+                    hashes: hashes.clone(),
+                    response: self.sdk.post_json(endpoint, &Value::Object(body))?,
+                });
+            }
+        }
+        Ok(responses)
+    }
+}
+
+impl TraceixSdk {
+    /// Search many hashes at once, e.g. a whole IOC list, in as few requests as the API
+    /// allows: hashes are grouped by algorithm and sent up to [`MAX_HASHES_PER_SEARCH`]
+    /// per request.
+    ///
+    /// The returned map is keyed by the normalized (lowercase) hash and has an entry for
+    /// every distinct input; hashes the server returned nothing for map to `Value::Null`.
+    ///
+    /// ```no_run
+    /// # use traceix_sdk::{SearchType, TraceixSdk};
+    /// # fn main() -> Result<(), traceix_sdk::TraceixError> {
+    /// let sdk = TraceixSdk::new(None)?;
+    /// let iocs = std::fs::read_to_string("iocs.txt")?;
+    /// let hashes: Vec<&str> = iocs.lines().filter(|l| !l.trim().is_empty()).collect();
+    /// for (hash, result) in sdk.hash_search_many(&hashes, SearchType::Capa)? {
+    ///     if !result.is_null() {
+    ///         println!("{hash}: {result}");
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn hash_search_many(
+        &self,
+        hashes: &[&str],
+        search_type: SearchType,
+    ) -> Result<HashMap<String, Value>, TraceixError> {
+        let mut results = HashMap::new();
+        for chunk in hashes.chunks(MAX_HASHES_PER_SEARCH) {
+            let responses = self
+                .search_hashes()
+                .hashes(chunk.iter().copied())
+                .search_type(search_type)
+                .send()?;
+            for response in responses {
+                results.extend(split_results(response));
+            }
+        }
+        Ok(results)
+    }
+
+    /// Start a multi-hash search; finish it with [`HashSearch::send`].
+    pub fn search_hashes(&self) -> HashSearch<'_> {
+        HashSearch {
+            sdk: self,
+            hashes: Vec::new(),
+            algorithm: None,
+            search_types: Vec::new(),
+            max_results: None,
+        }
+    }
+}
+
+/// Break a multi-hash response into one result per requested hash.
+///
+/// Results come back either keyed by hash (`{"results": {"<hash>": ...}}`) or as a list
+/// of records that name their hash. A single-hash request is answered with the bare result.
+fn split_results(response: HashSearchResponse) -> Vec<(String, Value)> {
+    let HashSearchResponse {
+        algorithm,
+        hashes,
+        response,
+        ..
+    } = response;
+    if let [hash] = hashes.as_slice() {
+        return vec![(hash.clone(), response)];
+    }
+
+    let body = match response {
+        Value::Object(mut map) => map
+            .remove("results")
+            .or_else(|| map.remove("data"))
+            .unwrap_or(Value::Object(map)),
+        other => other,
+    };
+    let mut found: HashMap<String, Value> = match body {
+        Value::Object(map) => map
+            .into_iter()
+            .map(|(hash, result)| (hash.to_ascii_lowercase(), result))
+            .collect(),
+        Value::Array(records) => records
+            .into_iter()
+            .filter_map(|record| {
+                let hash = [algorithm.as_str(), "hash"]
+                    .iter()
+                    .find_map(|key| record.get(*key).and_then(Value::as_str))?
+                    .to_ascii_lowercase();
+                Some((hash, record))
+            })
+            .collect(),
+        _ => HashMap::new(),
+    };
+    hashes
+        .into_iter()
+        .map(|hash| {
+            let result = found.remove(&hash).unwrap_or(Value::Null);
+            (hash, result)
+        })
+        .collect()
+}
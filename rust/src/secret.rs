@@ -0,0 +1,25 @@
+// src/secret.rs
+
+use std::fmt;
+use zeroize::Zeroizing;
+
+/// An API key. Its memory is wiped when it is dropped, and `Debug` output never shows it,
+/// so it can't leak through a logged builder or a core dump taken later.
+#[derive(Clone)]
+pub(crate) struct ApiKey(Zeroizing<String>);
+
+impl ApiKey {
+    pub(crate) fn new(key: String) -> Self {
+        Self(Zeroizing::new(key))
+    }
+
+    pub(crate) fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ApiKey(<redacted>)")
+    }
+}
@@ -0,0 +1,127 @@
+// src/status.rs
+
+use crate::TraceixError;
+use serde_json::Value;
+use std::fmt;
+
+/// Lifecycle of an analysis job as reported by the status endpoint.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum JobStatus {
+    Queued,
+    /// Being analyzed (reported as e.g. `running` or `processing`).
+    Running,
+    Completed,
+    Failed {
+        reason: String,
+    },
+    Cancelled,
+    /// A status this SDK version doesn't recognize, as sent by the server.
+    Unknown(String),
+}
+
+/// The status of an analysis; another name for [`JobStatus`].
+pub type AnalysisStatus = JobStatus;
+
+impl JobStatus {
+    /// Parse a raw status string (case-insensitive), using `reason` for failures.
+    ///
+    /// Unrecognized values become [`JobStatus::Unknown`]; only an empty status is `None`.
+    pub fn parse(status: &str, reason: Option<&str>) -> Option<JobStatus> {
+        let raw = status.trim();
+        if raw.is_empty() {
+            return None;
+        }
+        let status = match raw.to_ascii_lowercase().as_str() {
+            "queued" | "pending" | "waiting" | "submitted" => JobStatus::Queued,
+            "running" | "processing" | "in_progress" | "analyzing" => JobStatus::Running,
+            "completed" | "complete" | "done" | "finished" | "success" => JobStatus::Completed,
+            "failed" | "failure" | "error" => JobStatus::Failed {
+                reason: reason.unwrap_or("unknown error").to_string(),
+            },
+            "cancelled" | "canceled" => JobStatus::Cancelled,
+            _ => JobStatus::Unknown(raw.to_string()),
+        };
+        Some(status)
+    }
+
+    /// Extract the status from a `check_status` response body.
+    pub fn from_response(body: &Value) -> Option<JobStatus> {
+        let status = body
+            .get("status")
+            .or_else(|| body.get("state"))
+            .and_then(Value::as_str)?;
+        let reason = ["reason", "error", "message"]
+            .iter()
+            .find_map(|key| body.get(*key).and_then(Value::as_str));
+        Self::parse(status, reason)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobStatus::Queued => "queued",
+            JobStatus::Running => "running",
+            JobStatus::Completed => "completed",
+            JobStatus::Failed { .. } => "failed",
+            JobStatus::Cancelled => "cancelled",
+            JobStatus::Unknown(_) => "unknown",
+        }
+    }
+
+    /// Whether the job has reached a final state and will not change again.
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            JobStatus::Completed | JobStatus::Failed { .. } | JobStatus::Cancelled
+        )
+    }
+
+    /// Whether moving from `self` to `next` is a legal lifecycle step.
+    ///
+    /// Staying in the same state is always legal, since polling commonly observes it repeatedly.
+    /// Moves to or from [`JobStatus::Unknown`] are allowed, since nothing is known about them.
+    pub fn can_transition_to(&self, next: &JobStatus) -> bool {
+        use JobStatus::*;
+
+        match (self, next) {
+            (a, b) if std::mem::discriminant(a) == std::mem::discriminant(b) => true,
+            (Unknown(_), _) | (_, Unknown(_)) => true,
+            (Queued, Running | Completed | Failed { .. } | Cancelled) => true,
+            (Running, Completed | Failed { .. } | Cancelled) => true,
+            _ => false,
+        }
+    }
+
+    /// Move to `next`, rejecting illegal transitions (e.g. `Completed` back to `Queued`).
+    pub fn transition(&self, next: JobStatus) -> Result<JobStatus, TraceixError> {
+        if self.can_transition_to(&next) {
+            Ok(next)
+        } else {
+            Err(TraceixError::InvalidTransition {
+                from: self.clone(),
+                to: next,
+            })
+        }
+    }
+}
+
+impl fmt::Display for JobStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JobStatus::Failed { reason } => write!(f, "failed ({reason})"),
+            JobStatus::Unknown(status) => write!(f, "unknown ({status})"),
+            other => f.write_str(other.as_str()),
+        }
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl crate::TraceixSdk {
+    /// Check the status of a provided UUID and parse it into a [`JobStatus`].
+    pub fn job_status(&self, uuid: &str) -> Result<JobStatus, TraceixError> {
+        let body = self.check_status(uuid)?;
+        JobStatus::from_response(&body).ok_or_else(|| {
+            TraceixError::UnexpectedResponse(format!("no recognizable status in {body}"))
+        })
+    }
+}
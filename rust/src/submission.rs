@@ -0,0 +1,250 @@
+// src/submission.rs
+
+use crate::{Endpoint, JobStatus, TraceixError, TraceixSdk, UploadOptions};
+use serde_json::Value;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// How to poll an analysis until it finishes.
+///
+/// The delay between status checks starts at `interval` and is multiplied by
+/// `backoff` after every check, up to `max_interval`; a `backoff` of 1.0 polls at a
+/// fixed rate. With `max_wait` set, waiting fails with [`TraceixError::WaitTimeout`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct PollOptions {
+    pub interval: Duration,
+    pub max_wait: Option<Duration>,
+    pub backoff: f64,
+    pub max_interval: Duration,
+}
+
+impl Default for PollOptions {
+    /// Every 5 seconds, without a time limit.
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(5),
+            max_wait: None,
+            backoff: 1.0,
+            max_interval: Duration::from_secs(60),
+        }
+    }
+}
+
+impl PollOptions {
+    /// The default schedule, giving up after `max_wait`.
+    pub fn with_max_wait(max_wait: Duration) -> Self {
+        Self {
+            max_wait: Some(max_wait),
+            ..Self::default()
+        }
+    }
+
+    fn next_interval(&self, interval: Duration) -> Duration {
+        interval
+            .mul_f64(self.backoff.max(1.0))
+            .min(self.max_interval.max(self.interval))
+    }
+}
+
+/// How long [`TraceixSdk::analyze`] waits for an analysis before giving up.
+pub const DEFAULT_ANALYSIS_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// The final outcome of an upload that was polled to completion.
+#[derive(Clone, Debug)]
+pub struct AnalysisResult {
+    pub uuid: String,
+    pub status: JobStatus,
+    /// Response returned by the upload endpoint.
+    pub upload: Value,
+    /// Final status payload, including the analysis result.
+    pub result: Value,
+    /// Whether `result` carried a valid signature from the key configured with
+    /// `TraceixSdkBuilder::result_verification_key`. Always `false` without one.
+    pub verified: bool,
+}
+
+/// An uploaded sample, tying its UUID to the rest of the analysis lifecycle.
+#[derive(Clone)]
+pub struct Submission {
+    sdk: TraceixSdk,
+    uuid: String,
+    response: Value,
+}
+
+impl Submission {
+    /// A handle for an analysis started elsewhere, known only by its UUID.
+    pub(crate) fn from_uuid(sdk: &TraceixSdk, uuid: &str) -> Result<Self, TraceixError> {
+        if uuid.is_empty() {
+            return Err(TraceixError::NoUuidProvided);
+        }
+        Self::from_response(sdk, serde_json::json!({ "uuid": uuid }))
+    }
+
+    pub(crate) fn from_response(sdk: &TraceixSdk, response: Value) -> Result<Self, TraceixError> {
+        let uuid = response
+            .get("uuid")
+            .and_then(Value::as_str)
+            .filter(|u| !u.is_empty())
+            .ok_or_else(|| {
+                TraceixError::UnexpectedResponse(format!("no uuid in upload response {response}"))
+            })?
+            .to_string();
+
+        Ok(Self {
+            sdk: sdk.clone(),
+            uuid,
+            response,
+        })
+    }
+
+    /// The UUID assigned to this submission by the server.
+    pub fn uuid(&self) -> &str {
+        &self.uuid
+    }
+
+    /// The raw response returned by the upload endpoint.
+    pub fn response(&self) -> &Value {
+        &self.response
+    }
+
+    /// Fetch the current status of the analysis.
+    pub fn status(&self) -> Result<JobStatus, TraceixError> {
+        self.sdk.job_status(&self.uuid)
+    }
+
+    /// Block until the analysis reaches a terminal state and return it.
+    pub fn wait(&self) -> Result<JobStatus, TraceixError> {
+        self.wait_with(&PollOptions::default())
+    }
+
+    /// Like [`Submission::wait`], but fails with [`TraceixError::WaitTimeout`] after `max_wait`.
+    pub fn wait_timeout(&self, max_wait: Duration) -> Result<JobStatus, TraceixError> {
+        self.wait_with(&PollOptions::with_max_wait(max_wait))
+    }
+
+    /// Like [`Submission::wait`], polling on a custom schedule.
+    pub fn wait_with(&self, options: &PollOptions) -> Result<JobStatus, TraceixError> {
+        self.poll(options).map(|(status, ..)| status)
+    }
+
+    /// Wait for the analysis to finish and return the final status payload.
+    ///
+    /// Fails with [`TraceixError::JobNotCompleted`] if the job failed or was cancelled.
+    pub fn result(&self) -> Result<Value, TraceixError> {
+        self.result_with(&PollOptions::default())
+    }
+
+    /// Like [`Submission::result`], but fails with [`TraceixError::WaitTimeout`] after `max_wait`.
+    pub fn result_timeout(&self, max_wait: Duration) -> Result<Value, TraceixError> {
+        self.result_with(&PollOptions::with_max_wait(max_wait))
+    }
+
+    /// Like [`Submission::result`], polling on a custom schedule.
+    pub fn result_with(&self, options: &PollOptions) -> Result<Value, TraceixError> {
+        self.completed(options).map(|(body, _)| body)
+    }
+
+    /// Ask the server to cancel the analysis.
+    pub fn cancel(&self) -> Result<Value, TraceixError> {
+        self.sdk.cancel(&self.uuid)
+    }
+
+    fn completed(&self, options: &PollOptions) -> Result<(Value, bool), TraceixError> {
+        match self.poll(options)? {
+            (JobStatus::Completed, body, verified) => Ok((body, verified)),
+            (status, ..) => Err(TraceixError::JobNotCompleted {
+                uuid: self.uuid.clone(),
+                status,
+            }),
+        }
+    }
+
+    fn poll(&self, options: &PollOptions) -> Result<(JobStatus, Value, bool), TraceixError> {
+        let started = Instant::now();
+        let mut current = JobStatus::Queued;
+        let mut interval = options.interval;
+        loop {
+            let (body, verified) = self.sdk.post_json_verified(
+                &Endpoint::CHECK_STATUS,
+                &serde_json::json!({ "uuid": self.uuid }),
+            )?;
+            let next = JobStatus::from_response(&body).ok_or_else(|| {
+                TraceixError::UnexpectedResponse(format!("no recognizable status in {body}"))
+            })?;
+            // An unrecognized status says nothing about progress; keep the last known one.
+            if !matches!(next, JobStatus::Unknown(_)) {
+                current = current.transition(next)?;
+            }
+
+            if current.is_terminal() {
+                return Ok((current, body, verified));
+            }
+
+            let mut sleep = interval;
+            interval = options.next_interval(interval);
+            if let Some(max_wait) = options.max_wait {
+                let remaining = max_wait.saturating_sub(started.elapsed());
+                if remaining.is_zero() {
+                    return Err(TraceixError::WaitTimeout {
+                        uuid: self.uuid.clone(),
+                        waited: started.elapsed(),
+                    });
+                }
+                sleep = sleep.min(remaining);
+            }
+            self.sdk.pause(sleep)?;
+        }
+    }
+}
+
+impl TraceixSdk {
+    /// Poll the analysis behind `uuid` until it finishes and return the final status payload.
+    ///
+    /// Fails with [`TraceixError::WaitTimeout`] once `options.max_wait` has passed, and
+    /// with [`TraceixError::JobNotCompleted`] if the job failed or was cancelled.
+    pub fn wait_for_result(&self, uuid: &str, options: PollOptions) -> Result<Value, TraceixError> {
+        Submission::from_uuid(self, uuid)?.result_with(&options)
+    }
+
+    /// Upload a file for AI prediction and return a handle to the resulting submission.
+    pub fn upload(&self, filename: impl AsRef<Path>) -> Result<Submission, TraceixError> {
+        self.upload_with_options(filename, &UploadOptions::default())
+    }
+
+    /// Upload a file for AI prediction with explicit upload options.
+    pub fn upload_with_options(
+        &self,
+        filename: impl AsRef<Path>,
+        options: &UploadOptions,
+    ) -> Result<Submission, TraceixError> {
+        let response = self.ai_prediction_with_options(filename, options)?;
+        Submission::from_response(self, response)
+    }
+
+    /// Upload a file, wait for the analysis to complete, and return the final result.
+    ///
+    /// Gives up after [`DEFAULT_ANALYSIS_TIMEOUT`]; use [`Submission::result_timeout`]
+    /// on [`TraceixSdk::upload`] for a different limit.
+    pub fn analyze(&self, filename: impl AsRef<Path>) -> Result<AnalysisResult, TraceixError> {
+        self.analyze_with_options(filename, &UploadOptions::default())
+    }
+
+    /// Like [`TraceixSdk::analyze`], with explicit upload options.
+    pub fn analyze_with_options(
+        &self,
+        filename: impl AsRef<Path>,
+        options: &UploadOptions,
+    ) -> Result<AnalysisResult, TraceixError> {
+        let submission = self.upload_with_options(filename, options)?;
+        let (result, verified) =
+            submission.completed(&PollOptions::with_max_wait(DEFAULT_ANALYSIS_TIMEOUT))?;
+
+        Ok(AnalysisResult {
+            uuid: submission.uuid,
+            status: JobStatus::Completed,
+            upload: submission.response,
+            result,
+            verified,
+        })
+    }
+}
@@ -0,0 +1,131 @@
+// src/submit.rs
+
+use crate::{
+    Analyses, FullUpload, Priority, ProgressCallback, TraceixError, TraceixSdk, UploadOptions,
+    UploadProgress,
+};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Fluent front-end for submitting a file, built by [`TraceixSdk::submit`].
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # use traceix_sdk::{Analyses, Priority, TraceixSdk};
+/// # fn main() -> Result<(), traceix_sdk::TraceixError> {
+/// let sdk = TraceixSdk::new(None)?;
+/// let upload = sdk
+///     .submit("/evidence/invoice.exe")
+///     .tag("phishing")
+///     .case_id("case-42")
+///     .source("email-gateway")
+///     .priority(Priority::Urgent)
+///     .private()
+///     .timeout(Duration::from_secs(600))
+///     .analyses(Analyses::PREDICTION | Analyses::CAPA)
+///     .send()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+#[must_use = "a submission does nothing until `send` is called"]
+pub struct SubmitBuilder<'a> {
+    sdk: &'a TraceixSdk,
+    path: PathBuf,
+    options: UploadOptions,
+    analyses: Analyses,
+}
+
+impl<'a> SubmitBuilder<'a> {
+    /// Add a tag. Can be called repeatedly.
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.options.tags.push(tag.into());
+        self
+    }
+
+    /// Free-text description of the sample.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.options.description = Some(description.into());
+        self
+    }
+
+    /// Investigation the sample belongs to.
+    pub fn case_id(mut self, case_id: impl Into<String>) -> Self {
+        self.options.case_id = Some(case_id.into());
+        self
+    }
+
+    /// Where the sample was collected.
+    pub fn source(mut self, source: impl Into<String>) -> Self {
+        self.options.source = Some(source.into());
+        self
+    }
+
+    pub fn priority(mut self, priority: Priority) -> Self {
+        self.options.priority = priority;
+        self
+    }
+
+    /// Keep the sample out of public datasets and searches.
+    pub fn private(mut self) -> Self {
+        self.options.private = true;
+        self
+    }
+
+    /// Remove EXIF, XMP and IPTC metadata from images before they are sent.
+    pub fn strip_metadata(mut self) -> Self {
+        self.options.strip_metadata = true;
+        self
+    }
+
+    /// Timeout for each upload request.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.options.timeout = Some(timeout);
+        self
+    }
+
+    /// Identifier that lets an interrupted upload be resumed.
+    pub fn upload_id(mut self, upload_id: impl Into<String>) -> Self {
+        self.options.upload_id = Some(upload_id.into());
+        self
+    }
+
+    /// Report upload progress to `callback` for each file sent.
+    pub fn on_progress(
+        mut self,
+        callback: impl Fn(UploadProgress) + Send + Sync + 'static,
+    ) -> Self {
+        self.options.progress = Some(ProgressCallback::new(callback));
+        self
+    }
+
+    /// Replace all options at once, e.g. with a shared preset.
+    pub fn options(mut self, options: UploadOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Which analyses to run. Defaults to all of them.
+    pub fn analyses(mut self, analyses: Analyses) -> Self {
+        self.analyses = analyses;
+        self
+    }
+
+    /// Upload the file for each selected analysis.
+    pub fn send(self) -> Result<FullUpload, TraceixError> {
+        self.sdk
+            .full_upload_with_options(&self.path, self.analyses, &self.options)
+    }
+}
+
+impl TraceixSdk {
+    /// Start a fluent submission of `path`; finish it with [`SubmitBuilder::send`].
+    pub fn submit(&self, path: impl Into<PathBuf>) -> SubmitBuilder<'_> {
+        SubmitBuilder {
+            sdk: self,
+            path: path.into(),
+            options: UploadOptions::default(),
+            analyses: Analyses::default(),
+        }
+    }
+}
@@ -0,0 +1,75 @@
+// src/throttle.rs
+
+use std::io::{self, Read};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How far reads may run ahead of the configured rate before they start to block, so
+/// small reads don't each pay for a sleep. Idle time is never credited, so a pause
+/// between uploads doesn't turn into a burst at full link speed.
+const MAX_BURST: Duration = Duration::from_millis(250);
+
+/// Caps the combined rate at which upload bodies are read, shared by every clone of an
+/// SDK instance so concurrent uploads split the budget instead of each getting all of it.
+#[derive(Debug)]
+pub(crate) struct Throttle {
+    bytes_per_sec: u64,
+    /// When the bytes handed out so far are due to have been sent.
+    schedule: Mutex<Instant>,
+}
+
+impl Throttle {
+    pub(crate) fn new(bytes_per_sec: u64) -> Self {
+        Self {
+            bytes_per_sec: bytes_per_sec.max(1),
+            schedule: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Largest read worth accounting for at once; about a tenth of a second of budget
+    /// keeps the rate smooth without waking up for every few bytes.
+    fn max_read(&self) -> usize {
+        usize::try_from(self.bytes_per_sec / 10)
+            .unwrap_or(usize::MAX)
+            .max(1024)
+    }
+
+    /// Account for `n` bytes, blocking until sending them stays within the rate.
+    fn consume(&self, n: usize) {
+        let wait = {
+            let mut schedule = self.schedule.lock().unwrap();
+            let now = Instant::now();
+            let start = (*schedule).max(now);
+            *schedule = start + Duration::from_secs_f64(n as f64 / self.bytes_per_sec as f64);
+            schedule.saturating_duration_since(now + MAX_BURST)
+        };
+        if !wait.is_zero() {
+            thread::sleep(wait);
+        }
+    }
+}
+
+/// Reads `inner` no faster than an optional [`Throttle`] allows.
+pub(crate) struct ThrottledReader<R> {
+    inner: R,
+    throttle: Option<Arc<Throttle>>,
+}
+
+impl<R> ThrottledReader<R> {
+    pub(crate) fn new(inner: R, throttle: Option<Arc<Throttle>>) -> Self {
+        Self { inner, throttle }
+    }
+}
+
+impl<R: Read> Read for ThrottledReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let Some(throttle) = &self.throttle else {
+            return self.inner.read(buf);
+        };
+        let len = buf.len().min(throttle.max_read());
+        let n = self.inner.read(&mut buf[..len])?;
+        throttle.consume(n);
+        Ok(n)
+    }
+}
@@ -0,0 +1,278 @@
+// src/tls.rs
+
+use crate::builder::HttpClientBuilder;
+use crate::TraceixError;
+use reqwest::{Certificate, Identity};
+use std::fmt;
+#[cfg(feature = "cert-pinning")]
+use {
+    rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+    rustls::client::WebPkiServerVerifier,
+    rustls::crypto::{ring, CryptoProvider},
+    rustls::pki_types::pem::PemObject,
+    rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime},
+    rustls::{ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme},
+    sha2::{Digest, Sha256},
+    std::sync::Arc,
+};
+
+/// TLS trust settings collected by [`TraceixSdkBuilder`](crate::TraceixSdkBuilder).
+#[derive(Clone, Debug, Default)]
+pub(crate) struct TlsSettings {
+    /// Extra trusted roots, each a PEM bundle.
+    pub(crate) root_certificates: Vec<Vec<u8>>,
+    /// SHA-256 fingerprints as given to the builder.
+    #[cfg(feature = "cert-pinning")]
+    pub(crate) pins: Vec<String>,
+    /// Certificate presented to servers that ask for one (mutual TLS).
+    pub(crate) identity: Option<ClientIdentity>,
+}
+
+/// A client certificate and its private key.
+#[derive(Clone)]
+pub(crate) enum ClientIdentity {
+    /// PEM certificate chain and PEM private key.
+    Pem { certificate: Vec<u8>, key: Vec<u8> },
+    /// A DER-encoded PKCS#12 archive and its password.
+    #[cfg(feature = "default-tls")]
+    Pkcs12 { der: Vec<u8>, password: String },
+}
+
+impl ClientIdentity {
+    fn load(&self) -> Result<Identity, TraceixError> {
+        let identity = match self {
+            #[cfg(feature = "default-tls")]
+            ClientIdentity::Pem { certificate, key } => Identity::from_pkcs8_pem(certificate, key),
+            #[cfg(not(feature = "default-tls"))]
+            ClientIdentity::Pem { certificate, key } => {
+                let mut pem = certificate.clone();
+                pem.push(b'\n');
+                pem.extend_from_slice(key);
+                Identity::from_pem(&pem)
+            }
+            #[cfg(feature = "default-tls")]
+            ClientIdentity::Pkcs12 { der, password } => Identity::from_pkcs12_der(der, password),
+        };
+        identity.map_err(|e| TraceixError::InvalidArgument(format!("invalid client identity: {e}")))
+    }
+}
+
+impl fmt::Debug for ClientIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientIdentity::Pem { .. } => f.write_str("ClientIdentity::Pem"),
+            #[cfg(feature = "default-tls")]
+            ClientIdentity::Pkcs12 { .. } => f.write_str("ClientIdentity::Pkcs12"),
+        }
+    }
+}
+
+impl TlsSettings {
+    /// Configure `client` to trust the extra roots, present the client identity, and,
+    /// when pins are set, reject servers whose chain contains none of the pinned
+    /// certificates.
+    pub(crate) fn apply<B: HttpClientBuilder>(&self, mut client: B) -> Result<B, TraceixError> {
+        #[cfg(feature = "cert-pinning")]
+        if !self.pins.is_empty() {
+            return Ok(client.use_preconfigured_tls(self.pinned_config()?));
+        }
+
+        for pem in &self.root_certificates {
+            let certificates = Certificate::from_pem_bundle(pem).map_err(|e| {
+                TraceixError::InvalidArgument(format!("invalid root certificate: {e}"))
+            })?;
+            if certificates.is_empty() {
+                return Err(TraceixError::InvalidArgument(
+                    "root certificate PEM contains no certificates".into(),
+                ));
+            }
+            for certificate in certificates {
+                client = client.add_root_certificate(certificate);
+            }
+        }
+        if let Some(identity) = &self.identity {
+            // The identity is in rustls format, so make sure another crate enabling
+            // reqwest's native TLS doesn't switch the backend under us.
+            #[cfg(not(feature = "default-tls"))]
+            {
+                client = client.use_rustls_tls();
+            }
+            client = client.identity(identity.load()?);
+        }
+        Ok(client)
+    }
+
+    /// A rustls configuration trusting the Mozilla roots plus the extra roots, with
+    /// pinning layered on top of the usual chain and hostname checks.
+    #[cfg(feature = "cert-pinning")]
+    fn pinned_config(&self) -> Result<ClientConfig, TraceixError> {
+        let pins = self
+            .pins
+            .iter()
+            .map(|pin| parse_fingerprint(pin))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut roots = RootCertStore::empty();
+        roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+        for pem in &self.root_certificates {
+            let mut added = 0;
+            for certificate in CertificateDer::pem_slice_iter(pem) {
+                let certificate = certificate.map_err(|e| {
+                    TraceixError::InvalidArgument(format!("invalid root certificate: {e}"))
+                })?;
+                roots.add(certificate).map_err(|e| {
+                    TraceixError::InvalidArgument(format!("invalid root certificate: {e}"))
+                })?;
+                added += 1;
+            }
+            if added == 0 {
+                return Err(TraceixError::InvalidArgument(
+                    "root certificate PEM contains no certificates".into(),
+                ));
+            }
+        }
+
+        let provider = Arc::new(ring::default_provider());
+        let inner = WebPkiServerVerifier::builder_with_provider(Arc::new(roots), provider.clone())
+            .build()
+            .map_err(|e| TraceixError::InvalidArgument(format!("invalid TLS roots: {e}")))?;
+        let verifier = PinnedVerifier {
+            inner,
+            pins,
+            provider: provider.clone(),
+        };
+
+        let config = ClientConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .map_err(|e| TraceixError::InvalidArgument(format!("TLS configuration: {e}")))?
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(verifier));
+        let mut config = match &self.identity {
+            None => config.with_no_client_auth(),
+            Some(ClientIdentity::Pem { certificate, key }) => {
+                let invalid = |e: &dyn fmt::Display| {
+                    TraceixError::InvalidArgument(format!("invalid client identity: {e}"))
+                };
+                let chain = CertificateDer::pem_slice_iter(certificate)
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| invalid(&e))?;
+                let key = PrivateKeyDer::from_pem_slice(key).map_err(|e| invalid(&e))?;
+                config
+                    .with_client_auth_cert(chain, key)
+                    .map_err(|e| invalid(&e))?
+            }
+            #[cfg(feature = "default-tls")]
+            Some(ClientIdentity::Pkcs12 { .. }) => {
+                return Err(TraceixError::InvalidArgument(
+                    "PKCS#12 client identities cannot be combined with certificate pinning; \
+                     use a PEM certificate and key"
+                        .into(),
+                ))
+            }
+        };
+        config.alpn_protocols = if cfg!(feature = "http2") {
+            vec![b"h2".to_vec(), b"http/1.1".to_vec()]
+        } else {
+            vec![b"http/1.1".to_vec()]
+        };
+        Ok(config)
+    }
+}
+
+/// Parse a SHA-256 fingerprint written as 64 hex digits, optionally separated by colons
+/// as `openssl x509 -fingerprint -sha256` prints it.
+#[cfg(feature = "cert-pinning")]
+fn parse_fingerprint(pin: &str) -> Result<[u8; 32], TraceixError> {
+    let invalid = || {
+        TraceixError::InvalidArgument(format!(
+            "invalid certificate fingerprint {pin:?}: expected a SHA-256 digest in hex"
+        ))
+    };
+    let hex: Vec<u8> = pin.bytes().filter(|b| *b != b':').collect();
+    if hex.len() != 64 {
+        return Err(invalid());
+    }
+    let mut digest = [0u8; 32];
+    for (byte, pair) in digest.iter_mut().zip(hex.chunks(2)) {
+        let pair = std::str::from_utf8(pair).map_err(|_| invalid())?;
+        *byte = u8::from_str_radix(pair, 16).map_err(|_| invalid())?;
+    }
+    Ok(digest)
+}
+
+/// Runs the standard WebPKI checks, then requires the leaf or one of the intermediates
+/// presented by the server to match a pin.
+#[cfg(feature = "cert-pinning")]
+#[derive(Debug)]
+struct PinnedVerifier {
+    inner: Arc<WebPkiServerVerifier>,
+    pins: Vec<[u8; 32]>,
+    provider: Arc<CryptoProvider>,
+}
+
+#[cfg(feature = "cert-pinning")]
+impl ServerCertVerifier for PinnedVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let verified = self.inner.verify_server_cert(
+            end_entity,
+            intermediates,
+            server_name,
+            ocsp_response,
+            now,
+        )?;
+        let pinned = std::iter::once(end_entity)
+            .chain(intermediates)
+            .any(|certificate| {
+                let digest: [u8; 32] = Sha256::digest(certificate.as_ref()).into();
+                self.pins.contains(&digest)
+            });
+        if pinned {
+            Ok(verified)
+        } else {
+            Err(rustls::Error::General(
+                "server certificate does not match any pinned fingerprint".into(),
+            ))
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
@@ -0,0 +1,80 @@
+// src/transport.rs
+
+use crate::TraceixError;
+use reqwest::blocking::{Client, Request, Response};
+use reqwest::header::HeaderMap;
+use reqwest::{ResponseBuilderExt, StatusCode};
+use std::fmt;
+use std::sync::Arc;
+use url::Url;
+
+/// Sends the HTTP requests built by [`TraceixSdk`](crate::TraceixSdk).
+///
+/// The default is the SDK's own `reqwest` client. Install another with
+/// [`TraceixSdkBuilder::transport`](crate::TraceixSdkBuilder::transport) to answer
+/// requests from canned responses in unit tests, or to route them through a different
+/// HTTP stack.
+///
+/// Request bodies built from files are streamed; call `Body::buffer` on
+/// `request.body_mut()` to read one into memory. Responses can be built from an
+/// `http::Response` with `Response::from`. Limits, retries, observers, and error
+/// decoding all still apply around the transport.
+///
+/// ```no_run
+/// # use reqwest::blocking::{Request, Response};
+/// # use traceix_sdk::{TraceixError, TraceixSdk, Transport};
+/// struct Canned;
+///
+/// impl Transport for Canned {
+///     fn execute(&self, request: Request) -> Result<Response, TraceixError> {
+///         assert!(request.url().path().ends_with("/status"));
+///         let response = http::Response::builder()
+///             .status(200)
+///             .body(r#"{"status":"completed"}"#)
+///             .unwrap();
+///         Ok(Response::from(response))
+///     }
+/// }
+///
+/// # fn main() -> Result<(), TraceixError> {
+/// let sdk = TraceixSdk::builder().api_key("test").transport(Canned).build()?;
+/// assert_eq!(sdk.check_status("uuid")?["status"], "completed");
+/// # Ok(())
+/// # }
+/// ```
+pub trait Transport: Send + Sync {
+    fn execute(&self, request: Request) -> Result<Response, TraceixError>;
+}
+
+impl Transport for Client {
+    fn execute(&self, request: Request) -> Result<Response, TraceixError> {
+        Client::execute(self, request).map_err(TraceixError::from)
+    }
+}
+
+/// A response with a body already in memory, answering a request for `url`.
+pub(crate) fn buffered_response(
+    status: StatusCode,
+    headers: HeaderMap,
+    url: Url,
+    body: Vec<u8>,
+) -> Result<Response, TraceixError> {
+    let mut builder = http::Response::builder().status(status).url(url);
+    if let Some(map) = builder.headers_mut() {
+        *map = headers;
+    }
+    let response = builder
+        .body(body)
+        .map_err(|e| TraceixError::UnexpectedResponse(e.to_string()))?;
+    Ok(Response::from(response))
+}
+
+/// A transport installed on the builder.
+#[derive(Clone)]
+pub(crate) struct SharedTransport(pub(crate) Arc<dyn Transport>);
+
+impl fmt::Debug for SharedTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Transport")
+    }
+}
@@ -0,0 +1,228 @@
+// src/uploads.rs
+
+use crate::pagination::{self, Cursor, Page, Paginator};
+use crate::{decode_response, Endpoint, JobStatus, Priority, TraceixError, TraceixSdk};
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use serde::{Deserialize, Deserializer};
+use serde_json::{Map, Value};
+
+/// Default number of submissions per page of [`TraceixSdk::list_uploads`].
+pub const DEFAULT_LIST_PAGE_SIZE: usize = 50;
+
+/// Fetches one page of submissions for an [`UploadPages`] paginator.
+type FetchUploads<'a> =
+    dyn FnMut(Option<&Cursor>, usize) -> Result<Page<UploadSummary>, TraceixError> + 'a;
+
+/// Iterator over every submission matching a [`ListOptions`], returned by
+/// [`TraceixSdk::iter_uploads`].
+pub type UploadPages<'a> = Paginator<UploadSummary, Box<FetchUploads<'a>>>;
+
+/// Filters and paging for [`TraceixSdk::list_uploads`]. Every filter is optional.
+#[derive(Clone, Debug)]
+pub struct ListOptions {
+    /// Only submissions made at or after this time.
+    pub since: Option<DateTime<Utc>>,
+    /// Only submissions made before this time.
+    pub until: Option<DateTime<Utc>>,
+    /// Zero-based page to fetch, counted in `page_size` submissions.
+    pub page: u64,
+    pub page_size: usize,
+    /// Only submissions carrying this tag.
+    pub tag: Option<String>,
+}
+
+impl Default for ListOptions {
+    fn default() -> Self {
+        Self {
+            since: None,
+            until: None,
+            page: 0,
+            page_size: DEFAULT_LIST_PAGE_SIZE,
+            tag: None,
+        }
+    }
+}
+
+/// One of the account's earlier submissions, as listed by [`TraceixSdk::list_uploads`].
+#[derive(Clone, Debug, Deserialize)]
+#[non_exhaustive]
+pub struct UploadSummary {
+    pub uuid: String,
+    #[serde(default)]
+    pub filename: Option<String>,
+    #[serde(default)]
+    pub sha256: Option<String>,
+    #[serde(default)]
+    pub size: Option<u64>,
+    #[serde(default, deserialize_with = "job_status")]
+    pub status: Option<JobStatus>,
+    /// Submission timestamp as reported by the server.
+    #[serde(default)]
+    pub submitted_at: Option<String>,
+    #[serde(default)]
+    pub priority: Option<Priority>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub case_id: Option<String>,
+    #[serde(default)]
+    pub private: bool,
+}
+
+fn job_status<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<JobStatus>, D::Error> {
+    let status = Option::<String>::deserialize(deserializer)?;
+    Ok(status.and_then(|s| JobStatus::parse(&s, None)))
+}
+
+/// Confirmation that [`TraceixSdk::delete_upload`] removed a submission.
+#[derive(Clone, Debug, Deserialize)]
+#[non_exhaustive]
+pub struct DeletedUpload {
+    #[serde(default)]
+    pub uuid: String,
+    /// Deletion timestamp as reported by the server.
+    #[serde(default)]
+    pub deleted_at: Option<String>,
+    /// The derived data removed along with the sample, e.g. `prediction` or `capa`.
+    #[serde(default)]
+    pub artifacts: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct UploadList {
+    #[serde(alias = "items")]
+    uploads: Vec<UploadSummary>,
+    #[serde(default)]
+    next_token: Option<String>,
+}
+
+impl TraceixSdk {
+    /// Fetch one page of the account's earlier submissions, newest first.
+    ///
+    /// [`Page::next`] is the cursor of the following page, or `None` on the last one.
+    /// Use [`TraceixSdk::iter_uploads`] to walk every page.
+    pub fn list_uploads(&self, options: &ListOptions) -> Result<Page<UploadSummary>, TraceixError> {
+        let page_size = options.page_size.max(1);
+        let offset = options.page * page_size as u64;
+        self.fetch_uploads(options, Some(&Cursor::Offset(offset)), page_size)
+    }
+
+    /// Iterate over every submission matching `options`, starting at `options.page` and
+    /// requesting further pages as needed.
+    ///
+    /// ```no_run
+    /// # use chrono::{Duration, Utc};
+    /// # use traceix_sdk::{ListOptions, TraceixSdk};
+    /// # fn main() -> Result<(), traceix_sdk::TraceixError> {
+    /// let sdk = TraceixSdk::new(None)?;
+    /// let options = ListOptions {
+    ///     since: Some(Utc::now() - Duration::days(30)),
+    ///     tag: Some("case-42".to_string()),
+    ///     ..ListOptions::default()
+    /// };
+    /// for upload in sdk.iter_uploads(options) {
+    ///     let upload = upload?;
+    ///     println!("{} {:?}", upload.uuid, upload.filename);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn iter_uploads(&self, options: ListOptions) -> UploadPages<'_> {
+        let start = Cursor::Offset(options.page * options.page_size.max(1) as u64);
+        let page_size = options.page_size;
+        Paginator::resume(
+            start,
+            page_size,
+            Box::new(move |cursor, page_size| self.fetch_uploads(&options, cursor, page_size)),
+        )
+    }
+
+    /// Permanently remove a submission, its sample, and all derived results, e.g. to
+    /// honor a privacy request.
+    ///
+    /// Fails with [`TraceixError::SubmissionNotFound`] if the server doesn't know
+    /// `uuid`, and with [`TraceixError::NotPermitted`] if the account may not delete it,
+    /// each wrapped in [`TraceixError::Context`]; match on [`TraceixError::root`].
+    ///
+    /// ```no_run
+    /// # use traceix_sdk::{TraceixError, TraceixSdk};
+    /// # fn main() -> Result<(), TraceixError> {
+    /// let sdk = TraceixSdk::new(None)?;
+    /// match sdk.delete_upload("0b5c7c1e-3f0d-4a4e-9d3b-2f8a6a1c9e10") {
+    ///     Ok(deleted) => println!("deleted {} ({:?})", deleted.uuid, deleted.artifacts),
+    ///     Err(e) => match e.root() {
+    ///         TraceixError::SubmissionNotFound { .. } => println!("already gone"),
+    ///         _ => return Err(e),
+    ///     },
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn delete_upload(&self, uuid: &str) -> Result<DeletedUpload, TraceixError> {
+        if uuid.is_empty() {
+            return Err(TraceixError::NoUuidProvided);
+        }
+
+        let endpoint = &Endpoint::UPLOAD_DELETE;
+        let response = self
+            .post_json(endpoint, &serde_json::json!({ "uuid": uuid }))
+            .map_err(|e| {
+                let uuid = uuid.to_string();
+                let error = match e.status() {
+                    Some(StatusCode::NOT_FOUND) => TraceixError::SubmissionNotFound { uuid },
+                    Some(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) => {
+                        let message = match e.root() {
+                            TraceixError::Api { message, .. } => message.clone(),
+                            other => other.to_string(),
+                        };
+                        TraceixError::NotPermitted { uuid, message }
+                    }
+                    _ => return e,
+                };
+                error.with_context(endpoint, &self.build_url(endpoint.path), None)
+            })?;
+
+        let mut deleted: DeletedUpload = decode_response(response)?;
+        if deleted.uuid.is_empty() {
+            deleted.uuid = uuid.to_string();
+        }
+        Ok(deleted)
+    }
+
+    fn fetch_uploads(
+        &self,
+        options: &ListOptions,
+        cursor: Option<&Cursor>,
+        page_size: usize,
+    ) -> Result<Page<UploadSummary>, TraceixError> {
+        if let (Some(since), Some(until)) = (options.since, options.until) {
+            if since >= until {
+                return Err(TraceixError::InvalidArgument(
+                    "`since` must be before `until`".to_string(),
+                ));
+            }
+        }
+
+        let mut body = Map::new();
+        let offset = pagination::insert_cursor(&mut body, cursor, page_size);
+        if let Some(since) = options.since {
+            body.insert("since".into(), since.to_rfc3339().into());
+        }
+        if let Some(until) = options.until {
+            body.insert("until".into(), until.to_rfc3339().into());
+        }
+        if let Some(tag) = &options.tag {
+            body.insert("tag".into(), tag.clone().into());
+        }
+
+        let list: UploadList =
+            decode_response(self.post_json(&Endpoint::UPLOAD_LIST, &Value::Object(body))?)?;
+        Ok(Page::from_response(
+            list.uploads,
+            list.next_token,
+            offset,
+            page_size,
+        ))
+    }
+}
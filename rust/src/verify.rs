@@ -0,0 +1,82 @@
+// src/verify.rs
+
+use crate::TraceixError;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use ed25519_dalek::{Signature, VerifyingKey};
+use reqwest::header::HeaderValue;
+use serde_json::Value;
+
+/// Response header carrying a detached JWS over the exact response body.
+pub(crate) const RESULT_SIGNATURE_HEADER: &str = "x-traceix-signature";
+
+/// Checks detached JWS signatures (RFC 7515 appendix F, `alg: EdDSA`) on result payloads
+/// against the public key configured on the builder.
+pub(crate) struct ResultVerifier {
+    key: VerifyingKey,
+}
+
+impl ResultVerifier {
+    /// `public_key` is a raw 32-byte Ed25519 public key.
+    pub(crate) fn new(public_key: &[u8]) -> Result<Self, TraceixError> {
+        let bytes: [u8; 32] = public_key.try_into().map_err(|_| {
+            TraceixError::InvalidArgument(format!(
+                "result verification key must be 32 bytes, got {}",
+                public_key.len()
+            ))
+        })?;
+        let key = VerifyingKey::from_bytes(&bytes).map_err(|e| {
+            TraceixError::InvalidArgument(format!("invalid result verification key: {e}"))
+        })?;
+        Ok(Self { key })
+    }
+
+    /// Whether `signature` is a valid detached JWS by this key over exactly `payload`.
+    ///
+    /// A missing, malformed, or non-EdDSA signature counts as unverified.
+    pub(crate) fn verify(&self, signature: Option<&HeaderValue>, payload: &[u8]) -> bool {
+        let Some(jws) = signature.and_then(|s| s.to_str().ok()) else {
+            return false;
+        };
+        let Some((header, "", signature)) = split_detached(jws) else {
+            return false;
+        };
+        let Some(encoded_payload) = protected_header(header) else {
+            return false;
+        };
+        let Ok(signature) = URL_SAFE_NO_PAD
+            .decode(signature)
+            .map_err(|_| ())
+            .and_then(|raw| Signature::from_slice(&raw).map_err(|_| ()))
+        else {
+            return false;
+        };
+
+        let mut input = Vec::with_capacity(header.len() + 1 + payload.len() * 4 / 3 + 4);
+        input.extend_from_slice(header.as_bytes());
+        input.push(b'.');
+        if encoded_payload {
+            input.extend_from_slice(URL_SAFE_NO_PAD.encode(payload).as_bytes());
+        } else {
+            input.extend_from_slice(payload);
+        }
+        self.key.verify_strict(&input, &signature).is_ok()
+    }
+}
+
+/// Split a compact JWS into its header, payload, and signature segments.
+fn split_detached(jws: &str) -> Option<(&str, &str, &str)> {
+    let mut parts = jws.trim().splitn(3, '.');
+    Some((parts.next()?, parts.next()?, parts.next()?))
+}
+
+/// Validate the protected header and report whether the payload is base64url-encoded
+/// in the signing input (`false` for RFC 7797 `"b64": false` signatures).
+fn protected_header(encoded: &str) -> Option<bool> {
+    let raw = URL_SAFE_NO_PAD.decode(encoded).ok()?;
+    let header: Value = serde_json::from_slice(&raw).ok()?;
+    if header.get("alg").and_then(Value::as_str) != Some("EdDSA") {
+        return None;
+    }
+    Some(header.get("b64").and_then(Value::as_bool).unwrap_or(true))
+}
@@ -0,0 +1,331 @@
+// src/watcher.rs
+
+//! Watch a folder and submit every file dropped into it, e.g. a share SOC analysts copy
+//! samples to. Enabled by the `watch` feature.
+//!
+//! ```no_run
+//! # use std::path::Path;
+//! # use traceix_sdk::watcher::WatchOptions;
+//! # use traceix_sdk::{FullUpload, TraceixError, TraceixSdk};
+//! # fn main() -> Result<(), TraceixError> {
+//! let sdk = TraceixSdk::new(None)?;
+//! let options = WatchOptions::default().ignore("*.tmp").ignore("*.part");
+//! let watcher = sdk.watch(
+//!     "/srv/samples/incoming",
+//!     options,
+//!     |path: &Path, result: Result<FullUpload, TraceixError>| match result {
+//!         Ok(upload) => println!("{}: {:?}", path.display(), upload.prediction),
+//!         Err(e) => eprintln!("{}: {e}", path.display()),
+//!     },
+//! )?;
+//! // ... run until shutdown ...
+//! watcher.stop();
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::batch::{BatchOptions, BatchSender};
+use crate::directory::{relative_path, Glob};
+use crate::error::display_chain;
+use crate::{Analyses, FullUpload, TraceixError, TraceixSdk, UploadOptions};
+use notify::{EventKind, RecursiveMode, Watcher as _};
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Receives the outcome of every file a [`FolderWatcher`] submits.
+///
+/// Called from a single background thread, in completion order. Implemented for
+/// closures taking the path and the result.
+pub trait ResultSink: Send + 'static {
+    fn handle(&mut self, path: &Path, result: Result<FullUpload, TraceixError>);
+}
+
+impl<F> ResultSink for F
+where
+    F: FnMut(&Path, Result<FullUpload, TraceixError>) + Send + 'static,
+{
+    fn handle(&mut self, path: &Path, result: Result<FullUpload, TraceixError>) {
+        self(path, result)
+    }
+}
+
+/// A [`ResultSink`] appending one JSON object per file to a log, with `path` and either
+/// `prediction`/`capa`/`exif` or `error`.
+pub struct JsonLinesSink {
+    out: File,
+}
+
+impl JsonLinesSink {
+    /// Append to the file at `path`, creating it if needed.
+    pub fn append(path: impl AsRef<Path>) -> Result<Self, TraceixError> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self { out: file })
+    }
+}
+
+impl ResultSink for JsonLinesSink {
+    fn handle(&mut self, path: &Path, result: Result<FullUpload, TraceixError>) {
+        let line = match result {
+            Ok(upload) => json!({
+                "path": path,
+                "prediction": upload.prediction,
+                "capa": upload.capa,
+                "exif": upload.exif,
+            }),
+            Err(e) => json!({ "path": path, "error": display_chain(&e) }),
+        };
+        // A sink has nowhere to report its own failures; a full disk drops lines.
+        let _ = writeln!(self.out, "{line}");
+    }
+}
+
+/// How a [`FolderWatcher`] picks up and submits files.
+#[derive(Clone, Debug)]
+pub struct WatchOptions {
+    debounce: Duration,
+    ignore: Vec<String>,
+    recursive: bool,
+    existing: bool,
+    concurrency: usize,
+    analyses: Analyses,
+    upload: UploadOptions,
+}
+
+impl Default for WatchOptions {
+    /// Recursive, submitting a file once it has been quiet for two seconds, two at a
+    /// time, with all analyses.
+    fn default() -> Self {
+        Self {
+            debounce: Duration::from_secs(2),
+            ignore: Vec::new(),
+            recursive: true,
+            existing: false,
+            concurrency: 2,
+            analyses: Analyses::default(),
+            upload: UploadOptions::default(),
+        }
+    }
+}
+
+impl WatchOptions {
+    /// How long a file must go without changes before it is submitted, so files still
+    /// being copied in aren't uploaded half-written.
+    pub fn debounce(mut self, quiet: Duration) -> Self {
+        self.debounce = quiet;
+        self
+    }
+
+    /// Never submit files matching this glob, e.g. `*.tmp` for partial copies. Uses the
+    /// same syntax as [`SubmitOptions::include`](crate::SubmitOptions::include). Can be
+    /// called repeatedly.
+    pub fn ignore(mut self, pattern: impl Into<String>) -> Self {
+        self.ignore.push(pattern.into());
+        self
+    }
+
+    /// Also watch subdirectories. On by default.
+    pub fn recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    /// Submit the files already in the folder when watching starts, not only new ones.
+    pub fn include_existing(mut self, existing: bool) -> Self {
+        self.existing = existing;
+        self
+    }
+
+    /// Number of files uploaded at the same time. At least 1.
+    pub fn concurrency(mut self, files: usize) -> Self {
+        self.concurrency = files.max(1);
+        self
+    }
+
+    /// Which analyses to run on each file. Defaults to all of them.
+    pub fn analyses(mut self, analyses: Analyses) -> Self {
+        self.analyses = analyses;
+        self
+    }
+
+    /// Upload options applied to every file.
+    pub fn upload_options(mut self, options: UploadOptions) -> Self {
+        self.upload = options;
+        self
+    }
+}
+
+/// A running folder watch started by [`TraceixSdk::watch`].
+///
+/// Dropping it stops watching for new files; files already seen, including those still
+/// waiting out the debounce period, are still submitted in the background. Use
+/// [`FolderWatcher::stop`] to also wait for them.
+pub struct FolderWatcher {
+    watcher: notify::RecommendedWatcher,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl FolderWatcher {
+    /// Stop watching, then wait until every file seen so far has been submitted and
+    /// handed to the sink, without waiting out the debounce period for recent changes.
+    pub fn stop(self) {
+        let FolderWatcher { watcher, threads } = self;
+        drop(watcher);
+        for thread in threads {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl TraceixSdk {
+    /// Watch `dir` and run a full analysis on every file created or changed in it,
+    /// passing each outcome to `sink`.
+    ///
+    /// A file is submitted once it has had no filesystem events for the
+    /// [debounce](WatchOptions::debounce) period; a file changed again after that is
+    /// submitted again. See the [`watcher`](crate::watcher) module for an example.
+    pub fn watch(
+        &self,
+        dir: impl AsRef<Path>,
+        options: WatchOptions,
+        sink: impl ResultSink,
+    ) -> Result<FolderWatcher, TraceixError> {
+        let root = fs::canonicalize(dir.as_ref())?;
+        let ignore = options
+            .ignore
+            .iter()
+            .map(|p| Glob::new(p))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let (events_tx, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            let _ = events_tx.send(event);
+        })
+        .map_err(notify_error)?;
+        let mode = if options.recursive {
+            RecursiveMode::Recursive
+        } else {
+            RecursiveMode::NonRecursive
+        };
+        watcher.watch(&root, mode).map_err(notify_error)?;
+
+        let analyses = options.analyses;
+        let batch = self.batch(
+            BatchOptions {
+                workers: options.concurrency,
+                ..BatchOptions::default()
+            },
+            move |sdk, item| sdk.full_upload_with_options(&item.path, analyses, &item.options),
+        );
+        let queue = Queue {
+            root,
+            ignore,
+            sender: batch.sender(),
+            upload: options.upload,
+        };
+        if options.existing {
+            queue.existing(&queue.root, options.recursive);
+        }
+
+        let debounce = options.debounce;
+        let debouncer = thread::spawn(move || {
+            let mut pending: HashMap<PathBuf, Instant> = HashMap::new();
+            loop {
+                let tick = debounce.max(Duration::from_millis(50)) / 2;
+                match events.recv_timeout(tick) {
+                    Ok(Ok(event)) => {
+                        if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                            for path in event.paths {
+                                pending.insert(path, Instant::now());
+                            }
+                        }
+                    }
+                    // Errors from the OS watcher (e.g. a queue overflow) lose events
+                    // but don't end the watch.
+                    Ok(Err(_)) => {}
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => {
+                        // Watching stopped: submit files still waiting out the debounce
+                        // period rather than dropping them.
+                        for path in pending.keys() {
+                            queue.submit(path);
+                        }
+                        break;
+                    }
+                }
+                pending.retain(|path, last| {
+                    if last.elapsed() < debounce {
+                        return true;
+                    }
+                    queue.submit(path);
+                    false
+                });
+            }
+            queue.sender.close();
+        });
+
+        let mut sink = sink;
+        let results = thread::spawn(move || {
+            for done in batch.iter() {
+                sink.handle(&done.item.path, done.result);
+            }
+        });
+
+        Ok(FolderWatcher {
+            watcher,
+            threads: vec![debouncer, results],
+        })
+    }
+}
+
+/// Feeds settled files into the watcher's batch.
+struct Queue {
+    root: PathBuf,
+    ignore: Vec<Glob>,
+    sender: BatchSender,
+    upload: UploadOptions,
+}
+
+impl Queue {
+    /// Submit `path` if it is a regular file that isn't ignored.
+    fn submit(&self, path: &Path) {
+        if !fs::symlink_metadata(path).is_ok_and(|m| m.is_file()) {
+            return;
+        }
+        let relative = relative_path(&self.root, path);
+        let name = relative.rsplit('/').next().unwrap_or_default();
+        if self.ignore.iter().any(|g| g.matches(&relative, name)) {
+            return;
+        }
+        let _ = self
+            .sender
+            .push_with_options(path.to_path_buf(), self.upload.clone());
+    }
+
+    /// Submit the files already under `dir`.
+    fn existing(&self, dir: &Path, recursive: bool) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            match entry.file_type() {
+                Ok(t) if t.is_dir() && recursive => self.existing(&path, recursive),
+                Ok(t) if t.is_file() => self.submit(&path),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn notify_error(e: notify::Error) -> TraceixError {
+    match e.kind {
+        notify::ErrorKind::Io(e) => TraceixError::Io(e),
+        _ => TraceixError::Io(io::Error::other(e)),
+    }
+}
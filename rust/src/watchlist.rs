@@ -0,0 +1,59 @@
+// src/watchlist.rs
+
+use crate::hashing::normalize_hex_digest;
+use crate::{decode_response, Endpoint, TraceixError, TraceixSdk};
+use serde::{Deserialize, Serialize};
+
+/// A hash on the account's watchlist.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchlistEntry {
+    pub hash: String,
+    #[serde(default)]
+    pub added_at: Option<String>,
+    /// When the hash was last seen in the public corpus, if ever.
+    #[serde(default)]
+    pub last_seen: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Watchlist {
+    entries: Vec<WatchlistEntry>,
+}
+
+impl TraceixSdk {
+    /// Add hashes to the watchlist. The account is alerted when any of them appears
+    /// in the public corpus. Returns the full watchlist after the update.
+    pub fn watchlist_add(&self, hashes: &[&str]) -> Result<Vec<WatchlistEntry>, TraceixError> {
+        self.watchlist_update(&Endpoint::WATCHLIST_ADD, hashes)
+    }
+
+    /// Remove hashes from the watchlist. Returns the full watchlist after the update.
+    pub fn watchlist_remove(&self, hashes: &[&str]) -> Result<Vec<WatchlistEntry>, TraceixError> {
+        self.watchlist_update(&Endpoint::WATCHLIST_REMOVE, hashes)
+    }
+
+    /// List every hash on the watchlist, fetching every page.
+    pub fn watchlist_list(&self) -> Result<Vec<WatchlistEntry>, TraceixError> {
+        self.list_all(&Endpoint::WATCHLIST_LIST, "entries")
+    }
+
+    fn watchlist_update(
+        &self,
+        endpoint: &Endpoint,
+        hashes: &[&str],
+    ) -> Result<Vec<WatchlistEntry>, TraceixError> {
+        if hashes.is_empty() {
+            return Err(TraceixError::InvalidArgument(
+                "no hashes provided".to_string(),
+            ));
+        }
+        let hashes = hashes
+            .iter()
+            .map(|h| normalize_hex_digest(h).map(|(_, h)| h))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let list: Watchlist =
+            decode_response(self.post_json(endpoint, &serde_json::json!({ "hashes": hashes }))?)?;
+        Ok(list.entries)
+    }
+}